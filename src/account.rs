@@ -0,0 +1,152 @@
+use crate::utility::data_dir;
+use anyhow::{Context, Result};
+use ratatui::widgets::ListState;
+use rustypipe::client::{ClientType, RustyPipeQuery};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::path::PathBuf;
+
+/// A playlist of the logged in account
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccountPlaylist {
+    pub id: String,
+    pub name: String,
+}
+
+/// Playlists of the account, kept in `~/.local/share/ytrs/account_playlists.json`
+#[derive(Serialize, Deserialize, Default)]
+pub struct AccountPlaylists {
+    pub playlists: Vec<AccountPlaylist>,
+}
+
+impl AccountPlaylists {
+    pub fn path() -> PathBuf {
+        data_dir().join("account_playlists.json")
+    }
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse '{}'", path.to_string_lossy()))
+    }
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Fetch the playlists `video_id` can be added to from the account and cache them
+    pub async fn fetch(video_id: &str) -> Result<Self> {
+        let response = call(
+            "playlist/get_add_to_playlist",
+            json!({ "videoIds": [video_id] }),
+        )
+        .await?;
+        let mut playlists = Vec::new();
+        collect_playlists(&response, &mut playlists);
+        let fetched = Self { playlists };
+        fetched.save()?;
+        Ok(fetched)
+    }
+}
+
+/// State of the player pane saving the current media to a playlist of the account
+#[derive(Default)]
+pub struct SavePane {
+    pub playlists: Vec<AccountPlaylist>,
+    pub selected: ListState,
+    pub error: Option<String>,
+}
+
+impl SavePane {
+    /// The cached playlists, fetched from the account when there are none or `refresh` is set
+    pub async fn fetch(video_id: String, refresh: bool) -> Result<AccountPlaylists> {
+        let cached = AccountPlaylists::load().unwrap_or_default();
+        if refresh || cached.playlists.is_empty() {
+            AccountPlaylists::fetch(&video_id).await
+        } else {
+            Ok(cached)
+        }
+    }
+
+    pub fn loaded(&mut self, playlists: Result<AccountPlaylists>) {
+        match playlists {
+            Ok(playlists) => {
+                self.playlists = playlists.playlists;
+                self.error = None;
+            }
+            Err(e) => {
+                self.playlists.clear();
+                self.error = Some(e.to_string());
+            }
+        }
+        self.selected.select_first();
+    }
+
+    pub fn selected_playlist(&self) -> Option<&AccountPlaylist> {
+        self.selected.selected().and_then(|i| self.playlists.get(i))
+    }
+}
+
+/// Like `video_id` on the account
+pub async fn like(video_id: &str) -> Result<()> {
+    call("like/like", json!({ "target": { "videoId": video_id } })).await?;
+    Ok(())
+}
+
+/// Append `video_id` to a playlist of the account
+pub async fn add_to_playlist(playlist_id: &str, video_id: &str) -> Result<()> {
+    call(
+        "browse/edit_playlist",
+        json!({
+            "playlistId": playlist_id,
+            "actions": [{ "action": "ACTION_ADD_VIDEO", "addedVideoId": video_id }]
+        }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Call an endpoint of the YouTube API as the account, with the cookies or else the token of `ytrs login`
+async fn call(endpoint: &str, body: Value) -> Result<Value> {
+    let query: RustyPipeQuery = crate::auth::rustypipe().query().authenticated();
+    let ctype = query
+        .auth_enabled_client(&[ClientType::Desktop, ClientType::Tv])
+        .context("Not logged in, use 'ytrs login' or '--cookies'")?;
+    let response = query.raw(ctype, endpoint, &body).await;
+    let response = response.with_context(|| format!("YouTube refused '{endpoint}'"))?;
+    Ok(serde_json::from_str(&response)?)
+}
+
+// The renderers are nested differently depending on the client
+fn collect_playlists(value: &Value, playlists: &mut Vec<AccountPlaylist>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(option) = map.get("playlistAddToOptionRenderer")
+                && let Some(id) = option["playlistId"].as_str()
+            {
+                let name = option["title"]["simpleText"]
+                    .as_str()
+                    .or(option["title"]["runs"][0]["text"].as_str())
+                    .unwrap_or(id);
+                playlists.push(AccountPlaylist {
+                    id: id.to_owned(),
+                    name: name.to_owned(),
+                });
+                return;
+            }
+            map.values()
+                .for_each(|value| collect_playlists(value, playlists));
+        }
+        Value::Array(values) => values
+            .iter()
+            .for_each(|value| collect_playlists(value, playlists)),
+        _ => {}
+    }
+}
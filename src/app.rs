@@ -1,7 +1,34 @@
-use crate::cli::{AppActionCli, Cli};
+use crate::account::SavePane;
+use crate::archive::DownloadArchive;
+use crate::audio_tracks::AudioTracksPane;
+use crate::backend::{YoutubeBackend, backend};
+use crate::banner::{BannerAction, ErrorBanner, Retry};
+use crate::cli::{AppActionCli, Cli, TrendingCategory, WatchCli};
+use crate::config::PlayerChoice;
+use crate::explore::ExploreTab;
+use crate::feed::{Feed, FeedKind};
+use crate::gamepad::PadAction;
+use crate::images::{self, PlayerImage};
+use crate::jobs::{JobOutput, Jobs};
+use crate::link::YoutubeLink;
+use crate::llm::{Llm, SummaryCache, SummaryPane, print_progress};
+use crate::log::LogError;
 use crate::mpv::{MpvIpc, MpvSpawnOptions};
+use crate::podcasts::EpisodeProgress;
+use crate::progress::{self, Event as ProgressEvent};
+use crate::related::RelatedPane;
+use crate::remote::{NowPlaying, PlayerCommand, RemoteHub};
+use crate::script::ScriptError;
+use crate::session::Hangup;
+use crate::sponsorblock::{self, SponsorCategory};
+use crate::stats::StatsCache;
+use crate::storyboard::{SeekPreview, Storyboard};
+use crate::subscriptions::Subscriptions;
+use crate::subtitles::SubtitlesPane;
+use crate::transcript::{self, Cue, TranscriptSearch, TranscriptView};
+use crate::uploads::UploadsPane;
 use anyhow::{Context, Result, anyhow, bail};
-use chrono::{Timelike, Utc};
+use chrono::{DateTime, Timelike, Utc};
 use image::DynamicImage;
 use inquire::{Confirm, Select, Text as InquireText, validator::Validation};
 use inquire_derive::Selectable;
@@ -10,37 +37,52 @@ use lofty::file::{AudioFile, TaggedFile, TaggedFileExt};
 use lofty::picture::Picture;
 use lofty::probe::Probe;
 use lofty::tag::{Accessor, Tag, TagExt};
-use midir::{MidiInput, MidiInputPort, MidiOutput, MidiOutputConnection, MidiOutputPort};
-use ollama_rs::Ollama;
-use ollama_rs::generation::completion::request::GenerationRequest;
-use ratatui::crossterm::event::KeyModifiers;
+use ratatui::crossterm::event::{Event, EventStream, KeyEvent, KeyModifiers};
 use ratatui::prelude::*;
 use ratatui::style::Stylize;
-use ratatui::widgets::{Gauge, List, ListItem, ListState};
+use ratatui::widgets::{Gauge, List, ListItem, ListState, Wrap};
 use ratatui::{
-    crossterm::event::{KeyCode, read},
+    crossterm::event::KeyCode,
     layout::{Constraint, Layout},
-    widgets::{Block, Paragraph},
+    widgets::{Block, Clear, Paragraph},
 };
-use ratatui_image::{StatefulImage, picker};
+use ratatui_image::StatefulImage;
 use rustypipe::{
-    client::RustyPipe,
-    model::{TrackItem, VideoItem},
+    model::{PlaylistItem, TrackItem, VideoItem, paginator::Paginator},
+    param::search_filter::{ItemType, SearchFilter},
 };
 use serde_json::json;
+use std::collections::BTreeSet;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::ops::ControlFlow;
-use std::path::PathBuf;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use strum::IntoEnumIterator;
 use thiserror::Error;
+use tokio::sync::watch;
+use tokio_stream::StreamExt;
 use yt_dlp::Youtube;
-use yt_dlp::client::Libraries;
+use yt_dlp::client::{Libraries, LibraryInstaller};
 use yt_dlp::model::VideoCodecPreference;
 use yt_dlp::model::caption::Subtitle;
+use ytrs_core::queue::{Queue, Queued};
 
-use crate::utility::format_time;
+use crate::utility::{format_time, sanitize_file_name};
+use crate::watch::{WatchEntry, WatchList};
+
+// How long a toast stays over the player
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+// Pace of the player redraws while a spinner turns, and while nothing changes
+const ANIMATION_TICK: Duration = Duration::from_millis(150);
+const IDLE_TICK: Duration = Duration::from_secs(1);
+// Seconds behind the edge of a livestream still shown as live
+const LIVE_EDGE_MARGIN: f64 = 10.0;
+// Id of the mpv hook resolving the streams with `--direct-stream`, run before the yt-dlp one (priority 10)
+const LOAD_HOOK_ID: u64 = 1;
+const LOAD_HOOK_PRIORITY: i64 = 5;
+// Time a search entry stays selected before its details are fetched, scrolling does not spend the request budget
+const STATS_DELAY: Duration = Duration::from_secs(1);
 
 #[derive(Default)]
 pub struct YoutubeRs {
@@ -52,7 +94,43 @@ pub struct YoutubeRs {
     // Enter the player tui directly
     pub player: bool,
     pub run_midi: bool,
-    args: Cli,
+    // File listing the urls to download, '-' for stdin
+    pub batch: Option<PathBuf>,
+    // SponsorBlock categories cut out of downloads
+    pub sponsorblock: Vec<SponsorCategory>,
+    // Write the description and info.json next to downloads
+    pub sidecars: bool,
+    // Phrase searched in the downloaded transcript, asked for when `--find` has none
+    pub find: Option<Option<String>>,
+    // Transcript languages to download without prompting
+    pub langs: Vec<String>,
+    // Language the transcript is translated to
+    pub translate: Option<String>,
+    // Write the summary to a markdown file in the output dir
+    pub save_summary: bool,
+    // Ask questions about the video once the transcript is downloaded
+    pub chat: bool,
+    // Played by the player after the current media
+    pub queue: Queue<YoutubeResponse>,
+    // Message shown over the player for a few seconds and when it was raised
+    pub toast: Option<(String, Instant)>,
+    // Failure shown over the player until retried or dismissed
+    pub banner: Option<ErrorBanner>,
+    // Searches and thumbnails fetched while the player keeps running
+    pub jobs: Jobs,
+    // Regenerate the summaries instead of reading them from the cache
+    pub refresh: bool,
+    // Trending category listed when the player opens
+    pub trending: Option<TrendingCategory>,
+    // What the url given on the command line points to
+    pub link: Option<YoutubeLink>,
+    // Scheduled premiere the player starts once it goes live, with its start time
+    pub premiere: Option<(VideoItem, DateTime<Utc>)>,
+    // Language of the audio track played and downloaded, for videos dubbed in several
+    pub audio_language: Option<String>,
+    // mpv of the detached player the player comes back to, with the media first in its playlist
+    pub(crate) attached: Option<(MpvIpc, Option<YoutubeResponse>)>,
+    pub(crate) args: Cli,
 }
 #[derive(Default)]
 pub struct YoutubeRsBuilder {
@@ -65,6 +143,17 @@ pub struct YoutubeRsBuilder {
     // Enter the player tui directly
     pub player: Option<bool>,
     midi: bool,
+    batch: Option<PathBuf>,
+    sponsorblock: Vec<SponsorCategory>,
+    sidecars: bool,
+    find: Option<Option<String>>,
+    langs: Vec<String>,
+    translate: Option<String>,
+    save_summary: bool,
+    chat: bool,
+    refresh: bool,
+    trending: Option<TrendingCategory>,
+    link: Option<YoutubeLink>,
 }
 
 impl YoutubeRs {
@@ -86,26 +175,41 @@ pub enum AppAction {
     Quit,
 }
 
-#[derive(strum::Display, strum::EnumIter, Default, Clone, Selectable, Debug, Copy)]
+#[derive(
+    strum::Display, strum::EnumIter, Default, Clone, Selectable, Debug, Copy, serde::Deserialize,
+)]
 pub enum YoutubeAPI {
     Music,
     #[default]
     Video,
 }
-#[derive(Copy, Debug, Selectable, strum::Display, Clone)]
+#[derive(Copy, Debug, Selectable, strum::Display, Clone, serde::Deserialize)]
 pub enum FormatInquire {
     Audio,
     Video,
 }
 
-#[derive(strum::Display, strum::EnumIter, Clone, PartialEq, Copy)]
+#[derive(
+    strum::Display, strum::EnumIter, Clone, PartialEq, Copy, serde::Serialize, serde::Deserialize,
+)]
 pub enum Format {
     Audio { format: AudioFormat },
     Video { format: VideoFormat },
 }
 
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Clone, strum::Display, strum::EnumIter, Default, PartialEq, Copy, Debug, Selectable)]
+#[derive(
+    Clone,
+    strum::Display,
+    strum::EnumIter,
+    Default,
+    PartialEq,
+    Copy,
+    Debug,
+    Selectable,
+    serde::Serialize,
+    serde::Deserialize,
+)]
 pub enum AudioFormat {
     #[default]
     MP3,
@@ -113,7 +217,18 @@ pub enum AudioFormat {
 }
 
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Clone, strum::Display, strum::EnumIter, Default, PartialEq, Copy, Selectable, Debug)]
+#[derive(
+    Clone,
+    strum::Display,
+    strum::EnumIter,
+    Default,
+    PartialEq,
+    Copy,
+    Selectable,
+    Debug,
+    serde::Serialize,
+    serde::Deserialize,
+)]
 pub enum VideoFormat {
     #[default]
     MP4,
@@ -136,7 +251,7 @@ pub struct TrackInfo {
     view_count: Option<u64>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum YoutubeResponse {
     Video(VideoItem),
     Track(TrackItem),
@@ -144,23 +259,109 @@ pub enum YoutubeResponse {
 
 #[derive(Error, Debug)]
 pub enum YtrsError {
-    #[error("MPV not installed or not found in PATH")]
+    #[error("MPV not installed or not found in PATH, set its path with --mpv-path")]
     MpvNotFound,
     #[error("Quit successfully")]
     Quit,
 }
 
+#[derive(strum::Display)]
+pub enum BatchStatus {
+    Downloaded,
+    Skipped,
+    Failed(String),
+}
+
+/// State of the playlists tab of the search popup
+#[derive(Default)]
+pub struct PlaylistTab {
+    pub open: bool,
+    // Label and id of the playlists found by the last search
+    pub found: Vec<(String, String)>,
+    pub selected: ListState,
+    // Name of the playlist whose entries are listed in the popup
+    pub previewed: Option<String>,
+    // Entries of the previewed playlist picked with space
+    pub marked: BTreeSet<usize>,
+}
+
+/// State of the trending tab of the search popup
+#[derive(Default)]
+pub struct TrendingTab {
+    pub open: bool,
+    pub category: TrendingCategory,
+}
+
+/// The pane sharing the bottom of the player, one at a time
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SidePane {
+    Summary,
+    Transcript,
+    Related,
+    Save,
+    Uploads,
+    AudioTracks,
+    Subtitles,
+}
+
+impl SidePane {
+    /// Open the pane in place of the open one, or close it when it is the open one,
+    /// returns whether it is open
+    pub fn toggle(self, open: &mut Option<SidePane>) -> bool {
+        *open = (*open != Some(self)).then_some(self);
+        open.is_some()
+    }
+}
+
+/// Uploads of a channel opened from a result of the search popup
+#[derive(Default)]
+pub struct ChannelPage {
+    // Id and name of the channel whose uploads are listed in the popup
+    pub channel: Option<(String, String)>,
+    // Last page of uploads loaded
+    pub uploads: Option<Paginator<VideoItem>>,
+    // Results listed before opening the channel
+    pub back: Vec<(String, YoutubeResponse)>,
+}
+
+impl ChannelPage {
+    /// Leave the channel, returns the results it was opened from
+    pub fn close(&mut self) -> Option<Vec<(String, YoutubeResponse)>> {
+        self.channel.take()?;
+        self.uploads = None;
+        Some(std::mem::take(&mut self.back))
+    }
+}
+
 impl YoutubeRsBuilder {
     pub fn build(&mut self, cli: Cli) -> YoutubeRs {
         YoutubeRs {
             api: self.api,
             action: self.action.unwrap_or_default(),
-            mpv_installed: YoutubeRs::check_mpv().unwrap_or_default(),
+            mpv_installed: YoutubeRs::check_mpv(&cli).unwrap_or_default(),
             last_search: Some(self.last_search.clone().unwrap_or_default()),
             args: cli,
             summarize: self.summarize,
             player: self.player.unwrap_or_default(),
             run_midi: self.midi,
+            batch: self.batch.clone(),
+            sponsorblock: self.sponsorblock.clone(),
+            sidecars: self.sidecars,
+            find: self.find.clone(),
+            langs: self.langs.clone(),
+            translate: self.translate.clone(),
+            save_summary: self.save_summary,
+            chat: self.chat,
+            queue: Queue::default(),
+            toast: None,
+            banner: None,
+            jobs: Jobs::default(),
+            premiere: None,
+            audio_language: None,
+            attached: None,
+            refresh: self.refresh,
+            trending: self.trending,
+            link: self.link.clone(),
         }
     }
     pub fn api(&mut self, music: Option<bool>, prompt: bool) -> &mut Self {
@@ -171,7 +372,7 @@ impl YoutubeRsBuilder {
                 self.api = Some(YoutubeAPI::Video)
             }
         } else if prompt {
-            self.api = Some(YoutubeAPI::select("Select API").prompt().unwrap());
+            self.api = Some(YoutubeAPI::configured_or_prompt());
         }
 
         self
@@ -186,10 +387,30 @@ impl YoutubeRsBuilder {
                 AppActionCli::Download { .. } => AppAction::Download {
                     format: Default::default(),
                 },
-                AppActionCli::Player { .. } => AppAction::Player {
+                AppActionCli::Player { .. }
+                | AppActionCli::Search { .. }
+                | AppActionCli::Mood { .. }
+                | AppActionCli::Shorts { .. }
+                | AppActionCli::Ctl { .. }
+                | AppActionCli::Config { .. }
+                | AppActionCli::Subscriptions { .. }
+                | AppActionCli::Block { .. }
+                | AppActionCli::Midi { .. }
+                | AppActionCli::Login
+                | AppActionCli::Logout
+                | AppActionCli::Libs { .. }
+                | AppActionCli::SelfUpdate { .. }
+                | AppActionCli::Attach { .. } => AppAction::Player {
                     format: Default::default(),
                 },
                 AppActionCli::Transcript { .. } => AppAction::Transcript,
+                AppActionCli::Chapters { .. }
+                | AppActionCli::Comments { .. }
+                | AppActionCli::Digest { .. }
+                | AppActionCli::Keywords { .. } => AppAction::Transcript,
+                AppActionCli::Record { .. } | AppActionCli::Watch { .. } => AppAction::Download {
+                    format: Default::default(),
+                },
             });
         } else if let Some(action) = action {
             self.action = Some(action);
@@ -203,9 +424,9 @@ impl YoutubeRsBuilder {
     }
     pub fn prompt_download(&mut self) -> &mut Self {
         self.action = Some(AppAction::Download {
-            format: FormatInquire::select("Select Format")
-                .prompt()
-                .unwrap()
+            format: crate::config::config()
+                .download
+                .unwrap_or_else(|| FormatInquire::select("Select Format").prompt().unwrap())
                 .into(),
         });
         self
@@ -214,10 +435,14 @@ impl YoutubeRsBuilder {
         if let Some(AppAction::Download { format }) = &mut self.action {
             match format {
                 Format::Audio { format } => {
-                    *format = AudioFormat::select("Select Audio Format").prompt().unwrap()
+                    *format = crate::config::config().audio_format.unwrap_or_else(|| {
+                        AudioFormat::select("Select Audio Format").prompt().unwrap()
+                    })
                 }
                 Format::Video { format } => {
-                    *format = VideoFormat::select("Select Video Format").prompt().unwrap()
+                    *format = crate::config::config().video_format.unwrap_or_else(|| {
+                        VideoFormat::select("Select Video Format").prompt().unwrap()
+                    })
                 }
             }
         }
@@ -233,7 +458,7 @@ impl YoutubeRsBuilder {
         self.action = Some(AppAction::Player {
             format: FormatInquire::select("Format").prompt().unwrap().into(),
         });
-        self.api = Some(YoutubeAPI::select("Select API").prompt().unwrap());
+        self.api = Some(YoutubeAPI::configured_or_prompt());
         self
     }
     pub fn audio_player(&mut self) -> &mut Self {
@@ -268,26 +493,71 @@ impl YoutubeRsBuilder {
     }
     pub fn url(&mut self, url: impl Into<String>) -> &mut Self {
         let url: String = url.into();
-        if url.to_lowercase().starts_with("https://music.youtube.com") {
-            self.api = Some(YoutubeAPI::Music);
-        } else if url.to_lowercase().starts_with("https://www.youtube.com") {
-            self.api = Some(YoutubeAPI::Video);
-        } else {
-            self.api = Some(YoutubeAPI::select("Select API").prompt().unwrap());
-        }
+        self.link = YoutubeLink::parse(&url);
+        self.api = match &self.link {
+            Some(link) if link.music => Some(YoutubeAPI::Music),
+            Some(_) => Some(YoutubeAPI::Video),
+            None => Some(YoutubeAPI::configured_or_prompt()),
+        };
         self.last_search = Some(url);
         self
     }
+    pub fn sponsorblock(&mut self, categories: Vec<SponsorCategory>) -> &mut Self {
+        self.sponsorblock = categories;
+        self
+    }
+    pub fn sidecars(&mut self, sidecars: bool) -> &mut Self {
+        self.sidecars = sidecars;
+        self
+    }
+    pub fn batch(&mut self, batch: PathBuf) -> &mut Self {
+        self.batch = Some(batch);
+        self
+    }
     pub fn query(&mut self, query: impl Into<String>) -> &mut Self {
         self.last_search = Some(query.into());
         self
     }
+    pub fn refresh(&mut self, refresh: bool) -> &mut Self {
+        self.refresh = refresh;
+        self
+    }
+    pub fn trending(&mut self, category: Option<TrendingCategory>) -> &mut Self {
+        self.trending = category;
+        self
+    }
+    pub fn chat(&mut self, chat: bool) -> &mut Self {
+        self.chat = chat;
+        self
+    }
+    pub fn save_summary(&mut self, save: bool) -> &mut Self {
+        self.save_summary = save;
+        self
+    }
+    pub fn translate(&mut self, target: Option<String>) -> &mut Self {
+        self.translate = target;
+        self
+    }
+    pub fn langs(&mut self, langs: Vec<String>) -> &mut Self {
+        self.langs = langs;
+        self
+    }
+    pub fn find(&mut self, phrase: Option<String>) -> &mut Self {
+        self.find = Some(phrase);
+        self
+    }
     pub fn do_summarize(&mut self, summarize: bool) -> &mut Self {
         self.summarize = Some(summarize);
         self
     }
 }
 
+impl Queued for YoutubeResponse {
+    fn url(&self) -> String {
+        backend().watch_url(&self.get_id())
+    }
+}
+
 impl YoutubeResponse {
     pub fn get_id(&self) -> String {
         match self {
@@ -301,21 +571,78 @@ impl YoutubeResponse {
             YoutubeResponse::Track(track_item) => track_item.name.clone(),
         }
     }
+    pub fn get_author(&self) -> String {
+        match self {
+            YoutubeResponse::Video(video_item) => video_item
+                .channel
+                .as_ref()
+                .map(|channel| channel.name.clone())
+                .unwrap_or_default(),
+            YoutubeResponse::Track(track_item) => track_item
+                .artists
+                .iter()
+                .map(|artist| artist.name.as_str())
+                .collect::<Vec<&str>>()
+                .join(", "),
+        }
+    }
     pub fn get_duration(&self) -> u32 {
         match self {
             YoutubeResponse::Video(video_item) => video_item.duration.unwrap_or_default(),
             YoutubeResponse::Track(track_item) => track_item.duration.unwrap_or_default(),
         }
     }
+    /// Line shown in the lists of the search popup
+    pub fn is_live(&self) -> bool {
+        match self {
+            YoutubeResponse::Video(video_item) => video_item.is_live,
+            YoutubeResponse::Track(_) => false,
+        }
+    }
+    pub fn label(&self) -> String {
+        match self {
+            YoutubeResponse::Video(video_item) => VideoInfo::from(video_item).to_string(),
+            YoutubeResponse::Track(track_item) => TrackInfo::from(track_item).to_string(),
+        }
+    }
+    /// Id and name of the channel, the first artist of a track
+    pub fn get_channel(&self) -> Option<(String, String)> {
+        match self {
+            YoutubeResponse::Video(video_item) => video_item
+                .channel
+                .as_ref()
+                .map(|channel| (channel.id.clone(), channel.name.clone())),
+            YoutubeResponse::Track(track_item) => track_item
+                .artists
+                .iter()
+                .find_map(|artist| Some((artist.id.clone()?, artist.name.clone()))),
+        }
+    }
 }
 
 impl YoutubeRs {
     pub async fn process(&mut self) -> Result<()> {
         match self.action {
             AppAction::Download { format } => {
-                if !self.libraries_exist(&self.args.clone()) {
+                if !backend().offline() && !self.libraries_exist(&self.args.clone()) {
                     Self::install_lib(&self.args).await?;
                 }
+                if let Some(batch) = self.batch.clone() {
+                    return self.download_batch(&batch, format).await;
+                }
+                // A playlist link downloads the whole playlist, a video of a playlist only that video
+                if self
+                    .link
+                    .as_ref()
+                    .is_some_and(|link| link.video_id.is_none())
+                    && let Some((name, entries)) = self.link_playlist().await?
+                {
+                    let urls: Vec<String> = entries
+                        .iter()
+                        .map(|entry| Self::get_video_url(&entry.get_id()))
+                        .collect();
+                    return self.download_urls(&urls, format, Some(&name)).await;
+                }
                 let (video_id, video_name) = match self.api {
                     Some(YoutubeAPI::Music) => {
                         let (track, search) = Self::query_ytmusic(self.last_search.clone()).await?;
@@ -329,22 +656,23 @@ impl YoutubeRs {
                     }
                     None => return Ok(()),
                 };
-                let url = format!("https://www.youtube.com/watch?v={video_id}");
-                match format {
-                    Format::Audio { format } => {
-                        self.download_audio(&url, &video_name, format, &self.args)
-                            .await?;
-                    }
-                    Format::Video { format } => {
-                        self.download_video(&url, &video_name, format, &self.args)
-                            .await?;
-                    }
+                if self.args.pick_audio_track {
+                    self.pick_audio_track(&video_id).await?;
                 }
+                self.download(&video_id, &video_name, format, None).await?;
+                self.archive()?.insert(&video_id)?;
             }
             AppAction::Transcript => {
                 if !self.libraries_exist(&self.args.clone()) {
                     Self::install_lib(&self.args).await?;
                 }
+                if self
+                    .link
+                    .as_ref()
+                    .is_some_and(|link| link.video_id.is_none())
+                {
+                    bail!("The transcript needs the link of a video, not of a playlist");
+                }
                 let video_id = match self.api {
                     Some(YoutubeAPI::Music) => {
                         let (track, search) = Self::query_ytmusic(self.last_search.clone()).await?;
@@ -362,25 +690,60 @@ impl YoutubeRs {
             }
             AppAction::Player { format } => {
                 if !self.mpv_installed {
-                    self.mpv_installed = Self::check_mpv()?;
+                    self.mpv_installed = Self::check_mpv(&self.args)?;
                 }
-                let mut response = match self.api {
-                    Some(YoutubeAPI::Music) => {
-                        if self.player {
-                            None
-                        } else {
-                            let res = Self::query_ytmusic(self.last_search.clone()).await?;
-                            self.last_search = Some(res.1);
-                            Some(YoutubeResponse::Track(res.0))
-                        }
+                // The entries of a playlist link are queued, from its video on when it has one
+                let playlist = self.link_playlist().await;
+                let mut response = match playlist {
+                    Ok(Some((_, entries))) if !entries.is_empty() => {
+                        let mut entries = entries.into_iter();
+                        let first = entries.next();
+                        self.queue = entries.collect();
+                        first
                     }
-                    Some(YoutubeAPI::Video) => {
-                        let res = Self::query_ytvideo(self.last_search.clone()).await?;
-                        self.last_search = Some(res.1);
-                        Some(YoutubeResponse::Video(res.0))
+                    Err(e)
+                        if self
+                            .link
+                            .as_ref()
+                            .is_some_and(|link| link.video_id.is_none()) =>
+                    {
+                        return Err(e);
                     }
-                    None => None,
+                    _ => match self.api {
+                        Some(YoutubeAPI::Music) => {
+                            if self.player {
+                                None
+                            } else {
+                                let res = Self::query_ytmusic(self.last_search.clone()).await?;
+                                self.last_search = Some(res.1);
+                                Some(YoutubeResponse::Track(res.0))
+                            }
+                        }
+                        Some(YoutubeAPI::Video) => {
+                            let res = Self::query_ytvideo(self.last_search.clone()).await?;
+                            self.last_search = Some(res.1);
+                            Some(YoutubeResponse::Video(res.0))
+                        }
+                        None => None,
+                    },
                 };
+                // A scheduled premiere can not be loaded yet, count down until it goes live
+                if let Some(YoutubeResponse::Video(video)) = &mut response
+                    && video.is_upcoming
+                {
+                    let start = self.premiere_start(video).await?;
+                    if !self.wait_premiere(&video.name, start, "play").await? {
+                        return Ok(());
+                    }
+                    self.wait_live(&Self::get_video_url(&video.id)).await?;
+                    video.is_upcoming = false;
+                    video.is_live = true;
+                }
+                if self.args.pick_audio_track
+                    && let Some(YoutubeResponse::Video(video)) = &response
+                {
+                    self.pick_audio_track(&video.id).await?;
+                }
                 if response.is_none() {
                     self.player(
                         &mut None,
@@ -423,85 +786,40 @@ impl YoutubeRs {
         }
         Ok(())
     }
-    async fn player(
+    pub(crate) async fn player(
         &mut self,
         response: &mut Option<YoutubeResponse>,
         opt_thumbnail: &mut Option<DynamicImage>,
         audio_only: bool,
         run_midi: bool,
     ) {
-        let mut midi_in = MidiInput::new("midir reading input").expect("Could not open Midi Input");
-        midi_in.ignore(midir::Ignore::None);
-        let midi_out =
-            MidiOutput::new("midir forwarding output").expect("Could not open Midi Output");
-        let in_port = midi_in.ports();
-        let out_port = midi_out.ports();
-        let opt_midi_in_port: Option<&MidiInputPort> = if !run_midi {
-            None
-        } else {
-            match in_port.len() {
-                0 => None,
-                1 => Some(&in_port[0]),
-                _ => {
-                    let filter = |(i, p): (usize, &MidiInputPort)| -> String {
-                        format!("{i}:{}", midi_in.port_name(p).unwrap())
-                    };
-                    let mut inputs = vec![String::from("None")];
-                    in_port
-                        .iter()
-                        .enumerate()
-                        .for_each(|(i, p)| inputs.push(filter((i, p))));
-                    let res = inquire::Select::new("Select Midi Input Port", inputs)
-                        .prompt()
-                        .unwrap();
-                    let port = in_port
-                        .iter()
-                        .enumerate()
-                        .find(|(i, p)| filter((*i, p)) == res);
-                    match port {
-                        Some(p) => Some(&in_port[p.0]),
-                        None => None,
-                    }
-                }
-            }
-        };
-        let opt_midi_out_port: Option<&MidiOutputPort> = if !run_midi {
-            None
-        } else {
-            match out_port.len() {
-                0 => None,
-                1 => Some(&out_port[0]),
-                _ => {
-                    let filter = |(i, p): (usize, &MidiOutputPort)| -> String {
-                        format!("{i}:{}", midi_out.port_name(p).unwrap())
-                    };
-                    let mut inputs = vec![String::from("None")];
-                    out_port
-                        .iter()
-                        .enumerate()
-                        .for_each(|(i, p)| inputs.push(filter((i, p))));
-                    let res = inquire::Select::new("Select Midi Output Port", inputs)
-                        .prompt()
-                        .unwrap();
-                    let port = out_port
-                        .iter()
-                        .enumerate()
-                        .find(|(i, p)| filter((*i, p)) == res);
-                    match port {
-                        Some(p) => Some(&out_port[p.0]),
-                        None => None,
-                    }
-                }
-            }
-        };
-        let mut img = if let Some(dyn_thumbnail) = &opt_thumbnail
-            && let Ok(picker) = picker::Picker::from_query_stdio()
-        {
-            let protocol = picker.new_resize_protocol(dyn_thumbnail.clone());
-            Some(protocol)
+        if crate::config::config().player != PlayerChoice::Mpv {
+            self.basic_player(response, audio_only).await;
+            return;
+        }
+        let midi_ports = if run_midi {
+            crate::midi::select().ok()
         } else {
             None
         };
+        let mut img = PlayerImage::default();
+        match (
+            opt_thumbnail.take(),
+            response.as_ref().map(YoutubeResponse::get_id),
+        ) {
+            (Some(thumbnail), id) => match images::picker() {
+                Ok(picker) => img.set(&picker, thumbnail),
+                Err(e) => {
+                    self.banner = Some(ErrorBanner::new(
+                        format!("Could not draw the thumbnail: {e}"),
+                        id.map(Retry::Thumbnail),
+                    ));
+                }
+            },
+            // Fetching it failed before the player started
+            (None, Some(id)) => self.request_thumbnail(&id),
+            (None, None) => {}
+        }
         let mut empty_player = false;
         let mut audio_file_error = None;
         let mut file: Option<(TaggedFile, String)> = {
@@ -518,15 +836,9 @@ impl YoutubeRs {
                                     if let Some(tag) = tagged_file.primary_tag()
                                         && let Some(pic) = tag.pictures().first()
                                         && let Ok(dyn_img) = image::load_from_memory(pic.data())
+                                        && let Ok(picker) = images::picker()
                                     {
-                                        img = if let Ok(picker) = picker::Picker::from_query_stdio()
-                                        {
-                                            let protocole =
-                                                picker.new_resize_protocol(dyn_img.clone());
-                                            Some(protocole)
-                                        } else {
-                                            None
-                                        };
+                                        img.set(&picker, dyn_img);
                                     }
                                     Some((tagged_file, f.to_string_lossy().to_string()))
                                 }
@@ -554,256 +866,1709 @@ impl YoutubeRs {
                 None
             }
         };
-        let opts = MpvSpawnOptions::default();
-        let mut mpv = MpvIpc::spawn(&opts, audio_only)
-            .await
-            .context("Failed to spawn mpv process")
-            .expect("Could not spawn MPV");
-        let mpv_vol = mpv.observe_prop::<f64>("volume", 1.0).await;
-        if let Some(res) = response {
-            mpv.send_command(json!(["loadfile", Self::get_video_url(&res.get_id())]))
+        let mut extra_args = [self.audio_output_args(), crate::auth::mpv_args(&self.args)].concat();
+        if let Some(language) = &self.audio_language {
+            extra_args.push(format!(
+                "--ytdl-format={}",
+                crate::audio_tracks::ytdl_format(language, audio_only)
+            ));
+        }
+        match crate::locale::Locale::subtitles(&self.args) {
+            Ok(Some(language)) if language == "no" => extra_args.push("--sid=no".to_owned()),
+            Ok(Some(language)) => extra_args.push(format!("--slang={language}")),
+            _ => {}
+        }
+        extra_args.extend(self.args.mpv_options.iter().cloned());
+        let opts = MpvSpawnOptions {
+            mpv_path: self.args.mpv_path.clone(),
+            extra_args,
+            audio_command: self.args.audio_command.clone(),
+            ..Default::default()
+        };
+        // Back to a detached player, it already plays the queue
+        let reattached = self.attached.is_some();
+        let (mut mpv, mut first_media) = match self.attached.take() {
+            Some(attached) => attached,
+            // The terminal is still free to ask whether to try again
+            None => match Self::start_mpv(&opts, audio_only).await {
+                Some(mpv) => (mpv, None),
+                None => return,
+            },
+        };
+        let mut mpv_vol = mpv.observe_prop::<f64>("volume", 1.0).await;
+        // Resolve the streams of each media as mpv loads it, mpv waits for the answer
+        let mut load_hook_rx = if self.args.direct_stream {
+            Some(Self::add_load_hook(&mut mpv).await)
+        } else {
+            None
+        };
+        // The streams looked up over the network, the player keeps responding meanwhile
+        let (resolved_tx, mut resolved_rx) = tokio::sync::mpsc::unbounded_channel();
+        // Media the direct streams failed for, yt-dlp plays them
+        let mut direct_failed: Vec<String> = Vec::new();
+        // Seek to the time of the link once its media is loaded
+        let mut start_seek = match self.link.as_ref().and_then(|link| link.start) {
+            Some(start) if response.is_some() => Some((start, mpv.subscribe("file-loaded").await)),
+            _ => None,
+        };
+        // Resume the podcast episodes where they stopped
+        let mut episodes = EpisodeProgress::new(&mut mpv).await;
+        if reattached {
+            // mpv kept its playlist
+        } else if let Some(res) = response {
+            if let Err(e) = mpv
+                .send_command(json!(["loadfile", backend().watch_url(&res.get_id())]))
                 .await
-                .context("Failed to load media")
-                .expect("Could not send command to MPV");
+            {
+                self.banner = Some(ErrorBanner::new(
+                    format!("Could not load {}: {e}", res.get_name()),
+                    Some(Retry::Play(vec![res.clone()])),
+                ));
+            }
         } else if let Some(file) = &file {
-            mpv.send_command(json!(["loadfile", file.1]))
+            if let Err(e) = mpv.send_command(json!(["loadfile", file.1])).await {
+                self.banner = Some(ErrorBanner::new(
+                    format!("Could not load '{}': {e}", file.1),
+                    None,
+                ));
+            }
+        } else if !empty_player {
+            // The player opens empty, a media can still be searched
+            empty_player = true;
+            self.banner = Some(ErrorBanner::new(
+                audio_file_error.unwrap_or("No file found".to_string()),
+                None,
+            ));
+        }
+        if !reattached {
+            self.queue.load(&mut mpv).await;
+        }
+        let mut playlist_pos_rx = mpv.observe_prop::<i64>("playlist-pos", 0).await;
+        // What mpv plays at the first position of its playlist is in `first_media` once it moved past it
+        let mut previous_pos = if reattached {
+            mpv.get_prop::<i64>("playlist-pos")
                 .await
-                .context("Failed to load media")
-                .expect("Could not send command to MPV");
-        } else if empty_player {
-            // Pass
+                .unwrap_or_default()
         } else {
-            panic!(
-                "Error : {}",
-                audio_file_error.unwrap_or("No file found".to_string())
-            );
-        }
-        let (midi_volume_tx, midi_volume_rx) = std::sync::mpsc::channel();
-        let (midi_pause_tx, midi_pause_rx) = std::sync::mpsc::channel();
-        let _conn_in = if let Some(in_port) = opt_midi_in_port {
-            midi_in
-                .connect(
-                    in_port,
-                    "midir-read-input",
-                    move |_, message, midi_tx| {
-                        if message[0] == 224 {
-                            let volume_midi = u8_to_mpv_vol(message[2]);
-                            let _ = midi_tx.0.send(volume_midi);
-                        }
-                        if message[1] == 93 || message[1] == 94 {
-                            let _ = midi_tx.1.send(());
-                        }
-                    },
-                    (midi_volume_tx, midi_pause_tx),
-                )
-                .ok()
+            0
+        };
+        let mut end_file_rx = mpv.subscribe("end-file").await;
+        // Playlist entries that failed to load once and were tried again through other player clients
+        let mut retried: Vec<i64> = Vec::new();
+        let cast_target = if self.args.cast {
+            crate::cast::select().await.ok()
         } else {
             None
         };
-        let mut conn_out = if let Some(out_port) = opt_midi_out_port {
-            midi_out.connect(out_port, "midir-forward").ok()
+        let dlna_target = if self.args.dlna {
+            crate::dlna::select().await.ok()
         } else {
             None
         };
         let mut term = ratatui::init();
-        let time_rx = mpv.observe_prop::<f64>("playback-time", 0.0).await;
+        let mut time_rx = mpv.observe_prop::<f64>("playback-time", 0.0).await;
+        // Length of the DVR window for livestreams
+        let mut duration_rx = mpv.observe_prop::<f64>("duration", 0.0).await;
         let mut playback_time = 0.0;
         let mut vid_started = false;
         let loader = ["/", "|", "\\", "-"];
         let mut loader_idx = 0;
-        let mut pause_state = false;
+        let mut pause_state = reattached && mpv.get_prop::<bool>("pause").await.unwrap_or_default();
         let mut open_popup = false;
         let mut videos_list: Vec<(String, YoutubeResponse)> = Vec::new();
         let mut selected_list_item = ListState::default();
         let mut popup_query = String::new();
+        let mut playlist_tab = PlaylistTab::default();
+        let mut channel_page = ChannelPage::default();
+        let mut trending_tab = TrendingTab::default();
+        let mut explore_tab = ExploreTab::default();
+        if let Some(category) = self.trending {
+            trending_tab = TrendingTab {
+                open: true,
+                category,
+            };
+            self.show_trending(category, &mut videos_list, &mut selected_list_item)
+                .await;
+            open_popup = true;
+        }
+        let mut transcript_search = TranscriptSearch::default();
+        let mut summary = SummaryPane::default();
+        let mut transcript_view = TranscriptView::default();
+        let mut related = RelatedPane::default();
+        let mut save_pane = SavePane::default();
+        let mut uploads = UploadsPane::default();
+        let mut audio_tracks = AudioTracksPane::default();
+        let mut subtitles = SubtitlesPane::default();
+        let mut side_pane: Option<SidePane> = None;
+        let mut preview = SeekPreview::default();
+        let mut stats = StatsCache::default();
+        // Search entry selected and since when
+        let mut browsed: Option<(String, Instant)> = None;
+        let mut uploads_rx = self
+            .args
+            .notify_uploads
+            .map(|minutes| crate::uploads::spawn(Duration::from_secs(minutes * 60)));
+        // Media the related videos were already looked up for, and the ones queued that way
+        let mut autoplay_checked: Option<String> = None;
+        let mut autoplayed: Vec<String> = Vec::new();
+        let mut remote = RemoteHub::default();
+        if self.args.notify {
+            crate::notification::spawn(remote.remote());
+        }
+        if crate::config::config().inhibit_sleep != Some(false) {
+            crate::inhibit::spawn(remote.remote());
+        }
+        if let Some(path) = &self.args.now_playing {
+            crate::overlay::spawn(
+                remote.remote(),
+                path.clone(),
+                self.args.now_playing_template.clone(),
+            );
+        }
+        crate::control::spawn(remote.remote()).log_error("Could not listen for 'ytrs ctl'");
+        if let Some(ports) = midi_ports {
+            crate::midi::spawn(remote.remote(), ports);
+        }
+        if self.args.watch_clipboard {
+            crate::clipboard::spawn(remote.remote());
+        }
+        if let Some(target) = cast_target {
+            // mpv keeps playing muted so the TUI and the remotes still drive playback
+            let _ = mpv.set_prop("mute", true).await;
+            crate::cast::spawn(remote.remote(), target, audio_only);
+        }
+        if let Some(target) = dlna_target {
+            let _ = mpv.set_prop("mute", true).await;
+            crate::dlna::spawn(remote.remote(), target, audio_only);
+        }
+        if let Some(addr) = self.args.http {
+            crate::http::spawn(remote.remote(), addr)
+                .await
+                .log_error("Could not serve the HTTP remote");
+        }
+        if let Some(addr) = self.args.mpd {
+            crate::mpd::spawn(remote.remote(), addr)
+                .await
+                .log_error("Could not serve MPD");
+        }
+        if let Some(addr) = self.args.osc {
+            crate::osc::spawn(remote.remote(), addr)
+                .await
+                .log_error("Could not listen for OSC");
+        }
+        #[cfg(windows)]
+        crate::media_controls::spawn(remote.remote())
+            .log_error("Could not set up the media controls");
+        #[cfg(all(unix, not(target_os = "macos")))]
+        crate::mpris::spawn(remote.remote())
+            .await
+            .log_error("Could not register on MPRIS");
+        #[cfg(target_os = "macos")]
+        crate::now_playing::spawn(remote.remote()).log_error("Could not set up Now Playing");
+
+        let mut gamepad_rx = self.args.gamepad.then(crate::gamepad::spawn);
+        let mut terminal_events = EventStream::new();
+        let mut hangup = Hangup::listen();
+        // Left playing for 'ytrs attach', and whether the terminal is gone
+        let mut detached = false;
+        let mut hung_up = false;
+        // Drawn again on the next turn, set by anything shown changing
+        let mut redraw = true;
+        let mut drawn_second = None;
 
         // TUI Main Loop
         loop {
-            if let Some(v) = midi_volume_rx.try_iter().last() {
-                // v is from 0 to 130
-                mpv.send_command(json!(["set_property", "volume", v]))
-                    .await
-                    .unwrap();
+            summary.poll();
+            stats.poll();
+            for output in self.jobs.poll() {
+                self.apply_job(
+                    output,
+                    response,
+                    &mut img,
+                    &mut videos_list,
+                    &mut selected_list_item,
+                    &mut playlist_tab,
+                    &mut transcript_search,
+                    &mut transcript_view,
+                    &mut related,
+                    &mut save_pane,
+                    &mut audio_tracks,
+                );
             }
-            if let Ok(()) = midi_pause_rx.try_recv() {
-                pause_state = !pause_state;
-                let _ = mpv.set_prop("pause", pause_state).await;
+            if let Some(res) = response {
+                stats.request(&res.get_id());
             }
-            if !mpv.running().await {
-                break;
+            let selected_entry = selected_list_item
+                .selected()
+                .and_then(|i| videos_list.get(i))
+                .filter(|_| open_popup)
+                .map(|(_, entry)| entry.get_id());
+            match (&browsed, selected_entry) {
+                (Some((id, since)), Some(selected)) if *id == selected => {
+                    if since.elapsed() >= STATS_DELAY {
+                        stats.request(id);
+                    }
+                }
+                (_, selected) => browsed = selected.map(|id| (id, Instant::now())),
             }
-            if time_rx
-                .has_changed()
-                .expect("Error while checking if MPV time changed")
-            {
-                playback_time = *time_rx.borrow();
+            #[cfg(target_os = "macos")]
+            crate::now_playing::poll();
+            // The current media is either the first one or the queue entry before the position
+            let upcoming = (*playlist_pos_rx.borrow()).max(0) as usize;
+            if !mpv.running().await {
+                if !mpv.crashed() {
+                    break;
+                }
+                // mpv crashed or was killed, the session goes on in another one
+                ratatui::restore();
+                tracing::error!("mpv stopped unexpectedly");
+                println!("mpv stopped unexpectedly");
+                if !Confirm::new("Start it again where it stopped?")
+                    .with_default(true)
+                    .prompt()
+                    .unwrap_or_default()
+                {
+                    break;
+                }
+                let Some(restarted) = Self::start_mpv(&opts, audio_only).await else {
+                    break;
+                };
+                mpv = restarted;
+                term = ratatui::init();
+                // The observers and subscriptions followed the mpv that stopped
+                mpv_vol = mpv.observe_prop::<f64>("volume", 1.0).await;
+                time_rx = mpv.observe_prop::<f64>("playback-time", 0.0).await;
+                duration_rx = mpv.observe_prop::<f64>("duration", 0.0).await;
+                playlist_pos_rx = mpv.observe_prop::<i64>("playlist-pos", 0).await;
+                end_file_rx = mpv.subscribe("end-file").await;
+                episodes.save();
+                episodes = EpisodeProgress::new(&mut mpv).await;
+                if load_hook_rx.is_some() {
+                    load_hook_rx = Some(Self::add_load_hook(&mut mpv).await);
+                }
+                if self.args.cast || self.args.dlna {
+                    let _ = mpv.set_prop("mute", true).await;
+                }
+                audio_tracks.reload = None;
+                start_seek = Some((playback_time as u64, mpv.subscribe("file-loaded").await));
+                // The same playlist, from the media it was at
+                let first = if upcoming == 0 {
+                    response.clone()
+                } else {
+                    first_media.clone()
+                };
+                let entry = first
+                    .map(|media| media.url())
+                    .or_else(|| file.as_ref().map(|(_, path)| path.clone()));
+                if let Some(entry) = entry {
+                    let _ = mpv.send_command(json!(["loadfile", entry, "append"])).await;
+                }
+                self.queue.load(&mut mpv).await;
+                let _ = mpv.set_prop("playlist-pos", upcoming).await;
+                continue;
             }
+            // Seen, the wait below is for the next changes
+            playback_time = *time_rx.borrow_and_update();
+            let volume = *mpv_vol.borrow_and_update();
+            let duration = *duration_rx.borrow_and_update();
             if playback_time == 0.0 && !vid_started {
                 vid_started = true;
             }
-
-            let _ = term.draw(|f| {
-                self.draw(
-                    response,
-                    playback_time,
-                    vid_started,
-                    loader,
-                    &mut loader_idx,
-                    open_popup,
-                    &videos_list,
-                    &mut selected_list_item,
-                    &popup_query,
-                    &mut img,
-                    f,
-                    &mut file,
-                    empty_player,
-                    &mpv_vol.borrow(),
-                );
-            });
-            let event_happened = ratatui::crossterm::event::poll(Duration::from_millis(50)).ok();
-            if let Some(has_happened) = event_happened
-                && has_happened
-            {
-                let event = read().unwrap();
-                if open_popup {
-                    self.handle_popup_event(
-                        response,
+            // Keep counting down the premiere waited for, unless another message is shown
+            if let Some((video, start)) = &self.premiere {
+                if Utc::now() >= *start {
+                    // Premieres often go live a bit after their announced time, yt-dlp waits for them
+                    let _ = mpv
+                        .send_command(json!([
+                            "change-list",
+                            "ytdl-raw-options",
+                            "append",
+                            "wait-for-video=15"
+                        ]))
+                        .await;
+                    let mut video = video.clone();
+                    video.is_upcoming = false;
+                    video.is_live = true;
+                    self.play_entries(
                         &mut mpv,
-                        &mut open_popup,
-                        &mut videos_list,
-                        &mut selected_list_item,
-                        &mut popup_query,
+                        response,
                         &mut img,
-                        &event,
+                        vec![YoutubeResponse::Video(video)],
                     )
                     .await;
-                } else if let ControlFlow::Break(_) = self
-                    .handle_playback_event(
-                        response,
-                        &mut mpv,
-                        &mut pause_state,
-                        &mut open_popup,
-                        event,
-                        empty_player,
-                        &mut conn_out,
-                        &mpv_vol.borrow(),
-                    )
-                    .await
+                } else if self
+                    .toast
+                    .as_ref()
+                    .is_none_or(|(message, _)| message.starts_with("Premiere of"))
                 {
-                    break;
+                    self.toast = Some((
+                        crate::premiere::countdown(&video.name, *start),
+                        Instant::now(),
+                    ));
                 }
             }
-        }
-        mpv.quit().await;
-        ratatui::restore();
-    }
-
-    #[allow(clippy::too_many_arguments)]
-    async fn handle_popup_event(
-        &mut self,
-        response: &mut Option<YoutubeResponse>,
-        mpv: &mut MpvIpc,
-        open_popup: &mut bool,
-        videos_list: &mut Vec<(String, YoutubeResponse)>,
-        selected_list_item: &mut ListState,
-        popup_query: &mut String,
-        img: &mut Option<ratatui_image::protocol::StatefulProtocol>,
-        event: &ratatui::crossterm::event::Event,
-    ) {
-        if event.is_key_press()
-            && let KeyCode::Char(ch) = event.as_key_event().unwrap().code
-        {
-            popup_query.push(ch);
-        }
-        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Backspace {
-            if event.as_key_event().unwrap().modifiers == KeyModifiers::CONTROL {
-                popup_query.clear();
-            } else {
-                popup_query.pop();
+            if let Some((start, loaded_rx)) = &mut start_seek
+                && loaded_rx.try_recv().is_ok()
+            {
+                let _ = mpv.send_command(json!(["seek", *start, "absolute"])).await;
+                start_seek = None;
             }
-        }
-        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Tab {
-            self.api = match self.api {
-                Some(YoutubeAPI::Music) => Some(YoutubeAPI::Video),
-                Some(YoutubeAPI::Video) => Some(YoutubeAPI::Music),
-                None => None,
-            };
-        }
-        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Up {
-            selected_list_item.select_previous();
-        }
-        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Down {
-            selected_list_item.select_next();
-        }
-        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Esc {
-            *open_popup = !*open_popup;
-        }
-        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Enter {
-            if let Some(selected) = selected_list_item.selected()
-                && popup_query.is_empty()
+            if let Some((time, loaded_rx)) = &mut audio_tracks.reload
+                && loaded_rx.try_recv().is_ok()
             {
-                if let Some(vid) = videos_list.get(selected).map(|v| v.1.clone()) {
-                    popup_query.clear();
-                    mpv.send_command(json!(["loadfile", Self::get_video_url(&vid.get_id())]))
-                        .await
-                        .context("Failed to load media")
-                        .expect("Could not send command to MPV");
-                    if let Ok(thumbnail) = Self::fetch_yt_thumbnail(&vid.get_id(), &self.args).await
+                let _ = mpv.send_command(json!(["seek", *time, "absolute"])).await;
+                audio_tracks.reload = None;
+            }
+            // Follow mpv when it moves on to the next queued media
+            if playlist_pos_rx.has_changed().unwrap_or_default() {
+                let pos = *playlist_pos_rx.borrow_and_update();
+                // Going back to the first media, which is not in the queue
+                let back_to_first = if pos == 0
+                    && let Some(first) = &first_media
+                    && let Ok(path) = mpv.get_prop::<String>("path").await
+                    && path.contains(&first.get_id())
+                    && response.as_ref().map(YoutubeResponse::get_id) != Some(first.get_id())
+                {
+                    Some(first.clone())
+                } else {
+                    None
+                };
+                if pos > 0 && previous_pos == 0 {
+                    first_media = response.clone();
+                }
+                previous_pos = pos;
+                let next = if pos > 0 {
+                    self.queue.at(pos as usize).cloned()
+                } else {
+                    back_to_first
+                };
+                if let Some(next) = next {
+                    img.clear();
+                    self.request_thumbnail(&next.get_id());
+                    *response = Some(next);
+                    // The panes describe the previous media
+                    if matches!(
+                        side_pane,
+                        Some(
+                            SidePane::Summary
+                                | SidePane::Transcript
+                                | SidePane::AudioTracks
+                                | SidePane::Subtitles
+                        )
+                    ) {
+                        side_pane = None;
+                    }
+                    preview.close();
+                    if side_pane == Some(SidePane::Related)
+                        && let Some(res) = response
                     {
-                        *img = if let Ok(picker) = picker::Picker::from_query_stdio() {
-                            let protocol = picker.new_resize_protocol(thumbnail.clone());
-                            Some(protocol)
-                        } else {
-                            None
+                        self.request_related(&mut related, res.get_id());
+                    }
+                }
+            }
+            episodes
+                .update(&mut mpv, response.as_ref(), playback_time, duration)
+                .await;
+            // The queue ran out, follow up with what YouTube recommends
+            if self.args.autoplay_related
+                && let Some(res) = response
+                && upcoming >= self.queue.len()
+                && autoplay_checked.as_ref() != Some(&res.get_id())
+            {
+                let id = res.get_id();
+                autoplayed.push(id.clone());
+                // Enqueued like from a remote, once found
+                let commands = remote.remote().commands;
+                let skipped = autoplayed.clone();
+                let related_to = id.clone();
+                self.jobs.spawn("Finding what plays next", async move {
+                    JobOutput::Autoplay(
+                        async {
+                            if let Some(next) = crate::related::related(&related_to)
+                                .await?
+                                .into_iter()
+                                .find(|item| !skipped.contains(&item.get_id()))
+                            {
+                                commands.send(PlayerCommand::Enqueue(Box::new(next)))?;
+                            }
+                            Ok(())
+                        }
+                        .await,
+                    )
+                });
+                autoplay_checked = Some(id);
+            }
+
+            remote.publish_queue(
+                self.queue
+                    .after((*playlist_pos_rx.borrow()).max(0) as usize),
+            );
+            remote.publish(Self::now_playing(
+                response,
+                &file,
+                playback_time,
+                pause_state,
+                volume,
+            ));
+
+            // Playback time moves many times a second, the player shows whole seconds
+            let second = playback_time as u64;
+            if redraw || drawn_second != Some(second) {
+                redraw = false;
+                drawn_second = Some(second);
+                // The previewed frame takes the place of the thumbnail
+                let shown_img = if !preview.frame.is_empty() {
+                    &mut preview.frame
+                } else {
+                    &mut img
+                };
+                let _ = term.draw(|f| {
+                    self.draw(
+                        response,
+                        playback_time,
+                        duration,
+                        vid_started,
+                        loader,
+                        &mut loader_idx,
+                        open_popup,
+                        &videos_list,
+                        &mut selected_list_item,
+                        &popup_query,
+                        &mut playlist_tab,
+                        &channel_page,
+                        &trending_tab,
+                        &mut explore_tab,
+                        shown_img,
+                        f,
+                        &mut file,
+                        empty_player,
+                        &volume,
+                        &mut transcript_search,
+                        &summary,
+                        &mut transcript_view,
+                        &mut related,
+                        &mut save_pane,
+                        &mut uploads,
+                        &mut audio_tracks,
+                        &mut subtitles,
+                        side_pane,
+                        &stats,
+                    );
+                });
+            }
+            let browsing = open_popup
+                || transcript_search.open
+                || transcript_view.browsing
+                || matches!(
+                    side_pane,
+                    Some(
+                        SidePane::Related
+                            | SidePane::Save
+                            | SidePane::Uploads
+                            | SidePane::AudioTracks
+                            | SidePane::Subtitles
+                    )
+                );
+            // What moves on its own is drawn again at this pace, the rest when it changes
+            let tick = if self.jobs.status().is_some() || !vid_started {
+                ANIMATION_TICK
+            } else {
+                IDLE_TICK
+            };
+            let wake = tokio::select! {
+                Some(Ok(event)) = terminal_events.next() => Wake::Terminal(event),
+                Some(button) = async { gamepad_rx.as_mut()?.recv().await } => Wake::Pad(button),
+                Some(command) = remote.recv() => Wake::Command(command),
+                Some(ended) = end_file_rx.recv() => Wake::Ended(ended),
+                Some(hook) = async { load_hook_rx.as_mut()?.recv().await } => Wake::Hook(hook),
+                Some(video) = async { uploads_rx.as_mut()?.recv().await } => Wake::Upload(video),
+                Some(resolved) = resolved_rx.recv() => resolved,
+                Some(output) = self.jobs.done() => Wake::Job(output),
+                Some(token) = async { summary.tokens.as_mut()?.recv().await } => Wake::Token(token),
+                () = changed(&time_rx) => Wake::Playback,
+                () = changed(&mpv_vol) => Wake::Changed,
+                () = changed(&duration_rx) => Wake::Changed,
+                () = changed(&playlist_pos_rx) => Wake::Changed,
+                () = stats.fetched() => Wake::Changed,
+                () = img.resized() => Wake::Changed,
+                () = preview.frame.resized() => Wake::Changed,
+                () = mpv.stopped() => Wake::Changed,
+                () = hangup.recv() => Wake::Hangup,
+                () = tokio::time::sleep(tick) => Wake::Changed,
+            };
+            // The time alone redraws once its second changes
+            if !matches!(wake, Wake::Playback) {
+                redraw = true;
+            }
+            let event = match wake {
+                Wake::Terminal(event) => Some(event),
+                Wake::Pad(button) => match crate::gamepad::action(button, browsing) {
+                    Some(PadAction::Key(code)) => Some(Event::Key(KeyEvent::from(code))),
+                    Some(PadAction::Volume(step)) => {
+                        let _ = mpv.send_command(json!(["add", "volume", step])).await;
+                        None
+                    }
+                    None => None,
+                },
+                Wake::Command(command) => {
+                    if let ControlFlow::Break(_) = self
+                        .apply_remote_command(&mut mpv, command, &mut pause_state, upcoming)
+                        .await
+                    {
+                        break;
+                    }
+                    None
+                }
+                Wake::Ended(ended) => {
+                    self.retry_failed_entry(
+                        &mut mpv,
+                        ended,
+                        &mut direct_failed,
+                        &mut retried,
+                        &resolved_tx,
+                    )
+                    .await;
+                    None
+                }
+                Wake::Hook(hook) => {
+                    let hook_id = hook["hook_id"].clone();
+                    // The direct streams only have the default audio track, yt-dlp picks the others
+                    let id = match self.audio_language {
+                        None => Self::direct_stream_id(&mut mpv, &direct_failed).await,
+                        Some(_) => None,
+                    };
+                    match id {
+                        // mpv waits on the hook until the stream is found
+                        Some(id) => {
+                            let resolved = resolved_tx.clone();
+                            tokio::spawn(async move {
+                                let url = backend().direct_stream(&id, audio_only).await.ok();
+                                let _ = resolved.send(Wake::DirectStream { hook_id, url });
+                            });
+                        }
+                        None => {
+                            let _ = mpv.send_command(json!(["hook-ack", hook_id])).await;
+                        }
+                    }
+                    None
+                }
+                Wake::DirectStream { hook_id, url } => {
+                    if let Some(url) = url {
+                        let _ = mpv.set_prop("stream-open-filename", url).await;
+                    }
+                    let _ = mpv.send_command(json!(["hook-ack", hook_id])).await;
+                    None
+                }
+                Wake::Fallback { entry, stream } => {
+                    self.play_fallback(&mut mpv, entry, stream).await;
+                    None
+                }
+                Wake::Upload(video) => {
+                    self.toast = Some((
+                        format!("New upload: {} ('n' to see it)", video.name),
+                        Instant::now(),
+                    ));
+                    uploads.push(video);
+                    None
+                }
+                Wake::Job(output) => {
+                    self.apply_job(
+                        output,
+                        response,
+                        &mut img,
+                        &mut videos_list,
+                        &mut selected_list_item,
+                        &mut playlist_tab,
+                        &mut transcript_search,
+                        &mut transcript_view,
+                        &mut related,
+                        &mut save_pane,
+                        &mut audio_tracks,
+                    );
+                    None
+                }
+                Wake::Token(token) => {
+                    summary.text.push_str(&token);
+                    None
+                }
+                Wake::Hangup => {
+                    let first = if upcoming == 0 {
+                        response.clone()
+                    } else {
+                        first_media.clone()
+                    };
+                    let file = file.as_ref().map(|(_, path)| path.clone());
+                    detached = self
+                        .detach(&mut mpv, response, first, file, audio_only)
+                        .await
+                        .is_ok();
+                    hung_up = true;
+                    break;
+                }
+                Wake::Playback | Wake::Changed => None,
+            };
+            if let Some(typed) = event {
+                // Typed as is in the text fields, remapped for the player commands
+                let event = crate::config::remap(typed.clone());
+                if let Some(action) = self
+                    .banner
+                    .as_ref()
+                    .zip(event.as_ref())
+                    .and_then(|(banner, event)| banner.handle_event(event))
+                {
+                    self.banner = None;
+                    match action {
+                        BannerAction::Retry(Retry::Search(query)) => {
+                            // Searched again like when it was typed
+                            popup_query = query;
+                            open_popup = true;
+                            let enter = Event::Key(KeyEvent::from(KeyCode::Enter));
+                            self.handle_popup_event(
+                                response,
+                                &mut mpv,
+                                &mut open_popup,
+                                &mut videos_list,
+                                &mut selected_list_item,
+                                &mut popup_query,
+                                &mut playlist_tab,
+                                &mut channel_page,
+                                &mut trending_tab,
+                                &mut explore_tab,
+                                &mut img,
+                                &enter,
+                            )
+                            .await;
+                        }
+                        BannerAction::Retry(Retry::Play(entries)) => {
+                            self.play_entries(&mut mpv, response, &mut img, entries)
+                                .await;
+                        }
+                        BannerAction::Retry(Retry::Thumbnail(id)) => {
+                            // Another media may be playing meanwhile
+                            if response.as_ref().map(YoutubeResponse::get_id) == Some(id.clone()) {
+                                self.request_thumbnail(&id);
+                            }
+                        }
+                        BannerAction::Dismiss => {}
+                    }
+                } else if transcript_search.open {
+                    self.handle_transcript_event(
+                        response,
+                        &mut mpv,
+                        &mut transcript_search,
+                        &typed,
+                    )
+                    .await;
+                } else if open_popup {
+                    if let Some((name, entries)) = self
+                        .handle_popup_event(
+                            response,
+                            &mut mpv,
+                            &mut open_popup,
+                            &mut videos_list,
+                            &mut selected_list_item,
+                            &mut popup_query,
+                            &mut playlist_tab,
+                            &mut channel_page,
+                            &mut trending_tab,
+                            &mut explore_tab,
+                            &mut img,
+                            &typed,
+                        )
+                        .await
+                    {
+                        // The downloads prompt and print in the terminal, mpv keeps playing
+                        ratatui::restore();
+                        if let Err(e) = self.download_playlist(&name, &entries).await {
+                            println!("Could not download '{name}': {e}");
+                            tracing::warn!("Could not download '{name}': {e}");
+                        }
+                        let _ = InquireText::new("Press Enter to go back to the player").prompt();
+                        term = ratatui::init();
+                    }
+                } else if let Some(event) = event {
+                    if side_pane == Some(SidePane::Transcript)
+                        && Self::handle_transcript_view_event(
+                            &mut mpv,
+                            &mut transcript_view,
+                            &event,
+                        )
+                        .await
+                    {
+                        // Used by the transcript pane
+                    } else if side_pane == Some(SidePane::Related)
+                        && self
+                            .handle_related_event(
+                                response,
+                                &mut mpv,
+                                &mut img,
+                                &mut related,
+                                &event,
+                            )
+                            .await
+                    {
+                        // Used by the related pane
+                    } else if side_pane == Some(SidePane::Save)
+                        && let Some(res) = response
+                        && self
+                            .handle_save_event(res, &mut save_pane, &mut side_pane, &event)
+                            .await
+                    {
+                        // Used by the save pane
+                    } else if side_pane == Some(SidePane::Uploads)
+                        && self
+                            .handle_uploads_event(
+                                response,
+                                &mut mpv,
+                                &mut img,
+                                &mut uploads,
+                                &event,
+                            )
+                            .await
+                    {
+                        if let Some(entry) = uploads.download.take() {
+                            // The downloads prompt and print in the terminal, mpv keeps playing
+                            ratatui::restore();
+                            if let Err(e) = self.download_entry(&entry).await {
+                                println!("Could not download '{}': {e}", entry.get_name());
+                                tracing::warn!("Could not download '{}': {e}", entry.get_name());
+                            }
+                            let _ =
+                                InquireText::new("Press Enter to go back to the player").prompt();
+                            term = ratatui::init();
+                        }
+                    } else if side_pane == Some(SidePane::AudioTracks)
+                        && self
+                            .handle_audio_tracks_event(
+                                &mut mpv,
+                                audio_only,
+                                &mut audio_tracks,
+                                &event,
+                            )
+                            .await
+                    {
+                        // Used by the audio tracks pane
+                    } else if side_pane == Some(SidePane::Subtitles)
+                        && self
+                            .handle_subtitles_event(&mut mpv, &mut subtitles, &event)
+                            .await
+                    {
+                        // Used by the subtitles pane
+                    } else if self
+                        .handle_seek_preview_event(
+                            response,
+                            &mut mpv,
+                            playback_time,
+                            &mut preview,
+                            &event,
+                        )
+                        .await
+                    {
+                        // Used by the seek preview
+                    } else if self.handle_episode_event(response, &mut episodes, &event) {
+                        // Marked the episode played
+                    } else if event.is_key_press()
+                        && event.as_key_event().unwrap().code == KeyCode::Char('D')
+                    {
+                        let first = if upcoming == 0 {
+                            response.clone()
+                        } else {
+                            first_media.clone()
+                        };
+                        let file = file.as_ref().map(|(_, path)| path.clone());
+                        match self
+                            .detach(&mut mpv, response, first, file, audio_only)
+                            .await
+                        {
+                            Ok(()) => {
+                                detached = true;
+                                break;
+                            }
+                            Err(e) => {
+                                self.toast =
+                                    Some((format!("Could not detach: {e}"), Instant::now()));
+                            }
+                        }
+                    } else if let ControlFlow::Break(_) = self
+                        .handle_playback_event(
+                            response,
+                            &mut mpv,
+                            &mut pause_state,
+                            &mut open_popup,
+                            event,
+                            empty_player,
+                            &mut transcript_search,
+                            &mut summary,
+                            &mut transcript_view,
+                            &mut related,
+                            &mut audio_tracks,
+                            &mut subtitles,
+                            &mut side_pane,
+                        )
+                        .await
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+        episodes.save();
+        if !detached {
+            mpv.quit().await;
+        }
+        // Nothing to restore or print to once the terminal closed
+        if !hung_up {
+            ratatui::restore();
+        }
+        if detached && !hung_up {
+            println!("The player keeps playing, 'ytrs attach' brings it back");
+        }
+    }
+
+    /// Age-restricted or embed-blocked media fail to load, try them once more through other player clients,
+    /// then through the Invidious or Piped instance
+    async fn retry_failed_entry(
+        &mut self,
+        mpv: &mut MpvIpc,
+        ended: serde_json::Value,
+        direct_failed: &mut Vec<String>,
+        retried: &mut Vec<i64>,
+        resolved: &tokio::sync::mpsc::UnboundedSender<Wake>,
+    ) {
+        let Some(entry) = ended["playlist_entry_id"].as_i64() else {
+            return;
+        };
+        if ended["reason"] != "error" {
+            return;
+        }
+        let playlist = mpv
+            .get_prop::<Vec<serde_json::Value>>("playlist")
+            .await
+            .unwrap_or_default();
+        let Some(index) = playlist.iter().position(|e| e["id"] == entry) else {
+            return;
+        };
+        let video_id = playlist[index]["filename"]
+            .as_str()
+            .and_then(YoutubeLink::parse)
+            .and_then(|link| link.video_id);
+        if self.args.direct_stream
+            && let Some(id) = &video_id
+            && !direct_failed.contains(id)
+        {
+            direct_failed.push(id.clone());
+            let _ = mpv
+                .send_command(json!(["playlist-play-index", index]))
+                .await;
+            return;
+        }
+        if !retried.contains(&entry) {
+            if retried.is_empty() {
+                let _ = mpv
+                    .send_command(json!([
+                        "change-list",
+                        "ytdl-raw-options",
+                        "append",
+                        crate::auth::fallback_mpv_option()
+                    ]))
+                    .await;
+            }
+            retried.push(entry);
+            let _ = mpv
+                .send_command(json!(["playlist-play-index", index]))
+                .await;
+            self.toast = Some((
+                "Retrying through another player ...".to_owned(),
+                Instant::now(),
+            ));
+            return;
+        }
+        match video_id {
+            Some(id) if crate::fallback::enabled() => {
+                let resolved = resolved.clone();
+                tokio::spawn(async move {
+                    let stream = crate::fallback::stream_url(&id).await.ok();
+                    let _ = resolved.send(Wake::Fallback { entry, stream });
+                });
+            }
+            _ => self.play_fallback(mpv, entry, None).await,
+        }
+    }
+
+    /// Play the playlist `entry` from the `stream` the Invidious or Piped instance found
+    async fn play_fallback(&mut self, mpv: &mut MpvIpc, entry: i64, stream: Option<String>) {
+        let playlist = mpv
+            .get_prop::<Vec<serde_json::Value>>("playlist")
+            .await
+            .unwrap_or_default();
+        // The entry may have been removed meanwhile
+        let index = playlist.iter().position(|e| e["id"] == entry);
+        if let Some(stream) = stream
+            && let Some(index) = index
+        {
+            // Replace the entry in place so the queue keeps its order
+            let _ = mpv
+                .send_command(json!(["loadfile", stream, "append"]))
+                .await;
+            let _ = mpv
+                .send_command(json!(["playlist-move", playlist.len(), index]))
+                .await;
+            let _ = mpv
+                .send_command(json!(["playlist-remove", index + 1]))
+                .await;
+            let _ = mpv
+                .send_command(json!(["playlist-play-index", index]))
+                .await;
+            self.toast = Some((
+                "Playing through the fallback instance ...".to_owned(),
+                Instant::now(),
+            ));
+        } else {
+            self.toast = Some((
+                "Could not play the media, even through another player".to_owned(),
+                Instant::now(),
+            ));
+        }
+    }
+
+    /// Start mpv, asking whether to try again when it fails
+    async fn start_mpv(opts: &MpvSpawnOptions, audio_only: bool) -> Option<MpvIpc> {
+        loop {
+            #[cfg(feature = "libmpv")]
+            let started = MpvIpc::embed(opts, audio_only).await;
+            #[cfg(not(feature = "libmpv"))]
+            let started = MpvIpc::spawn(opts, audio_only).await;
+            match started {
+                Ok(mpv) => return Some(mpv),
+                Err(e) => {
+                    tracing::error!("Could not start mpv: {e:#}");
+                    println!("Could not start mpv: {e:#}");
+                    if !Confirm::new("Try again?")
+                        .with_default(true)
+                        .prompt()
+                        .unwrap_or_default()
+                    {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Have mpv wait on each media it loads for the direct streams to be resolved
+    async fn add_load_hook(mpv: &mut MpvIpc) -> tokio::sync::mpsc::Receiver<serde_json::Value> {
+        let hook_rx = mpv.subscribe("hook").await;
+        let _ = mpv
+            .send_command(json!([
+                "hook-add",
+                "on_load",
+                LOAD_HOOK_ID,
+                LOAD_HOOK_PRIORITY
+            ]))
+            .await;
+        hook_rx
+    }
+
+    /// mpv arguments sending the audio to the output picked on the command line
+    fn audio_output_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(fifo) = &self.args.snapcast {
+            // The default sample format of snapserver
+            args.extend(crate::mpv::pcm_output_args(&fifo.to_string_lossy()));
+        }
+        if let Some(device) = &self.args.audio_device {
+            args.push(format!("--audio-device={device}"));
+        }
+        args
+    }
+
+    fn now_playing(
+        response: &Option<YoutubeResponse>,
+        file: &Option<(TaggedFile, String)>,
+        playback_time: f64,
+        paused: bool,
+        volume: f64,
+    ) -> NowPlaying {
+        let mut now_playing = NowPlaying {
+            position: playback_time,
+            paused,
+            volume,
+            ..Default::default()
+        };
+        if let Some(res) = response {
+            let id = res.get_id();
+            now_playing.title = res.get_name();
+            now_playing.artist = res.get_author();
+            now_playing.duration = res.get_duration() as f64;
+            now_playing.thumbnail = Some(format!("https://img.youtube.com/vi/{id}/hqdefault.jpg"));
+            now_playing.url = Some(Self::get_video_url(&id));
+        } else if let Some((tagged_file, path)) = file {
+            let tag = tagged_file.primary_tag();
+            now_playing.title = tag
+                .and_then(|tag| tag.title().map(|t| t.to_string()))
+                .unwrap_or_else(|| {
+                    PathBuf::from(path)
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string()
+                });
+            now_playing.artist = tag
+                .and_then(|tag| tag.artist().map(|a| a.to_string()))
+                .unwrap_or_default();
+            now_playing.duration = tagged_file.properties().duration().as_secs_f64();
+        }
+        now_playing
+    }
+
+    /// `upcoming` is the index in the queue of the first media after the current one
+    async fn apply_remote_command(
+        &mut self,
+        mpv: &mut MpvIpc,
+        command: PlayerCommand,
+        pause_state: &mut bool,
+        upcoming: usize,
+    ) -> ControlFlow<()> {
+        match command {
+            PlayerCommand::Play | PlayerCommand::Pause | PlayerCommand::TogglePause => {
+                *pause_state = match command {
+                    PlayerCommand::Play => false,
+                    PlayerCommand::Pause => true,
+                    _ => !*pause_state,
+                };
+                let _ = mpv.set_prop("pause", *pause_state).await;
+            }
+            PlayerCommand::Next => {
+                let _ = mpv.send_command(json!(["playlist-next"])).await;
+            }
+            PlayerCommand::Previous => {
+                let _ = mpv.send_command(json!(["playlist-prev"])).await;
+            }
+            PlayerCommand::Stop => return ControlFlow::Break(()),
+            PlayerCommand::SeekRelative(offset) => {
+                let _ = mpv.send_command(json!(["seek", offset, "relative"])).await;
+            }
+            PlayerCommand::SeekAbsolute(position) => {
+                let _ = mpv
+                    .send_command(json!(["seek", position, "absolute"]))
+                    .await;
+            }
+            PlayerCommand::SetVolume(volume) => {
+                let _ = mpv.set_prop("volume", volume.clamp(0.0, 130.0)).await;
+            }
+            PlayerCommand::Enqueue(media) => {
+                self.toast = Some((format!("Queued {}", media.get_name()), Instant::now()));
+                let _ = self.queue.push(mpv, *media).await;
+            }
+            PlayerCommand::Dequeue(index) => {
+                self.queue.remove(mpv, upcoming + index).await;
+            }
+            PlayerCommand::ClearQueue => self.queue.clear(mpv).await,
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Replace what mpv plays with `entries`, the first one starts right away
+    async fn play_entries(
+        &mut self,
+        mpv: &mut MpvIpc,
+        response: &mut Option<YoutubeResponse>,
+        img: &mut PlayerImage,
+        entries: Vec<YoutubeResponse>,
+    ) {
+        let mut entries = entries.into_iter();
+        let Some(first) = entries.next() else {
+            return;
+        };
+        // Picking another media cancels the premiere waited for
+        self.premiere = None;
+        if let YoutubeResponse::Video(video) = &first
+            && video.is_upcoming
+        {
+            match self.premiere_start(video).await {
+                Ok(Some(start)) => {
+                    self.toast = Some((
+                        crate::premiere::countdown(&video.name, start),
+                        Instant::now(),
+                    ));
+                    self.premiere = Some((video.clone(), start));
+                }
+                _ => {
+                    self.toast = Some((
+                        format!(
+                            "'{}' is a premiere scheduled for an unknown time",
+                            video.name
+                        ),
+                        Instant::now(),
+                    ));
+                }
+            }
+            return;
+        }
+        if let Err(e) = mpv
+            .send_command(json!(["loadfile", backend().watch_url(&first.get_id())]))
+            .await
+        {
+            let mut retried = vec![first.clone()];
+            retried.extend(entries);
+            self.banner = Some(ErrorBanner::new(
+                format!("Could not load {}: {e}", first.get_name()),
+                Some(Retry::Play(retried)),
+            ));
+            return;
+        }
+        img.clear();
+        self.request_thumbnail(&first.get_id());
+        *response = Some(first);
+        // loadfile replaced the whole mpv playlist
+        self.queue.forget();
+        for entry in entries {
+            let _ = self.queue.push(mpv, entry).await;
+        }
+    }
+
+    /// Show what a background job found, or raise its failure
+    #[allow(clippy::too_many_arguments)]
+    fn apply_job(
+        &mut self,
+        output: JobOutput,
+        response: &Option<YoutubeResponse>,
+        img: &mut PlayerImage,
+        videos_list: &mut Vec<(String, YoutubeResponse)>,
+        selected_list_item: &mut ListState,
+        playlist_tab: &mut PlaylistTab,
+        transcript_search: &mut TranscriptSearch,
+        transcript_view: &mut TranscriptView,
+        related: &mut RelatedPane,
+        save_pane: &mut SavePane,
+        audio_tracks: &mut AudioTracksPane,
+    ) {
+        match output {
+            JobOutput::Search {
+                found: Ok(found), ..
+            } => *videos_list = found,
+            JobOutput::Search {
+                query,
+                source,
+                found: Err(e),
+            } => {
+                self.banner = Some(ErrorBanner::new(
+                    format!("Could not search {source}: {e}"),
+                    Some(Retry::Search(query)),
+                ));
+            }
+            JobOutput::Playlists {
+                found: Ok(found), ..
+            } => {
+                playlist_tab.found = found;
+                playlist_tab.previewed = None;
+                videos_list.clear();
+            }
+            JobOutput::Playlists {
+                query,
+                found: Err(e),
+            } => {
+                self.banner = Some(ErrorBanner::new(
+                    format!("Could not search the playlists: {e}"),
+                    Some(Retry::Search(query)),
+                ));
+            }
+            JobOutput::Playlist {
+                found: Ok((name, entries)),
+                ..
+            } => {
+                *videos_list = entries;
+                selected_list_item.select_first();
+                playlist_tab.previewed = Some(name);
+                playlist_tab.marked.clear();
+            }
+            JobOutput::Playlist {
+                label,
+                found: Err(e),
+            } => {
+                self.toast = Some((format!("Could not open {label}: {e}"), Instant::now()));
+            }
+            // Another media may be playing by now
+            JobOutput::Thumbnail { video_id, image } => {
+                if response.as_ref().map(YoutubeResponse::get_id) == Some(video_id.clone()) {
+                    self.show_thumbnail(img, &video_id, image);
+                }
+            }
+            JobOutput::Related { video_id, found } => related.loaded(&video_id, found),
+            JobOutput::Cues {
+                video_id,
+                search: true,
+                found,
+            } => transcript_search.loaded(&video_id, found),
+            JobOutput::Cues {
+                video_id, found, ..
+            } => transcript_view.loaded(&video_id, found),
+            JobOutput::AudioTracks { video_id, found } => audio_tracks.loaded(&video_id, found),
+            JobOutput::AccountPlaylists(playlists) => save_pane.loaded(playlists),
+            JobOutput::Autoplay(enqueued) => {
+                enqueued.log_error("Could not autoplay a related video")
+            }
+        }
+    }
+
+    /// Fetch the thumbnail of `video_id` in the background, it is shown by `thumbnail_protocol`
+    fn request_thumbnail(&mut self, video_id: &str) {
+        let video_id = video_id.to_owned();
+        let args = self.args.clone();
+        self.jobs.spawn("Loading the thumbnail", async move {
+            JobOutput::Thumbnail {
+                image: Self::fetch_yt_thumbnail(&video_id, &args).await,
+                video_id,
+            }
+        });
+    }
+
+    /// Fetch the videos related to `video_id` in the background, unless `related` lists them
+    fn request_related(&mut self, related: &mut RelatedPane, video_id: String) {
+        if !related.request(&video_id) {
+            return;
+        }
+        self.jobs.spawn("Loading the related videos", async move {
+            JobOutput::Related {
+                found: crate::related::related(&video_id).await,
+                video_id,
+            }
+        });
+    }
+
+    /// Fetch the cues of `video_id` in the background, for the transcript search when `search` is set
+    fn request_cues(&mut self, video_id: String, search: bool) {
+        let args = self.args.clone();
+        self.jobs.spawn("Loading the transcript", async move {
+            JobOutput::Cues {
+                found: Self::fetch_video_cues(&args, &video_id).await,
+                video_id,
+                search,
+            }
+        });
+    }
+
+    /// Fetch the playlists of the account in the background, see `SavePane::fetch`
+    fn request_account_playlists(&mut self, video_id: String, refresh: bool) {
+        self.jobs
+            .spawn("Loading the playlists of the account", async move {
+                JobOutput::AccountPlaylists(SavePane::fetch(video_id, refresh).await)
+            });
+    }
+
+    /// Show the fetched thumbnail of `video_id` in the image protocol of the terminal, a failure
+    /// raises the error banner
+    fn show_thumbnail(
+        &mut self,
+        img: &mut PlayerImage,
+        video_id: &str,
+        thumbnail: Result<DynamicImage>,
+    ) {
+        match thumbnail.and_then(|thumbnail| Ok((images::picker()?, thumbnail))) {
+            Ok((picker, thumbnail)) => img.set(&picker, thumbnail),
+            Err(e) => {
+                self.banner = Some(ErrorBanner::new(
+                    format!("Could not show the thumbnail: {e}"),
+                    Some(Retry::Thumbnail(video_id.to_owned())),
+                ));
+            }
+        }
+    }
+
+    /// Returns the name and entries of a playlist to download
+    #[allow(clippy::too_many_arguments)]
+    async fn handle_popup_event(
+        &mut self,
+        response: &mut Option<YoutubeResponse>,
+        mpv: &mut MpvIpc,
+        open_popup: &mut bool,
+        videos_list: &mut Vec<(String, YoutubeResponse)>,
+        selected_list_item: &mut ListState,
+        popup_query: &mut String,
+        playlist_tab: &mut PlaylistTab,
+        channel_page: &mut ChannelPage,
+        trending_tab: &mut TrendingTab,
+        explore_tab: &mut ExploreTab,
+        img: &mut PlayerImage,
+        event: &ratatui::crossterm::event::Event,
+    ) -> Option<(String, Vec<YoutubeResponse>)> {
+        let ctrl = event
+            .as_key_event()
+            .is_some_and(|key| key.modifiers.contains(KeyModifiers::CONTROL));
+        // Space marks the entries of a playlist instead of being typed
+        let marking = playlist_tab.previewed.is_some()
+            && channel_page.channel.is_none()
+            && popup_query.is_empty();
+        if event.is_key_press()
+            && let KeyCode::Char(ch) = event.as_key_event().unwrap().code
+            && !ctrl
+            && !trending_tab.open
+        {
+            if ch == ' ' && marking {
+                if let Some(selected) = selected_list_item.selected()
+                    && !playlist_tab.marked.remove(&selected)
+                {
+                    playlist_tab.marked.insert(selected);
+                }
+            } else {
+                popup_query.push(ch);
+            }
+        }
+        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Backspace {
+            if ctrl {
+                popup_query.clear();
+            } else {
+                popup_query.pop();
+            }
+        }
+        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Tab {
+            // Music, its charts and genres, Video, the playlists then the trending entries
+            let left_tab = trending_tab.open || explore_tab.open;
+            if trending_tab.open {
+                trending_tab.open = false;
+                self.api = self.api.map(|_| YoutubeAPI::Music);
+            } else if playlist_tab.open {
+                playlist_tab.open = false;
+                trending_tab.open = true;
+            } else if explore_tab.open {
+                explore_tab.open = false;
+                self.api = Some(YoutubeAPI::Video);
+            } else {
+                match self.api {
+                    Some(YoutubeAPI::Music) => {
+                        explore_tab.open = true;
+                        if explore_tab.levels.is_empty() {
+                            explore_tab.reset();
+                        }
+                    }
+                    _ => playlist_tab.open = true,
+                }
+            }
+            let left_channel = channel_page.close().is_some();
+            if playlist_tab.previewed.take().is_some()
+                || playlist_tab.open
+                || explore_tab.open
+                || left_channel
+                || left_tab
+            {
+                videos_list.clear();
+            }
+            if trending_tab.open {
+                self.show_trending(trending_tab.category, videos_list, selected_list_item)
+                    .await;
+            }
+        }
+        if event.is_key_press()
+            && trending_tab.open
+            && channel_page.channel.is_none()
+            && let code @ (KeyCode::Left | KeyCode::Right) = event.as_key_event().unwrap().code
+        {
+            let categories: Vec<TrendingCategory> = TrendingCategory::iter().collect();
+            let index = categories
+                .iter()
+                .position(|c| *c == trending_tab.category)
+                .unwrap_or_default();
+            let index = if code == KeyCode::Right {
+                (index + 1) % categories.len()
+            } else {
+                (index + categories.len() - 1) % categories.len()
+            };
+            trending_tab.category = categories[index];
+            self.show_trending(trending_tab.category, videos_list, selected_list_item)
+                .await;
+        }
+        let browsing_playlists = playlist_tab.open && playlist_tab.previewed.is_none();
+        let browsing_explore = explore_tab.open && playlist_tab.previewed.is_none();
+        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Up {
+            if browsing_explore {
+                explore_tab.selected.select_previous();
+            } else if browsing_playlists {
+                playlist_tab.selected.select_previous();
+            } else {
+                selected_list_item.select_previous();
+            }
+        }
+        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Down {
+            if browsing_explore {
+                explore_tab.selected.select_next();
+            } else if browsing_playlists {
+                playlist_tab.selected.select_next();
+            } else {
+                // Going past the last upload of a channel loads the next page
+                if selected_list_item.selected() == Some(videos_list.len().saturating_sub(1))
+                    && let Some(uploads) = &channel_page.uploads
+                {
+                    let next = uploads.next(crate::auth::rustypipe().query()).await;
+                    match next {
+                        Ok(next) => {
+                            if let Some(next) = &next {
+                                videos_list.extend(
+                                    next.items
+                                        .iter()
+                                        .map(|v| (VideoInfo::from(v).to_string(), v.into())),
+                                );
+                            }
+                            channel_page.uploads = next;
+                        }
+                        Err(e) => {
+                            self.toast =
+                                Some((format!("Could not load more uploads: {e}"), Instant::now()));
+                        }
+                    }
+                }
+                selected_list_item.select_next();
+            }
+        }
+        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Esc {
+            if let Some(back) = channel_page.close() {
+                // Back to the results the channel was opened from
+                *videos_list = back;
+                selected_list_item.select_first();
+            } else if playlist_tab.previewed.take().is_some() {
+                // Back to the playlists found
+                videos_list.clear();
+            } else if browsing_explore && explore_tab.back() {
+                // Up one level of the tree
+            } else {
+                *open_popup = !*open_popup;
+            }
+        }
+        if event.is_key_press() && ctrl && !browsing_playlists && !browsing_explore {
+            let selected_entry = selected_list_item
+                .selected()
+                .and_then(|selected| videos_list.get(selected))
+                .map(|(_, entry)| entry.clone());
+            let selected_channel = selected_entry.as_ref().and_then(|e| e.get_channel());
+            match event.as_key_event().unwrap().code {
+                // The artist page of a track, with its albums and radio
+                KeyCode::Char('o')
+                    if channel_page.channel.is_none()
+                        && matches!(selected_entry, Some(YoutubeResponse::Track(_))) =>
+                {
+                    if let Some((id, name)) = selected_channel {
+                        match explore_tab.open_artist(id, name.clone()).await {
+                            Ok(()) => {
+                                playlist_tab.open = false;
+                                playlist_tab.previewed = None;
+                                trending_tab.open = false;
+                            }
+                            Err(e) => {
+                                self.toast =
+                                    Some((format!("Could not open {name}: {e}"), Instant::now()));
+                            }
+                        }
+                    }
+                }
+                KeyCode::Char('o') if channel_page.channel.is_none() => {
+                    if let Some((id, name)) = selected_channel {
+                        let rp = crate::auth::rustypipe();
+                        let channel = rp.query().unauthenticated().channel_videos(&id).await;
+                        match channel {
+                            Ok(channel) => {
+                                let uploads = channel
+                                    .content
+                                    .items
+                                    .iter()
+                                    .map(|v| (VideoInfo::from(v).to_string(), v.into()))
+                                    .collect();
+                                channel_page.back = std::mem::replace(videos_list, uploads);
+                                channel_page.uploads = Some(channel.content);
+                                channel_page.channel = Some((id, channel.name));
+                                selected_list_item.select_first();
+                            }
+                            Err(e) => {
+                                self.toast =
+                                    Some((format!("Could not open {name}: {e}"), Instant::now()));
+                            }
+                        }
+                    }
+                }
+                KeyCode::Char('s') => {
+                    if let Some((id, name)) = channel_page.channel.clone().or(selected_channel) {
+                        let text = match Subscriptions::subscribe(&id, &name) {
+                            Ok(true) => format!("Subscribed to {name}"),
+                            Ok(false) => format!("Already subscribed to {name}"),
+                            Err(e) => format!("Could not subscribe to {name}: {e}"),
+                        };
+                        self.toast = Some((text, Instant::now()));
+                    }
+                }
+                KeyCode::Char('b') => {
+                    if let Some((id, name)) = channel_page.channel.clone().or(selected_channel) {
+                        let text = match crate::filter::block_channel(&id) {
+                            Ok(blocked) => {
+                                videos_list.retain(|(_, entry)| crate::filter::allowed(entry));
+                                playlist_tab.marked.clear();
+                                selected_list_item.select_first();
+                                match blocked {
+                                    true => format!("Blocked {name}"),
+                                    false => format!("{name} was already blocked"),
+                                }
+                            }
+                            Err(e) => format!("Could not block {name}: {e}"),
                         };
-                    } else {
-                        *img = None;
+                        self.toast = Some((text, Instant::now()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if event.is_key_press()
+            && ctrl
+            && channel_page.channel.is_none()
+            && let Some(name) = playlist_tab.previewed.clone()
+        {
+            // The marked entries, or all of them when none is
+            let picked: Vec<YoutubeResponse> = videos_list
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| playlist_tab.marked.is_empty() || playlist_tab.marked.contains(i))
+                .map(|(_, (_, entry))| entry.clone())
+                .collect();
+            match event.as_key_event().unwrap().code {
+                KeyCode::Char('a') => {
+                    for entry in &picked {
+                        let _ = self.queue.push(mpv, entry.clone()).await;
                     }
-                    *response = Some(vid);
+                    self.toast = Some((
+                        format!("Queued {} entries of {name}", picked.len()),
+                        Instant::now(),
+                    ));
+                    playlist_tab.marked.clear();
+                }
+                KeyCode::Char('p') => {
+                    self.play_entries(mpv, response, img, picked).await;
                     videos_list.clear();
+                    playlist_tab.previewed = None;
                 }
-            } else if !popup_query.is_empty() {
-                match self.api {
-                    Some(YoutubeAPI::Music) => {
-                        let rp = RustyPipe::new();
-                        let found_videos = rp
-                            .query()
-                            .unauthenticated()
-                            .music_search_tracks(popup_query.clone())
-                            .await
-                            .context("Failed to search YouTube Music")
-                            .expect("Failed to fetch youtube with rustypipe");
-                        YoutubeRs::cleanup_rustypipe_cache();
-                        *videos_list = found_videos
-                            .clone()
-                            .items
-                            .items
-                            .into_iter()
-                            .map(|track| (TrackInfo::from(&track).to_string(), track.into()))
-                            .collect();
-                        popup_query.clear();
+                KeyCode::Char('d') => return Some((name, picked)),
+                _ => {}
+            }
+        }
+        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Enter {
+            if browsing_explore && !popup_query.is_empty() {
+                if let Err(e) = explore_tab.search(popup_query).await {
+                    self.toast = Some((e.to_string(), Instant::now()));
+                }
+                popup_query.clear();
+            } else if browsing_explore {
+                let opened = match explore_tab.expand().await {
+                    Ok(Some(node)) => crate::explore::tracks(node).await.map(Some),
+                    Ok(None) => Ok(None),
+                    Err(e) => Err(e),
+                };
+                match opened {
+                    Ok(Some((name, tracks))) => {
+                        *videos_list = tracks;
+                        selected_list_item.select_first();
+                        playlist_tab.previewed = Some(name);
+                        playlist_tab.marked.clear();
                     }
-                    Some(YoutubeAPI::Video) => {
-                        let found_videos = RustyPipe::new()
+                    Ok(None) => {}
+                    Err(e) => self.toast = Some((e.to_string(), Instant::now())),
+                }
+            } else if browsing_playlists && popup_query.is_empty() {
+                if let Some((label, id)) = playlist_tab
+                    .selected
+                    .selected()
+                    .and_then(|selected| playlist_tab.found.get(selected))
+                    .cloned()
+                {
+                    self.jobs.spawn("Opening the playlist", async move {
+                        let rp = crate::auth::rustypipe();
+                        let found = rp.query().unauthenticated().playlist(&id).await;
+                        JobOutput::Playlist {
+                            label,
+                            found: found.map_err(Into::into).map(|playlist| {
+                                let entries = playlist
+                                    .videos
+                                    .items
+                                    .iter()
+                                    .map(|v| (VideoInfo::from(v).to_string(), v.into()))
+                                    .collect();
+                                (playlist.name, entries)
+                            }),
+                        }
+                    });
+                }
+            } else if let Some(selected) = selected_list_item.selected()
+                && popup_query.is_empty()
+            {
+                if let Some(vid) = videos_list.get(selected).map(|v| v.1.clone()) {
+                    popup_query.clear();
+                    self.play_entries(mpv, response, img, vec![vid]).await;
+                    videos_list.clear();
+                    playlist_tab.previewed = None;
+                    channel_page.close();
+                }
+            } else if playlist_tab.open && !popup_query.is_empty() {
+                let query = std::mem::take(popup_query);
+                self.jobs
+                    .spawn(format!("Searching the playlists '{query}'"), async move {
+                        let found = crate::auth::rustypipe()
                             .query()
                             .unauthenticated()
-                            .search(popup_query.clone())
-                            .await
-                            .context("Failed to search YouTube")
-                            .unwrap();
-                        YoutubeRs::cleanup_rustypipe_cache();
-                        *videos_list = found_videos
-                            .items
-                            .items
-                            .iter()
-                            .map(|v| (VideoInfo::from(v).to_string(), v.into()))
-                            .collect();
-                        popup_query.clear();
-                    }
-                    None => {}
+                            .search_filter::<PlaylistItem, _>(
+                                query.clone(),
+                                &SearchFilter::new().item_type(ItemType::Playlist),
+                            )
+                            .await;
+                        let found = found.map_err(Into::into).map(|found| {
+                            found
+                                .items
+                                .items
+                                .into_iter()
+                                .map(|p| {
+                                    let label = format!(
+                                        "{}{}\n\tChannel: {}",
+                                        p.name,
+                                        p.video_count
+                                            .map(|count| format!(" ({count} videos)"))
+                                            .unwrap_or_default(),
+                                        p.channel.map(|c| c.name).unwrap_or_default()
+                                    );
+                                    (label, p.id)
+                                })
+                                .collect()
+                        });
+                        JobOutput::Playlists { query, found }
+                    });
+            } else if !popup_query.is_empty()
+                && let Some(api) = self.api
+            {
+                let query = std::mem::take(popup_query);
+                let label = format!("Searching '{query}'");
+                match api {
+                    YoutubeAPI::Music => self.jobs.spawn(label, async move {
+                        let found = backend().search_tracks(&query).await.map(|found| {
+                            crate::filter::tracks(found)
+                                .into_iter()
+                                .map(|track| (TrackInfo::from(&track).to_string(), track.into()))
+                                .collect()
+                        });
+                        JobOutput::Search {
+                            query,
+                            source: "YouTube Music",
+                            found,
+                        }
+                    }),
+                    YoutubeAPI::Video => self.jobs.spawn(label, async move {
+                        let found = backend().search_videos(&query).await.map(|found| {
+                            crate::filter::videos(found)
+                                .iter()
+                                .map(|v| (VideoInfo::from(v).to_string(), v.into()))
+                                .collect()
+                        });
+                        JobOutput::Search {
+                            query,
+                            source: "YouTube",
+                            found,
+                        }
+                    }),
                 }
             }
         }
+        None
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -811,6 +2576,7 @@ impl YoutubeRs {
         &mut self,
         response: &mut Option<YoutubeResponse>,
         playback_time: f64,
+        stream_duration: f64,
         vid_started: bool,
         loader: [&str; 4],
         loader_idx: &mut usize,
@@ -818,22 +2584,38 @@ impl YoutubeRs {
         videos_list: &[(String, YoutubeResponse)],
         selected_list_item: &mut ListState,
         popup_query: &String,
-        img: &mut Option<ratatui_image::protocol::StatefulProtocol>,
+        playlist_tab: &mut PlaylistTab,
+        channel_page: &ChannelPage,
+        trending_tab: &TrendingTab,
+        explore_tab: &mut ExploreTab,
+        img: &mut PlayerImage,
         f: &mut Frame<'_>,
         file: &mut Option<(TaggedFile, String)>,
         empty_player: bool,
         mpv_vol: &f64,
+        transcript_search: &mut TranscriptSearch,
+        summary: &SummaryPane,
+        transcript_view: &mut TranscriptView,
+        related: &mut RelatedPane,
+        save_pane: &mut SavePane,
+        uploads: &mut UploadsPane,
+        audio_tracks: &mut AudioTracksPane,
+        subtitles: &mut SubtitlesPane,
+        side_pane: Option<SidePane>,
+        stats: &StatsCache,
     ) {
         if vid_started {
             // General Layout
             let layout = Layout::vertical(Constraint::from_percentages([60, 40])).split(f.area());
             // Top Image
-            if let Some(protocol) = img {
-                let img_layout = layout[0];
-                // remove 50% width on both sides
-                let img_layout = img_layout.centered_horizontally(Constraint::Percentage(50));
-                // Size of the image once resized to the area to fit
-                let img_size = protocol.size_for(ratatui_image::Resize::Scale(None), img_layout);
+            // Nothing to draw until the first resize of a new image is done
+            if !img.is_empty()
+                && let img_layout = layout[0].centered_horizontally(Constraint::Percentage(50))
+                && let Some(img_size) = img
+                    .protocol()
+                    .size_for(ratatui_image::Resize::Scale(None), img_layout)
+            {
+                // Centered in the middle 50% of the width
                 let width_dif = img_layout.width - img_size.width;
                 let height_dif = img_layout.height - img_size.height;
                 let img_place = Rect::new(
@@ -845,85 +2627,783 @@ impl YoutubeRs {
                 f.render_stateful_widget(
                     StatefulImage::default().resize(ratatui_image::Resize::Scale(None)),
                     img_place,
-                    protocol,
+                    img.protocol(),
+                );
+            }
+
+            // Bottom Panel, shared with the summary, transcript, related, save, uploads, audio tracks or subtitles pane when one is open
+            let info_layout = if let Some(side_pane) = side_pane {
+                let panes =
+                    Layout::horizontal(Constraint::from_percentages([50, 50])).split(layout[1]);
+                match side_pane {
+                    SidePane::Summary => Self::render_summary(summary, f, panes[1]),
+                    SidePane::Transcript => {
+                        Self::render_transcript_view(transcript_view, playback_time, f, panes[1])
+                    }
+                    SidePane::Related => Self::render_related(related, f, panes[1]),
+                    SidePane::Save => Self::render_save_pane(save_pane, f, panes[1]),
+                    SidePane::Uploads => Self::render_uploads(uploads, f, panes[1]),
+                    SidePane::AudioTracks => Self::render_audio_tracks(audio_tracks, f, panes[1]),
+                    SidePane::Subtitles => Self::render_subtitles(subtitles, f, panes[1]),
+                }
+                panes[0]
+            } else {
+                layout[1].centered_horizontally(Constraint::Percentage(50))
+            };
+            if transcript_search.open {
+                Self::render_transcript_search(transcript_search, f, info_layout);
+            } else if open_popup {
+                self.render_yt_search_popup(
+                    videos_list,
+                    selected_list_item,
+                    popup_query,
+                    playlist_tab,
+                    channel_page,
+                    trending_tab,
+                    explore_tab,
+                    stats,
+                    f,
+                    info_layout,
+                );
+            } else {
+                self.render_yt_player(
+                    response,
+                    playback_time,
+                    stream_duration,
+                    stats,
+                    f,
+                    info_layout,
+                    file,
+                    empty_player,
+                    mpv_vol,
                 );
-                if let Some(x) = protocol.last_encoding_result()
-                    && let Err(e) = x
+            }
+            self.render_jobs(f);
+            self.render_toast(f);
+            if let Some(banner) = &self.banner {
+                banner.render(f);
+            }
+        } else {
+            // Vid not started
+            if Utc::now().second().is_multiple_of(2) {
+                *loader_idx = loader_idx.saturating_add(1) % loader.len();
+            }
+            Block::bordered()
+                .title(format!("[Loading MPV {}]", loader[*loader_idx]))
+                .render(f.area(), f.buffer_mut());
+        }
+    }
+
+    /// What runs in the background, in the bottom right corner
+    fn render_jobs(&self, f: &mut Frame<'_>) {
+        let Some(status) = self.jobs.status() else {
+            return;
+        };
+        let width = (status.chars().count() as u16 + 4).min(f.area().width);
+        let area = Rect::new(
+            f.area().right() - width,
+            f.area().bottom().saturating_sub(3),
+            width,
+            3.min(f.area().height),
+        );
+        Clear.render(area, f.buffer_mut());
+        Paragraph::new(status)
+            .block(Block::bordered().style(crate::config::config().theme.panel()))
+            .render(area, f.buffer_mut());
+    }
+
+    fn render_toast(&mut self, f: &mut Frame<'_>) {
+        let Some((text, raised)) = &self.toast else {
+            return;
+        };
+        if raised.elapsed() > TOAST_DURATION {
+            self.toast = None;
+            return;
+        }
+        let width = (text.chars().count() as u16 + 4).min(f.area().width);
+        let area = Rect::new(f.area().right() - width, f.area().y, width, 3);
+        Clear.render(area, f.buffer_mut());
+        Paragraph::new(text.as_str())
+            .block(Block::bordered().style(crate::config::config().theme.panel()))
+            .render(area, f.buffer_mut());
+    }
+
+    fn render_summary(summary: &SummaryPane, f: &mut Frame<'_>, area: Rect) {
+        let text = if summary.text.is_empty() {
+            "Generating summary ..."
+        } else {
+            summary.text.as_str()
+        };
+        Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .scroll((summary.scroll, 0))
+            .block(
+                Block::bordered()
+                    .title_top("Summary")
+                    .title_alignment(HorizontalAlignment::Center)
+                    .title_bottom("['s' Close | PgUp/PgDn Scroll]")
+                    .style(crate::config::config().theme.panel()),
+            )
+            .render(area, f.buffer_mut());
+    }
+
+    fn render_transcript_search(
+        transcript_search: &mut TranscriptSearch,
+        f: &mut Frame<'_>,
+        info_layout: Rect,
+    ) {
+        let areas =
+            Layout::vertical([Constraint::Length(3), Constraint::Fill(3)]).split(info_layout);
+        Paragraph::new(format!("Find: {}", transcript_search.query))
+            .block(
+                Block::bordered()
+                    .title_top(
+                        transcript_search
+                            .error
+                            .clone()
+                            .unwrap_or("Transcript".to_string()),
+                    )
+                    .title_alignment(HorizontalAlignment::Center)
+                    .style(crate::config::config().theme.panel()),
+            )
+            .render(areas[0], f.buffer_mut());
+        let list = List::new(
+            transcript_search
+                .matches
+                .iter()
+                .map(|cue| {
+                    ListItem::from(format!("{} {}", format_time(cue.start as u32), cue.text))
+                })
+                .collect::<Vec<ListItem>>(),
+        )
+        .block(
+            Block::bordered()
+                .title_bottom("[▼▲ Select Match | (Esc) Player | (Enter) Find/Seek to Match]")
+                .style(crate::config::config().theme.panel()),
+        )
+        .highlight_symbol(">")
+        .highlight_style(crate::config::config().theme.highlight());
+        f.render_stateful_widget(list, areas[1], &mut transcript_search.selected);
+    }
+
+    async fn fetch_video_cues(args: &Cli, video_id: &str) -> Result<Vec<Cue>> {
+        let video = Self::get_fetcher(args)
+            .await?
+            .fetch_video_infos(Self::get_video_url(&video_id.to_owned()))
+            .await?;
+        transcript::fetch_cues(&video, None).await
+    }
+
+    fn render_transcript_view(
+        transcript_view: &mut TranscriptView,
+        playback_time: f64,
+        f: &mut Frame<'_>,
+        area: Rect,
+    ) {
+        if !transcript_view.browsing {
+            let current = transcript_view.current(playback_time);
+            transcript_view.selected.select(current);
+        }
+        let list = List::new(
+            transcript_view
+                .cues
+                .iter()
+                .map(|cue| {
+                    ListItem::from(format!("{} {}", format_time(cue.start as u32), cue.text))
+                })
+                .collect::<Vec<ListItem>>(),
+        )
+        .block(
+            Block::bordered()
+                .title_top(
+                    transcript_view
+                        .error
+                        .clone()
+                        .unwrap_or("Transcript".to_string()),
+                )
+                .title_alignment(HorizontalAlignment::Center)
+                .title_bottom("['t' Close | ▼▲ Browse | (Enter) Seek | (Esc) Follow Playback]")
+                .style(crate::config::config().theme.panel()),
+        )
+        .highlight_symbol(">")
+        .highlight_style(crate::config::config().theme.highlight());
+        f.render_stateful_widget(list, area, &mut transcript_view.selected);
+    }
+
+    fn render_related(related: &mut RelatedPane, f: &mut Frame<'_>, area: Rect) {
+        let list = List::new(
+            related
+                .items
+                .iter()
+                .map(|item| ListItem::from(item.label()))
+                .collect::<Vec<ListItem>>(),
+        )
+        .block(
+            Block::bordered()
+                .title_top(related.error.clone().unwrap_or("Related".to_string()))
+                .title_alignment(HorizontalAlignment::Center)
+                .title_bottom("['r' Close | ▼▲ Select | (Enter) Play | 'a' Queue]")
+                .style(crate::config::config().theme.panel()),
+        )
+        .highlight_symbol(">")
+        .highlight_style(crate::config::config().theme.highlight());
+        f.render_stateful_widget(list, area, &mut related.selected);
+    }
+
+    fn render_audio_tracks(audio_tracks: &mut AudioTracksPane, f: &mut Frame<'_>, area: Rect) {
+        let list = List::new(
+            audio_tracks
+                .tracks
+                .iter()
+                .map(|track| ListItem::from(track.label.clone()))
+                .collect::<Vec<ListItem>>(),
+        )
+        .block(
+            Block::bordered()
+                .title_top(
+                    audio_tracks
+                        .error
+                        .clone()
+                        .unwrap_or("Audio tracks".to_string()),
+                )
+                .title_alignment(HorizontalAlignment::Center)
+                .title_bottom("['v' Close | ▼▲ Select | (Enter) Switch]")
+                .style(crate::config::config().theme.panel()),
+        )
+        .highlight_symbol(">")
+        .highlight_style(crate::config::config().theme.highlight());
+        f.render_stateful_widget(list, area, &mut audio_tracks.selected);
+    }
+
+    fn render_subtitles(subtitles: &mut SubtitlesPane, f: &mut Frame<'_>, area: Rect) {
+        let list = List::new(
+            subtitles
+                .tracks
+                .iter()
+                .map(|track| ListItem::from(track.label.clone()))
+                .collect::<Vec<ListItem>>(),
+        )
+        .block(
+            Block::bordered()
+                .title_top(if subtitles.tracks.len() > 1 {
+                    "Subtitles"
+                } else {
+                    "No subtitles"
+                })
+                .title_alignment(HorizontalAlignment::Center)
+                .title_bottom("['c' Close | ▼▲ Select | (Enter) Show]")
+                .style(crate::config::config().theme.panel()),
+        )
+        .highlight_symbol(">")
+        .highlight_style(crate::config::config().theme.highlight());
+        f.render_stateful_widget(list, area, &mut subtitles.selected);
+    }
+
+    fn render_uploads(uploads: &mut UploadsPane, f: &mut Frame<'_>, area: Rect) {
+        let list = List::new(
+            uploads
+                .items
+                .iter()
+                .map(|item| ListItem::from(item.label()))
+                .collect::<Vec<ListItem>>(),
+        )
+        .block(
+            Block::bordered()
+                .title_top(if uploads.items.is_empty() {
+                    "No new upload yet"
+                } else {
+                    "New uploads"
+                })
+                .title_alignment(HorizontalAlignment::Center)
+                .title_bottom("['n' Close | ▼▲ Select | (Enter) Play | 'a' Queue | 'd' Download]")
+                .style(crate::config::config().theme.panel()),
+        )
+        .highlight_symbol(">")
+        .highlight_style(crate::config::config().theme.highlight());
+        f.render_stateful_widget(list, area, &mut uploads.selected);
+    }
+
+    fn render_save_pane(save_pane: &mut SavePane, f: &mut Frame<'_>, area: Rect) {
+        let list = List::new(
+            save_pane
+                .playlists
+                .iter()
+                .map(|playlist| ListItem::from(playlist.name.clone()))
+                .collect::<Vec<ListItem>>(),
+        )
+        .block(
+            Block::bordered()
+                .title_top(
+                    save_pane
+                        .error
+                        .clone()
+                        .unwrap_or("Save to playlist".to_string()),
+                )
+                .title_alignment(HorizontalAlignment::Center)
+                .title_bottom("['p' Close | ▼▲ Select | (Enter) Add | 'u' Refresh]")
+                .style(crate::config::config().theme.panel()),
+        )
+        .highlight_symbol(">")
+        .highlight_style(crate::config::config().theme.highlight());
+        f.render_stateful_widget(list, area, &mut save_pane.selected);
+    }
+
+    /// Returns whether the event was used by the save pane
+    async fn handle_save_event(
+        &mut self,
+        res: &YoutubeResponse,
+        save_pane: &mut SavePane,
+        side_pane: &mut Option<SidePane>,
+        event: &ratatui::crossterm::event::Event,
+    ) -> bool {
+        let Some(key) = event.as_key_press_event() else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Up => save_pane.selected.select_previous(),
+            KeyCode::Down => save_pane.selected.select_next(),
+            KeyCode::Char('u') => self.request_account_playlists(res.get_id(), true),
+            KeyCode::Enter => {
+                if let Some(playlist) = save_pane.selected_playlist() {
+                    let message =
+                        match crate::account::add_to_playlist(&playlist.id, &res.get_id()).await {
+                            Ok(()) => format!("Added to {}", playlist.name),
+                            Err(e) => e.to_string(),
+                        };
+                    self.toast = Some((message, Instant::now()));
+                    *side_pane = None;
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns whether the event was used by the related pane
+    async fn handle_related_event(
+        &mut self,
+        response: &mut Option<YoutubeResponse>,
+        mpv: &mut MpvIpc,
+        img: &mut PlayerImage,
+        related: &mut RelatedPane,
+        event: &ratatui::crossterm::event::Event,
+    ) -> bool {
+        let Some(key) = event.as_key_press_event() else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Up => related.selected.select_previous(),
+            KeyCode::Down => related.selected.select_next(),
+            KeyCode::Enter => {
+                if let Some(item) = related.selected_item().cloned() {
+                    self.play_entries(mpv, response, img, vec![item.clone()])
+                        .await;
+                    self.request_related(related, item.get_id());
+                }
+            }
+            KeyCode::Char('a') => {
+                if let Some(item) = related.selected_item().cloned() {
+                    self.toast = Some((format!("Queued {}", item.get_name()), Instant::now()));
+                    let _ = self.queue.push(mpv, item).await;
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns whether the event was used by the audio tracks pane
+    async fn handle_audio_tracks_event(
+        &mut self,
+        mpv: &mut MpvIpc,
+        audio_only: bool,
+        audio_tracks: &mut AudioTracksPane,
+        event: &ratatui::crossterm::event::Event,
+    ) -> bool {
+        let Some(key) = event.as_key_press_event() else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Up => audio_tracks.selected.select_previous(),
+            KeyCode::Down => audio_tracks.selected.select_next(),
+            KeyCode::Enter => {
+                let Some(track) = audio_tracks.selected_track().cloned() else {
+                    return true;
+                };
+                if let Some(id) = track.mpv_id {
+                    let _ = mpv.set_prop("aid", id).await;
+                } else {
+                    // mpv only gets the picked track from yt-dlp, the media is reloaded where it was
+                    let time = mpv.get_prop::<f64>("time-pos").await.unwrap_or_default();
+                    let _ = mpv
+                        .set_prop(
+                            "ytdl-format",
+                            crate::audio_tracks::ytdl_format(&track.language, audio_only),
+                        )
+                        .await;
+                    audio_tracks.reload = Some((time, mpv.subscribe("file-loaded").await));
+                    let _ = mpv
+                        .send_command(json!(["playlist-play-index", "current"]))
+                        .await;
+                    // The queued media and the downloads get the same language
+                    self.audio_language = Some(track.language.clone());
+                }
+                self.toast = Some((format!("Audio track: {}", track.label), Instant::now()));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns whether the event was used by the subtitles pane
+    async fn handle_subtitles_event(
+        &mut self,
+        mpv: &mut MpvIpc,
+        subtitles: &mut SubtitlesPane,
+        event: &ratatui::crossterm::event::Event,
+    ) -> bool {
+        let Some(key) = event.as_key_press_event() else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Up => subtitles.selected.select_previous(),
+            KeyCode::Down => subtitles.selected.select_next(),
+            KeyCode::Enter => {
+                if let Some(track) = subtitles.selected_track() {
+                    let _ = match track.id {
+                        Some(id) => mpv.set_prop("sid", id).await,
+                        None => mpv.set_prop("sid", "no").await,
+                    };
+                    self.toast = Some((format!("Subtitles: {}", track.label), Instant::now()));
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns whether the event marked the episode played or not
+    fn handle_episode_event(
+        &mut self,
+        response: &Option<YoutubeResponse>,
+        episodes: &mut EpisodeProgress,
+        event: &ratatui::crossterm::event::Event,
+    ) -> bool {
+        if !event
+            .as_key_press_event()
+            .is_some_and(|key| key.code == KeyCode::Char('m'))
+        {
+            return false;
+        }
+        let Some(played) = response
+            .as_ref()
+            .and_then(|res| episodes.toggle_played(res))
+        else {
+            return false;
+        };
+        let message = if played {
+            "Marked as played"
+        } else {
+            "Marked as not played"
+        };
+        self.toast = Some((message.to_owned(), Instant::now()));
+        true
+    }
+
+    /// Returns whether the event was used by the seek preview
+    async fn handle_seek_preview_event(
+        &mut self,
+        response: &Option<YoutubeResponse>,
+        mpv: &mut MpvIpc,
+        playback_time: f64,
+        preview: &mut SeekPreview,
+        event: &ratatui::crossterm::event::Event,
+    ) -> bool {
+        let Some(key) = event.as_key_press_event() else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Char(c @ ('<' | '>')) => {
+                let Some(res) = response else {
+                    return false;
+                };
+                let video_id = res.get_id();
+                if preview.video_id.as_ref() != Some(&video_id) {
+                    let video = match Self::get_fetcher(&self.args).await {
+                        Ok(fetcher) => fetcher
+                            .fetch_video_infos(Self::get_video_url(&video_id))
+                            .await
+                            .ok(),
+                        Err(_) => None,
+                    };
+                    preview.storyboard = video.as_ref().and_then(Storyboard::from_video);
+                    preview.video_id = Some(video_id);
+                }
+                let duration = mpv.get_prop::<f64>("duration").await.unwrap_or_default();
+                // Longer steps in longer videos
+                let step = (duration / 50.0).max(10.0);
+                let from = preview.target.unwrap_or(playback_time);
+                let mut target = if c == '<' { from - step } else { from + step }.max(0.0);
+                if duration > 0.0 {
+                    target = target.min(duration);
+                }
+                let message = match preview.show(target).await {
+                    Ok(()) if preview.storyboard.is_some() => format!(
+                        "Preview {} ((Enter) Seek | (Esc) Cancel)",
+                        format_time(target as u32)
+                    ),
+                    Ok(()) => format!(
+                        "No storyboard, seek to {} ((Enter) Seek | (Esc) Cancel)",
+                        format_time(target as u32)
+                    ),
+                    Err(e) => e.to_string(),
+                };
+                self.toast = Some((message, Instant::now()));
+            }
+            KeyCode::Enter if preview.target.is_some() => {
+                if let Some(target) = preview.target {
+                    let _ = mpv.send_command(json!(["seek", target, "absolute"])).await;
+                }
+                preview.close();
+            }
+            KeyCode::Esc if preview.target.is_some() => preview.close(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns whether the event was used by the uploads pane
+    async fn handle_uploads_event(
+        &mut self,
+        response: &mut Option<YoutubeResponse>,
+        mpv: &mut MpvIpc,
+        img: &mut PlayerImage,
+        uploads: &mut UploadsPane,
+        event: &ratatui::crossterm::event::Event,
+    ) -> bool {
+        let Some(key) = event.as_key_press_event() else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Up => uploads.selected.select_previous(),
+            KeyCode::Down => uploads.selected.select_next(),
+            KeyCode::Enter => {
+                if let Some(item) = uploads.selected_item().cloned() {
+                    self.play_entries(mpv, response, img, vec![item]).await;
+                }
+            }
+            KeyCode::Char('a') => {
+                if let Some(item) = uploads.selected_item().cloned() {
+                    self.toast = Some((format!("Queued {}", item.get_name()), Instant::now()));
+                    let _ = self.queue.push(mpv, item).await;
+                }
+            }
+            KeyCode::Char('d') => uploads.download = uploads.selected_item().cloned(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns whether the event was used by the transcript pane
+    async fn handle_transcript_view_event(
+        mpv: &mut MpvIpc,
+        transcript_view: &mut TranscriptView,
+        event: &ratatui::crossterm::event::Event,
+    ) -> bool {
+        let Some(key) = event.as_key_press_event() else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Up => {
+                transcript_view.browsing = true;
+                transcript_view.selected.select_previous();
+            }
+            KeyCode::Down => {
+                transcript_view.browsing = true;
+                transcript_view.selected.select_next();
+            }
+            KeyCode::Esc => transcript_view.browsing = false,
+            KeyCode::Enter => {
+                if let Some(cue) = transcript_view
+                    .selected
+                    .selected()
+                    .and_then(|i| transcript_view.cues.get(i))
                 {
-                    panic!("Error with last encoding result for image '{e}'");
+                    let _ = mpv
+                        .send_command(json!(["seek", cue.start, "absolute"]))
+                        .await;
                 }
+                transcript_view.browsing = false;
             }
+            _ => return false,
+        }
+        true
+    }
 
-            // Bottom Panel
-            let info_layout = layout[1];
-            let info_layout = info_layout.centered_horizontally(Constraint::Percentage(50));
-            if open_popup {
-                self.render_yt_search_popup(
-                    videos_list,
-                    selected_list_item,
-                    popup_query,
-                    f,
-                    info_layout,
-                );
-            } else {
-                self.render_yt_player(
-                    response,
-                    playback_time,
-                    f,
-                    info_layout,
-                    file,
-                    empty_player,
-                    mpv_vol,
-                );
+    async fn handle_transcript_event(
+        &mut self,
+        response: &Option<YoutubeResponse>,
+        mpv: &mut MpvIpc,
+        transcript_search: &mut TranscriptSearch,
+        event: &ratatui::crossterm::event::Event,
+    ) {
+        let Some(key) = event.as_key_press_event() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char(ch) => transcript_search.query.push(ch),
+            KeyCode::Backspace => {
+                transcript_search.query.pop();
             }
-        } else {
-            // Vid not started
-            if Utc::now().second().is_multiple_of(2) {
-                *loader_idx = loader_idx.saturating_add(1) % loader.len();
+            KeyCode::Up => transcript_search.selected.select_previous(),
+            KeyCode::Down => transcript_search.selected.select_next(),
+            KeyCode::Esc => transcript_search.open = false,
+            KeyCode::Enter if !transcript_search.query.is_empty() => {
+                let Some(res) = response else {
+                    return;
+                };
+                let video_id = res.get_id();
+                // Searched once the cues are fetched
+                if transcript_search.request(&video_id) {
+                    self.request_cues(video_id, true);
+                } else {
+                    transcript_search.search();
+                }
             }
-            Block::bordered()
-                .title(format!("[Loading MPV {}]", loader[*loader_idx]))
-                .render(f.area(), f.buffer_mut());
+            KeyCode::Enter => {
+                if let Some(cue) = transcript_search
+                    .selected
+                    .selected()
+                    .and_then(|i| transcript_search.matches.get(i))
+                {
+                    let _ = mpv
+                        .send_command(json!(["seek", cue.start, "absolute"]))
+                        .await;
+                    transcript_search.open = false;
+                }
+            }
+            _ => {}
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn render_yt_search_popup(
         &mut self,
         videos_list: &[(String, YoutubeResponse)],
         selected_list_item: &mut ListState,
         popup_query: &String,
+        playlist_tab: &mut PlaylistTab,
+        channel_page: &ChannelPage,
+        trending_tab: &TrendingTab,
+        explore_tab: &mut ExploreTab,
+        stats: &StatsCache,
         f: &mut Frame<'_>,
         info_layout: Rect,
     ) {
         // Popup for yt search
         let areas =
             Layout::vertical([Constraint::Length(3), Constraint::Fill(3)]).split(info_layout);
-        Paragraph::new(format!("YTSearch: {popup_query}"))
+        let browsing_explore = explore_tab.open && playlist_tab.previewed.is_none();
+        let (search, title) = match (&channel_page.channel, &playlist_tab.previewed) {
+            (Some((_, name)), _) => (
+                format!("Channel: {name}"),
+                "[▼▲ Select Upload | (Esc) Back | (Enter) Play Upload | (Ctrl+S) Subscribe | (Ctrl+B) Block]".to_owned(),
+            ),
+            (None, Some(name)) => (
+                format!("Playlist: {name}"),
+                "[▼▲ Select Entry | Space Mark | (Esc) Back | (Enter) Play Entry | Marked or all: (Ctrl+P) Play (Ctrl+A) Queue (Ctrl+D) Download]".to_owned(),
+            ),
+            (None, None) if explore_tab.open => (
+                format!("Explore: {} | Albums: {popup_query}", explore_tab.path()),
+                "[▼▲ Select | (Esc) Back | (Enter) Open/Search Albums | Tab Change Api: Explore]".to_owned(),
+            ),
+            (None, None) if trending_tab.open => (
+                format!("Trending: {}", trending_tab.category),
+                "[▼▲ Select Entry | ◀▶ Category | (Esc) Player | (Enter) Play Entry | (Ctrl+O) Channel | (Ctrl+B) Block | Tab Change Api: Trending]".to_owned(),
+            ),
+            (None, None) if playlist_tab.open => (
+                format!("Playlists: {popup_query}"),
+                "[▼▲ Select Playlist | (Esc) Player | (Enter) Search/Open Playlist | Tab Change Api: Playlists]".to_owned(),
+            ),
+            (None, None) => (
+                format!("YTSearch: {popup_query}"),
+                format!("[▼▲ Select Entry | (Esc) Player | (Enter) Search/Play Entry | (Ctrl+O) Channel | (Ctrl+S) Subscribe | (Ctrl+B) Block | Tab Change Api: {}]",self.api.unwrap_or_default()),
+            ),
+        };
+        Paragraph::new(search)
             .block(
                 Block::bordered()
                     .title_top("Search")
                     .title_alignment(HorizontalAlignment::Center)
-                    .yellow()
-                    .on_blue(),
+                    .style(crate::config::config().theme.panel()),
             )
             .render(areas[0], f.buffer_mut());
-        let list = List::new(
+        let browsing_playlists = playlist_tab.open && playlist_tab.previewed.is_none();
+        let items = if browsing_explore {
+            explore_tab
+                .lines()
+                .iter()
+                .map(|line| ListItem::from(line.0.clone()))
+                .collect::<Vec<ListItem>>()
+        } else if browsing_playlists {
+            playlist_tab
+                .found
+                .iter()
+                .map(|p| ListItem::from(p.0.clone()))
+                .collect::<Vec<ListItem>>()
+        } else {
+            let marking = playlist_tab.previewed.is_some() && channel_page.channel.is_none();
             videos_list
                 .iter()
-                .map(|v| ListItem::from(v.0.clone()))
-                .collect::<Vec<ListItem>>(),
-        )
-        .block(
-            Block::bordered()
-                .title_bottom(
-                    format!("[▼▲ Select Entry | (Esc) Player | (Enter) Search/Play Entry | Tab Change Api: {}]",self.api.unwrap_or_default()),
+                .enumerate()
+                .map(|(i, v)| {
+                    if marking && playlist_tab.marked.contains(&i) {
+                        ListItem::from(format!("[x] {}", v.0))
+                    } else {
+                        ListItem::from(v.0.clone())
+                    }
+                })
+                .collect::<Vec<ListItem>>()
+        };
+        // Details of the selected entry under the list
+        let details = selected_list_item
+            .selected()
+            .and_then(|i| videos_list.get(i))
+            .filter(|_| !browsing_explore && !browsing_playlists)
+            .map(|(_, entry)| stats.get(entry).to_string())
+            .filter(|details| !details.is_empty());
+        let areas = if let Some(details) = details {
+            let list_areas =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).split(areas[1]);
+            Paragraph::new(details)
+                .centered()
+                .block(
+                    Block::bordered()
+                        .title_top("Details")
+                        .title_alignment(HorizontalAlignment::Center)
+                        .style(crate::config::config().theme.panel()),
                 )
-                .style(Style::default().yellow().on_blue()),
-        )
-        .highlight_symbol(">")
-        .highlight_style(Style::default().red().on_cyan())
-        .direction(ratatui::widgets::ListDirection::TopToBottom);
-        f.render_stateful_widget(list, areas[1], selected_list_item);
+                .render(list_areas[1], f.buffer_mut());
+            [areas[0], list_areas[0]]
+        } else {
+            [areas[0], areas[1]]
+        };
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .title_bottom(title)
+                    .style(crate::config::config().theme.panel()),
+            )
+            .highlight_symbol(">")
+            .highlight_style(crate::config::config().theme.highlight())
+            .direction(ratatui::widgets::ListDirection::TopToBottom);
+        if browsing_explore {
+            f.render_stateful_widget(list, areas[1], &mut explore_tab.selected);
+        } else if browsing_playlists {
+            f.render_stateful_widget(list, areas[1], &mut playlist_tab.selected);
+        } else {
+            f.render_stateful_widget(list, areas[1], selected_list_item);
+        }
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -931,6 +3411,8 @@ impl YoutubeRs {
         &mut self,
         response: &mut Option<YoutubeResponse>,
         playback_time: f64,
+        stream_duration: f64,
+        stats: &StatsCache,
         f: &mut Frame<'_>,
         info_layout: Rect,
         file: &mut Option<(TaggedFile, String)>,
@@ -939,33 +3421,71 @@ impl YoutubeRs {
     ) {
         // Playback Info When Audio is from Youtube
         if let Some(res) = response {
-            Block::bordered()
-                .style(Style::default().on_blue().yellow())
-                .title_top(format!(
+            let live = res.is_live();
+            // How far behind the live edge, within the DVR window
+            let behind = (stream_duration - playback_time).max(0.0);
+            let title = if live && behind >= LIVE_EDGE_MARGIN {
+                format!(
+                    "{} - ● LIVE -{}",
+                    res.get_name(),
+                    format_time(behind as u32)
+                )
+            } else if live {
+                format!("{} - ● LIVE", res.get_name())
+            } else {
+                format!(
                     "{} - {}:{}",
                     res.get_name(),
                     format_time(playback_time as u32),
                     format_time(res.get_duration()),
-                ))
+                )
+            };
+            Block::bordered()
+                .style(crate::config::config().theme.panel())
+                .title_top(title)
                 .title_alignment(HorizontalAlignment::Center)
                 .title_top(format!("[Vol:{mpv_vol}]"))
                 .title_alignment(HorizontalAlignment::Right)
-                .title_bottom("['q' Quit | ▲▼ Volume(+/-) | ◀▶ Seek | 'y' Yank URL |'o' YtSearch]")
+                .title_bottom(format!(
+                    "['q' Quit | ▲▼ Volume(+/-) | ◀▶ Seek | 'j'/'k' Next/Previous | 'y' Yank URL |'o' YtSearch | 'f' Find in Transcript | 't' Transcript | 's' Summary | 'r' Related | 'l' Like | 'p' Save | 'n' New Uploads | 'v' Audio Tracks | 'c' Subtitles | 'm' Mark Played | '<' '>' Preview Seek | 'D' Detach{}]",
+                    if live { " | 'e' Live Edge" } else { "" }
+                ))
                 .title_alignment(HorizontalAlignment::Center)
                 .render(info_layout, f.buffer_mut());
-            let gauge_layout = info_layout
-                .inner(Margin {
-                    horizontal: 1,
-                    vertical: 1,
-                })
-                .centered_vertically(Constraint::Percentage(50));
-            Gauge::default()
-                .block(Block::bordered().style(Style::default().yellow().on_blue()))
-                .ratio(playback_time / res.get_duration() as f64)
-                .render(gauge_layout, f.buffer_mut());
+            let inner = info_layout.inner(Margin {
+                horizontal: 1,
+                vertical: 1,
+            });
+            Paragraph::new(stats.get(res).to_string())
+                .centered()
+                .render(Rect { height: 1, ..inner }, f.buffer_mut());
+            let gauge_layout = inner.centered_vertically(Constraint::Percentage(50));
+            // The gauge of a livestream spans its DVR window, full at the live edge
+            let (ratio, length) = if live {
+                (playback_time / stream_duration, stream_duration)
+            } else {
+                (
+                    playback_time / res.get_duration() as f64,
+                    res.get_duration() as f64,
+                )
+            };
+            let gauge = Gauge::default()
+                .block(Block::bordered().style(crate::config::config().theme.panel()))
+                .ratio(if length > 0.0 {
+                    ratio.clamp(0.0, 1.0)
+                } else if live {
+                    1.0
+                } else {
+                    0.0
+                });
+            if live {
+                gauge.label("LIVE").render(gauge_layout, f.buffer_mut());
+            } else {
+                gauge.render(gauge_layout, f.buffer_mut());
+            }
         } else if let Some(file) = file {
             Block::bordered()
-                .style(Style::default().yellow().on_blue())
+                .style(crate::config::config().theme.panel())
                 .title_top(format!(
                     "{} - {}:{}",
                     PathBuf::from(&file.1)
@@ -987,12 +3507,12 @@ impl YoutubeRs {
                 .centered_vertically(Constraint::Percentage(50));
 
             Gauge::default()
-                .block(Block::bordered().style(Style::default().yellow().on_blue()))
+                .block(Block::bordered().style(crate::config::config().theme.panel()))
                 .ratio(playback_time / file.0.properties().duration().as_secs_f64())
                 .render(gauge_layout, f.buffer_mut());
         } else if empty_player {
             Block::bordered()
-                .style(Style::default().on_blue().yellow())
+                .style(crate::config::config().theme.panel())
                 .title_alignment(HorizontalAlignment::Center)
                 .title_bottom("['q' Quit | ▲▼ Volume(+/-) | ◀▶ Seek | 'y' Yank URL |'o' YtSearch]")
                 .title_alignment(HorizontalAlignment::Center)
@@ -1004,7 +3524,7 @@ impl YoutubeRs {
                 })
                 .centered_vertically(Constraint::Percentage(50));
             Gauge::default()
-                .block(Block::bordered().style(Style::default().yellow().on_blue()))
+                .block(Block::bordered().style(crate::config::config().theme.panel()))
                 .ratio(playback_time / 1.0)
                 .render(gauge_layout, f.buffer_mut());
         }
@@ -1015,14 +3535,11 @@ impl YoutubeRs {
             .map_err(|e| anyhow::anyhow!("Clipboard error: {:?}", e))?;
         Ok(())
     }
-    fn get_video_url(video_id: &String) -> String {
+    pub(crate) fn get_video_url(video_id: &String) -> String {
         format!("https://www.youtube.com/watch?v={video_id}")
     }
-    fn cleanup_rustypipe_cache() {
-        std::fs::remove_file("./rustypipe_cache.json").expect("Could not clean cache");
-    }
 
-    async fn fetch_yt_thumbnail(video_id: &str, args: &Cli) -> Result<DynamicImage> {
+    pub(crate) async fn fetch_yt_thumbnail(video_id: &str, args: &Cli) -> Result<DynamicImage> {
         let thumbnail_url = if Self::ytdlp_exist(args) {
             Self::get_fetcher(args)
                 .await?
@@ -1041,27 +3558,209 @@ impl YoutubeRs {
         Ok(image::load_from_memory(&thumbnail_bytes)?)
     }
 
+    /// Download the video as `format`, optionally inside a sub directory of the output directory
+    pub(crate) async fn download(
+        &self,
+        video_id: &str,
+        video_name: &str,
+        format: Format,
+        subdir: Option<&str>,
+    ) -> Result<PathBuf> {
+        let (_, out) = Self::get_libs_path(&self.args);
+        let output = Self::output_file_name(&self.args, video_name, subdir, &format.extension())?;
+        if let Some(downloaded) = backend().download(video_id, &out.join(output)).await? {
+            progress::emit(ProgressEvent::Downloaded {
+                id: video_id,
+                title: video_name,
+                path: &downloaded,
+            });
+            return Ok(downloaded);
+        }
+        let url = Self::get_video_url(&video_id.to_string());
+        let fetcher = Self::get_fetcher(&self.args).await?;
+        let downloaded = match self
+            .download_with(&fetcher, video_id, video_name, format, subdir)
+            .await
+        {
+            // The whole chain, the download adds its context to the yt-dlp error
+            Err(e) if crate::auth::is_restricted(&format!("{e:#}")) => {
+                progress::say(
+                    "Age-restricted or embed-blocked, retrying through another player ...".yellow(),
+                );
+                progress::emit(ProgressEvent::Step { step: "fallback" });
+                let fallback = Self::get_fallback_fetcher(&self.args).await?;
+                self.download_with(&fallback, video_id, video_name, format, subdir)
+                    .await?
+            }
+            downloaded => downloaded?,
+        };
+        let video = if !self.sponsorblock.is_empty() || self.sidecars {
+            Some(
+                Self::get_fetcher(&self.args)
+                    .await?
+                    .fetch_video_infos(url)
+                    .await?,
+            )
+        } else {
+            None
+        };
+        if let Some(video) = &video
+            && !self.sponsorblock.is_empty()
+        {
+            // The download itself went well, keep it uncut rather than failing the entry
+            if let Err(e) = self
+                .remove_sponsors(video_id, &downloaded, video, &format)
+                .await
+            {
+                tracing::warn!("SponsorBlock failed for {video_id}: {e:#}");
+                progress::say(
+                    format!("SponsorBlock failed, keeping the download uncut: {e:#}").yellow(),
+                );
+            }
+        }
+        if let Some(video) = &video
+            && self.sidecars
+        {
+            Self::write_sidecars(&downloaded, video)?;
+        }
+        Feed::record(FeedKind::Download, video_id, video_name, subdir)
+            .log_error("Could not write the feed");
+        progress::emit(ProgressEvent::Downloaded {
+            id: video_id,
+            title: video_name,
+            path: &downloaded,
+        });
+        Ok(downloaded)
+    }
+
+    /// Cut the SponsorBlock segments of `video_id` out of `downloaded`
+    async fn remove_sponsors(
+        &self,
+        video_id: &str,
+        downloaded: &Path,
+        video: &yt_dlp::model::Video,
+        format: &Format,
+    ) -> Result<()> {
+        let segments = sponsorblock::fetch_segments(video_id, &self.sponsorblock).await?;
+        if segments.is_empty() {
+            return Ok(());
+        }
+        progress::say(format!(
+            "Removing {} SponsorBlock segment(s) ...",
+            segments.len()
+        ));
+        progress::emit(ProgressEvent::Step {
+            step: "sponsorblock",
+        });
+        sponsorblock::remove_segments(
+            &Self::get_libs(&self.args).ffmpeg,
+            downloaded,
+            &segments,
+            &video.chapters,
+            matches!(format, Format::Audio { .. }),
+        )
+        .await
+    }
+
+    /// Write the description and the full metadata next to the downloaded file
+    fn write_sidecars(downloaded: &Path, video: &yt_dlp::model::Video) -> Result<()> {
+        let upload_date = chrono::DateTime::from_timestamp(video.upload_date, 0)
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        let description = format!(
+            "{}\n{}\nChannel: {}\nUploaded: {upload_date}\nTags: {}\n\n{}\n",
+            video.title,
+            Self::get_video_url(&video.id),
+            video.channel,
+            video.tags.join(", "),
+            video.description
+        );
+        std::fs::write(downloaded.with_extension("description.txt"), description)?;
+        std::fs::write(
+            downloaded.with_extension("info.json"),
+            serde_json::to_string_pretty(video)?,
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn output_file_name(
+        args: &Cli,
+        video_name: &str,
+        subdir: Option<&str>,
+        extension: &str,
+    ) -> Result<String> {
+        let safe_name = sanitize_file_name(video_name);
+        match subdir {
+            Some(dir) => {
+                let dir = sanitize_file_name(dir);
+                let (_, out) = Self::get_libs_path(args);
+                std::fs::create_dir_all(out.join(&dir))?;
+                Ok(format!("{dir}/{safe_name}.{extension}"))
+            }
+            None => Ok(format!("{safe_name}.{extension}")),
+        }
+    }
+
+    async fn download_with(
+        &self,
+        fetcher: &Youtube,
+        video_id: &str,
+        video_name: &str,
+        format: Format,
+        subdir: Option<&str>,
+    ) -> Result<PathBuf> {
+        crate::ratelimit::throttled(|| async move {
+            match format {
+                Format::Audio { format } => {
+                    self.download_audio(fetcher, video_id, video_name, format, subdir, &self.args)
+                        .await
+                }
+                Format::Video { format } => {
+                    self.download_video(fetcher, video_id, video_name, format, subdir, &self.args)
+                        .await
+                }
+            }
+        })
+        .await
+    }
+
     async fn download_audio(
         &self,
-        url: &str,
+        fetcher: &Youtube,
+        video_id: &str,
         video_name: &str,
         format: AudioFormat,
+        subdir: Option<&str>,
         args: &Cli,
-    ) -> Result<()> {
-        println!("Downloading Audio ...");
-        let fetcher = Self::get_fetcher(args).await?;
-        let safe_name =
-            video_name.replace(|c: char| !c.is_alphanumeric() && c != ' ' && c != '-', "_");
-        let vid_info = fetcher.fetch_video_infos(url.to_string()).await?;
-        let downloaded = fetcher
-            .download_audio_stream_with_quality(
-                url.to_string(),
-                format!("{safe_name}.{}", format.to_string().to_lowercase()),
-                yt_dlp::model::AudioQuality::Best,
-                yt_dlp::model::AudioCodecPreference::Custom(format.to_string()),
-            )
+    ) -> Result<PathBuf> {
+        progress::say("Downloading Audio ...");
+        progress::emit(ProgressEvent::Step { step: "audio" });
+        let vid_info = fetcher
+            .fetch_video_infos(Self::get_video_url(&video_id.to_owned()))
             .await?;
-        println!("Audio downloaded at '{downloaded:?}'");
+        let output =
+            Self::output_file_name(args, video_name, subdir, &format.to_string().to_lowercase())?;
+        let track = self
+            .audio_language
+            .as_deref()
+            .and_then(|language| crate::audio_tracks::best_format(&vid_info, language));
+        let downloaded = match track {
+            Some(track) => {
+                let (_, out) = Self::get_libs_path(args);
+                crate::audio_tracks::download(
+                    fetcher,
+                    &Self::get_libs(args).ffmpeg,
+                    video_id,
+                    track,
+                    &out.join(&output),
+                )
+                .await?
+            }
+            None => {
+                ytrs_core::download::audio(fetcher, video_id, &output, &format.to_string()).await?
+            }
+        };
+        progress::say(format!("Audio downloaded at '{downloaded:?}'"));
         let tagged_file = Probe::open(&downloaded)?;
         let file_type = tagged_file.guess_file_type()?;
         let mut tagged_file = file_type.read()?;
@@ -1092,34 +3791,330 @@ impl YoutubeRs {
                 .pic_type(lofty::picture::PictureType::CoverFront)
                 .build(),
         );
-        tag.save_to_path(downloaded, WriteOptions::default())?;
+        tag.save_to_path(&downloaded, WriteOptions::default())?;
+
+        Ok(downloaded)
+    }
+
+    pub async fn watch(&mut self, command: WatchCli) -> Result<()> {
+        let mut list = WatchList::load()?;
+        match command {
+            WatchCli::Add { url, backfill } => {
+                let format = Format::prompt("Select Audio or Video")?;
+                let mut entry = WatchEntry::resolve(&url, format).await?;
+                if backfill {
+                    entry.seen.clear();
+                }
+                println!("Watching {entry}");
+                list.entries.retain(|e| e.id != entry.id);
+                list.entries.push(entry);
+                list.save()?;
+            }
+            WatchCli::Remove => {
+                if list.entries.is_empty() {
+                    println!("Nothing is watched");
+                    return Ok(());
+                }
+                let entry = Select::new("Stop watching", list.entries.clone()).prompt()?;
+                list.entries.retain(|e| e.id != entry.id);
+                list.save()?;
+            }
+            WatchCli::List => {
+                for entry in &list.entries {
+                    println!("{entry}");
+                }
+            }
+            WatchCli::Feed { output, downloads } => {
+                let feed = Feed::load()?;
+                match output {
+                    Some(path) => feed.write_rss(&path, downloads)?,
+                    None => println!("{}", feed.rss(downloads)),
+                }
+            }
+            WatchCli::Run {
+                interval,
+                once,
+                feed,
+                feed_downloads,
+            } => {
+                if !self.libraries_exist(&self.args.clone()) {
+                    Self::install_lib(&self.args).await?;
+                }
+                loop {
+                    self.watch_check(&mut list).await?;
+                    if let Some(path) = &feed
+                        && let Err(e) = Feed::load().and_then(|f| f.write_rss(path, feed_downloads))
+                    {
+                        println!("Could not write the feed: {e}");
+                        tracing::warn!("Could not write the feed: {e}");
+                    }
+                    if once {
+                        break;
+                    }
+                    println!("Next check in {interval} minute(s)");
+                    tokio::time::sleep(Duration::from_secs(interval * 60)).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn watch_check(&self, list: &mut WatchList) -> Result<()> {
+        let mut archive = self.archive()?;
+        for i in 0..list.entries.len() {
+            let entry = list.entries[i].clone();
+            println!(
+                "[{}] Checking {entry}",
+                chrono::Local::now().format("%Y-%m-%d %H:%M")
+            );
+            let uploads = match entry.new_uploads().await {
+                Ok(uploads) => uploads,
+                Err(e) => {
+                    println!("Could not check {entry}: {e}");
+                    tracing::warn!("Could not check {entry}: {e}");
+                    continue;
+                }
+            };
+            for video in uploads {
+                if !archive.contains(&video.id) {
+                    if let Err(e) = self
+                        .download(&video.id, &video.name, entry.format, Some(&entry.name))
+                        .await
+                    {
+                        println!("Could not download '{}': {e}", video.name);
+                        tracing::warn!("Could not download '{}': {e}", video.name);
+                        continue;
+                    }
+                    archive.insert(&video.id)?;
+                }
+                Feed::record(FeedKind::Upload, &video.id, &video.name, Some(&entry.name))
+                    .log_error("Could not write the feed");
+                list.entries[i].seen.insert(video.id);
+                list.save()?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn archive(&self) -> Result<DownloadArchive> {
+        let (_, out) = Self::get_libs_path(&self.args);
+        DownloadArchive::open(out.join("archive.txt"))
+    }
+
+    async fn download_batch(&self, batch: &Path, format: Format) -> Result<()> {
+        let content = if batch == Path::new("-") {
+            std::io::read_to_string(std::io::stdin()).context("Could not read urls from stdin")?
+        } else {
+            std::fs::read_to_string(batch).with_context(|| {
+                format!("Could not read batch file '{}'", batch.to_string_lossy())
+            })?
+        };
+        let mut urls: Vec<String> = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if !line.is_empty() && !line.starts_with('#') && !urls.iter().any(|url| url == line) {
+                urls.push(line.to_owned());
+            }
+        }
+        self.download_urls(&urls, format, None).await
+    }
 
+    /// Download one entry from the player, asking for the format
+    async fn download_entry(&mut self, entry: &YoutubeResponse) -> Result<()> {
+        let name = entry.get_name();
+        let format = Format::prompt(&format!("Download '{name}' as"))?;
+        if !self.libraries_exist(&self.args.clone()) {
+            Self::install_lib(&self.args).await?;
+        }
+        let id = entry.get_id();
+        self.download(&id, &name, format, None).await?;
+        self.archive()?.insert(&id)?;
         Ok(())
     }
 
+    /// Download every entry of a playlist from the player, in a sub directory named after it
+    async fn download_playlist(&mut self, name: &str, entries: &[YoutubeResponse]) -> Result<()> {
+        let format = Format::prompt(&format!(
+            "Download {} entries of '{name}' as",
+            entries.len()
+        ))?;
+        if !self.libraries_exist(&self.args.clone()) {
+            Self::install_lib(&self.args).await?;
+        }
+        let urls: Vec<String> = entries
+            .iter()
+            .map(|entry| Self::get_video_url(&entry.get_id()))
+            .collect();
+        self.download_urls(&urls, format, Some(name)).await
+    }
+
+    /// Id and title of the video of `url`, through the other player clients when it is restricted
+    async fn download_infos(
+        fetcher: &Youtube,
+        fallback: &Youtube,
+        url: &str,
+    ) -> Result<(String, String)> {
+        if backend().offline() {
+            let id = YoutubeLink::parse(url)
+                .and_then(|link| link.video_id)
+                .with_context(|| format!("'{url}' is not the link of a video"))?;
+            let video = backend().video(&id).await?;
+            return Ok((video.id, video.name));
+        }
+        let video = match fetcher.fetch_video_infos(url.to_string()).await {
+            Err(e) if crate::auth::is_restricted(&e.to_string()) => {
+                fallback.fetch_video_infos(url.to_string()).await
+            }
+            fetched => fetched,
+        }?;
+        Ok((video.id, video.title))
+    }
+
+    /// Download `urls` once each and print what happened to every one of them
+    pub(crate) async fn download_urls(
+        &self,
+        urls: &[String],
+        format: Format,
+        subdir: Option<&str>,
+    ) -> Result<()> {
+        let fetcher = Self::get_fetcher(&self.args).await?;
+        let fallback = Self::get_fallback_fetcher(&self.args).await?;
+        let mut archive = self.archive()?;
+        let mut report: Vec<(String, BatchStatus)> = Vec::new();
+        for (i, url) in urls.iter().enumerate() {
+            progress::say(format!("[{}/{}] {url}", i + 1, urls.len()));
+            progress::emit(ProgressEvent::Start {
+                index: i + 1,
+                total: urls.len(),
+                url,
+            });
+            let (id, title) = match Self::download_infos(&fetcher, &fallback, url).await {
+                Ok(infos) => infos,
+                Err(e) => {
+                    progress::emit(ProgressEvent::Failed {
+                        entry: url,
+                        error: format!("{e:#}"),
+                    });
+                    report.push((url.to_string(), BatchStatus::Failed(e.to_string())));
+                    continue;
+                }
+            };
+            if archive.contains(&id) {
+                progress::emit(ProgressEvent::Skipped {
+                    id: &id,
+                    title: &title,
+                });
+                report.push((title, BatchStatus::Skipped));
+                continue;
+            }
+            let res = self.download(&id, &title, format, subdir).await;
+            match res.and_then(|_| archive.insert(&id)) {
+                Ok(()) => report.push((title, BatchStatus::Downloaded)),
+                Err(e) => {
+                    progress::emit(ProgressEvent::Failed {
+                        entry: url,
+                        error: format!("{e:#}"),
+                    });
+                    report.push((title, BatchStatus::Failed(e.to_string())))
+                }
+            }
+        }
+        progress::say(format!("\n{:<12}Entry", "Status"));
+        for (entry, status) in &report {
+            let label = format!("{:<12}", status.to_string());
+            match status {
+                BatchStatus::Downloaded => progress::say(format!(
+                    "{}{entry}",
+                    ratatui::crossterm::style::Stylize::green(label)
+                )),
+                BatchStatus::Skipped => progress::say(format!(
+                    "{}{entry}",
+                    ratatui::crossterm::style::Stylize::yellow(label)
+                )),
+                BatchStatus::Failed(e) => progress::say(format!(
+                    "{}{entry}\n\t{e}",
+                    ratatui::crossterm::style::Stylize::red(label)
+                )),
+            }
+        }
+        let count =
+            |status: fn(&BatchStatus) -> bool| report.iter().filter(|(_, s)| status(s)).count();
+        let downloaded = count(|s| matches!(s, BatchStatus::Downloaded));
+        let skipped = count(|s| matches!(s, BatchStatus::Skipped));
+        let failed = count(|s| matches!(s, BatchStatus::Failed(_)));
+        progress::say(format!(
+            "{downloaded} downloaded, {skipped} skipped, {failed} failed"
+        ));
+        progress::emit(ProgressEvent::Done {
+            downloaded,
+            skipped,
+            failed,
+        });
+        if failed == 0 {
+            Ok(())
+        } else if failed == report.len() {
+            bail!("All the {failed} downloads failed")
+        } else {
+            Err(ScriptError::PartialFailure {
+                failed,
+                total: report.len(),
+            }
+            .into())
+        }
+    }
+
     async fn download_video(
         &self,
-        url: &str,
+        fetcher: &Youtube,
+        video_id: &str,
         video_name: &str,
         format: VideoFormat,
+        subdir: Option<&str>,
         args: &Cli,
-    ) -> Result<()> {
-        println!("Downloading Video ...");
-        let fetcher = Self::get_fetcher(args).await?;
-        let safe_name =
-            video_name.replace(|c: char| !c.is_alphanumeric() && c != ' ' && c != '-', "_");
-        let downloaded = fetcher
-            .download_video_with_quality(
-                url.to_string(),
-                format!("{safe_name}.{}", format.to_string().to_lowercase()),
+    ) -> Result<PathBuf> {
+        progress::say("Downloading Video ...");
+        progress::emit(ProgressEvent::Step { step: "video" });
+        let output =
+            Self::output_file_name(args, video_name, subdir, &format.to_string().to_lowercase())?;
+        if let Some(language) = &self.audio_language {
+            return Self::download_video_dubbed(fetcher, video_id, output, format, language).await;
+        }
+        let downloaded =
+            ytrs_core::download::video(fetcher, video_id, &output, &format.to_string()).await?;
+        progress::say(format!("Video Downloaded at '{downloaded:?}'"));
+        Ok(downloaded)
+    }
+
+    /// Download the best video with the audio track in `language`, or the best one when missing
+    async fn download_video_dubbed(
+        fetcher: &Youtube,
+        video_id: &str,
+        output: String,
+        format: VideoFormat,
+        language: &str,
+    ) -> Result<PathBuf> {
+        let video = fetcher
+            .fetch_video_infos(Self::get_video_url(&video_id.to_owned()))
+            .await?;
+        let video_format = video
+            .select_video_format(
                 yt_dlp::model::VideoQuality::Best,
                 VideoCodecPreference::Custom(format.to_string()),
-                yt_dlp::model::AudioQuality::Best,
-                yt_dlp::model::AudioCodecPreference::MP3,
             )
+            .context("No video stream found")?;
+        let audio_format = crate::audio_tracks::best_format(&video, language)
+            .or_else(|| video.best_audio_format())
+            .context("No audio stream found")?;
+        let video_file = format!("{}.video.{:?}", video.id, video_format.download_info.ext);
+        let audio_file = format!("{}.audio.{:?}", video.id, audio_format.download_info.ext);
+        let video_path = fetcher.download_format(video_format, &video_file).await?;
+        let audio_path = fetcher.download_format(audio_format, &audio_file).await?;
+        let downloaded = fetcher
+            .combine_audio_and_video(&audio_file, &video_file, output)
             .await?;
-        println!("Video Downloaded at '{downloaded:?}'");
-        Ok(())
+        let _ = std::fs::remove_file(video_path);
+        let _ = std::fs::remove_file(audio_path);
+        progress::say(format!("Video Downloaded at '{downloaded:?}'"));
+        Ok(downloaded)
     }
 
     async fn download_transcript(&self, video_id: &str, args: &Cli) -> Result<()> {
@@ -1130,143 +4125,292 @@ impl YoutubeRs {
 
         let languages = fetcher.list_subtitle_languages(&video);
         if languages.is_empty() {
-            println!("Finding Generated Captions");
-            let cap: Vec<(String, &Vec<yt_dlp::model::caption::AutomaticCaption>)> = video
+            progress::say("Finding Generated Captions");
+            let mut cap: Vec<(String, &Vec<yt_dlp::model::caption::AutomaticCaption>)> = video
                 .automatic_captions
                 .iter()
                 .map(|v| (v.0.clone(), v.1))
                 .collect();
+            cap.sort_by(|a, b| a.0.cmp(&b.0));
             if cap.is_empty() {
-                println!("No Generated Caption found");
+                progress::say("No Generated Caption found");
                 if !video.description.is_empty() {
                     println!("{}: \n{}", "Video Description".green(), video.description);
                 }
                 return Ok(());
             }
-            let lang = match inquire::Select::new(
+            let langs = self.select_languages(
                 "Generated Lang",
                 cap.iter().map(|(lang, _)| lang.clone()).collect(),
-            )
-            .prompt()
-            {
-                Ok(l) => l,
-                Err(e) => match e {
-                    inquire::InquireError::OperationCanceled => Err(anyhow!(YtrsError::Quit))?,
-                    _ => Err(e)?,
-                },
+            )?;
+            let Some((first_lang, first_cap)) = cap.iter().find(|(l, _)| langs.contains(l)) else {
+                return Ok(());
             };
-            for (l, cap) in cap {
-                if lang == l {
-                    let res: Vec<Subtitle> = cap
-                        .iter()
-                        .map(|v| Subtitle::from_automatic_caption(v, l.clone()))
-                        .collect();
-                    let res_to_dl = match inquire::Select::new("Caption", res).prompt() {
-                        Ok(res) => res,
-                        Err(e) => match e {
-                            inquire::InquireError::OperationCanceled => {
-                                Err(anyhow!(YtrsError::Quit))?
-                            }
-                            _ => Err(e)?,
-                        },
-                    };
-                    let response = reqwest::Client::new()
-                        .get(res_to_dl.url.clone())
-                        .send()
-                        .await?
-                        .text()
-                        .await?;
-                    let (_, out) = Self::get_libs_path(&self.args);
-                    let mut f = OpenOptions::new().write(true).create(true).open(format!(
+            let formats: Vec<Subtitle> = first_cap
+                .iter()
+                .map(|v| Subtitle::from_automatic_caption(v, first_lang.clone()))
+                .collect();
+            let format =
+                prompt_or_quit(inquire::Select::new("Caption", formats).prompt())?.extension;
+            for (l, cap) in cap.iter().filter(|(l, _)| langs.contains(l)) {
+                let Some(res_to_dl) = cap
+                    .iter()
+                    .find(|c| c.extension == format)
+                    .map(|c| Subtitle::from_automatic_caption(c, l.clone()))
+                else {
+                    progress::say(format!("No '{format}' caption for '{l}'"));
+                    continue;
+                };
+                let response = reqwest::Client::new()
+                    .get(res_to_dl.url.clone())
+                    .send()
+                    .await?
+                    .text()
+                    .await?;
+                let (_, out) = Self::get_libs_path(&self.args);
+                let mut f = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(format!(
                         "{}/subtitle_{l}.{}",
                         out.to_string_lossy(),
                         res_to_dl.file_extension()
                     ))?;
-                    f.write_all(response.as_bytes())?;
-                    println!(
-                        "AutoGenerated Captions downloaded at '{}/subtitle_{l}.{}'",
-                        out.to_string_lossy(),
-                        res_to_dl.file_extension()
-                    );
-                    let res = if let Some(b) = self.summarize {
-                        println!("Summarize : {b}");
-                        b
-                    } else {
-                        match inquire::Confirm::new("Summarize with ai ?")
-                            .with_starting_input("N")
-                            .prompt()
-                        {
-                            Ok(b) => b,
-                            Err(e) => match e {
-                                inquire::InquireError::OperationCanceled => {
-                                    Err(anyhow!(YtrsError::Quit))?
-                                }
-                                _ => Err(e)?,
-                            },
-                        }
-                    };
-                    if res {
-                        use tokio::io::{self, AsyncWriteExt};
-                        use tokio_stream::StreamExt;
-
-                        let ollama = Ollama::default();
-                        let models = ollama.list_local_models().await?;
-                        let model = match inquire::Select::new(
-                            "Which LLM to use:",
-                            models.iter().map(|llm| llm.name.clone()).collect(),
-                        )
-                        .prompt()
-                        {
-                            Ok(v) => v,
-                            Err(e) => match e {
-                                inquire::InquireError::OperationCanceled => {
-                                    Err(anyhow!(YtrsError::Quit))?
-                                }
-                                _ => Err(e)?,
-                            },
-                        };
-                        println!("Generating response ...\n");
-                        let mut stream = ollama.generate_stream(GenerationRequest::new(
-                            model,
-                            format!("Summarize this content in '{l}' in a few bullet points: \n```{}```", response),
-                        )).await?;
-                        let mut stdout = io::stdout();
-                        while let Some(res) = stream.next().await {
-                            let responses = res?;
-                            for resp in responses {
-                                stdout.write_all(resp.response.as_bytes()).await?;
-                                stdout.flush().await?;
-                            }
-                        }
-                        println!("\n");
-                    }
+                f.write_all(response.as_bytes())?;
+                let path = out.join(format!("subtitle_{l}.{}", res_to_dl.file_extension()));
+                progress::say(format!(
+                    "AutoGenerated Captions downloaded at '{}'",
+                    path.to_string_lossy()
+                ));
+                progress::emit(ProgressEvent::Transcript {
+                    lang: l,
+                    path: &path,
+                });
+                if self.find.is_some() {
+                    self.find_in_transcript(&response, res_to_dl.file_extension())?;
+                }
+                self.translate_transcript(&response, res_to_dl.file_extension(), l)
+                    .await?;
+                // Summarize only once, in the first selected language
+                if l == first_lang {
+                    self.summarize_transcript(&response, l, &video).await?;
+                    self.chat_about_transcript(&response, &video).await?;
                 }
             }
             return Ok(());
         }
-        println!("Finding Subtitles");
+        progress::say("Finding Subtitles");
 
-        let selected_lang = match inquire::Select::new("Lang", languages).prompt() {
-            Ok(v) => v,
-            Err(e) => match e {
-                inquire::InquireError::OperationCanceled => Err(anyhow!(YtrsError::Quit))?,
-                _ => Err(e)?,
-            },
+        let langs = self.select_languages("Lang", languages)?;
+        for selected_lang in &langs {
+            let subtitle_path = fetcher
+                .download_subtitle(
+                    &video,
+                    selected_lang.clone(),
+                    format!("subtitle_{selected_lang}.srt"),
+                )
+                .await?;
+            progress::say(format!("Subtitle downloaded to: {subtitle_path:?}"));
+            progress::emit(ProgressEvent::Transcript {
+                lang: selected_lang,
+                path: &subtitle_path,
+            });
+            let content = std::fs::read_to_string(&subtitle_path)?;
+            if self.find.is_some() {
+                self.find_in_transcript(&content, "srt")?;
+            }
+            self.translate_transcript(&content, "srt", selected_lang)
+                .await?;
+            if Some(selected_lang) == langs.first() {
+                self.chat_about_transcript(&content, &video).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Languages requested with `--lang` ('all' selects everything), prompted otherwise
+    fn select_languages(&self, message: &str, available: Vec<String>) -> Result<Vec<String>> {
+        if self.langs.iter().any(|l| l == "all") {
+            return Ok(available);
+        }
+        if !self.langs.is_empty() {
+            for lang in self.langs.iter().filter(|l| !available.contains(l)) {
+                progress::say(format!("No transcript in '{lang}'"));
+            }
+            return Ok(available
+                .into_iter()
+                .filter(|l| self.langs.contains(l))
+                .collect());
+        }
+        prompt_or_quit(
+            inquire::MultiSelect::new(message, available)
+                .with_help_message("Space to select | Enter to download the selection")
+                .with_validator(|selection: &[inquire::list_option::ListOption<&String>]| {
+                    if selection.is_empty() {
+                        Ok(Validation::Invalid("Select at least one language".into()))
+                    } else {
+                        Ok(Validation::Valid)
+                    }
+                })
+                .prompt(),
+        )
+    }
+
+    /// Write a srt translated with the LLM next to the downloaded transcript
+    async fn translate_transcript(&self, content: &str, extension: &str, lang: &str) -> Result<()> {
+        let Some(target) = &self.translate else {
+            return Ok(());
         };
-        // Download English subtitles
-        let subtitle_path = fetcher
-            .download_subtitle(
-                &video,
-                selected_lang.clone(),
-                format!("subtitle_{selected_lang}.srt"),
-            )
-            .await?;
-        println!("Subtitle downloaded to: {:?}", subtitle_path);
+        let cues = transcript::parse(content, extension)?;
+        let llm = Llm::select(&self.args).await?;
+        let translated = transcript::translate(&llm, &cues, target).await?;
+        let (_, out) = Self::get_libs_path(&self.args);
+        let path = out.join(format!(
+            "subtitle_{lang}_{}.srt",
+            sanitize_file_name(target)
+        ));
+        std::fs::write(&path, transcript::to_srt(&translated))?;
+        progress::say(format!(
+            "Translated subtitle written to '{}'",
+            path.to_string_lossy()
+        ));
+        progress::emit(ProgressEvent::Transcript {
+            lang: target,
+            path: &path,
+        });
+        Ok(())
+    }
 
+    async fn summarize_transcript(
+        &self,
+        transcript: &str,
+        lang: &str,
+        video: &yt_dlp::model::Video,
+    ) -> Result<()> {
+        let res = if let Some(b) = self.summarize {
+            println!("Summarize : {b}");
+            b
+        } else {
+            prompt_or_quit(
+                inquire::Confirm::new("Summarize with ai ?")
+                    .with_starting_input("N")
+                    .prompt(),
+            )?
+        };
+        if !res {
+            return Ok(());
+        }
+        let prompt = format!("Summarize this content in '{lang}' in a few bullet points:");
+        let cache = SummaryCache::new(&video.id, &prompt);
+        let summary = match cache.load().filter(|_| !self.refresh) {
+            Some(summary) => {
+                println!("{summary}\n");
+                summary
+            }
+            None => {
+                let llm = Llm::select(&self.args).await?;
+                let transcript = llm.condense(transcript, print_progress).await?;
+                println!("Generating response ...\n");
+                let summary = llm
+                    .print_stream(format!("{prompt} \n```{transcript}```"))
+                    .await?;
+                cache.store(&summary)?;
+                summary
+            }
+        };
+        if self.save_summary {
+            let upload_date = chrono::DateTime::from_timestamp(video.upload_date, 0)
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            let (_, out) = Self::get_libs_path(&self.args);
+            let path = out.join(format!("{}-summary.md", sanitize_file_name(&video.title)));
+            std::fs::write(
+                &path,
+                format!(
+                    "# {}\n\n- Url: {}\n- Channel: {}\n- Uploaded: {upload_date}\n\n## Summary\n\n{}\n",
+                    video.title,
+                    Self::get_video_url(&video.id),
+                    video.channel,
+                    summary.trim()
+                ),
+            )?;
+            progress::say(format!("Summary written to '{}'", path.to_string_lossy()));
+            progress::emit(ProgressEvent::Summary { path: &path });
+        }
         Ok(())
     }
 
-    fn yt_prompt(opt_search: Option<String>) -> Result<String> {
+    /// Answer questions about the video until an empty question or Esc
+    async fn chat_about_transcript(
+        &self,
+        transcript: &str,
+        video: &yt_dlp::model::Video,
+    ) -> Result<()> {
+        if !self.chat {
+            return Ok(());
+        }
+        let llm = Llm::select(&self.args).await?;
+        let mut history = String::new();
+        loop {
+            let question = match inquire::Text::new("Question:")
+                .with_help_message("Enter an empty question or press Esc to stop")
+                .prompt()
+            {
+                Ok(q) if !q.trim().is_empty() => q,
+                Ok(_) | Err(inquire::InquireError::OperationCanceled) => return Ok(()),
+                Err(e) => Err(e)?,
+            };
+            let answer = llm
+                .print_stream(format!(
+                    "You answer questions about the YouTube video '{}' from '{}' using its transcript.\n\
+                    Transcript:\n```{transcript}```\n{history}\nQuestion: {question}\nAnswer:",
+                    video.title, video.channel
+                ))
+                .await?;
+            history.push_str(&format!(
+                "\nQuestion: {question}\nAnswer: {}\n",
+                answer.trim()
+            ));
+        }
+    }
+
+    /// Print the timestamps of the transcript lines matching the phrase of `--find`, or the
+    /// phrases asked for when it has none
+    fn find_in_transcript(&self, content: &str, extension: &str) -> Result<()> {
+        let cues = transcript::parse(content, extension)?;
+        let print_matches = |phrase: &str| {
+            let matches = transcript::search(&cues, phrase);
+            if matches.is_empty() {
+                println!("No match for '{phrase}'");
+            }
+            for cue in matches {
+                println!(
+                    "{} {}",
+                    ratatui::crossterm::style::Stylize::green(
+                        format_time(cue.start as u32).to_string()
+                    ),
+                    cue.text
+                );
+            }
+        };
+        if let Some(Some(phrase)) = &self.find {
+            print_matches(phrase);
+            return Ok(());
+        }
+        loop {
+            let phrase = InquireText::new("Find:")
+                .with_help_message("Leave empty to stop searching")
+                .prompt()?;
+            if phrase.trim().is_empty() {
+                return Ok(());
+            }
+            print_matches(phrase.trim());
+        }
+    }
+
+    pub(crate) fn yt_prompt(opt_search: Option<String>) -> Result<String> {
         InquireText::new("Youtube Search:")
             .with_help_message("Press Escape to cancel | Ctrl+C to exit")
             .with_initial_value(&opt_search.unwrap_or_default())
@@ -1285,28 +4429,144 @@ impl YoutubeRs {
             .context("Failed to read search input")
     }
 
-    async fn query_ytmusic(opt_search: Option<String>) -> Result<(TrackItem, String)> {
-        let search_term = Self::yt_prompt(opt_search)?;
-        let rp = RustyPipe::new();
-        let found_videos = rp
+    /// Search YouTube, or YouTube Music when `music`, without prompting
+    pub(crate) async fn search(terms: &str, music: bool) -> Result<Vec<YoutubeResponse>> {
+        if music {
+            Ok(crate::filter::tracks(backend().search_tracks(terms).await?)
+                .into_iter()
+                .map(YoutubeResponse::from)
+                .collect())
+        } else {
+            Ok(crate::filter::videos(backend().search_videos(terms).await?)
+                .iter()
+                .map(YoutubeResponse::from)
+                .collect())
+        }
+    }
+
+    /// What is trending in `category`, without any query
+    pub(crate) async fn trending_entries(
+        category: TrendingCategory,
+    ) -> Result<Vec<YoutubeResponse>> {
+        let query = crate::auth::rustypipe().query().unauthenticated();
+        let found = match category {
+            TrendingCategory::Videos => query
+                .trending()
+                .await
+                .map(|videos| videos.iter().map(YoutubeResponse::from).collect()),
+            TrendingCategory::Music => query.music_charts(None).await.map(|charts| {
+                charts
+                    .trending_tracks
+                    .into_iter()
+                    .chain(charts.top_tracks)
+                    .map(YoutubeResponse::from)
+                    .collect()
+            }),
+            TrendingCategory::NewMusic => query
+                .music_new_videos()
+                .await
+                .map(|tracks| tracks.into_iter().map(YoutubeResponse::from).collect()),
+        };
+        found
+            .map(|entries: Vec<YoutubeResponse>| {
+                entries.into_iter().filter(crate::filter::allowed).collect()
+            })
+            .with_context(|| format!("Failed to fetch the trending {category}"))
+    }
+
+    /// List the trending entries of `category` in the search popup
+    async fn show_trending(
+        &mut self,
+        category: TrendingCategory,
+        videos_list: &mut Vec<(String, YoutubeResponse)>,
+        selected_list_item: &mut ListState,
+    ) {
+        match Self::trending_entries(category).await {
+            Ok(entries) => {
+                *videos_list = entries.into_iter().map(|e| (e.label(), e)).collect();
+                selected_list_item.select_first();
+            }
+            Err(e) => {
+                videos_list.clear();
+                self.toast = Some((e.to_string(), Instant::now()));
+            }
+        }
+    }
+
+    /// Id of the media mpv is loading when its direct streams are to be looked up, `None` leaves it to yt-dlp
+    async fn direct_stream_id(mpv: &mut MpvIpc, skipped: &[String]) -> Option<String> {
+        let filename = mpv.get_prop::<String>("stream-open-filename").await.ok()?;
+        YoutubeLink::parse(&filename)
+            .and_then(|link| link.video_id)
+            .filter(|id| !skipped.contains(id))
+    }
+
+    /// Turn a video url into the search item of that video
+    pub(crate) async fn resolve_video(url: &str) -> Result<YoutubeResponse> {
+        let link =
+            YoutubeLink::parse(url).ok_or_else(|| anyhow!("'{url}' is not a YouTube link"))?;
+        let Some(id) = link.video_id else {
+            bail!("'{url}' is not a video");
+        };
+        if link.music {
+            Ok(YoutubeResponse::Track(backend().track(&id).await?))
+        } else {
+            Ok(YoutubeResponse::Video(backend().video(&id).await?))
+        }
+    }
+
+    /// Name and entries of the playlist of the url given on the command line, from its video on when it has one
+    async fn link_playlist(&self) -> Result<Option<(String, Vec<YoutubeResponse>)>> {
+        let Some(YoutubeLink {
+            playlist_id: Some(id),
+            video_id,
+            ..
+        }) = &self.link
+        else {
+            return Ok(None);
+        };
+        let playlist = crate::auth::rustypipe()
             .query()
             .unauthenticated()
-            .music_search_tracks(search_term.clone())
+            .playlist(id)
             .await
-            .context("Failed to search YouTube Music")?;
-        Self::cleanup_rustypipe_cache();
-        let mut found_videos_str: Vec<String> = found_videos
-            .clone()
-            .items
+            .with_context(|| format!("Failed to fetch the playlist '{id}'"))?;
+        let mut entries: Vec<YoutubeResponse> = playlist
+            .videos
             .items
-            .into_iter()
-            .map(|track| TrackInfo::from(&track).colored())
+            .iter()
+            .map(YoutubeResponse::from)
+            .collect();
+        if let Some(video_id) = video_id
+            && let Some(position) = entries.iter().position(|entry| entry.get_id() == *video_id)
+        {
+            entries.drain(..position);
+        }
+        Ok(Some((playlist.name, entries)))
+    }
+
+    pub(crate) async fn query_ytmusic(opt_search: Option<String>) -> Result<(TrackItem, String)> {
+        // A link plays that exact track
+        if let Some(id) = opt_search
+            .as_deref()
+            .and_then(YoutubeLink::parse)
+            .and_then(|link| link.video_id)
+        {
+            return Ok((backend().track(&id).await?, opt_search.unwrap_or_default()));
+        }
+        let search_term = Self::yt_prompt(opt_search)?;
+        let found_videos = crate::filter::tracks(backend().search_tracks(&search_term).await?);
+        let mut found_videos_str: Vec<String> = found_videos
+            .iter()
+            .map(|track| TrackInfo::from(track).colored())
             .collect();
         found_videos_str.push("Exit".red().to_string());
-        let selected_vid_str = Select::new("Select Music", found_videos_str)
-            .prompt()
-            .context("Failed to select music")?;
-        if selected_vid_str == "Exit".red().to_string().as_str() {
+        // The index of the choice, titles can repeat
+        let selected = Select::new("Select Music", found_videos_str)
+            .raw_prompt()
+            .context("Failed to select music")?
+            .index;
+        if selected == found_videos.len() {
             let confirm = Confirm::new("Exit application?")
                 .with_default(true)
                 .prompt()?;
@@ -1314,44 +4574,39 @@ impl YoutubeRs {
                 bail!("User cancelled");
             }
         }
-        if let Some(vid) = found_videos
-            .items
-            .items
-            .into_iter()
-            .find(|track| TrackInfo::from(track).colored() == selected_vid_str)
-        {
+        if let Some(vid) = found_videos.into_iter().nth(selected) {
             Ok((vid, search_term))
         } else {
             bail!("Selected music not found. Please try again.");
         }
     }
-    async fn query_ytvideo(opt_search: Option<String>) -> Result<(VideoItem, String)> {
+    pub(crate) async fn query_ytvideo(opt_search: Option<String>) -> Result<(VideoItem, String)> {
+        if let Some(id) = opt_search
+            .as_deref()
+            .and_then(YoutubeLink::parse)
+            .and_then(|link| link.video_id)
+        {
+            return Ok((backend().video(&id).await?, opt_search.unwrap_or_default()));
+        }
         let search_term = Self::yt_prompt(opt_search.clone())?;
-        let found_videos: rustypipe::model::SearchResult<VideoItem> = RustyPipe::new()
-            .query()
-            .unauthenticated()
-            .search(search_term.clone())
-            .await
-            .context("Failed to search YouTube")?;
-        Self::cleanup_rustypipe_cache();
-        if found_videos.items.items.len() == 1
-            && let Some(item) = found_videos.items.items.first()
+        let found_videos = crate::filter::videos(backend().search_videos(&search_term).await?);
+        if found_videos.len() == 1
+            && let Some(item) = found_videos.first()
         {
             return Ok((item.clone(), opt_search.clone().unwrap_or_default()));
         }
         let mut videos: Vec<String> = found_videos
-            .items
-            .items
             .iter()
             .map(|v: &VideoItem| VideoInfo::from(v).colored())
             .collect();
         videos.push("Exit".red().to_string());
 
-        let video_entry = Select::new("Select video to watch", videos)
+        let selected = Select::new("Select video to watch", videos)
             .with_help_message("Type to filter | Arrow keys to navigate | Enter to select")
-            .prompt()
-            .context("Failed to select video")?;
-        if video_entry == "Exit".red().to_string().as_str() {
+            .raw_prompt()
+            .context("Failed to select video")?
+            .index;
+        if selected == found_videos.len() {
             let confirm = Confirm::new("Exit application?")
                 .with_default(true)
                 .prompt()?;
@@ -1359,49 +4614,31 @@ impl YoutubeRs {
                 bail!("User cancelled");
             }
         }
-        let selected_vid = found_videos
-            .items
-            .items
-            .into_iter()
-            .find(|v| VideoInfo::from(v).colored() == video_entry);
-        if let Some(vid) = selected_vid {
+        if let Some(vid) = found_videos.into_iter().nth(selected) {
             Ok((vid, search_term))
         } else {
             bail!("Selected video not found. Please try again.");
         }
     }
-    pub fn check_mpv() -> Result<bool> {
-        let output = std::process::Command::new("mpv")
-            .args(["--version"])
-            .output();
+    pub fn check_mpv(args: &Cli) -> Result<bool> {
+        // libmpv is built in, and the other players tell themselves when they can not start
+        if cfg!(feature = "libmpv") || crate::config::config().player != PlayerChoice::Mpv {
+            return Ok(true);
+        }
+        let mpv = args.mpv_path.as_deref().unwrap_or(Path::new("mpv"));
+        let output = std::process::Command::new(mpv).args(["--version"]).output();
         match output {
             Ok(output) => Ok(output.status.success()),
             Err(_) => Err(YtrsError::MpvNotFound.into()),
         }
     }
-    fn ytdlp_exist(args: &Cli) -> bool {
-        if cfg!(target_os = "windows") {
-            PathBuf::from(format!(
-                "{}.exe",
-                Self::get_libs(args).youtube.to_string_lossy()
-            ))
-            .exists()
-        } else {
-            Self::get_libs(args).youtube.exists()
-        }
+    pub(crate) fn ytdlp_exist(args: &Cli) -> bool {
+        crate::ytdlp::installed(&Self::get_libs(args).youtube)
     }
-    fn ffmpeg_check(args: &Cli) -> bool {
-        if cfg!(target_os = "windows") {
-            PathBuf::from(format!(
-                "{}.exe",
-                Self::get_libs(args).ffmpeg.to_string_lossy()
-            ))
-            .exists()
-        } else {
-            Self::get_libs(args).ffmpeg.exists()
-        }
+    pub(crate) fn ffmpeg_check(args: &Cli) -> bool {
+        crate::ytdlp::installed(&Self::get_libs(args).ffmpeg)
     }
-    fn libraries_exist(&mut self, args: &Cli) -> bool {
+    pub(crate) fn libraries_exist(&mut self, args: &Cli) -> bool {
         if !Self::ytdlp_exist(args) {
             println!(
                 "YT-DLP not found at '{}'",
@@ -1417,119 +4654,51 @@ impl YoutubeRs {
         Self::ytdlp_exist(args) && Self::ffmpeg_check(args)
     }
 
-    async fn install_lib(args: &Cli) -> Result<()> {
+    pub(crate) async fn install_lib(args: &Cli) -> Result<()> {
         println!("Installing Libraries");
-        let (exec_dir, output_dir) = Self::get_libs_path(args);
-        let _ = Youtube::with_new_binaries(exec_dir, output_dir).await?;
+        // Only what is found neither in the libs folder nor in the PATH
+        if !Self::ytdlp_exist(args) {
+            crate::ytdlp::install(&crate::ytdlp::executable(args), args.ytdlp_channel).await?;
+        }
+        if !Self::ffmpeg_check(args) {
+            let (exec_dir, _) = Self::get_libs_path(args);
+            LibraryInstaller::new(exec_dir).install_ffmpeg(None).await?;
+        }
         Ok(())
     }
-    #[cfg(target_os = "windows")]
-    fn get_libs_path(args: &Cli) -> (PathBuf, PathBuf) {
-        let exec_dir = if let Some(libs_path) = &args.libs_path {
-            libs_path.join("libs")
-        } else {
-            if cfg!(target_os = "windows") {
-                PathBuf::from(env!("USERPROFILE"))
-                    .join(".config")
-                    .join("ytrs")
-                    .join("libs")
-            } else if cfg!(target_os = "linux") || cfg!(target_os = "macos") {
-                if let Ok(home_path_str) = std::env::var("HOME") {
-                    PathBuf::from(home_path_str)
-                        .join(".config")
-                        .join("ytrs")
-                        .join("libs")
-                } else {
-                    PathBuf::from("libs")
-                }
-            } else {
-                PathBuf::from("libs")
-            }
-        };
-        let output_dir = if let Some(output) = &args.output_path {
-            output.join("output")
-        } else {
-            if cfg!(target_os = "windows") {
-                PathBuf::from(env!("USERPROFILE"))
-                    .join(".config")
-                    .join("ytrs")
-                    .join("output")
-            } else if cfg!(target_os = "linux") || cfg!(target_os = "macos") {
-                if let Ok(home_path_str) = std::env::var("Home") {
-                    PathBuf::from(home_path_str)
-                        .join(".config")
-                        .join("ytrs")
-                        .join("output")
-                } else {
-                    PathBuf::from("output")
-                }
-            } else {
-                PathBuf::from("output")
-            }
-        };
-        (exec_dir, output_dir)
-    }
-
-    #[cfg(target_os = "linux")]
-    fn get_libs_path(args: &Cli) -> (PathBuf, PathBuf) {
-        let exec_dir = if let Some(libs_path) = &args.libs_path {
-            libs_path.join("libs")
-        } else if let Ok(home_path_str) = std::env::var("HOME") {
-            PathBuf::from(home_path_str)
-                .join(".config")
-                .join("ytrs")
-                .join("libs")
-        } else {
-            PathBuf::from("libs")
-        };
-        let output_dir = if let Some(output) = &args.output_path {
-            output.join("output")
-        } else if let Ok(home_path_str) = std::env::var("HOME") {
-            PathBuf::from(home_path_str)
-                .join(".config")
-                .join("ytrs")
-                .join("output")
-        } else {
-            PathBuf::from("output")
-        };
-        (exec_dir, output_dir)
-    }
-    #[cfg(target_os = "macos")]
-    fn get_libs_path(args: &Cli) -> (PathBuf, PathBuf) {
-        let exec_dir = if let Some(libs_path) = &args.libs_path {
-            libs_path.join("libs")
-        } else if let Ok(home_path_str) = std::env::var("HOME") {
-            PathBuf::from(home_path_str)
-                .join(".config")
-                .join("ytrs")
-                .join("libs")
-        } else {
-            PathBuf::from("libs")
+    /// Folders of yt-dlp and ffmpeg, and of the downloads, `~/.local/share/ytrs` unless given
+    pub(crate) fn get_libs_path(args: &Cli) -> (PathBuf, PathBuf) {
+        let exec_dir = match &args.libs_path {
+            Some(libs_path) => libs_path.join("libs"),
+            None => crate::utility::data_dir().join("libs"),
         };
-        let output_dir = if let Some(output) = &args.output_path {
-            output.join("output")
-        } else if let Ok(home_path_str) = std::env::var("HOME") {
-            PathBuf::from(home_path_str)
-                .join(".config")
-                .join("ytrs")
-                .join("output")
-        } else {
-            PathBuf::from("output")
+        let output_dir = match &args.output_path {
+            Some(output) => output.join("output"),
+            None => crate::utility::data_dir().join("output"),
         };
         (exec_dir, output_dir)
     }
-    fn get_libs(args: &Cli) -> Libraries {
+    /// yt-dlp and ffmpeg of the libs folder or of the PATH, in the order of `--libs-order`
+    pub(crate) fn get_libs(args: &Cli) -> Libraries {
         let (libs, _) = Self::get_libs_path(args);
-        let youtube = libs.join("yt-dlp");
-        let ffmpeg = libs.join("ffmpeg");
+        let youtube = crate::ytdlp::locate(&args.libs_order, libs.join("yt-dlp"), "yt-dlp");
+        let ffmpeg = crate::ytdlp::locate(&args.libs_order, libs.join("ffmpeg"), "ffmpeg");
         Libraries::new(youtube, ffmpeg)
     }
-    async fn get_fetcher(args: &Cli) -> Result<Youtube> {
+    pub(crate) async fn get_fetcher(args: &Cli) -> Result<Youtube> {
         let (_, out) = Self::get_libs_path(args);
         let libs = Self::get_libs(args);
-        Youtube::new(libs, out)
+        let mut fetcher = Youtube::new(libs, out)
             .await
-            .context("Failed to retrieve Youtube Fetcher")
+            .context("Failed to retrieve Youtube Fetcher")?;
+        fetcher.with_args(crate::auth::ytdlp_args(args));
+        Ok(fetcher)
+    }
+    /// Fetcher going through other player clients, for age-restricted and embed-blocked videos
+    pub(crate) async fn get_fallback_fetcher(args: &Cli) -> Result<Youtube> {
+        let mut fetcher = Self::get_fetcher(args).await?;
+        fetcher.with_args(crate::auth::fallback_ytdlp_args());
+        Ok(fetcher)
     }
     #[allow(clippy::too_many_arguments)]
     async fn handle_playback_event(
@@ -1540,8 +4709,13 @@ impl YoutubeRs {
         open_popup: &mut bool,
         event: ratatui::crossterm::event::Event,
         empty_player: bool,
-        conn_out: &mut Option<MidiOutputConnection>,
-        mpv_vol: &f64,
+        transcript_search: &mut TranscriptSearch,
+        summary: &mut SummaryPane,
+        transcript_view: &mut TranscriptView,
+        related: &mut RelatedPane,
+        audio_tracks: &mut AudioTracksPane,
+        subtitles: &mut SubtitlesPane,
+        side_pane: &mut Option<SidePane>,
     ) -> ControlFlow<()> {
         if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Char('q') {
             return ControlFlow::Break(());
@@ -1551,7 +4725,7 @@ impl YoutubeRs {
             && let Some(res) = response
         {
             let current_url = Self::get_video_url(&res.get_id());
-            let _ = Self::clipboard(&current_url);
+            Self::clipboard(&current_url).log_error("Could not copy the url");
         }
         if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Char(' ') {
             *pause_state = !*pause_state;
@@ -1565,15 +4739,16 @@ impl YoutubeRs {
         }
         if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Up {
             let _ = mpv.send_command(json!(["add", "volume", "5"])).await;
-            if let Some(out_midi_connection) = conn_out {
-                let _ = out_midi_connection.send(&[224, 0, u32_to_midi(*mpv_vol as u32)]);
-            }
+        }
+        // Skip through the queue, like swiping through shorts
+        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Char('j') {
+            let _ = mpv.send_command(json!(["playlist-next"])).await;
+        }
+        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Char('k') {
+            let _ = mpv.send_command(json!(["playlist-prev"])).await;
         }
         if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Down {
             let _ = mpv.send_command(json!(["add", "volume", "-5"])).await;
-            if let Some(out_midi_connection) = conn_out {
-                let _ = out_midi_connection.send(&[224, 0, u32_to_midi(*mpv_vol as u32)]);
-            }
         }
         if (response.is_some() | empty_player)
             && event.is_key_press()
@@ -1581,16 +4756,155 @@ impl YoutubeRs {
         {
             *open_popup = !*open_popup;
         }
+        if response.is_some()
+            && event.is_key_press()
+            && event.as_key_event().unwrap().code == KeyCode::Char('f')
+        {
+            transcript_search.open = true;
+        }
+        if event.is_key_press()
+            && event.as_key_event().unwrap().code == KeyCode::Char('s')
+            && let Some(res) = response
+        {
+            let video_id = res.get_id();
+            if SidePane::Summary.toggle(side_pane) && summary.video_id.as_ref() != Some(&video_id) {
+                summary.start(video_id, self.args.clone());
+            }
+        }
+        if event.is_key_press()
+            && event.as_key_event().unwrap().code == KeyCode::Char('t')
+            && let Some(res) = response
+        {
+            let video_id = res.get_id();
+            if SidePane::Transcript.toggle(side_pane) && transcript_view.request(&video_id) {
+                self.request_cues(video_id, false);
+            }
+        }
+        if event.is_key_press()
+            && event.as_key_event().unwrap().code == KeyCode::Char('r')
+            && let Some(res) = response
+            && SidePane::Related.toggle(side_pane)
+        {
+            self.request_related(related, res.get_id());
+        }
+        if event.is_key_press()
+            && event.as_key_event().unwrap().code == KeyCode::Char('p')
+            && let Some(res) = response
+            && SidePane::Save.toggle(side_pane)
+        {
+            self.request_account_playlists(res.get_id(), false);
+        }
+        if event.is_key_press()
+            && event.as_key_event().unwrap().code == KeyCode::Char('e')
+            && response.as_ref().is_some_and(YoutubeResponse::is_live)
+            && let Ok(window) = mpv.get_prop::<f64>("duration").await
+        {
+            // The very end of the window is still being fetched
+            let _ = mpv
+                .send_command(json!(["seek", (window - 3.0).max(0.0), "absolute"]))
+                .await;
+            self.toast = Some(("Back to the live edge".to_owned(), Instant::now()));
+        }
+        if event.is_key_press() && event.as_key_event().unwrap().code == KeyCode::Char('n') {
+            SidePane::Uploads.toggle(side_pane);
+        }
+        if event.is_key_press()
+            && event.as_key_event().unwrap().code == KeyCode::Char('v')
+            && (response.is_some() || !empty_player)
+        {
+            // Empty for local files
+            let video_id = response
+                .as_ref()
+                .map(YoutubeResponse::get_id)
+                .unwrap_or_default();
+            if SidePane::AudioTracks.toggle(side_pane) && audio_tracks.request(&video_id) {
+                // Local files bring all their tracks, the videos are reloaded with the one picked through yt-dlp
+                let tracks = crate::audio_tracks::mpv_tracks(mpv).await;
+                if tracks.is_empty() && !video_id.is_empty() {
+                    let args = self.args.clone();
+                    self.jobs.spawn("Loading the audio tracks", async move {
+                        JobOutput::AudioTracks {
+                            found: Self::audio_tracks(&args, &video_id).await,
+                            video_id,
+                        }
+                    });
+                } else {
+                    audio_tracks.loaded(&video_id, Ok(tracks));
+                }
+            }
+        }
+        if event.is_key_press()
+            && event.as_key_event().unwrap().code == KeyCode::Char('c')
+            && (response.is_some() || !empty_player)
+            && SidePane::Subtitles.toggle(side_pane)
+        {
+            subtitles.load(mpv).await;
+        }
+        if event.is_key_press()
+            && event.as_key_event().unwrap().code == KeyCode::Char('l')
+            && let Some(res) = response
+        {
+            let message = match crate::account::like(&res.get_id()).await {
+                Ok(()) => format!("Liked {}", res.get_name()),
+                Err(e) => e.to_string(),
+            };
+            self.toast = Some((message, Instant::now()));
+        }
+        if *side_pane == Some(SidePane::Summary) && event.is_key_press() {
+            match event.as_key_event().unwrap().code {
+                KeyCode::PageUp => summary.scroll = summary.scroll.saturating_sub(5),
+                KeyCode::PageDown => summary.scroll = summary.scroll.saturating_add(5),
+                _ => {}
+            }
+        }
         ControlFlow::Continue(())
     }
 }
 
-fn u32_to_midi(val: u32) -> u8 {
-    ((val * 127) / 130) as u8
+/// What woke the player up
+enum Wake {
+    Terminal(Event),
+    Pad(gilrs::Button),
+    Command(PlayerCommand),
+    Ended(serde_json::Value),
+    Hook(serde_json::Value),
+    Upload(VideoItem),
+    Job(JobOutput),
+    // The direct stream of the media mpv waits on the load hook of
+    DirectStream {
+        hook_id: serde_json::Value,
+        url: Option<String>,
+    },
+    // The Invidious or Piped stream of a playlist entry that failed to play
+    Fallback {
+        entry: i64,
+        stream: Option<String>,
+    },
+    // Of the summary being streamed
+    Token(String),
+    // The terminal closed
+    Hangup,
+    // Only the playback time moved
+    Playback,
+    // Anything else shown may have changed
+    Changed,
+}
+
+/// Resolves once `rx` holds a value not seen yet, leaving it unseen.
+/// Never resolves once the sender is gone, mpv stopping wakes the player instead
+async fn changed<T>(rx: &watch::Receiver<T>) {
+    if rx.clone().changed().await.is_err() {
+        std::future::pending::<()>().await;
+    }
 }
 
-fn u8_to_mpv_vol(val: u8) -> u32 {
-    ((val as u32 * 130) / 127).clamp(0, 130)
+/// Turn a cancelled prompt (Escape) into a clean quit
+fn prompt_or_quit<T>(res: std::result::Result<T, inquire::InquireError>) -> Result<T> {
+    match res {
+        Ok(v) => Ok(v),
+        Err(inquire::InquireError::OperationCanceled) => Err(anyhow!(YtrsError::Quit)),
+        Err(e) => Err(e.into()),
+    }
 }
 
 impl VideoInfo {
@@ -1663,6 +4977,29 @@ impl From<String> for VideoFormat {
             .0
     }
 }
+impl Format {
+    /// Extension of the downloaded file
+    pub fn extension(&self) -> String {
+        match self {
+            Format::Audio { format } => format.to_string().to_lowercase(),
+            Format::Video { format } => format.to_string().to_lowercase(),
+        }
+    }
+
+    /// Ask for audio or video, then for the file format
+    pub fn prompt(message: &str) -> Result<Self> {
+        let mut format = Self::from(FormatInquire::select(message).prompt()?);
+        match &mut format {
+            Format::Audio { format } => {
+                *format = AudioFormat::select("Select Audio Format").prompt()?
+            }
+            Format::Video { format } => {
+                *format = VideoFormat::select("Select Video Format").prompt()?
+            }
+        }
+        Ok(format)
+    }
+}
 impl Default for Format {
     fn default() -> Self {
         Self::Audio {
@@ -0,0 +1,47 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Record of already downloaded video ids, stored one `youtube <id>` entry per line
+/// (the same layout yt-dlp uses for `--download-archive`).
+pub struct DownloadArchive {
+    path: PathBuf,
+    ids: HashSet<String>,
+}
+
+impl DownloadArchive {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let ids = if path.exists() {
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("Could not read archive '{}'", path.to_string_lossy()))?
+                .lines()
+                .filter_map(|line| line.split_whitespace().nth(1))
+                .map(str::to_owned)
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        Ok(Self { path, ids })
+    }
+    pub fn contains(&self, video_id: &str) -> bool {
+        self.ids.contains(video_id)
+    }
+    pub fn insert(&mut self, video_id: &str) -> Result<()> {
+        if !self.ids.insert(video_id.to_owned()) {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut f = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)
+            .with_context(|| format!("Could not open archive '{}'", self.path.to_string_lossy()))?;
+        writeln!(f, "youtube {video_id}")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use crate::app::YoutubeRs;
+use crate::cli::Cli;
+use crate::mpv::MpvIpc;
+use anyhow::{Context, Result, bail};
+use inquire::Select;
+use ratatui::widgets::ListState;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use yt_dlp::Youtube;
+use yt_dlp::model::Video;
+use yt_dlp::model::format::Format;
+
+/// An audio track of a video dubbed in several languages or with a commentary
+#[derive(Clone)]
+pub struct AudioTrack {
+    pub language: String,
+    pub label: String,
+    // Id of the track in mpv when it already loaded all of them, e.g. for local files
+    pub mpv_id: Option<i64>,
+}
+
+impl std::fmt::Display for AudioTrack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+/// State of the player pane listing the audio tracks of the current media
+#[derive(Default)]
+pub struct AudioTracksPane {
+    // Media the tracks belong to
+    pub video_id: Option<String>,
+    pub tracks: Vec<AudioTrack>,
+    pub selected: ListState,
+    pub error: Option<String>,
+    // Where to seek back to once mpv reloaded the media with the picked track
+    pub reload: Option<(f64, mpsc::Receiver<serde_json::Value>)>,
+}
+
+impl AudioTracksPane {
+    pub fn selected_track(&self) -> Option<&AudioTrack> {
+        self.selected.selected().and_then(|i| self.tracks.get(i))
+    }
+
+    /// Whether the tracks of `video_id`, empty for local files, are to be listed again
+    pub fn request(&mut self, video_id: &str) -> bool {
+        if self.video_id.as_deref() == Some(video_id) {
+            return false;
+        }
+        self.tracks.clear();
+        self.error = None;
+        self.video_id = Some(video_id.to_owned());
+        true
+    }
+
+    /// List the tracks found for `video_id`, unless another media was requested meanwhile
+    pub fn loaded(&mut self, video_id: &str, found: Result<Vec<AudioTrack>>) {
+        if self.video_id.as_deref() != Some(video_id) {
+            return;
+        }
+        match found {
+            Ok(tracks) => {
+                self.error = tracks.is_empty().then(|| "No other audio track".to_owned());
+                self.tracks = tracks;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        self.selected.select_first();
+    }
+}
+
+/// The audio tracks of `video`, one per language, in the order YouTube lists them
+pub fn tracks(video: &Video) -> Vec<AudioTrack> {
+    let mut tracks: Vec<AudioTrack> = Vec::new();
+    for format in video.formats.iter().filter(|format| format.is_audio()) {
+        let Some(language) = &format.language else {
+            continue;
+        };
+        if tracks.iter().any(|track| &track.language == language) {
+            continue;
+        }
+        // e.g. 'French dubbed-auto, medium'
+        let label = match format
+            .format_note
+            .as_deref()
+            .and_then(|note| note.split(", ").next())
+        {
+            Some(name) => format!("{name} [{language}]"),
+            None => language.clone(),
+        };
+        tracks.push(AudioTrack {
+            language: language.clone(),
+            label,
+            mpv_id: None,
+        });
+    }
+    tracks
+}
+
+/// The best audio format of `video` in `language`
+pub fn best_format<'a>(video: &'a Video, language: &str) -> Option<&'a Format> {
+    video
+        .formats
+        .iter()
+        .filter(|format| format.is_audio() && format.language.as_deref() == Some(language))
+        .max_by(|a, b| video.compare_audio_formats(a, b))
+}
+
+/// Download the audio `track` of `video_id` to `output`, converted by ffmpeg to the format of its
+/// extension the way yt-dlp extracts the audio
+pub async fn download(
+    fetcher: &Youtube,
+    ffmpeg: &Path,
+    video_id: &str,
+    track: &Format,
+    output: &Path,
+) -> Result<PathBuf> {
+    let raw = format!("{video_id}.audio.{:?}", track.download_info.ext);
+    let raw = fetcher.download_format(track, &raw).await?;
+    let status = Command::new(ffmpeg)
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(&raw)
+        .arg("-vn")
+        .arg(output)
+        .status()
+        .await;
+    let _ = std::fs::remove_file(&raw);
+    let status = status.context("Could not start ffmpeg")?;
+    if !status.success() {
+        let _ = std::fs::remove_file(output);
+        bail!("ffmpeg could not convert the audio track ({status})");
+    }
+    Ok(output.to_path_buf())
+}
+
+/// yt-dlp format of mpv playing the audio track in `language`, or the default one when missing
+pub fn ytdl_format(language: &str, audio_only: bool) -> String {
+    if audio_only {
+        format!("ba[language={language}]/ba/b")
+    } else {
+        format!("bv*+ba[language={language}]/bv*+ba/b")
+    }
+}
+
+/// The audio tracks mpv loaded for the current media, only filled when there are several
+pub async fn mpv_tracks(mpv: &mut MpvIpc) -> Vec<AudioTrack> {
+    let list = mpv
+        .get_prop::<Vec<serde_json::Value>>("track-list")
+        .await
+        .unwrap_or_default();
+    let tracks: Vec<AudioTrack> = list
+        .iter()
+        .filter(|track| track["type"] == "audio")
+        .filter_map(|track| {
+            let id = track["id"].as_i64()?;
+            let language = track["lang"].as_str().unwrap_or_default().to_owned();
+            let label = match (track["title"].as_str(), language.as_str()) {
+                (Some(title), "") => title.to_owned(),
+                (Some(title), language) => format!("{title} [{language}]"),
+                (None, "") => format!("Track {id}"),
+                (None, language) => language.to_owned(),
+            };
+            Some(AudioTrack {
+                language,
+                label,
+                mpv_id: Some(id),
+            })
+        })
+        .collect();
+    if tracks.len() > 1 { tracks } else { Vec::new() }
+}
+
+impl YoutubeRs {
+    /// The audio tracks yt-dlp finds for `video_id`
+    pub(crate) async fn audio_tracks(args: &Cli, video_id: &str) -> Result<Vec<AudioTrack>> {
+        let video = Self::get_fetcher(args)
+            .await?
+            .fetch_video_infos(Self::get_video_url(&video_id.to_owned()))
+            .await?;
+        Ok(tracks(&video))
+    }
+
+    /// Ask which audio track to play or download when `video_id` has several
+    pub(crate) async fn pick_audio_track(&mut self, video_id: &str) -> Result<()> {
+        let tracks = Self::audio_tracks(&self.args, video_id).await?;
+        if tracks.len() > 1 {
+            let track = Select::new("Audio track", tracks).prompt()?;
+            self.audio_language = Some(track.language);
+        }
+        Ok(())
+    }
+}
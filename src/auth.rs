@@ -0,0 +1,237 @@
+use crate::app::YoutubeRs;
+use crate::cli::Cli;
+use crate::locale::Locale;
+use crate::log::LogError;
+use crate::utility::{cache_dir, data_dir};
+use anyhow::{Context, Result};
+use rustypipe::cache::CacheStorage;
+use rustypipe::client::{ClientType, RustyPipe};
+use rustypipe::param::{Country, Language};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::process::Command;
+
+// yt-dlp messages of the videos the default player clients cannot get
+const RESTRICTED: [&str; 4] = [
+    "confirm your age",
+    "age-restricted",
+    "inappropriate for some users",
+    "Playback on other websites has been disabled",
+];
+// Player contexts YouTube is more lenient with for age-restricted and embed-blocked videos
+const FALLBACK_CLIENTS: &str = "youtube:player_client=tv_embedded,web_embedded,tv";
+
+// Client of every RustyPipe query, set up by `init`
+static CLIENT: OnceLock<RustyPipe> = OnceLock::new();
+
+/// RustyPipe cache in `~/.local/share/ytrs/rustypipe_cache.json`: client versions, player data,
+/// the token of `ytrs login` and the cookies
+struct FileCache;
+
+impl FileCache {
+    fn path() -> PathBuf {
+        data_dir().join("rustypipe_cache.json")
+    }
+
+    /// Forget the client versions and player data once older than `retention_days`,
+    /// and the cookies when none are given, the login of `ytrs login` stays
+    fn prune(retention_days: u64, keep_cookies: bool) -> Result<()> {
+        let path = Self::path();
+        // Where `ytrs login` used to keep its token
+        let legacy = data_dir().join("auth.json");
+        if !path.exists() && legacy.exists() {
+            std::fs::rename(&legacy, &path)?;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return Ok(());
+        };
+        let age = std::fs::metadata(&path)?
+            .modified()?
+            .elapsed()
+            .unwrap_or_default();
+        let mut cache: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse '{}'", path.to_string_lossy()))?;
+        let Some(cache) = cache.as_object_mut() else {
+            return Ok(());
+        };
+        let mut pruned = false;
+        if age > Duration::from_secs(retention_days * 24 * 3600) {
+            pruned |= cache.remove("clients").is_some();
+            pruned |= cache.remove("deobf").is_some();
+        }
+        if !keep_cookies {
+            pruned |= cache.remove("auth_cookie").is_some();
+        }
+        if pruned {
+            Self.write(&serde_json::to_string(cache)?);
+        }
+        Ok(())
+    }
+}
+
+impl CacheStorage for FileCache {
+    fn write(&self, data: &str) {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // Only the user can read the token and the cookies
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(&path)
+            .and_then(|mut file| file.write_all(data.as_bytes()))
+            .log_error("Could not write the RustyPipe cache");
+    }
+
+    fn read(&self) -> Option<String> {
+        std::fs::read_to_string(Self::path()).ok()
+    }
+}
+
+fn client(country: Option<Country>, lang: Option<Language>) -> RustyPipe {
+    // The error reports land in the cache folder, instead of the working directory
+    let mut builder = RustyPipe::builder()
+        .storage_dir(cache_dir())
+        .storage(Box::new(FileCache));
+    if let Some(country) = country {
+        builder = builder.country(country);
+    }
+    if let Some(lang) = lang {
+        builder = builder.lang(lang);
+    }
+    builder.build().unwrap_or_else(|_| RustyPipe::new())
+}
+
+/// RustyPipe client, authenticated after `ytrs login` or when `--cookies` or `--cookies-from-browser` is used
+pub fn rustypipe() -> RustyPipe {
+    // RustyPipe refreshes the token when it expires
+    CLIENT.get_or_init(|| client(None, None)).clone()
+}
+
+/// Whether the client holds the cookies the library of the account needs
+pub fn has_cookies() -> bool {
+    rustypipe().query().auth_enabled(ClientType::Desktop)
+}
+
+/// Log in on the TV login page with a device code, the token is kept for the next runs
+pub async fn login() -> Result<()> {
+    let rp = rustypipe();
+    let code = rp
+        .user_auth_get_code()
+        .await
+        .context("Failed to get a login code")?;
+    println!(
+        "Open {} and enter the code {} (valid {} minutes)",
+        code.verification_url,
+        code.user_code,
+        code.expires_in / 60
+    );
+    println!("Waiting for the login ...");
+    rp.user_auth_wait_for_login(&code)
+        .await
+        .context("The login failed")?;
+    println!(
+        "Logged in, the token is kept in '{}'",
+        FileCache::path().to_string_lossy()
+    );
+    Ok(())
+}
+
+/// Revoke the token of `ytrs login` and forget it
+pub async fn logout() -> Result<()> {
+    let rp = rustypipe();
+    if !rp.query().auth_enabled(ClientType::Tv) {
+        println!("Not logged in");
+        return Ok(());
+    }
+    rp.user_auth_logout()
+        .await
+        .context("Could not revoke the token")?;
+    println!("Logged out");
+    Ok(())
+}
+
+/// Set up the RustyPipe client in the configured locale, logged in with the cookies given on the command line
+pub async fn init(args: &Cli) -> Result<()> {
+    let keep_cookies = args.cookies.is_some() || args.cookies_from_browser.is_some();
+    FileCache::prune(args.cache_retention, keep_cookies)?;
+    let (country, lang) = Locale::resolve(args)?;
+    let _ = CLIENT.set(client(country, lang));
+    let cookies = match (&args.cookies, &args.cookies_from_browser) {
+        (Some(file), _) => std::fs::read_to_string(file)
+            .with_context(|| format!("Could not read '{}'", file.to_string_lossy()))?,
+        (None, Some(browser)) => browser_cookies(args, browser).await?,
+        (None, None) => return Ok(()),
+    };
+    rustypipe()
+        .user_auth_set_cookie_txt(&cookies)
+        .await
+        .context("The cookies do not hold a YouTube login")?;
+    Ok(())
+}
+
+/// Have yt-dlp export the cookies of `browser`, RustyPipe only reads cookies files
+async fn browser_cookies(args: &Cli, browser: &str) -> Result<String> {
+    let file = std::env::temp_dir().join("ytrs-cookies.txt");
+    // The page does not matter, yt-dlp writes the cookies file before exiting
+    Command::new(YoutubeRs::ytdlp_bin(args))
+        .args(["--cookies-from-browser", browser, "--cookies"])
+        .arg(&file)
+        .args([
+            "--skip-download",
+            "--flat-playlist",
+            "--ignore-errors",
+            "https://www.youtube.com/feed/history",
+        ])
+        .output()
+        .await
+        .context("Could not start yt-dlp")?;
+    let cookies = std::fs::read_to_string(&file)
+        .with_context(|| format!("yt-dlp could not export the cookies of '{browser}'"))?;
+    // They hold the login of the browser
+    let _ = std::fs::remove_file(&file);
+    Ok(cookies)
+}
+
+/// yt-dlp arguments passing the cookies on
+pub fn ytdlp_args(args: &Cli) -> Vec<String> {
+    match (&args.cookies, &args.cookies_from_browser) {
+        (Some(file), _) => vec!["--cookies".to_owned(), file.to_string_lossy().into_owned()],
+        (None, Some(browser)) => vec!["--cookies-from-browser".to_owned(), browser.clone()],
+        (None, None) => Vec::new(),
+    }
+}
+
+/// Whether yt-dlp failed because the video is age-restricted or blocked outside YouTube
+pub fn is_restricted(error: &str) -> bool {
+    RESTRICTED.iter().any(|message| error.contains(message))
+}
+
+/// yt-dlp arguments getting the video through other player clients
+pub fn fallback_ytdlp_args() -> Vec<String> {
+    vec!["--extractor-args".to_owned(), FALLBACK_CLIENTS.to_owned()]
+}
+
+/// Option added to the `ytdl-raw-options` of mpv to get the videos through other player clients
+pub fn fallback_mpv_option() -> String {
+    format!("extractor-args={FALLBACK_CLIENTS}")
+}
+
+/// mpv arguments passing the cookies on to its yt-dlp hook
+pub fn mpv_args(args: &Cli) -> Vec<String> {
+    match (&args.cookies, &args.cookies_from_browser) {
+        (Some(file), _) => vec![format!(
+            "--ytdl-raw-options-append=cookies={}",
+            file.to_string_lossy()
+        )],
+        (None, Some(browser)) => vec![format!(
+            "--ytdl-raw-options-append=cookies-from-browser={browser}"
+        )],
+        (None, None) => Vec::new(),
+    }
+}
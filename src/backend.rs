@@ -0,0 +1,433 @@
+use crate::app::YoutubeRs;
+use crate::cli::Cli;
+use crate::ratelimit::throttled;
+use anyhow::{Context, Result, anyhow, bail};
+use rustypipe::model::{AudioFormat, TrackItem, VideoFormat, VideoItem};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Where the searches, the metadata and the streams come from
+pub trait YoutubeBackend {
+    async fn search_videos(&self, terms: &str) -> Result<Vec<VideoItem>>;
+    async fn search_tracks(&self, terms: &str) -> Result<Vec<TrackItem>>;
+    /// The video `video_id`, even unlisted or age-restricted ones a search does not list
+    async fn video(&self, video_id: &str) -> Result<VideoItem>;
+    /// The YouTube Music track `video_id`
+    async fn track(&self, video_id: &str) -> Result<TrackItem>;
+    /// Videos recommended after `video_id`, most relevant first
+    async fn related(&self, video_id: &str) -> Result<Vec<VideoItem>>;
+    /// Url and mime type of a stream a receiver can play without yt-dlp
+    async fn stream(&self, video_id: &str, audio_only: bool) -> Result<(String, String)>;
+    /// Url of the best streams mpv can open without yt-dlp
+    async fn direct_stream(&self, video_id: &str, audio_only: bool) -> Result<String>;
+    /// What mpv loads to play `video_id`
+    fn watch_url(&self, video_id: &str) -> String;
+    /// Save `video_id` to `output` without yt-dlp, `None` leaves the download to it
+    async fn download(&self, video_id: &str, output: &Path) -> Result<Option<PathBuf>>;
+}
+
+pub enum Backend {
+    RustyPipe(RustyPipeBackend),
+    Mock(MockBackend),
+}
+
+static BACKEND: OnceLock<Backend> = OnceLock::new();
+
+/// Use the mock backend when `--mock-backend` is given
+pub fn configure(args: &Cli) {
+    let _ = BACKEND.set(if args.mock_backend {
+        Backend::Mock(MockBackend)
+    } else {
+        Backend::RustyPipe(RustyPipeBackend)
+    });
+}
+
+pub fn backend() -> &'static Backend {
+    BACKEND.get_or_init(|| Backend::RustyPipe(RustyPipeBackend))
+}
+
+impl Backend {
+    /// Whether the downloads go without yt-dlp and ffmpeg, which are then not installed
+    pub fn offline(&self) -> bool {
+        matches!(self, Self::Mock(_))
+    }
+}
+
+impl YoutubeBackend for Backend {
+    async fn search_videos(&self, terms: &str) -> Result<Vec<VideoItem>> {
+        match self {
+            Self::RustyPipe(backend) => backend.search_videos(terms).await,
+            Self::Mock(backend) => backend.search_videos(terms).await,
+        }
+    }
+    async fn search_tracks(&self, terms: &str) -> Result<Vec<TrackItem>> {
+        match self {
+            Self::RustyPipe(backend) => backend.search_tracks(terms).await,
+            Self::Mock(backend) => backend.search_tracks(terms).await,
+        }
+    }
+    async fn video(&self, video_id: &str) -> Result<VideoItem> {
+        match self {
+            Self::RustyPipe(backend) => backend.video(video_id).await,
+            Self::Mock(backend) => backend.video(video_id).await,
+        }
+    }
+    async fn track(&self, video_id: &str) -> Result<TrackItem> {
+        match self {
+            Self::RustyPipe(backend) => backend.track(video_id).await,
+            Self::Mock(backend) => backend.track(video_id).await,
+        }
+    }
+    async fn related(&self, video_id: &str) -> Result<Vec<VideoItem>> {
+        match self {
+            Self::RustyPipe(backend) => backend.related(video_id).await,
+            Self::Mock(backend) => backend.related(video_id).await,
+        }
+    }
+    async fn stream(&self, video_id: &str, audio_only: bool) -> Result<(String, String)> {
+        match self {
+            Self::RustyPipe(backend) => backend.stream(video_id, audio_only).await,
+            Self::Mock(backend) => backend.stream(video_id, audio_only).await,
+        }
+    }
+    async fn direct_stream(&self, video_id: &str, audio_only: bool) -> Result<String> {
+        match self {
+            Self::RustyPipe(backend) => backend.direct_stream(video_id, audio_only).await,
+            Self::Mock(backend) => backend.direct_stream(video_id, audio_only).await,
+        }
+    }
+    fn watch_url(&self, video_id: &str) -> String {
+        match self {
+            Self::RustyPipe(backend) => backend.watch_url(video_id),
+            Self::Mock(backend) => backend.watch_url(video_id),
+        }
+    }
+    async fn download(&self, video_id: &str, output: &Path) -> Result<Option<PathBuf>> {
+        match self {
+            Self::RustyPipe(backend) => backend.download(video_id, output).await,
+            Self::Mock(backend) => backend.download(video_id, output).await,
+        }
+    }
+}
+
+// Separate video and audio streams played as one, the way the yt-dlp hook of mpv does it
+fn edl(urls: &[&str]) -> String {
+    let tracks: Vec<String> = urls
+        .iter()
+        .map(|url| format!("!no_clip;!no_chapters;%{}%{url}", url.len()))
+        .collect();
+    format!("edl://{}", tracks.join(";!new_stream;"))
+}
+
+/// YouTube through RustyPipe, searching the Invidious or Piped instance when it fails
+pub struct RustyPipeBackend;
+
+impl YoutubeBackend for RustyPipeBackend {
+    async fn search_videos(&self, terms: &str) -> Result<Vec<VideoItem>> {
+        let found = throttled(|| async move {
+            ytrs_core::search::videos(&crate::auth::rustypipe().query().unauthenticated(), terms)
+                .await
+        })
+        .await;
+        match found {
+            Ok(found) => Ok(found),
+            Err(_) if crate::fallback::enabled() => crate::fallback::search(terms).await,
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn search_tracks(&self, terms: &str) -> Result<Vec<TrackItem>> {
+        throttled(|| async move {
+            ytrs_core::search::tracks(&crate::auth::rustypipe().query().unauthenticated(), terms)
+                .await
+        })
+        .await
+    }
+
+    async fn video(&self, video_id: &str) -> Result<VideoItem> {
+        let player =
+            throttled(
+                || async move { Ok(crate::auth::rustypipe().query().player(video_id).await?) },
+            )
+            .await
+            .with_context(|| format!("Could not find the video '{video_id}'"))?;
+        let details = player.details;
+        // RustyPipe items cannot be built outside of it, they are deserialized instead
+        serde_json::from_value(json!({
+            "id": details.id,
+            "name": details.name.unwrap_or_default(),
+            "duration": details.duration,
+            "thumbnail": details.thumbnail,
+            "channel": {
+                "id": details.channel_id,
+                "name": details.channel_name.unwrap_or_default(),
+                "avatar": [],
+                "verification": "none",
+                "subscriber_count": null,
+            },
+            "publish_date": null,
+            "publish_date_txt": null,
+            "view_count": details.view_count,
+            "is_live": details.is_live,
+            "is_short": false,
+            "is_upcoming": false,
+            "short_description": details.description,
+        }))
+        .context("The video details match the RustyPipe model")
+    }
+
+    async fn track(&self, video_id: &str) -> Result<TrackItem> {
+        let details = throttled(|| async move {
+            Ok(crate::auth::rustypipe()
+                .query()
+                .music_details(video_id)
+                .await?)
+        })
+        .await
+        .with_context(|| format!("Could not find the track '{video_id}'"))?;
+        Ok(details.track)
+    }
+
+    async fn related(&self, video_id: &str) -> Result<Vec<VideoItem>> {
+        let details = throttled(|| async move {
+            Ok(crate::auth::rustypipe()
+                .query()
+                .unauthenticated()
+                .video_details(video_id)
+                .await?)
+        })
+        .await;
+        Ok(details
+            .context("Failed to fetch the related videos")?
+            .recommended
+            .items)
+    }
+
+    async fn stream(&self, video_id: &str, audio_only: bool) -> Result<(String, String)> {
+        let player =
+            throttled(
+                || async move { Ok(crate::auth::rustypipe().query().player(video_id).await?) },
+            )
+            .await
+            .context("Failed to get the streams");
+        let player = player?;
+        let video = player
+            .video_streams
+            .iter()
+            .filter(|stream| stream.format == VideoFormat::Mp4)
+            .max_by_key(|stream| stream.height)
+            .map(|stream| (stream.url.clone(), stream.mime.clone()));
+        let audio = player
+            .audio_streams
+            .iter()
+            .filter(|stream| stream.format == AudioFormat::M4a)
+            .max_by_key(|stream| stream.bitrate)
+            .map(|stream| (stream.url.clone(), stream.mime.clone()));
+        let (url, mime) = if audio_only {
+            audio.or(video)
+        } else {
+            video.or(audio)
+        }
+        .ok_or_else(|| anyhow!("No stream the receiver can play"))?;
+        let mime = mime.split(';').next().unwrap_or_default().to_owned();
+        Ok((url, mime))
+    }
+
+    async fn direct_stream(&self, video_id: &str, audio_only: bool) -> Result<String> {
+        let player =
+            throttled(
+                || async move { Ok(crate::auth::rustypipe().query().player(video_id).await?) },
+            )
+            .await
+            .context("Failed to get the streams")?;
+        let audio = player
+            .audio_streams
+            .iter()
+            .max_by_key(|stream| stream.bitrate)
+            .map(|stream| stream.url.as_str());
+        if audio_only {
+            return audio.map(str::to_owned).context("No audio stream");
+        }
+        let video = player
+            .video_only_streams
+            .iter()
+            .max_by_key(|stream| (stream.height, stream.fps, stream.bitrate))
+            .map(|stream| stream.url.as_str());
+        match (video, audio) {
+            (Some(video), Some(audio)) => Ok(edl(&[video, audio])),
+            // The streams holding both are of a lower quality
+            _ => player
+                .video_streams
+                .iter()
+                .max_by_key(|stream| stream.height)
+                .map(|stream| stream.url.clone())
+                .context("No video stream"),
+        }
+    }
+
+    fn watch_url(&self, video_id: &str) -> String {
+        YoutubeRs::get_video_url(&video_id.to_owned())
+    }
+    async fn download(&self, _video_id: &str, _output: &Path) -> Result<Option<PathBuf>> {
+        Ok(None)
+    }
+}
+
+// Results of each mock search
+const MOCK_RESULTS: u32 = 5;
+
+/// Made up results without any network access, the same for the same query
+pub struct MockBackend;
+
+impl MockBackend {
+    fn video(id: String, name: String, seconds: u32) -> VideoItem {
+        // RustyPipe items cannot be built outside of it, they are deserialized instead
+        serde_json::from_value(json!({
+            "id": id,
+            "name": name,
+            "duration": seconds,
+            "thumbnail": [],
+            "channel": {
+                "id": "UCmockchannel0000000000",
+                "name": "Mock channel",
+                "avatar": [],
+                "verification": "none",
+                "subscriber_count": null,
+            },
+            "publish_date": null,
+            "publish_date_txt": null,
+            "view_count": 1000 + seconds as u64,
+            "is_live": false,
+            "is_short": false,
+            "is_upcoming": false,
+            "short_description": null,
+        }))
+        .expect("The mock video matches the RustyPipe model")
+    }
+
+    fn track(id: String, name: String, seconds: u32) -> TrackItem {
+        serde_json::from_value(json!({
+            "id": id,
+            "name": name,
+            "duration": seconds,
+            "cover": [],
+            "artists": [{ "id": null, "name": "Mock artist" }],
+            "artist_id": null,
+            "album": null,
+            "view_count": 1000 + seconds as u64,
+            "track_type": "track",
+            "track_nr": null,
+            "by_va": false,
+        }))
+        .expect("The mock track matches the RustyPipe model")
+    }
+
+    // Lengths and ids derived from the text, so the results are stable
+    fn seed(text: &str) -> u32 {
+        text.bytes()
+            .fold(0u32, |seed, b| seed.wrapping_mul(31).wrapping_add(b as u32))
+    }
+
+    fn id(seed: u32, n: u32) -> String {
+        format!("mock{:07}", (seed.wrapping_add(n)) % 10_000_000)
+    }
+
+    // Whether `text` is the id of a mock result, only those can be looked up and downloaded
+    fn is_id(text: &str) -> bool {
+        text.len() == 11
+            && text
+                .strip_prefix("mock")
+                .is_some_and(|n| n.bytes().all(|b| b.is_ascii_digit()))
+    }
+}
+
+impl YoutubeBackend for MockBackend {
+    async fn search_videos(&self, terms: &str) -> Result<Vec<VideoItem>> {
+        let seed = Self::seed(terms);
+        Ok((1..=MOCK_RESULTS)
+            .map(|n| {
+                Self::video(
+                    Self::id(seed, n),
+                    format!("{terms} video {n}"),
+                    60 + seed.wrapping_add(n * 37) % 600,
+                )
+            })
+            .collect())
+    }
+
+    async fn search_tracks(&self, terms: &str) -> Result<Vec<TrackItem>> {
+        let seed = Self::seed(terms);
+        Ok((1..=MOCK_RESULTS)
+            .map(|n| {
+                Self::track(
+                    Self::id(seed, n),
+                    format!("{terms} track {n}"),
+                    120 + seed.wrapping_add(n * 23) % 240,
+                )
+            })
+            .collect())
+    }
+
+    async fn video(&self, video_id: &str) -> Result<VideoItem> {
+        if !Self::is_id(video_id) {
+            bail!("Video unavailable: '{video_id}' is not a mock result");
+        }
+        Ok(Self::video(
+            video_id.to_owned(),
+            format!("Mock video {video_id}"),
+            60 + Self::seed(video_id) % 600,
+        ))
+    }
+
+    async fn track(&self, video_id: &str) -> Result<TrackItem> {
+        if !Self::is_id(video_id) {
+            bail!("Video unavailable: '{video_id}' is not a mock result");
+        }
+        Ok(Self::track(
+            video_id.to_owned(),
+            format!("Mock track {video_id}"),
+            120 + Self::seed(video_id) % 240,
+        ))
+    }
+
+    async fn related(&self, video_id: &str) -> Result<Vec<VideoItem>> {
+        let seed = Self::seed(video_id);
+        Ok((1..=MOCK_RESULTS)
+            .map(|n| {
+                Self::video(
+                    Self::id(seed, n),
+                    format!("Related to {video_id} {n}"),
+                    60 + seed.wrapping_add(n * 37) % 600,
+                )
+            })
+            .collect())
+    }
+
+    async fn stream(&self, video_id: &str, _audio_only: bool) -> Result<(String, String)> {
+        Ok((self.watch_url(video_id), "audio/wav".to_owned()))
+    }
+
+    async fn direct_stream(&self, video_id: &str, _audio_only: bool) -> Result<String> {
+        Ok(self.watch_url(video_id))
+    }
+
+    fn watch_url(&self, video_id: &str) -> String {
+        // A tone generated by mpv, its pitch tells the media apart
+        format!(
+            "av://lavfi:sine=frequency={}:duration=30",
+            220 + Self::seed(video_id) % 660
+        )
+    }
+
+    async fn download(&self, video_id: &str, output: &Path) -> Result<Option<PathBuf>> {
+        if !Self::is_id(video_id) {
+            bail!("Video unavailable: '{video_id}' is not a mock result");
+        }
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(output, format!("Mock media {video_id}\n"))?;
+        Ok(Some(output.to_path_buf()))
+    }
+}
@@ -0,0 +1,74 @@
+use crate::app::YoutubeResponse;
+use ratatui::Frame;
+use ratatui::crossterm::event::{Event, KeyCode, KeyModifiers};
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, Paragraph, Widget, Wrap};
+
+/// What the error banner tries again
+#[derive(Clone)]
+pub enum Retry {
+    /// Search this query of the popup again
+    Search(String),
+    /// Load these entries in mpv again
+    Play(Vec<YoutubeResponse>),
+    /// Fetch the thumbnail of this media again
+    Thumbnail(String),
+}
+
+/// What the user did with the error banner
+pub enum BannerAction {
+    Retry(Retry),
+    Dismiss,
+}
+
+/// Failure shown over the player until dismissed, instead of ending the session
+pub struct ErrorBanner {
+    message: String,
+    retry: Option<Retry>,
+}
+
+impl ErrorBanner {
+    pub fn new(message: String, retry: Option<Retry>) -> Self {
+        tracing::warn!("{message}");
+        Self { message, retry }
+    }
+
+    /// 'Ctrl+R' retries and 'Esc' dismisses, the other keys go on to the player
+    pub fn handle_event(&self, event: &Event) -> Option<BannerAction> {
+        let key = event.as_key_event().filter(|_| event.is_key_press())?;
+        match key.code {
+            KeyCode::Esc => Some(BannerAction::Dismiss),
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.retry.clone().map(BannerAction::Retry)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, f: &mut Frame<'_>) {
+        let hint = if self.retry.is_some() {
+            "'Ctrl+R' Retry 'Esc' Dismiss"
+        } else {
+            "'Esc' Dismiss"
+        };
+        let width = f.area().width.min(80);
+        let area = Rect::new(
+            f.area().x + (f.area().width - width) / 2,
+            f.area().y,
+            width,
+            5.min(f.area().height),
+        );
+        Clear.render(area, f.buffer_mut());
+        Paragraph::new(self.message.as_str())
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::bordered()
+                    .title("[Error]")
+                    .title_bottom(Line::from(hint).right_aligned())
+                    .style(Style::default().fg(Color::White).bg(Color::Red)),
+            )
+            .render(area, f.buffer_mut());
+    }
+}
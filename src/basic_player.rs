@@ -0,0 +1,255 @@
+use crate::app::{YoutubeResponse, YoutubeRs};
+use crate::backend::{YoutubeBackend, backend};
+use crate::config::PlayerChoice;
+use crate::utility::format_time;
+use inquire::Confirm;
+use ratatui::crossterm::event::{Event, EventStream, KeyCode};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Gauge, Paragraph};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+use ytrs_core::audio::AudioPlayer;
+use ytrs_core::player::PlayerBackend;
+use ytrs_core::vlc::{Vlc, VlcSpawnOptions};
+
+// How often the state of the player is asked for
+const REFRESH: Duration = Duration::from_millis(500);
+// Seconds of a seek and percents of a volume step
+const SEEK_STEP: f64 = 5.0;
+const VOLUME_STEP: f64 = 5.0;
+
+/// What woke the reduced player up
+enum Wake {
+    Terminal(Event),
+    // The stream of a media to play, in the order of the playlist
+    Stream(String, String),
+    Refresh,
+}
+
+/// What the reduced player shows
+#[derive(Default)]
+struct PlayerState {
+    title: String,
+    position: f64,
+    duration: f64,
+    volume: f64,
+    paused: bool,
+}
+
+impl YoutubeRs {
+    /// Play `response`, or the file searched, then the queue with the player of the config
+    /// when it is not mpv, with only the playback controls
+    pub(crate) async fn basic_player(
+        &mut self,
+        response: &Option<YoutubeResponse>,
+        audio_only: bool,
+    ) {
+        match crate::config::config().player {
+            PlayerChoice::Vlc => {
+                let opts = VlcSpawnOptions {
+                    vlc_path: crate::config::config().vlc_path.clone(),
+                    ..Default::default()
+                };
+                let mut vlc = loop {
+                    match Vlc::spawn(&opts, audio_only).await {
+                        Ok(vlc) => break vlc,
+                        Err(e) => {
+                            tracing::error!("Could not start VLC: {e:#}");
+                            println!("Could not start VLC: {e:#}");
+                            if !Confirm::new("Try again?")
+                                .with_default(true)
+                                .prompt()
+                                .unwrap_or_default()
+                            {
+                                return;
+                            }
+                        }
+                    }
+                };
+                self.play_with(&mut vlc, "VLC", response, audio_only).await;
+            }
+            PlayerChoice::Builtin => match AudioPlayer::open().await {
+                // It can only decode the audio
+                Ok(mut player) => self.play_with(&mut player, "Audio", response, true).await,
+                Err(e) => {
+                    tracing::error!("Could not play the audio: {e:#}");
+                    println!("Could not play the audio: {e:#}");
+                }
+            },
+            PlayerChoice::Mpv => {}
+        }
+    }
+
+    async fn play_with(
+        &mut self,
+        player: &mut impl PlayerBackend,
+        name: &str,
+        response: &Option<YoutubeResponse>,
+        audio_only: bool,
+    ) {
+        let mut pending: Vec<String> = response.iter().map(YoutubeResponse::get_id).collect();
+        pending.extend(self.queue.entries().iter().map(YoutubeResponse::get_id));
+        // What the player does not know the title of is shown by its position in the playlist
+        let mut titles: Vec<String> = response.iter().map(YoutubeResponse::get_name).collect();
+        // The file searched plays when there is no media
+        let file = match response {
+            Some(_) => None,
+            None => self
+                .last_search
+                .as_ref()
+                .map(PathBuf::from)
+                .filter(|path| path.is_file()),
+        };
+        if let Some(path) = &file {
+            titles.push(
+                path.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string(),
+            );
+            let url = path.to_string_lossy();
+            if let Err(e) = player.load(&url).await {
+                tracing::warn!("Could not load '{url}': {e:#}");
+            }
+        } else if pending.is_empty() {
+            println!("Nothing to play");
+            player.quit().await;
+            return;
+        }
+        titles.extend(self.queue.entries().iter().map(YoutubeResponse::get_name));
+        // Looked up in order in the background, the keys are not kept waiting for the network
+        let mut loaded = file.is_some();
+        let (streams_tx, mut streams_rx) = mpsc::unbounded_channel();
+        let streams = tokio::spawn(async move {
+            for id in pending {
+                let url = stream_url(&id, audio_only).await;
+                if streams_tx.send((id, url)).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut terminal_events = EventStream::new();
+        let mut refresh = tokio::time::interval(REFRESH);
+        let mut term = ratatui::init();
+        let mut state = PlayerState::default();
+        while player.running().await {
+            let wake = tokio::select! {
+                Some(Ok(event)) = terminal_events.next() => Wake::Terminal(event),
+                Some((id, url)) = streams_rx.recv() => Wake::Stream(id, url),
+                _ = refresh.tick() => Wake::Refresh,
+            };
+            let event = match wake {
+                Wake::Terminal(event) => event,
+                Wake::Stream(id, url) => {
+                    // The first media starts the playlist, the others follow it
+                    let done = if loaded {
+                        player.append(&url).await
+                    } else {
+                        player.load(&url).await
+                    };
+                    match done {
+                        Ok(()) => loaded = true,
+                        Err(e) => tracing::warn!("Could not queue {id}: {e:#}"),
+                    }
+                    continue;
+                }
+                Wake::Refresh => {
+                    state = Self::player_state(player, &titles).await;
+                    let _ = term.draw(|f| render(f, name, &state));
+                    continue;
+                }
+            };
+            let Some(event) = crate::config::remap(event) else {
+                continue;
+            };
+            let Some(key) = event.as_key_event().filter(|_| event.is_key_press()) else {
+                continue;
+            };
+            // The keys of the full player
+            let done = match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Char(' ') => player.set_paused(!state.paused).await,
+                KeyCode::Left => player.seek(-SEEK_STEP, true).await,
+                KeyCode::Right => player.seek(SEEK_STEP, true).await,
+                KeyCode::Up => player.set_volume(state.volume + VOLUME_STEP).await,
+                KeyCode::Down => player.set_volume(state.volume - VOLUME_STEP).await,
+                KeyCode::Char('j') => player.next().await,
+                KeyCode::Char('k') => player.previous().await,
+                _ => continue,
+            };
+            if let Err(e) = done {
+                tracing::warn!("{name} did not take '{:?}': {e:#}", key.code);
+            }
+            // Show the change right away
+            refresh.reset_immediately();
+        }
+        streams.abort();
+        player.quit().await;
+        ratatui::restore();
+    }
+
+    /// Ask `player` for what the reduced player shows
+    async fn player_state(player: &mut impl PlayerBackend, titles: &[String]) -> PlayerState {
+        let title = match player.playlist_pos().await.and_then(|pos| titles.get(pos)) {
+            Some(title) => title.clone(),
+            None => player.title().await.unwrap_or_default(),
+        };
+        PlayerState {
+            title,
+            position: player.position().await.unwrap_or_default(),
+            duration: player.duration().await.unwrap_or_default(),
+            volume: player.volume().await.unwrap_or_default(),
+            paused: player.paused().await.unwrap_or_default(),
+        }
+    }
+}
+
+/// A stream the player can open, RustyPipe finds it or else the player is given the watch url
+async fn stream_url(id: &str, audio_only: bool) -> String {
+    match backend().stream(id, audio_only).await {
+        Ok((url, _)) => url,
+        Err(e) => {
+            tracing::warn!("No direct stream for {id}: {e:#}");
+            backend().watch_url(id)
+        }
+    }
+}
+
+fn render(f: &mut Frame<'_>, name: &str, state: &PlayerState) {
+    let [title, gauge, status, help] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(3),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(f.area());
+    let theme = &crate::config::config().theme;
+    Paragraph::new(state.title.as_str())
+        .block(
+            Block::bordered()
+                .title(format!("[{name}]"))
+                .style(theme.panel()),
+        )
+        .render(title, f.buffer_mut());
+    let ratio = if state.duration > 0.0 {
+        (state.position / state.duration).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    Gauge::default()
+        .block(Block::bordered().style(theme.panel()))
+        .gauge_style(theme.highlight())
+        .ratio(ratio)
+        .label(format!(
+            "{} / {}",
+            format_time(state.position as u32),
+            format_time(state.duration as u32)
+        ))
+        .render(gauge, f.buffer_mut());
+    let paused = if state.paused { " Paused" } else { "" };
+    Line::from(format!("Volume {:.0}%{paused}", state.volume)).render(status, f.buffer_mut());
+    Line::from("'Space' Pause 'Left/Right' Seek 'Up/Down' Volume 'j/k' Next/Previous 'q' Quit")
+        .render(help, f.buffer_mut());
+}
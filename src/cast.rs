@@ -0,0 +1,169 @@
+use crate::backend::{YoutubeBackend, backend};
+use crate::remote::{NowPlaying, PlayerRemote};
+use anyhow::{Result, anyhow, bail};
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use rust_cast::CastDevice;
+use rust_cast::channels::media::{GenericMediaMetadata, Image, Media, Metadata, StreamType};
+use rust_cast::channels::receiver::CastDeviceApp;
+use std::time::{Duration, Instant};
+
+const SERVICE: &str = "_googlecast._tcp.local.";
+// The receiver drops senders that stay silent for too long
+const HEARTBEAT: Duration = Duration::from_secs(5);
+// Drift allowed between mpv and the receiver before seeking the receiver
+const MAX_DRIFT: f64 = 3.0;
+
+/// A Google Cast device found on the network
+#[derive(Clone)]
+pub struct CastTarget {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+}
+
+impl std::fmt::Display for CastTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.host)
+    }
+}
+
+/// Look for Google Cast devices for a few seconds and let the user pick one
+pub async fn select() -> Result<CastTarget> {
+    println!("Looking for cast devices ...");
+    let mdns = ServiceDaemon::new()?;
+    let events = mdns.browse(SERVICE)?;
+    let mut targets: Vec<CastTarget> = Vec::new();
+    let deadline = tokio::time::sleep(Duration::from_secs(3));
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            _ = &mut deadline => break,
+            event = events.recv_async() => match event {
+                Ok(ServiceEvent::ServiceResolved(service)) => {
+                    let Some(address) = service.get_addresses_v4().into_iter().next() else {
+                        continue;
+                    };
+                    let target = CastTarget {
+                        name: service
+                            .get_property_val_str("fn")
+                            .unwrap_or(service.get_fullname())
+                            .to_owned(),
+                        host: address.to_string(),
+                        port: service.get_port(),
+                    };
+                    if !targets.iter().any(|known| known.host == target.host) {
+                        targets.push(target);
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => break,
+            },
+        }
+    }
+    let _ = mdns.shutdown();
+    if targets.is_empty() {
+        bail!("No cast device found");
+    }
+    Ok(inquire::Select::new("Cast to", targets).prompt()?)
+}
+
+/// Play what mpv plays on `target`, mpv stays the clock the receiver follows
+pub fn spawn(remote: PlayerRemote, target: CastTarget, audio_only: bool) {
+    let runtime = tokio::runtime::Handle::current();
+    let mut now_playing = remote.now_playing;
+    std::thread::spawn(move || -> Result<()> {
+        let device = CastDevice::connect_without_host_verification(target.host, target.port)?;
+        device.connection.connect("receiver-0")?;
+        let app = device
+            .receiver
+            .launch_app(&CastDeviceApp::DefaultMediaReceiver)?;
+        device.connection.connect(app.transport_id.as_str())?;
+
+        // Url of the loaded media and its session on the receiver
+        let mut loaded: Option<(String, i32)> = None;
+        let mut last = NowPlaying::default();
+        let mut last_update = Instant::now();
+        loop {
+            let changed = runtime.block_on(tokio::time::timeout(HEARTBEAT, now_playing.changed()));
+            if let Ok(Err(_)) = changed {
+                // The player closed
+                break;
+            }
+            device.heartbeat.ping()?;
+            let current = now_playing.borrow_and_update().clone();
+            let elapsed = last_update.elapsed().as_secs_f64();
+            last_update = Instant::now();
+
+            if current.url != loaded.as_ref().map(|(url, _)| url.clone()) {
+                loaded = None;
+                if let Some(url) = &current.url {
+                    let media = runtime.block_on(stream(url, &current, audio_only))?;
+                    let status = device.media.load(
+                        app.transport_id.as_str(),
+                        app.session_id.as_str(),
+                        &media,
+                    )?;
+                    if let Some(entry) = status.entries.first() {
+                        loaded = Some((url.clone(), entry.media_session_id));
+                    }
+                }
+            } else if let Some((_, session)) = &loaded {
+                if current.paused != last.paused {
+                    if current.paused {
+                        device.media.pause(app.transport_id.as_str(), *session)?;
+                    } else {
+                        device.media.play(app.transport_id.as_str(), *session)?;
+                    }
+                }
+                let expected = last.position + if last.paused { 0.0 } else { elapsed };
+                if (current.position - expected).abs() > MAX_DRIFT {
+                    device.media.seek(
+                        app.transport_id.as_str(),
+                        *session,
+                        Some(current.position as f32),
+                        None,
+                    )?;
+                }
+            }
+            if current.volume != last.volume {
+                device
+                    .receiver
+                    .set_volume((current.volume / 100.0).clamp(0.0, 1.0) as f32)?;
+            }
+            last = current;
+        }
+        device.receiver.stop_app(app.session_id.as_str())?;
+        Ok(())
+    });
+}
+
+/// Direct stream of the video the receiver can play
+async fn stream(url: &str, now_playing: &NowPlaying, audio_only: bool) -> Result<Media> {
+    let (content_id, mime) = direct_stream(url, audio_only).await?;
+    Ok(Media {
+        content_id,
+        stream_type: StreamType::Buffered,
+        content_type: mime,
+        metadata: Some(Metadata::Generic(GenericMediaMetadata {
+            title: Some(now_playing.title.clone()),
+            subtitle: Some(now_playing.artist.clone()),
+            images: now_playing
+                .thumbnail
+                .clone()
+                .map(Image::new)
+                .into_iter()
+                .collect(),
+            release_date: None,
+        })),
+        duration: Some(now_playing.duration as f32),
+    })
+}
+
+/// Url and mime type of a stream of the video renderers usually play (mp4 or m4a)
+pub(crate) async fn direct_stream(url: &str, audio_only: bool) -> Result<(String, String)> {
+    let id = url
+        .split("v=")
+        .nth(1)
+        .ok_or_else(|| anyhow!("No video id in '{url}'"))?;
+    backend().stream(id, audio_only).await
+}
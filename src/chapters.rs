@@ -0,0 +1,152 @@
+use crate::app::YoutubeRs;
+use crate::llm::Llm;
+use crate::sponsorblock;
+use crate::transcript::{self, Cue};
+use crate::utility::sanitize_file_name;
+use anyhow::{Context, Result, bail};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use yt_dlp::model::chapter::Chapter;
+
+/// Parse a `[hh:]mm:ss` timestamp
+fn parse_timestamp(t: &str) -> Option<f64> {
+    t.split(':').try_fold(0.0, |acc, part| {
+        Some(acc * 60.0 + part.parse::<f64>().ok()?)
+    })
+}
+
+/// Ask the LLM for chapter titles, returned sorted and ending where the next one starts
+pub async fn generate(llm: &Llm, cues: &[Cue], duration: f64) -> Result<Vec<Chapter>> {
+    let lines: String = cues
+        .iter()
+        .map(|cue| format!("{} {}\n", description_timestamp(cue.start), cue.text))
+        .collect();
+    let answer = llm
+        .generate(format!(
+            "Split this timestamped transcript into chapters. Answer only with one chapter per line \
+            formatted as '<timestamp> <short title>', the first chapter starting at 00:00.\n\n{lines}"
+        ))
+        .await?;
+    let mut starts: Vec<(f64, String)> = answer
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches(['-', '*', ' ']);
+            let (time, title) = line.split_once(' ')?;
+            let start = parse_timestamp(time.trim_matches(['[', ']', '(', ')']))?;
+            let title = title.trim().trim_start_matches(['-', ':', ' ']).to_string();
+            (start < duration && !title.is_empty()).then_some((start, title))
+        })
+        .collect();
+    starts.sort_by(|a, b| a.0.total_cmp(&b.0));
+    starts.dedup_by(|a, b| a.0 == b.0);
+    if starts.is_empty() {
+        bail!("The LLM did not answer with any chapter");
+    }
+    let ends: Vec<f64> = starts
+        .iter()
+        .skip(1)
+        .map(|(start, _)| *start)
+        .chain([duration])
+        .collect();
+    Ok(starts
+        .into_iter()
+        .zip(ends)
+        .map(|((start, title), end)| Chapter {
+            start_time: start,
+            end_time: end,
+            title: Some(title),
+        })
+        .collect())
+}
+
+/// `mm:ss` or `h:mm:ss`, as YouTube descriptions write timestamps
+fn description_timestamp(t: f64) -> String {
+    let t = t as u32;
+    if t >= 3600 {
+        format!("{}:{:02}:{:02}", t / 3600, t / 60 % 60, t % 60)
+    } else {
+        format!("{:02}:{:02}", t / 60, t % 60)
+    }
+}
+
+/// Chapters in the `00:00 Title` form used by YouTube descriptions
+pub fn to_description(chapters: &[Chapter]) -> String {
+    chapters
+        .iter()
+        .map(|chapter| {
+            format!(
+                "{} {}\n",
+                description_timestamp(chapter.start_time),
+                chapter.title_or("")
+            )
+        })
+        .collect()
+}
+
+/// Replace the chapters of `file` with ffmpeg
+pub async fn embed(ffmpeg: &Path, file: &Path, chapters: &[Chapter]) -> Result<()> {
+    let extension = file
+        .extension()
+        .context("File has no extension")?
+        .to_string_lossy();
+    let meta = file.with_extension("chapters.txt");
+    let tmp = file.with_extension(format!("chapters.{extension}"));
+    std::fs::write(&meta, sponsorblock::chapters_metadata(chapters, &[]))?;
+    let status = Command::new(ffmpeg)
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(file)
+        .arg("-i")
+        .arg(&meta)
+        .args(["-map", "0", "-map_metadata", "0", "-map_chapters", "1"])
+        .args(["-c", "copy"])
+        .arg(&tmp)
+        .status()
+        .await;
+    let _ = std::fs::remove_file(&meta);
+    if !status.is_ok_and(|s| s.success()) {
+        let _ = std::fs::remove_file(&tmp);
+        bail!(
+            "ffmpeg could not write the chapters to '{}'",
+            file.display()
+        );
+    }
+    std::fs::rename(&tmp, file)?;
+    Ok(())
+}
+
+impl YoutubeRs {
+    /// Generate chapters from the transcript of the selected video
+    pub async fn chapters(&mut self, save: bool, embed_into: Option<PathBuf>) -> Result<()> {
+        if !self.libraries_exist(&self.args.clone()) {
+            Self::install_lib(&self.args).await?;
+        }
+        let (video, search) = Self::query_ytvideo(self.last_search.clone()).await?;
+        self.last_search = Some(search);
+        let info = Self::get_fetcher(&self.args)
+            .await?
+            .fetch_video_infos(Self::get_video_url(&video.id))
+            .await?;
+        let cues = transcript::fetch_cues(&info, None).await?;
+        let llm = Llm::select(&self.args).await?;
+        println!("Generating chapters ...\n");
+        let duration = video
+            .duration
+            .map(f64::from)
+            .or(cues.last().map(|cue| cue.end))
+            .unwrap_or_default();
+        let chapters = generate(&llm, &cues, duration).await?;
+        let description = to_description(&chapters);
+        println!("{description}");
+        if save {
+            let (_, out) = Self::get_libs_path(&self.args);
+            let path = out.join(format!("{}-chapters.txt", sanitize_file_name(&info.title)));
+            std::fs::write(&path, &description)?;
+            println!("Chapters written to '{}'", path.to_string_lossy());
+        }
+        if let Some(file) = embed_into {
+            embed(&Self::get_libs(&self.args).ffmpeg, &file, &chapters).await?;
+            println!("Chapters embedded into '{}'", file.to_string_lossy());
+        }
+        Ok(())
+    }
+}
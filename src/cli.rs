@@ -1,7 +1,13 @@
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 use clap::Parser;
 
+use crate::llm::{LlmBackend, parse_keep_alive};
+use crate::sponsorblock::SponsorCategory;
+use crate::ytdlp::{LibsSource, YtdlpChannel};
+use ollama_rs::generation::parameters::KeepAlive;
+
 #[derive(clap::Parser, Clone, Debug)]
 #[command(name = "ytrs")]
 #[command(about = "A CLI for initializing the YTRS TUI with arguments")]
@@ -10,6 +16,136 @@ pub struct Cli {
     pub libs_path: Option<PathBuf>,
     #[clap(short, long)]
     pub output_path: Option<PathBuf>,
+    /// Log more to `~/.local/share/ytrs/logs`: '-v' what happens, '-vv' the details, '-vvv' the libraries too
+    #[clap(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+    /// Where yt-dlp and ffmpeg are looked for first, 'private,system' unless given
+    #[clap(long, global = true, value_enum, value_delimiter = ',')]
+    pub libs_order: Vec<LibsSource>,
+    /// Release channel yt-dlp is installed and updated from
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub ytdlp_channel: YtdlpChannel,
+    /// LLM backend used to summarize and translate transcripts
+    #[clap(long, global = true, value_enum, default_value_t)]
+    pub llm: LlmBackend,
+    /// Url of the Ollama server (e.g. 'http://192.168.1.20:11434')
+    #[clap(long, global = true)]
+    pub ollama_url: Option<String>,
+    /// Base url of the OpenAI compatible api (e.g. 'https://api.groq.com/openai/v1'),
+    /// the api key is read from YTRS_LLM_API_KEY or OPENAI_API_KEY, else from the system keyring
+    #[clap(long, global = true)]
+    pub openai_url: Option<String>,
+    /// Model to use instead of prompting for one
+    #[clap(long, global = true, alias = "ollama-model")]
+    pub llm_model: Option<String>,
+    /// Sampling temperature of the LLM, lower is more deterministic
+    #[clap(long, global = true)]
+    pub llm_temperature: Option<f32>,
+    /// Maximum number of tokens generated per answer
+    #[clap(long, global = true)]
+    pub llm_max_tokens: Option<i32>,
+    /// System prompt sent with every request
+    #[clap(long, global = true)]
+    pub llm_system: Option<String>,
+    /// How long Ollama keeps the model loaded: '-1' forever, '0' unload right away, or '10m'
+    #[clap(long, global = true, value_parser = parse_keep_alive)]
+    pub llm_keep_alive: Option<KeepAlive>,
+    /// Show a desktop notification when the player moves on to another track
+    #[clap(long, global = true)]
+    pub notify: bool,
+    /// Keep this file updated with the track being played, as json if it ends with '.json'
+    #[clap(long, global = true)]
+    pub now_playing: Option<PathBuf>,
+    /// Text written to the now playing file, with {title}, {artist}, {url}, {position}, {duration} and {status}
+    #[clap(long, global = true, default_value = crate::overlay::DEFAULT_TEMPLATE)]
+    pub now_playing_template: String,
+    /// Serve the HTTP remote control api on this address (e.g. '0.0.0.0:8080')
+    #[clap(long, global = true)]
+    pub http: Option<SocketAddr>,
+    /// Let MPD clients control the player from this address (e.g. '0.0.0.0:6600')
+    #[clap(long, global = true)]
+    pub mpd: Option<SocketAddr>,
+    /// Accept OSC messages on this UDP address (e.g. '0.0.0.0:9000')
+    #[clap(long, global = true)]
+    pub osc: Option<SocketAddr>,
+    /// Play on a Google Cast device of the network, the player becomes its remote
+    #[clap(long, global = true)]
+    pub cast: bool,
+    /// Play on a DLNA/UPnP renderer of the network (smart TV, AV receiver), the player becomes its remote
+    #[clap(long, global = true, conflicts_with = "cast")]
+    pub dlna: bool,
+    /// Send the audio to a Snapcast server through the fifo of its pipe source (e.g. '/tmp/snapfifo')
+    #[clap(long, global = true, conflicts_with = "audio_device")]
+    pub snapcast: Option<PathBuf>,
+    /// Pipe the raw audio (s16le, 48000 Hz, stereo) into this shell command instead of playing it
+    #[clap(long, global = true, conflicts_with_all = ["snapcast", "audio_device"])]
+    pub audio_command: Option<String>,
+    /// Audio device mpv plays on (e.g. 'pulse/living_room'), 'mpv --audio-device=help' lists them
+    #[clap(long, global = true)]
+    pub audio_device: Option<String>,
+    /// mpv binary to run instead of `mpv` from the PATH
+    #[clap(long, global = true)]
+    pub mpv_path: Option<PathBuf>,
+    /// Option given as is to mpv, after the ones of the config (e.g. '--mpv-option=--af=loudnorm'), can be repeated
+    #[clap(long = "mpv-option", global = true, allow_hyphen_values = true)]
+    pub mpv_options: Vec<String>,
+    /// Queue the YouTube urls copied to the clipboard while the player runs
+    #[clap(long, global = true)]
+    pub watch_clipboard: bool,
+    /// Netscape cookies file of a logged in YouTube session, for members-only and age-gated videos
+    #[clap(long, global = true, conflicts_with = "cookies_from_browser")]
+    pub cookies: Option<PathBuf>,
+    /// Use the YouTube login of a browser (e.g. 'firefox', 'chrome:Profile 1'), read by yt-dlp
+    #[clap(long, global = true)]
+    pub cookies_from_browser: Option<String>,
+    /// Days the YouTube client versions and player data stay cached in the config folder, 0 refreshes them on each start
+    #[clap(long, global = true, default_value_t = 7)]
+    pub cache_retention: u64,
+    /// Country of the search results, trending videos and music charts (e.g. 'DE')
+    #[clap(long, global = true)]
+    pub region: Option<String>,
+    /// Language of the search results and titles (e.g. 'de', 'en-GB'), `--lang` of `transcript` picks the subtitles
+    #[clap(long, global = true)]
+    pub language: Option<String>,
+    /// Language of the subtitles the player shows when the media has them (e.g. 'en'), 'no' hides them
+    #[clap(long, global = true)]
+    pub sub_language: Option<String>,
+    /// Invidious instance searched and streamed from when YouTube cannot be reached (e.g. 'https://inv.nadeko.net')
+    #[clap(long, global = true, conflicts_with = "piped")]
+    pub invidious: Option<String>,
+    /// Piped API instance searched and streamed from when YouTube cannot be reached (e.g. 'https://pipedapi.kavin.rocks')
+    #[clap(long, global = true)]
+    pub piped: Option<String>,
+    /// Serve made up results without any network access, for testing
+    #[clap(long, global = true, hide = true)]
+    pub mock_backend: bool,
+    /// Control the player with a gamepad
+    #[clap(long, global = true)]
+    pub gamepad: bool,
+    /// Queue the top related video when nothing else is queued
+    #[clap(long, global = true)]
+    pub autoplay_related: bool,
+    /// Play the streams RustyPipe finds instead of going through yt-dlp, which stays the fallback
+    #[clap(long, global = true)]
+    pub direct_stream: bool,
+    /// Check the subscribed channels for new uploads every this many minutes while the player runs
+    #[clap(long, global = true)]
+    pub notify_uploads: Option<u64>,
+    /// Ask which audio track to play or download for videos dubbed in several languages
+    #[clap(long, global = true)]
+    pub pick_audio_track: bool,
+    /// Requests sent to YouTube per minute at most, 0 for no limit, refused ones are retried later on their own
+    #[clap(long, global = true, default_value_t = 30)]
+    pub requests_per_minute: usize,
+    /// Show the results hidden by `~/.config/ytrs/filter.json`
+    #[clap(long, global = true)]
+    pub no_filter: bool,
+    /// Never ask in the terminal nor open the player, fail instead, for scripts
+    #[clap(long, global = true)]
+    pub non_interactive: bool,
+    /// Print json lines: the progress of downloads and transcripts, the search results and the status
+    #[clap(long, global = true)]
+    pub json: bool,
     #[command(subcommand)]
     pub command: Option<AppActionCli>,
 }
@@ -23,13 +159,39 @@ impl Default for Cli {
 pub enum AppActionCli {
     /// Download directly from the url or query from the TUI
     Download {
+        /// Url to download, '-' reads the urls from stdin (one per line)
+        #[clap(conflicts_with_all = ["query", "url", "batch"])]
+        source: Option<String>,
         #[clap(short, long, conflicts_with = "url")]
         query: Option<String>,
         #[clap(short, long)]
         url: Option<String>,
+        /// Download every url listed in the file (one per line), use '-' to read from stdin
+        #[clap(short, long, conflicts_with_all = ["query", "url"])]
+        batch: Option<PathBuf>,
+        /// Cut these SponsorBlock categories out of the downloaded file
+        #[clap(short, long, value_enum, value_delimiter = ',')]
+        sponsorblock: Vec<SponsorCategory>,
+        /// Write the description and the metadata json next to the downloaded file
+        #[clap(short, long)]
+        info: bool,
+    },
+    /// Print the search results without opening the player, one per line: url, title, channel and duration separated by tabs
+    Search {
+        #[clap(required = true, num_args = 1..)]
+        query: Vec<String>,
+        /// Search the tracks of YouTube Music instead of the videos
+        #[clap(short, long)]
+        music: bool,
+        /// Results printed, at most
+        #[clap(short, long, default_value_t = 20)]
+        limit: usize,
     },
     /// Play from the provided url or file
     Player {
+        /// Url or query to play, '-' reads them from stdin: links are queued, other text is searched
+        #[clap(conflicts_with_all = ["file", "url", "trending"])]
+        source: Option<String>,
         #[clap(short, long)]
         file: Option<PathBuf>,
         #[clap(short, long, conflicts_with = "file")]
@@ -38,6 +200,9 @@ pub enum AppActionCli {
         api: Option<PlayerAPI>,
         #[clap(short, long)]
         midi: bool,
+        /// Start by browsing what is trending instead of an empty player
+        #[clap(short, long, conflicts_with_all = ["file", "url"], num_args = 0..=1, default_missing_value = "videos")]
+        trending: Option<TrendingCategory>,
     },
     /// Download the transcript using the query
     Transcript {
@@ -47,6 +212,275 @@ pub enum AppActionCli {
         url: Option<String>,
         #[clap(short, long, help = "Requires Ollama")]
         summarize: Option<bool>,
+        /// Save the summary as '<video-title>-summary.md' in the output dir
+        #[clap(short, long)]
+        markdown: bool,
+        /// Generate the summary again instead of reusing the cached one
+        #[clap(short, long)]
+        refresh: bool,
+        /// Ask questions about the video once the transcript is downloaded
+        #[clap(short, long, help_heading = "Requires Ollama")]
+        chat: bool,
+        /// Print the timestamps where this phrase is said, without a phrase ask for the phrases to find
+        #[clap(short, long, num_args = 0..=1)]
+        find: Option<Option<String>>,
+        /// Languages to download (e.g. 'en,fr'), 'all' downloads every available language
+        #[clap(short, long, value_delimiter = ',')]
+        lang: Vec<String>,
+        /// Translate the transcript to this language with the LLM and save it as srt
+        #[clap(short, long, help_heading = "Requires Ollama")]
+        translate: Option<String>,
+    },
+    /// Generate chapters from the transcript with the LLM
+    Chapters {
+        #[clap(short, long, conflicts_with = "url")]
+        query: Option<String>,
+        #[clap(short, long)]
+        url: Option<String>,
+        /// Save the chapters as '<video-title>-chapters.txt' in the output dir
+        #[clap(short, long)]
+        save: bool,
+        /// Write the chapters into the metadata of this downloaded file
+        #[clap(short, long)]
+        embed: Option<PathBuf>,
+    },
+    /// Summarize what people are saying in the comments with the LLM
+    Comments {
+        #[clap(short, long, conflicts_with = "url")]
+        query: Option<String>,
+        #[clap(short, long)]
+        url: Option<String>,
+        /// Number of top comments to read
+        #[clap(short, long, default_value_t = 100)]
+        limit: usize,
+    },
+    /// Write a markdown digest summarizing every video of a playlist with the LLM
+    Digest {
+        /// Url of the playlist
+        #[clap(short, long)]
+        url: String,
+        /// Maximum number of videos to summarize
+        #[clap(short, long, default_value_t = 50)]
+        limit: usize,
+        /// Generate the summaries again instead of reusing the cached ones
+        #[clap(short, long)]
+        refresh: bool,
+    },
+    /// Let the LLM build a queue of tracks from a description and play it
+    Mood {
+        /// What you want to listen to (e.g. '90s trip-hop for rainy coding')
+        description: String,
+        /// Number of tracks to ask for
+        #[clap(short, long, default_value_t = 15)]
+        count: usize,
+        #[clap(short, long)]
+        midi: bool,
+    },
+    /// Play shorts back to back, 'j' and 'k' skip to the next and previous one
+    Shorts {
+        /// Search the shorts instead of browsing the ones of the subscribed channels
+        #[clap(short, long)]
+        query: Option<String>,
+        /// Shorts queued, at most
+        #[clap(short, long, default_value_t = 50)]
+        limit: usize,
+        #[clap(short, long)]
+        midi: bool,
+    },
+    /// Extract keywords from the transcript with the LLM and write them to the audio file tags
+    Keywords {
+        #[clap(short, long, conflicts_with = "url")]
+        query: Option<String>,
+        #[clap(short, long)]
+        url: Option<String>,
+        /// Audio file to tag, defaults to the file downloaded from this video in the output dir
+        #[clap(short, long)]
+        file: Option<PathBuf>,
+    },
+    /// Record an ongoing livestream
+    Record {
+        #[clap(short, long, conflicts_with = "url")]
+        query: Option<String>,
+        #[clap(short, long)]
+        url: Option<String>,
+        /// Record from the beginning of the stream instead of from now
+        #[clap(short, long)]
+        from_start: bool,
+    },
+    /// Automatically download new uploads from channels and playlists
+    Watch {
+        #[command(subcommand)]
+        command: WatchCli,
+    },
+    /// Follow channels locally and browse their recent uploads
+    Subscriptions {
+        #[command(subcommand)]
+        command: SubscriptionsCli,
+    },
+    /// Hide channels and title keywords from the searches, related videos and feeds
+    Block {
+        #[command(subcommand)]
+        command: BlockCli,
+    },
+    /// Open or locate the settings file, `~/.config/ytrs/config.toml`
+    Config {
+        #[command(subcommand)]
+        command: ConfigCli,
+    },
+    /// Control the running player
+    Ctl {
+        #[command(subcommand)]
+        command: CtlCli,
+    },
+    /// Bind the controls of a midi device to player actions
+    Midi {
+        #[command(subcommand)]
+        command: MidiCli,
+    },
+    /// Log in to YouTube with a code entered on another device, like on a TV
+    Login,
+    /// Log out and forget the token of 'login'
+    Logout,
+    /// Install, update or show the versions of yt-dlp and ffmpeg
+    Libs {
+        #[command(subcommand)]
+        command: LibsCli,
+    },
+    /// Bring back the player detached with 'D', mpv kept playing its queue
+    Attach {
+        #[clap(short, long)]
+        midi: bool,
+    },
+    /// Replace ytrs by the binary of the latest GitHub release
+    SelfUpdate {
+        /// Only tell whether a newer version is out
+        #[clap(short, long)]
+        check: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum LibsCli {
+    /// Install what is missing of yt-dlp and ffmpeg
+    Install,
+    /// Download yt-dlp again from its release channel, when an extractor broke
+    Update,
+    /// Print the versions installed
+    Versions,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum SubscriptionsCli {
+    /// Subscribe to a channel url
+    Add { url: String },
+    /// Subscribe to the channels of a Google Takeout csv, an OPML file or a NewPipe export
+    Import { file: PathBuf },
+    /// Unsubscribe from a channel
+    Remove,
+    /// List the subscribed channels
+    List,
+    /// Recent uploads of the subscribed channels, newest first, to play or download
+    Feed {
+        /// Uploads listed, at most
+        #[clap(short, long, default_value_t = 50)]
+        limit: usize,
+    },
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum BlockCli {
+    /// Block a channel url
+    Channel { url: String },
+    /// Block the titles containing a keyword, whatever the case
+    Keyword { keyword: String },
+    /// Unblock a channel or a keyword
+    Remove,
+    /// List the blocked channels and keywords
+    List,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum ConfigCli {
+    /// Edit the settings with $EDITOR, starting from a commented template
+    Edit,
+    /// Print where the settings are
+    Path,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum MidiCli {
+    /// Pick an action then move a control of the device to bind it
+    Learn,
+    /// List the bindings
+    List,
+    /// Go back to the default bindings
+    Reset,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum CtlCli {
+    Play,
+    Pause,
+    PlayPause,
+    Next,
+    Previous,
+    /// Close the player
+    Stop,
+    /// Seek to this position in seconds, or by this offset when prefixed with '+' or '-'
+    #[clap(allow_hyphen_values = true)]
+    Seek {
+        position: String,
+    },
+    /// Set the volume, from 0 to 130
+    Volume {
+        value: f64,
+    },
+    /// Add a video to the queue
+    AddUrl {
+        url: String,
+    },
+    /// Clear the queue
+    Clear,
+    /// Print what is playing
+    Status,
+}
+
+#[derive(clap::Subcommand, Clone, Debug)]
+pub enum WatchCli {
+    /// Register a channel or playlist url
+    Add {
+        url: String,
+        /// Also download the uploads already published
+        #[clap(short, long)]
+        backfill: bool,
+    },
+    /// Unregister a channel or playlist
+    Remove,
+    /// List the registered channels and playlists
+    List,
+    /// Check for new uploads and download them into per-channel folders
+    Run {
+        /// Minutes between two checks
+        #[clap(short, long, default_value_t = 60)]
+        interval: u64,
+        /// Check only once then exit
+        #[clap(long)]
+        once: bool,
+        /// Keep an RSS feed of the new uploads in this file after every check
+        #[clap(long)]
+        feed: Option<PathBuf>,
+        /// Also list the downloads in the feed
+        #[clap(long, requires = "feed")]
+        feed_downloads: bool,
+    },
+    /// Print the RSS feed of the new uploads
+    Feed {
+        /// Write the feed to this file instead
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+        /// Also list the downloads
+        #[clap(short, long)]
+        downloads: bool,
     },
 }
 
@@ -55,3 +489,16 @@ pub enum PlayerAPI {
     Video,
     Music,
 }
+
+#[derive(
+    clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, strum::Display, strum::EnumIter,
+)]
+pub enum TrendingCategory {
+    /// Trending page of YouTube
+    #[default]
+    Videos,
+    /// Trending tracks of the YouTube Music charts
+    Music,
+    /// New music videos on YouTube Music
+    NewMusic,
+}
@@ -0,0 +1,41 @@
+use crate::app::YoutubeRs;
+use crate::remote::{PlayerCommand, PlayerRemote};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Queue the YouTube videos copied to the clipboard while the player runs
+pub fn spawn(remote: PlayerRemote) {
+    tokio::spawn(async move {
+        // What was copied before the player started is not queued
+        let mut last = read().await;
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        while !remote.commands.is_closed() {
+            interval.tick().await;
+            let copied = read().await;
+            if copied == last {
+                continue;
+            }
+            last = copied.clone();
+            let Some(text) = copied else {
+                continue;
+            };
+            let text = text.trim();
+            if !(text.contains("youtube.com/") || text.contains("youtu.be/")) {
+                continue;
+            }
+            if let Ok(media) = YoutubeRs::resolve_video(text).await {
+                let _ = remote
+                    .commands
+                    .send(PlayerCommand::Enqueue(Box::new(media)));
+            }
+        }
+    });
+}
+
+async fn read() -> Option<String> {
+    tokio::task::spawn_blocking(|| terminal_clipboard::get_string().ok())
+        .await
+        .ok()
+        .flatten()
+}
@@ -0,0 +1,62 @@
+use crate::app::YoutubeRs;
+use crate::llm::Llm;
+use anyhow::Result;
+use rustypipe::model::richtext::ToPlaintext;
+
+/// Top comments of a video as `[likes] text` lines
+pub async fn fetch_top_comments(video_id: &str, limit: usize) -> Result<Vec<String>> {
+    let rp = crate::auth::rustypipe();
+    let comments = async {
+        let mut comments = rp
+            .query()
+            .unauthenticated()
+            .video_details(video_id)
+            .await?
+            .top_comments;
+        comments.extend_limit(rp.query(), limit).await?;
+        Ok::<_, rustypipe::error::Error>(comments)
+    }
+    .await;
+    let comments = comments?;
+    Ok(comments
+        .items
+        .iter()
+        .take(limit)
+        .map(|comment| {
+            format!(
+                "[{} likes{}] {}",
+                comment.like_count.unwrap_or_default(),
+                if comment.by_owner {
+                    ", by the author"
+                } else {
+                    ""
+                },
+                comment.text.to_plaintext().replace('\n', " ")
+            )
+        })
+        .collect())
+}
+
+impl YoutubeRs {
+    /// Summarize what people are saying in the comments of the selected video
+    pub async fn comments(&mut self, limit: usize) -> Result<()> {
+        let (video, search) = Self::query_ytvideo(self.last_search.clone()).await?;
+        self.last_search = Some(search);
+        println!("Fetching comments ...");
+        let comments = fetch_top_comments(&video.id, limit).await?;
+        if comments.is_empty() {
+            println!("No comment found for '{}'", video.name);
+            return Ok(());
+        }
+        let llm = Llm::select(&self.args).await?;
+        println!("Generating response ...\n");
+        llm.print_stream(format!(
+            "Here are the top comments of the YouTube video '{}'. Summarize the overall sentiment \
+            of the viewers and the highlights they talk about in a few bullet points:\n```{}```",
+            video.name,
+            comments.join("\n")
+        ))
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,335 @@
+use crate::app::{AudioFormat, FormatInquire, VideoFormat, YoutubeAPI};
+use crate::cli::{Cli, ConfigCli};
+use crate::llm::{LlmBackend, parse_keep_alive};
+use crate::utility::config_dir;
+use crate::ytdlp::{LibsSource, YtdlpChannel};
+use anyhow::{Context, Result, anyhow, bail};
+use clap::ArgMatches;
+use clap::parser::ValueSource;
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::style::{Color, Style};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+// Written by 'config edit' when there is no config yet, every setting is optional
+const TEMPLATE: &str = r##"# Settings of ytrs, the command line arguments take precedence
+
+# API searched instead of asking: "Music" or "Video"
+# api = "Music"
+
+# What to download instead of asking: "Audio" or "Video", and in which format
+# download = "Audio"
+# audio_format = "MP3"   # MP3 or WAV
+# video_format = "MP4"   # MP4, AVI or MOV
+
+# Where yt-dlp and ffmpeg are installed, and where the downloads go
+# libs_path = "/home/me/.local/share/ytrs"
+# output_path = "/home/me/Music"
+
+# mpv binary when it is not the one of the PATH
+# mpv_path = "/opt/mpv/bin/mpv"
+
+# Given as is to mpv, after the arguments of ytrs and before the --mpv-option ones
+# mpv_options = ["--volume=60", "--replaygain=track", "--af=loudnorm"]
+
+# What plays the media: "mpv", or where mpv is not available "vlc" or "builtin" for the
+# audio only, with a simpler player
+# player = "mpv"
+# vlc_path = "/usr/bin/vlc"
+
+# Where yt-dlp and ffmpeg are looked for, in order: "private" for the copies ytrs installs in
+# its libs folder, "system" for the PATH, the first one found is used
+# libs_order = ["system", "private"]
+
+# Release channel of yt-dlp: "stable", or "nightly" for the latest fixes of the extractors
+# ytdlp_channel = "nightly"
+
+# Tell at startup when a newer release is out, checked once a day, 'ytrs self-update' installs it
+# check_updates = false
+
+# Keep the computer from sleeping while a media plays, released on pause
+# inhibit_sleep = false
+
+# Keys of the player standing for other ones: the key pressed = the key of the player
+# Single characters or Enter, Esc, Tab, Backspace, Space, Up, Down, Left, Right
+# The key of the player then only works through its new key, the searches type the keys as pressed
+[keys]
+# x = "q"
+# Space = "Right"
+
+# Colors of the panes and of the selected line: names like "yellow" or "#rrggbb"
+[theme]
+# foreground = "yellow"
+# background = "blue"
+# highlight_foreground = "red"
+# highlight_background = "cyan"
+
+[llm]
+# backend = "ollama"   # ollama or openai
+# ollama_url = "http://192.168.1.20:11434"
+# openai_url = "https://api.groq.com/openai/v1"
+# model = "llama3.2"
+# temperature = 0.2
+# max_tokens = 1024
+# system = "Answer in French"
+# keep_alive = "10m"
+"##;
+
+/// Settings of `~/.config/ytrs/config.toml`, the command line arguments take precedence
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // Asked for otherwise
+    pub api: Option<YoutubeAPI>,
+    pub download: Option<FormatInquire>,
+    pub audio_format: Option<AudioFormat>,
+    pub video_format: Option<VideoFormat>,
+    pub libs_path: Option<PathBuf>,
+    pub output_path: Option<PathBuf>,
+    pub mpv_path: Option<PathBuf>,
+    mpv_options: Vec<String>,
+    pub player: PlayerChoice,
+    pub vlc_path: Option<PathBuf>,
+    libs_order: Vec<LibsSource>,
+    pub ytdlp_channel: Option<YtdlpChannel>,
+    // Tell at startup when a newer release is out, unless false
+    pub check_updates: Option<bool>,
+    // Keep the computer awake while a media plays, unless false
+    pub inhibit_sleep: Option<bool>,
+    // Key pressed in the player, and the key of the player it stands for
+    keys: HashMap<String, String>,
+    pub theme: Theme,
+    llm: LlmConfig,
+}
+
+/// What plays the media
+#[derive(Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PlayerChoice {
+    #[default]
+    Mpv,
+    /// Only the playback controls, the panes need mpv
+    Vlc,
+    /// Only the audio, decoded by ytrs itself
+    Builtin,
+}
+
+/// Colors of the player
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    #[serde(deserialize_with = "color")]
+    foreground: Color,
+    #[serde(deserialize_with = "color")]
+    background: Color,
+    #[serde(deserialize_with = "color")]
+    highlight_foreground: Color,
+    #[serde(deserialize_with = "color")]
+    highlight_background: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            foreground: Color::Yellow,
+            background: Color::Blue,
+            highlight_foreground: Color::Red,
+            highlight_background: Color::Cyan,
+        }
+    }
+}
+
+impl Theme {
+    /// Style of the panes, popups and gauges
+    pub fn panel(&self) -> Style {
+        Style::default().fg(self.foreground).bg(self.background)
+    }
+
+    /// Style of the selected line of the lists
+    pub fn highlight(&self) -> Style {
+        Style::default()
+            .fg(self.highlight_foreground)
+            .bg(self.highlight_background)
+    }
+}
+
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Color::from_str(&name).map_err(|_| serde::de::Error::custom(format!("unknown color '{name}'")))
+}
+
+/// Settings of the LLM summarizing and translating the transcripts
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct LlmConfig {
+    backend: Option<LlmBackend>,
+    ollama_url: Option<String>,
+    openai_url: Option<String>,
+    model: Option<String>,
+    temperature: Option<f32>,
+    max_tokens: Option<i32>,
+    system: Option<String>,
+    keep_alive: Option<String>,
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        config_dir().join("config.toml")
+    }
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        let config: Self = toml::from_str(&content)
+            .with_context(|| format!("Could not parse '{}'", path.to_string_lossy()))?;
+        for (pressed, stands_for) in &config.keys {
+            if parse_key(pressed).is_none() || parse_key(stands_for).is_none() {
+                bail!("Unknown key in '{pressed} = \"{stands_for}\"' of the [keys]");
+            }
+        }
+        Ok(config)
+    }
+
+    /// Fill in the arguments not given on the command line of `matches`
+    pub fn apply(&self, args: &mut Cli, matches: &ArgMatches) -> Result<()> {
+        args.libs_path = args.libs_path.take().or_else(|| self.libs_path.clone());
+        args.output_path = args.output_path.take().or_else(|| self.output_path.clone());
+        args.mpv_path = args.mpv_path.take().or_else(|| self.mpv_path.clone());
+        if args.libs_order.is_empty() {
+            args.libs_order = self.libs_order.clone();
+        }
+        // The last one of an option counts for mpv, the command line comes after
+        args.mpv_options = [
+            self.mpv_options.clone(),
+            std::mem::take(&mut args.mpv_options),
+        ]
+        .concat();
+        if let Some(channel) = self.ytdlp_channel
+            && matches.value_source("ytdlp_channel") != Some(ValueSource::CommandLine)
+        {
+            args.ytdlp_channel = channel;
+        }
+        if let Some(backend) = self.llm.backend
+            && matches.value_source("llm") != Some(ValueSource::CommandLine)
+        {
+            args.llm = backend;
+        }
+        let llm = &self.llm;
+        args.ollama_url = args.ollama_url.take().or_else(|| llm.ollama_url.clone());
+        args.openai_url = args.openai_url.take().or_else(|| llm.openai_url.clone());
+        args.llm_model = args.llm_model.take().or_else(|| llm.model.clone());
+        args.llm_temperature = args.llm_temperature.or(llm.temperature);
+        args.llm_max_tokens = args.llm_max_tokens.or(llm.max_tokens);
+        args.llm_system = args.llm_system.take().or_else(|| llm.system.clone());
+        if args.llm_keep_alive.is_none()
+            && let Some(keep_alive) = &llm.keep_alive
+        {
+            args.llm_keep_alive = Some(parse_keep_alive(keep_alive).map_err(|e| anyhow!(e))?);
+        }
+        Ok(())
+    }
+}
+
+/// Keep the settings for the rest of the run
+pub fn configure(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+/// The settings loaded at startup
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    Some(match name {
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Tab" => KeyCode::Tab,
+        "Backspace" => KeyCode::Backspace,
+        "Space" => KeyCode::Char(' '),
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        _ => return None,
+    })
+}
+
+/// The key of the player `event` stands for, as remapped in the [keys], `None` for a key
+/// another one took the place of and that is not remapped itself
+pub fn remap(event: Event) -> Option<Event> {
+    let Event::Key(key) = event else {
+        return Some(event);
+    };
+    let keys = &config().keys;
+    let remapped = keys.iter().find_map(|(pressed, stands_for)| {
+        (parse_key(pressed) == Some(key.code))
+            .then(|| parse_key(stands_for))
+            .flatten()
+    });
+    if let Some(code) = remapped {
+        return Some(Event::Key(KeyEvent { code, ..key }));
+    }
+    // Unbound once remapped, e.g. 'q' no longer quits with x = "q"
+    if keys
+        .values()
+        .any(|stands_for| parse_key(stands_for) == Some(key.code))
+    {
+        return None;
+    }
+    Some(event)
+}
+
+/// Run the `config` subcommand
+pub fn command(command: ConfigCli) -> Result<()> {
+    let path = Config::path();
+    match command {
+        ConfigCli::Path => println!("{}", path.to_string_lossy()),
+        ConfigCli::Edit => {
+            if !path.exists() {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, TEMPLATE)?;
+            }
+            let editor = std::env::var("VISUAL")
+                .or_else(|_| std::env::var("EDITOR"))
+                .unwrap_or_else(|_| {
+                    if cfg!(target_os = "windows") {
+                        "notepad".to_owned()
+                    } else {
+                        "vi".to_owned()
+                    }
+                });
+            std::process::Command::new(&editor)
+                .arg(&path)
+                .status()
+                .with_context(|| format!("Could not run '{editor}', set $EDITOR"))?;
+            match Config::load() {
+                Ok(_) => println!("Saved '{}'", path.to_string_lossy()),
+                Err(e) => println!("{e:#}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+impl YoutubeAPI {
+    /// The API of the config, or else the one picked by the user
+    pub fn configured_or_prompt() -> Self {
+        config()
+            .api
+            .unwrap_or_else(|| Self::select("Select API").prompt().unwrap())
+    }
+}
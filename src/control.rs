@@ -0,0 +1,147 @@
+use crate::app::YoutubeRs;
+use crate::cli::CtlCli;
+use crate::remote::{NowPlaying, PlayerCommand, PlayerRemote};
+use crate::utility::format_time;
+use anyhow::{Result, anyhow, bail};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\ytrs";
+
+/// Socket of the running player, `$XDG_RUNTIME_DIR/ytrs/ytrs.sock`
+#[cfg(unix)]
+fn socket_path() -> std::path::PathBuf {
+    crate::utility::runtime_dir().join("ytrs.sock")
+}
+
+/// Listen for `ytrs ctl` commands, one command per connection
+#[cfg(unix)]
+pub fn spawn(remote: PlayerRemote) -> Result<()> {
+    let path = socket_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Left behind by a player that did not exit cleanly
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(handle(remote.clone(), stream));
+        }
+    });
+    Ok(())
+}
+
+/// Listen for `ytrs ctl` commands, one command per connection
+#[cfg(windows)]
+pub fn spawn(remote: PlayerRemote) -> Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(PIPE_NAME)?;
+    tokio::spawn(async move {
+        while server.connect().await.is_ok() {
+            let Ok(next) = ServerOptions::new().create(PIPE_NAME) else {
+                break;
+            };
+            tokio::spawn(handle(remote.clone(), std::mem::replace(&mut server, next)));
+        }
+    });
+    Ok(())
+}
+
+async fn handle(remote: PlayerRemote, stream: impl AsyncRead + AsyncWrite + Unpin) -> Result<()> {
+    let mut stream = BufReader::new(stream);
+    let mut line = String::new();
+    stream.read_line(&mut line).await?;
+    let answer = match execute(&remote, line.trim()).await {
+        Ok(answer) => answer,
+        Err(e) => format!("ERR {e}"),
+    };
+    stream.write_all(format!("{answer}\n").as_bytes()).await?;
+    Ok(())
+}
+
+async fn execute(remote: &PlayerRemote, line: &str) -> Result<String> {
+    let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+    let command = match command {
+        "status" => return Ok(serde_json::to_string(&*remote.now_playing.borrow())?),
+        "play" => PlayerCommand::Play,
+        "pause" => PlayerCommand::Pause,
+        "play-pause" => PlayerCommand::TogglePause,
+        "next" => PlayerCommand::Next,
+        "previous" => PlayerCommand::Previous,
+        "stop" => PlayerCommand::Stop,
+        "clear" => PlayerCommand::ClearQueue,
+        "volume" => PlayerCommand::SetVolume(arg.parse()?),
+        "seek" if arg.starts_with(['+', '-']) => PlayerCommand::SeekRelative(arg.parse()?),
+        "seek" => PlayerCommand::SeekAbsolute(arg.parse()?),
+        "add-url" => PlayerCommand::Enqueue(Box::new(YoutubeRs::resolve_video(arg).await?)),
+        _ => bail!("Unknown command '{command}'"),
+    };
+    remote
+        .commands
+        .send(command)
+        .map_err(|_| anyhow!("The player stopped"))?;
+    Ok("OK".to_owned())
+}
+
+/// Send `command` to the running player and print its answer
+pub async fn ctl(command: CtlCli, json: bool) -> Result<()> {
+    let line = match &command {
+        CtlCli::Play => "play".to_owned(),
+        CtlCli::Pause => "pause".to_owned(),
+        CtlCli::PlayPause => "play-pause".to_owned(),
+        CtlCli::Next => "next".to_owned(),
+        CtlCli::Previous => "previous".to_owned(),
+        CtlCli::Stop => "stop".to_owned(),
+        CtlCli::Clear => "clear".to_owned(),
+        CtlCli::Volume { value } => format!("volume {value}"),
+        CtlCli::Seek { position } => format!("seek {position}"),
+        CtlCli::AddUrl { url } => format!("add-url {url}"),
+        CtlCli::Status => "status".to_owned(),
+    };
+    let answer = send(&line).await?;
+    if let Some(e) = answer.strip_prefix("ERR ") {
+        bail!("{e}");
+    }
+    match command {
+        CtlCli::Status if !json => {
+            let now_playing: NowPlaying = serde_json::from_str(&answer)?;
+            if now_playing.title.is_empty() {
+                println!("Nothing playing");
+            } else {
+                println!(
+                    "{} {} - {} {}/{} volume {}",
+                    if now_playing.paused {
+                        "Paused"
+                    } else {
+                        "Playing"
+                    },
+                    now_playing.artist,
+                    now_playing.title,
+                    format_time(now_playing.position as u32),
+                    format_time(now_playing.duration as u32),
+                    now_playing.volume as u32
+                );
+            }
+        }
+        CtlCli::Status => println!("{answer}"),
+        _ => {}
+    }
+    Ok(())
+}
+
+async fn send(line: &str) -> Result<String> {
+    #[cfg(unix)]
+    let stream = tokio::net::UnixStream::connect(socket_path()).await;
+    #[cfg(windows)]
+    let stream = tokio::net::windows::named_pipe::ClientOptions::new().open(PIPE_NAME);
+    let mut stream = BufReader::new(
+        stream.map_err(|_| anyhow!("No player is running, start one with 'ytrs player'"))?,
+    );
+    stream.write_all(format!("{line}\n").as_bytes()).await?;
+    let mut answer = String::new();
+    stream.read_line(&mut answer).await?;
+    Ok(answer.trim_end().to_owned())
+}
@@ -0,0 +1,64 @@
+use ratatui::crossterm::terminal::is_raw_mode_enabled;
+use std::backtrace::Backtrace;
+use std::panic::PanicHookInfo;
+
+const ISSUES_URL: &str = "https://github.com/Owlyat/ytrs/issues";
+
+/// On a panic of the main thread, where the player loop runs, give the terminal back,
+/// kill mpv and print where the details were logged, then exit. A panicking task is
+/// only logged, its handle reports it as before
+pub fn install() {
+    std::panic::set_hook(Box::new(|info| {
+        let message = message(info);
+        let location = info
+            .location()
+            .map(|location| location.to_string())
+            .unwrap_or_default();
+        tracing::error!(
+            "Panicked at {location}: {message}\n{}",
+            Backtrace::force_capture()
+        );
+        if std::thread::current().name() != Some("main") {
+            return;
+        }
+        clean_up();
+        eprintln!("\nytrs crashed: {message}\n  at {location}");
+        report_hint();
+        // The player would go on without its terminal
+        std::process::exit(101);
+    }));
+}
+
+/// Give the terminal back and kill mpv when an error ends ytrs
+pub fn on_error(error: &anyhow::Error) {
+    let in_player = is_raw_mode_enabled().unwrap_or(false);
+    clean_up();
+    tracing::error!("{error:#}");
+    if in_player {
+        report_hint();
+    }
+}
+
+fn clean_up() {
+    if is_raw_mode_enabled().unwrap_or(false) {
+        ratatui::restore();
+    }
+    ytrs_core::player::kill_spawned();
+}
+
+fn message(info: &PanicHookInfo) -> String {
+    if let Some(message) = info.payload().downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_owned()
+    }
+}
+
+fn report_hint() {
+    eprintln!(
+        "The log is in '{}', attach it when reporting the problem at {ISSUES_URL}",
+        crate::log::dir().to_string_lossy()
+    );
+}
@@ -0,0 +1,104 @@
+use crate::app::YoutubeRs;
+use crate::llm::{Llm, SummaryCache, print_progress};
+use crate::transcript;
+use crate::utility::sanitize_file_name;
+use anyhow::{Context, Result, bail};
+use rustypipe::model::UrlTarget;
+
+impl YoutubeRs {
+    /// Summarize every video of a playlist and write a markdown digest with an overall synthesis
+    pub async fn digest(&mut self, url: &str, limit: usize) -> Result<()> {
+        if !self.libraries_exist(&self.args.clone()) {
+            Self::install_lib(&self.args).await?;
+        }
+        let rp = crate::auth::rustypipe();
+        let playlist = async {
+            let UrlTarget::Playlist { id } = rp
+                .query()
+                .resolve_string(url, false)
+                .await
+                .context("Failed to resolve url")?
+            else {
+                bail!("'{url}' is not a playlist");
+            };
+            let mut playlist = rp.query().playlist(&id).await?;
+            playlist.videos.extend_limit(rp.query(), limit).await?;
+            Ok(playlist)
+        }
+        .await;
+        let playlist = playlist?;
+
+        let llm = Llm::select(&self.args).await?;
+        let fetcher = Self::get_fetcher(&self.args).await?;
+        let videos: Vec<_> = playlist.videos.items.iter().take(limit).collect();
+        let mut summaries: Vec<(String, String, String)> = Vec::new();
+        for (i, video) in videos.iter().enumerate() {
+            println!("[{}/{}] {}", i + 1, videos.len(), video.name);
+            let video_url = Self::get_video_url(&video.id);
+            let cues = match fetcher.fetch_video_infos(video_url.clone()).await {
+                Ok(info) => transcript::fetch_cues(&info, None).await,
+                Err(e) => Err(e.into()),
+            };
+            let summary = match cues {
+                Ok(cues) => {
+                    let prompt = "Summarize this content in a few bullet points:";
+                    let cache = SummaryCache::new(&video.id, prompt);
+                    match cache.load().filter(|_| !self.refresh) {
+                        Some(summary) => summary.trim().to_string(),
+                        None => {
+                            let transcript = cues
+                                .iter()
+                                .map(|cue| cue.text.as_str())
+                                .collect::<Vec<&str>>()
+                                .join("\n");
+                            let transcript = llm.condense(&transcript, print_progress).await?;
+                            let summary = llm
+                                .generate(format!("{prompt} \n```{transcript}```"))
+                                .await?;
+                            cache.store(&summary)?;
+                            summary.trim().to_string()
+                        }
+                    }
+                }
+                Err(e) => {
+                    println!("Skipped: {e}");
+                    tracing::warn!("Skipped: {e}");
+                    format!("_No transcript: {e}_")
+                }
+            };
+            summaries.push((video.name.clone(), video_url, summary));
+        }
+        if summaries.is_empty() {
+            println!("'{}' has no video", playlist.name);
+            return Ok(());
+        }
+
+        println!("Generating the overview ...\n");
+        let overview = llm
+            .print_stream(format!(
+                "Here are the summaries of the videos of the playlist '{}'. Write an overview \
+                of the main themes and takeaways across all of them:\n```{}```",
+                playlist.name,
+                summaries
+                    .iter()
+                    .map(|(name, _, summary)| format!("{name}:\n{summary}"))
+                    .collect::<Vec<String>>()
+                    .join("\n\n")
+            ))
+            .await?;
+
+        let mut digest = format!(
+            "# {}\n\n{url}\n\n## Overview\n\n{}\n\n## Videos\n",
+            playlist.name,
+            overview.trim()
+        );
+        for (name, video_url, summary) in &summaries {
+            digest.push_str(&format!("\n### [{name}]({video_url})\n\n{summary}\n"));
+        }
+        let (_, out) = Self::get_libs_path(&self.args);
+        let path = out.join(format!("{}-digest.md", sanitize_file_name(&playlist.name)));
+        std::fs::write(&path, digest)?;
+        println!("Digest written to '{}'", path.to_string_lossy());
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+use crate::remote::{NowPlaying, PlayerRemote};
+use crate::utility::escape_xml;
+use anyhow::{Context, Result, bail};
+use reqwest::{Client, Url};
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+
+const SSDP_ADDR: &str = "239.255.255.250:1900";
+const AV_TRANSPORT: &str = "urn:schemas-upnp-org:service:AVTransport:1";
+const RENDERING_CONTROL: &str = "urn:schemas-upnp-org:service:RenderingControl:1";
+// Drift allowed between mpv and the renderer before seeking the renderer
+const MAX_DRIFT: f64 = 3.0;
+
+/// A DLNA/UPnP media renderer found on the network
+#[derive(Clone)]
+pub struct DlnaTarget {
+    pub name: String,
+    av_transport: Url,
+    rendering_control: Option<Url>,
+}
+
+impl std::fmt::Display for DlnaTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({})",
+            self.name,
+            self.av_transport.host_str().unwrap_or_default()
+        )
+    }
+}
+
+/// Look for DLNA renderers for a few seconds and let the user pick one
+pub async fn select() -> Result<DlnaTarget> {
+    println!("Looking for DLNA renderers ...");
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    let search = format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: {SSDP_ADDR}\r\nMAN: \"ssdp:discover\"\r\nMX: 2\r\nST: {AV_TRANSPORT}\r\n\r\n"
+    );
+    socket.send_to(search.as_bytes(), SSDP_ADDR).await?;
+
+    let mut locations: Vec<String> = Vec::new();
+    let mut buf = [0; 2048];
+    let deadline = tokio::time::sleep(Duration::from_secs(3));
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            _ = &mut deadline => break,
+            received = socket.recv_from(&mut buf) => {
+                let Ok((len, _)) = received else {
+                    break;
+                };
+                let response = String::from_utf8_lossy(&buf[..len]);
+                let location = response.lines().find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("location").then(|| value.trim().to_owned())
+                });
+                if let Some(location) = location
+                    && !locations.contains(&location)
+                {
+                    locations.push(location);
+                }
+            }
+        }
+    }
+
+    let client = Client::new();
+    let mut targets = Vec::new();
+    for location in locations {
+        if let Ok(target) = describe(&client, &location).await {
+            targets.push(target);
+        }
+    }
+    if targets.is_empty() {
+        bail!("No DLNA renderer found");
+    }
+    Ok(inquire::Select::new("Play on", targets).prompt()?)
+}
+
+/// Read the device description to find its name and control urls
+async fn describe(client: &Client, location: &str) -> Result<DlnaTarget> {
+    let location = Url::parse(location)?;
+    let description = client
+        .get(location.clone())
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let base = match tag(&description, "URLBase") {
+        Some(base) => Url::parse(base)?,
+        None => location,
+    };
+    let control = |service: &str| -> Option<Url> {
+        let (_, rest) = description.split_once(&format!("<serviceType>{service}</serviceType>"))?;
+        base.join(tag(rest, "controlURL")?).ok()
+    };
+    Ok(DlnaTarget {
+        name: tag(&description, "friendlyName")
+            .unwrap_or("Unknown renderer")
+            .to_owned(),
+        av_transport: control(AV_TRANSPORT).context("The device has no AVTransport service")?,
+        rendering_control: control(RENDERING_CONTROL),
+    })
+}
+
+/// Play what mpv plays on `target`, mpv stays the clock the renderer follows
+pub fn spawn(remote: PlayerRemote, target: DlnaTarget, audio_only: bool) {
+    let mut now_playing = remote.now_playing;
+    tokio::spawn(async move {
+        let client = Client::new();
+        let transport = |action: &'static str, args: Vec<(&'static str, String)>| {
+            soap(&client, &target.av_transport, AV_TRANSPORT, action, args)
+        };
+
+        let mut loaded: Option<String> = None;
+        let mut last = NowPlaying::default();
+        let mut last_update = Instant::now();
+        // Ends when the player closes
+        while now_playing.changed().await.is_ok() {
+            let current = now_playing.borrow_and_update().clone();
+            let elapsed = last_update.elapsed().as_secs_f64();
+            last_update = Instant::now();
+
+            if current.url != loaded {
+                loaded = None;
+                if let Some(url) = &current.url {
+                    let (stream, mime) = crate::cast::direct_stream(url, audio_only).await?;
+                    let metadata = didl(&current, &stream, &mime);
+                    transport(
+                        "SetAVTransportURI",
+                        vec![("CurrentURI", stream), ("CurrentURIMetaData", metadata)],
+                    )
+                    .await?;
+                    if !current.paused {
+                        transport("Play", vec![("Speed", "1".to_owned())]).await?;
+                    }
+                    loaded = Some(url.clone());
+                }
+            } else if loaded.is_some() {
+                if current.paused != last.paused {
+                    if current.paused {
+                        transport("Pause", vec![]).await?;
+                    } else {
+                        transport("Play", vec![("Speed", "1".to_owned())]).await?;
+                    }
+                }
+                let expected = last.position + if last.paused { 0.0 } else { elapsed };
+                if (current.position - expected).abs() > MAX_DRIFT {
+                    transport(
+                        "Seek",
+                        vec![
+                            ("Unit", "REL_TIME".to_owned()),
+                            ("Target", time(current.position)),
+                        ],
+                    )
+                    .await?;
+                }
+            }
+            if current.volume != last.volume
+                && let Some(rendering_control) = &target.rendering_control
+            {
+                let volume = current.volume.clamp(0.0, 100.0) as u32;
+                soap(
+                    &client,
+                    rendering_control,
+                    RENDERING_CONTROL,
+                    "SetVolume",
+                    vec![
+                        ("Channel", "Master".to_owned()),
+                        ("DesiredVolume", volume.to_string()),
+                    ],
+                )
+                .await?;
+            }
+            last = current;
+        }
+        transport("Stop", vec![]).await
+    });
+}
+
+/// Call `action` of `service` on the renderer
+async fn soap(
+    client: &Client,
+    control: &Url,
+    service: &str,
+    action: &str,
+    args: Vec<(&str, String)>,
+) -> Result<()> {
+    let args: String = args
+        .into_iter()
+        .map(|(name, value)| format!("<{name}>{}</{name}>", escape_xml(&value)))
+        .collect();
+    let body = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/"><s:Body><u:{action} xmlns:u="{service}"><InstanceID>0</InstanceID>{args}</u:{action}></s:Body></s:Envelope>"#
+    );
+    client
+        .post(control.clone())
+        .header("Content-Type", r#"text/xml; charset="utf-8""#)
+        .header("SOAPAction", format!(r#""{service}#{action}""#))
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()
+        .with_context(|| format!("The renderer refused '{action}'"))?;
+    Ok(())
+}
+
+/// DIDL-Lite metadata, most TVs show it and some refuse media without it
+fn didl(now_playing: &NowPlaying, stream: &str, mime: &str) -> String {
+    let class = if mime.starts_with("audio") {
+        "object.item.audioItem.musicTrack"
+    } else {
+        "object.item.videoItem"
+    };
+    let art = now_playing
+        .thumbnail
+        .as_deref()
+        .map(|thumbnail| {
+            format!(
+                "<upnp:albumArtURI>{}</upnp:albumArtURI>",
+                escape_xml(thumbnail)
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        r#"<DIDL-Lite xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/"><item id="0" parentID="-1" restricted="1"><dc:title>{}</dc:title><upnp:artist>{}</upnp:artist><upnp:class>{class}</upnp:class>{art}<res protocolInfo="http-get:*:{mime}:*" duration="{}">{}</res></item></DIDL-Lite>"#,
+        escape_xml(&now_playing.title),
+        escape_xml(&now_playing.artist),
+        time(now_playing.duration),
+        escape_xml(stream)
+    )
+}
+
+/// Text of the first `<name>` element
+fn tag<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let (_, rest) = xml.split_once(&format!("<{name}>"))?;
+    let (text, _) = rest.split_once(&format!("</{name}>"))?;
+    Some(text.trim())
+}
+
+/// UPnP time, `H:MM:SS`
+fn time(secs: f64) -> String {
+    let secs = secs.max(0.0) as u64;
+    format!("{}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+}
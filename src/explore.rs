@@ -0,0 +1,394 @@
+use crate::app::{TrackInfo, YoutubeResponse};
+use crate::podcasts::{Podcasts, Show};
+use anyhow::{Context, Result};
+use ratatui::widgets::ListState;
+use rustypipe::model::AlbumType;
+use rustypipe::param::{COUNTRIES, Country};
+
+// Name of the level listing the podcasts, and prefix of their searches
+const PODCASTS: &str = "Podcasts";
+
+/// A line of the YouTube Music charts, moods, genres and artists tree
+#[derive(Clone)]
+pub enum ExploreNode {
+    // Chart playlists of the country, or of the default one
+    Charts(Option<Country>),
+    Countries,
+    Genres { moods: bool },
+    Genre(String),
+    Artist(String),
+    // Most played tracks of the artist, when it has no playlist of them
+    TopSongs(String),
+    // The nodes below list tracks instead of other nodes
+    Playlist(String),
+    Album(String),
+    Radio(String),
+    // The library of the account logged in with cookies
+    Library,
+    SavedPlaylists,
+    LikedTracks,
+    History,
+    // Podcasts opened before, a query searches the others
+    Podcasts,
+    Podcast(Show),
+}
+
+impl ExploreNode {
+    fn has_tracks(&self) -> bool {
+        matches!(
+            self,
+            Self::TopSongs(_)
+                | Self::Playlist(_)
+                | Self::Album(_)
+                | Self::Radio(_)
+                | Self::LikedTracks
+                | Self::History
+                | Self::Podcast(_)
+        )
+    }
+}
+
+/// State of the explore tab of the search popup
+#[derive(Default)]
+pub struct ExploreTab {
+    pub open: bool,
+    // Name and lines of each level from the root to the one listed
+    pub levels: Vec<(String, Vec<(String, ExploreNode)>)>,
+    pub selected: ListState,
+    // Opened on an artist from another list, leaving the artist goes back to it
+    pub from_result: bool,
+}
+
+impl ExploreTab {
+    /// Lines of the level listed
+    pub fn lines(&self) -> &[(String, ExploreNode)] {
+        self.levels
+            .last()
+            .map(|(_, lines)| lines.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Names of the levels, from the root
+    pub fn path(&self) -> String {
+        self.levels
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<&str>>()
+            .join(" > ")
+    }
+
+    /// Go back to the root when the tab opens for the first time
+    pub fn reset(&mut self) {
+        let mut lines = vec![
+            ("Charts".to_owned(), ExploreNode::Charts(None)),
+            ("Charts by country".to_owned(), ExploreNode::Countries),
+            ("Moods".to_owned(), ExploreNode::Genres { moods: true }),
+            ("Genres".to_owned(), ExploreNode::Genres { moods: false }),
+            (PODCASTS.to_owned(), ExploreNode::Podcasts),
+        ];
+        if crate::auth::has_cookies() {
+            lines.push(("My library".to_owned(), ExploreNode::Library));
+        }
+        self.levels = vec![("YouTube Music".to_owned(), lines)];
+        self.selected.select_first();
+    }
+
+    /// List the children of the selected line, except for the lines of tracks which are returned
+    pub async fn expand(&mut self) -> Result<Option<ExploreNode>> {
+        let Some((name, node)) = self
+            .selected
+            .selected()
+            .and_then(|selected| self.lines().get(selected))
+            .cloned()
+        else {
+            return Ok(None);
+        };
+        if node.has_tracks() {
+            return Ok(Some(node));
+        }
+        self.open(name, node).await?;
+        Ok(None)
+    }
+
+    /// List the children of `node` below the level listed
+    pub async fn open(&mut self, name: String, node: ExploreNode) -> Result<()> {
+        let lines = children(node).await?;
+        self.levels.push((name, lines));
+        self.selected.select_first();
+        Ok(())
+    }
+
+    /// List what matches `query` below the level listed, podcasts from their level and albums otherwise
+    pub async fn search(&mut self, query: &str) -> Result<()> {
+        let in_podcasts = self
+            .levels
+            .last()
+            .is_some_and(|(name, _)| name.starts_with(PODCASTS));
+        if !in_podcasts {
+            return self.search_albums(query).await;
+        }
+        let lines = crate::podcasts::search(query)
+            .await?
+            .into_iter()
+            .map(|show| (show.name.clone(), ExploreNode::Podcast(show)))
+            .collect();
+        self.levels.push((format!("{PODCASTS} '{query}'"), lines));
+        self.selected.select_first();
+        Ok(())
+    }
+
+    /// List the albums matching `query` below the level listed
+    async fn search_albums(&mut self, query: &str) -> Result<()> {
+        let found = crate::auth::rustypipe()
+            .query()
+            .unauthenticated()
+            .music_search_albums(query)
+            .await;
+        let lines = found
+            .context("Failed to search YouTube Music")?
+            .items
+            .items
+            .into_iter()
+            .map(|album| {
+                let artists: Vec<String> = album.artists.into_iter().map(|a| a.name).collect();
+                let year = album.year.map(|y| format!(" ({y})")).unwrap_or_default();
+                (
+                    format!(
+                        "{}{year}\n\tArtist(s): [{}]",
+                        album.name,
+                        artists.join(", ")
+                    ),
+                    ExploreNode::Album(album.id),
+                )
+            })
+            .collect();
+        self.levels.push((format!("Albums '{query}'"), lines));
+        self.selected.select_first();
+        Ok(())
+    }
+
+    /// Open the page of an artist found in another list
+    pub async fn open_artist(&mut self, id: String, name: String) -> Result<()> {
+        if !self.open {
+            self.levels.clear();
+            self.from_result = true;
+        }
+        self.open(name, ExploreNode::Artist(id)).await?;
+        self.open = true;
+        Ok(())
+    }
+
+    /// Go up one level, returns false at the root
+    pub fn back(&mut self) -> bool {
+        if self.levels.len() < 2 {
+            if !self.from_result {
+                return false;
+            }
+            // Back to the list the artist was opened from
+            self.from_result = false;
+            self.open = false;
+            self.levels.clear();
+            return true;
+        }
+        self.levels.pop();
+        self.selected.select_first();
+        true
+    }
+}
+
+async fn children(node: ExploreNode) -> Result<Vec<(String, ExploreNode)>> {
+    let query = crate::auth::rustypipe().query().unauthenticated();
+    let lines = match node {
+        ExploreNode::Charts(country) => {
+            let charts = query.music_charts(country).await;
+            charts
+                .context("Failed to fetch the charts")?
+                .playlists
+                .into_iter()
+                .map(|p| (p.name, ExploreNode::Playlist(p.id)))
+                .collect()
+        }
+        ExploreNode::Countries => {
+            let mut countries: Vec<(String, ExploreNode)> = COUNTRIES
+                .iter()
+                .map(|c| (c.name().to_owned(), ExploreNode::Charts(Some(*c))))
+                .collect();
+            countries.sort_by(|a, b| a.0.cmp(&b.0));
+            countries
+        }
+        ExploreNode::Genres { moods } => {
+            let genres = query.music_genres().await;
+            genres
+                .context("Failed to fetch the moods and genres")?
+                .into_iter()
+                .filter(|g| g.is_mood == moods)
+                .map(|g| (g.name, ExploreNode::Genre(g.id)))
+                .collect()
+        }
+        ExploreNode::Genre(id) => {
+            let genre = query.music_genre(&id).await;
+            genre
+                .context("Failed to fetch the genre")?
+                .sections
+                .into_iter()
+                .flat_map(|section| {
+                    section.playlists.into_iter().map(move |p| {
+                        (
+                            format!("{}: {}", section.name, p.name),
+                            ExploreNode::Playlist(p.id),
+                        )
+                    })
+                })
+                .collect()
+        }
+        ExploreNode::Artist(id) => {
+            let artist = query.music_artist(&id, false).await;
+            let artist = artist.context("Failed to fetch the artist")?;
+            let mut lines = vec![(
+                "Top songs".to_owned(),
+                match artist.tracks_playlist_id {
+                    Some(playlist_id) => ExploreNode::Playlist(playlist_id),
+                    None => ExploreNode::TopSongs(id),
+                },
+            )];
+            if let Some(radio_id) = artist.radio_id {
+                lines.push(("Radio".to_owned(), ExploreNode::Radio(radio_id)));
+            }
+            for album in artist.albums {
+                let kind = match album.album_type {
+                    AlbumType::Single => "Single",
+                    AlbumType::Ep => "EP",
+                    _ => "Album",
+                };
+                let year = album.year.map(|y| format!(" ({y})")).unwrap_or_default();
+                lines.push((
+                    format!("{kind}: {}{year}", album.name),
+                    ExploreNode::Album(album.id),
+                ));
+            }
+            for playlist in artist.playlists {
+                lines.push((
+                    format!("Playlist: {}", playlist.name),
+                    ExploreNode::Playlist(playlist.id),
+                ));
+            }
+            for similar in artist.similar_artists {
+                lines.push((
+                    format!("Similar: {}", similar.name),
+                    ExploreNode::Artist(similar.id),
+                ));
+            }
+            lines
+        }
+        ExploreNode::Podcasts => Podcasts::load()?
+            .shows
+            .into_iter()
+            .map(|show| (show.name.clone(), ExploreNode::Podcast(show)))
+            .collect(),
+        ExploreNode::Library => vec![
+            ("Liked music".to_owned(), ExploreNode::LikedTracks),
+            ("Saved playlists".to_owned(), ExploreNode::SavedPlaylists),
+            ("History".to_owned(), ExploreNode::History),
+        ],
+        ExploreNode::SavedPlaylists => {
+            let playlists = query.music_saved_playlists().await;
+            playlists
+                .context("Failed to fetch the saved playlists")?
+                .items
+                .into_iter()
+                .map(|p| (p.name, ExploreNode::Playlist(p.id)))
+                .collect()
+        }
+        ExploreNode::TopSongs(_)
+        | ExploreNode::Playlist(_)
+        | ExploreNode::Album(_)
+        | ExploreNode::Radio(_)
+        | ExploreNode::LikedTracks
+        | ExploreNode::History
+        | ExploreNode::Podcast(_) => Vec::new(),
+    };
+    Ok(lines)
+}
+
+/// Name and labeled tracks of a node listing tracks, numbered for an album
+pub async fn tracks(node: ExploreNode) -> Result<(String, Vec<(String, YoutubeResponse)>)> {
+    let album = matches!(node, ExploreNode::Album(_));
+    let query = crate::auth::rustypipe().query().unauthenticated();
+    if let ExploreNode::History = node {
+        // Watched videos, not only music
+        let history = query.history().await;
+        let lines = history
+            .context("Failed to fetch the history")?
+            .items
+            .iter()
+            .map(|watched| {
+                let video = YoutubeResponse::from(&watched.item);
+                (video.label(), video)
+            })
+            .collect();
+        return Ok(("History".to_owned(), lines));
+    }
+    let tracks = match node {
+        ExploreNode::Podcast(show) => return episodes(show).await,
+        ExploreNode::TopSongs(id) => query
+            .music_artist(&id, false)
+            .await
+            .map(|artist| (format!("{} top songs", artist.name), artist.tracks)),
+        ExploreNode::Playlist(id) => query
+            .music_playlist(&id)
+            .await
+            .map(|playlist| (playlist.name, playlist.tracks.items)),
+        ExploreNode::Album(id) => query
+            .music_album(&id)
+            .await
+            .map(|album| (album.name, album.tracks)),
+        ExploreNode::Radio(id) => query
+            .music_radio(&id)
+            .await
+            .map(|radio| ("Radio".to_owned(), radio.items)),
+        ExploreNode::LikedTracks => query
+            .music_liked_tracks()
+            .await
+            .map(|liked| ("Liked music".to_owned(), liked.tracks.items)),
+        _ => return Ok((String::new(), Vec::new())),
+    };
+    let (name, tracks) = tracks.context("Failed to fetch the tracks")?;
+    let lines = tracks
+        .into_iter()
+        .map(|track| {
+            let label = match track.track_nr.filter(|_| album) {
+                Some(nr) => format!("{nr:>2}. {}", TrackInfo::from(&track)),
+                None => TrackInfo::from(&track).to_string(),
+            };
+            (label, YoutubeResponse::from(track))
+        })
+        .collect();
+    Ok((name, lines))
+}
+
+/// Episodes of the podcast `show`, marked once played or started
+async fn episodes(show: Show) -> Result<(String, Vec<(String, YoutubeResponse)>)> {
+    let playlist = crate::auth::rustypipe()
+        .query()
+        .unauthenticated()
+        .music_playlist(&show.id)
+        .await;
+    let playlist = playlist.context("Failed to fetch the episodes")?;
+    let mut podcasts = Podcasts::load()?;
+    let lines = playlist
+        .tracks
+        .items
+        .into_iter()
+        .map(|episode| {
+            let label = format!(
+                "{}{}",
+                podcasts.marker(&episode.id),
+                TrackInfo::from(&episode)
+            );
+            (label, YoutubeResponse::from(episode))
+        })
+        .collect();
+    podcasts.follow(show);
+    podcasts.save()?;
+    Ok((playlist.name, lines))
+}
@@ -0,0 +1,167 @@
+use crate::cli::Cli;
+use anyhow::{Context, Result, bail};
+use reqwest::Url;
+use rustypipe::model::VideoItem;
+use serde_json::{Value, json};
+use std::sync::OnceLock;
+
+/// Instance of an alternative YouTube frontend, used when RustyPipe or yt-dlp fail
+enum Instance {
+    Invidious(String),
+    Piped(String),
+}
+
+static INSTANCE: OnceLock<Instance> = OnceLock::new();
+
+/// Remember the instance given with `--invidious` or `--piped`
+pub fn configure(args: &Cli) {
+    let instance = match (&args.invidious, &args.piped) {
+        (Some(url), _) => Instance::Invidious(url.trim_end_matches('/').to_owned()),
+        (None, Some(url)) => Instance::Piped(url.trim_end_matches('/').to_owned()),
+        (None, None) => return,
+    };
+    let _ = INSTANCE.set(instance);
+}
+
+pub fn enabled() -> bool {
+    INSTANCE.get().is_some()
+}
+
+async fn get(url: Url) -> Result<Value> {
+    let body = reqwest::Client::new()
+        .get(url)
+        .send()
+        .await
+        .context("Could not reach the fallback instance")?
+        .error_for_status()?
+        .text()
+        .await?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Search videos on the instance
+pub async fn search(terms: &str) -> Result<Vec<VideoItem>> {
+    let videos = match INSTANCE.get() {
+        Some(Instance::Invidious(base)) => {
+            let url = Url::parse_with_params(
+                &format!("{base}/api/v1/search"),
+                [("q", terms), ("type", "video")],
+            )?;
+            get(url)
+                .await?
+                .as_array()
+                .map(|items| items.iter().filter_map(invidious_video).collect())
+        }
+        Some(Instance::Piped(base)) => {
+            let url = Url::parse_with_params(
+                &format!("{base}/search"),
+                [("q", terms), ("filter", "videos")],
+            )?;
+            get(url)
+                .await?
+                .get("items")
+                .and_then(Value::as_array)
+                .map(|items| items.iter().filter_map(piped_video).collect())
+        }
+        None => bail!("No Invidious or Piped instance given"),
+    };
+    videos.context("Unexpected answer of the fallback instance")
+}
+
+/// Url of a stream of the video mpv can play without yt-dlp
+pub async fn stream_url(video_id: &str) -> Result<String> {
+    let url = match INSTANCE.get() {
+        Some(Instance::Invidious(base)) => {
+            // Proxied by the instance, YouTube only serves the streams to the client they were made for
+            let video = get(Url::parse(&format!(
+                "{base}/api/v1/videos/{video_id}?local=true"
+            ))?)
+            .await?;
+            video["formatStreams"]
+                .as_array()
+                .and_then(|streams| streams.last())
+                .and_then(|stream| stream["url"].as_str())
+                .map(|url| match url.starts_with('/') {
+                    true => format!("{base}{url}"),
+                    false => url.to_owned(),
+                })
+        }
+        Some(Instance::Piped(base)) => {
+            let streams = get(Url::parse(&format!("{base}/streams/{video_id}"))?).await?;
+            streams["hls"]
+                .as_str()
+                .filter(|hls| !hls.is_empty())
+                .map(str::to_owned)
+        }
+        None => bail!("No Invidious or Piped instance given"),
+    };
+    url.context("The fallback instance has no stream of this video")
+}
+
+// RustyPipe items cannot be built outside of it, they are deserialized instead
+fn video_item(video: Value) -> Option<VideoItem> {
+    serde_json::from_value(video).ok()
+}
+
+fn invidious_video(item: &Value) -> Option<VideoItem> {
+    if item["type"] != "video" {
+        return None;
+    }
+    video_item(json!({
+        "id": item["videoId"],
+        "name": item["title"],
+        "duration": item["lengthSeconds"].as_u64().filter(|secs| *secs > 0),
+        "thumbnail": item["videoThumbnails"]
+            .as_array()
+            .map(|thumbnails| thumbnails
+                .iter()
+                .map(|t| json!({ "url": t["url"], "width": t["width"], "height": t["height"] }))
+                .collect::<Vec<Value>>())
+            .unwrap_or_default(),
+        "channel": {
+            "id": item["authorId"],
+            "name": item["author"],
+            "avatar": [],
+            "verification": "none",
+            "subscriber_count": null,
+        },
+        "publish_date": null,
+        "publish_date_txt": item["publishedText"],
+        "view_count": item["viewCount"],
+        "is_live": item["liveNow"].as_bool().unwrap_or_default(),
+        "is_short": false,
+        "is_upcoming": item["isUpcoming"].as_bool().unwrap_or_default(),
+        "short_description": item["description"],
+    }))
+}
+
+fn piped_video(item: &Value) -> Option<VideoItem> {
+    if item["type"] != "stream" {
+        return None;
+    }
+    let id = item["url"].as_str()?.split("v=").nth(1)?;
+    let channel_id = item["uploaderUrl"]
+        .as_str()
+        .and_then(|url| url.strip_prefix("/channel/"));
+    let duration = item["duration"].as_i64().filter(|secs| *secs > 0);
+    video_item(json!({
+        "id": id,
+        "name": item["title"],
+        "duration": duration,
+        "thumbnail": [{ "url": item["thumbnail"], "width": 0, "height": 0 }],
+        "channel": channel_id.map(|channel_id| json!({
+            "id": channel_id,
+            "name": item["uploaderName"],
+            "avatar": [],
+            "verification": "none",
+            "subscriber_count": null,
+        })),
+        "publish_date": null,
+        "publish_date_txt": item["uploadedDate"],
+        "view_count": item["views"].as_u64(),
+        "is_live": duration.is_none(),
+        "is_short": item["isShort"].as_bool().unwrap_or_default(),
+        "is_upcoming": false,
+        "short_description": item["shortDescription"],
+    }))
+}
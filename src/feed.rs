@@ -0,0 +1,112 @@
+use crate::app::YoutubeRs;
+use crate::utility::{data_dir, escape_xml};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// Older items are dropped
+const MAX_ITEMS: usize = 200;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum FeedKind {
+    // Found by `ytrs watch` on a watched channel or playlist
+    Upload,
+    Download,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct FeedItem {
+    pub kind: FeedKind,
+    pub id: String,
+    pub title: String,
+    // Watched channel or playlist, or the folder of the download
+    pub source: Option<String>,
+    // Unix timestamp of when it was found or downloaded
+    pub date: i64,
+}
+
+/// New uploads and downloads, newest first, kept in `~/.local/share/ytrs/feed.json`
+#[derive(Serialize, Deserialize, Default)]
+pub struct Feed {
+    pub items: Vec<FeedItem>,
+}
+
+impl Feed {
+    pub fn path() -> PathBuf {
+        data_dir().join("feed.json")
+    }
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse '{}'", path.to_string_lossy()))
+    }
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn record(kind: FeedKind, id: &str, title: &str, source: Option<&str>) -> Result<()> {
+        let mut feed = Self::load()?;
+        feed.items.insert(
+            0,
+            FeedItem {
+                kind,
+                id: id.to_owned(),
+                title: title.to_owned(),
+                source: source.map(str::to_owned),
+                date: chrono::Utc::now().timestamp(),
+            },
+        );
+        feed.items.truncate(MAX_ITEMS);
+        feed.save()
+    }
+
+    /// RSS 2.0 document of the new uploads, and of the downloads when `downloads` is set
+    pub fn rss(&self, downloads: bool) -> String {
+        let mut listed: Vec<&str> = Vec::new();
+        let mut items = String::new();
+        for item in &self.items {
+            // A watched upload is downloaded right after being found
+            if (item.kind == FeedKind::Download && !downloads) || listed.contains(&&*item.id) {
+                continue;
+            }
+            listed.push(&item.id);
+            let title = match &item.source {
+                Some(source) => format!("{source} - {}", item.title),
+                None => item.title.clone(),
+            };
+            let date = chrono::DateTime::from_timestamp(item.date, 0)
+                .unwrap_or_default()
+                .to_rfc2822();
+            items.push_str(&format!(
+                "<item><title>{}</title><link>{}</link><guid isPermaLink=\"false\">{}</guid><pubDate>{date}</pubDate><category>{}</category></item>",
+                escape_xml(&title),
+                YoutubeRs::get_video_url(&item.id),
+                escape_xml(&item.id),
+                match item.kind {
+                    FeedKind::Upload => "upload",
+                    FeedKind::Download => "download",
+                },
+            ));
+        }
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><rss version=\"2.0\"><channel><title>ytrs</title><link>https://www.youtube.com</link><description>New uploads of the watched channels and playlists</description>{items}</channel></rss>"
+        )
+    }
+
+    /// Write the RSS document to `path`, through a temporary file so readers never see half of it
+    pub fn write_rss(&self, path: &PathBuf, downloads: bool) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, self.rss(downloads))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("Could not write '{}'", path.to_string_lossy()))
+    }
+}
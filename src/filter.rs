@@ -0,0 +1,199 @@
+use crate::app::YoutubeResponse;
+use crate::cli::{BlockCli, Cli};
+use crate::utility::config_dir;
+use anyhow::{Context, Result, bail};
+use inquire::Select;
+use rustypipe::model::{TrackItem, UrlTarget, VideoItem};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock, RwLockReadGuard};
+
+/// Results hidden from the searches, related videos, trending lists and feeds, kept in `~/.config/ytrs/filter.json`
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ContentFilter {
+    // Hidden when the title contains one of them, whatever the case
+    pub keywords: Vec<String>,
+    // Names or ids of the hidden channels and artists
+    pub channels: Vec<String>,
+    // Bounds in seconds, the media without a duration are kept
+    pub min_duration: Option<u32>,
+    pub max_duration: Option<u32>,
+    pub hide_live: bool,
+    pub hide_shorts: bool,
+}
+
+// Blocking a channel from the player applies right away
+static FILTER: OnceLock<RwLock<ContentFilter>> = OnceLock::new();
+
+/// Load the filter, unless `--no-filter` is given
+pub fn configure(args: &Cli) -> Result<()> {
+    let filter = if args.no_filter {
+        ContentFilter::default()
+    } else {
+        ContentFilter::load()?
+    };
+    let _ = FILTER.set(RwLock::new(filter));
+    Ok(())
+}
+
+fn filter() -> RwLockReadGuard<'static, ContentFilter> {
+    FILTER
+        .get_or_init(Default::default)
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Hide the channel `id` from now on, returns false when it already was
+pub fn block_channel(id: &str) -> Result<bool> {
+    let mut saved = ContentFilter::load()?;
+    if saved.channels.iter().any(|channel| channel == id) {
+        return Ok(false);
+    }
+    saved.channels.push(id.to_owned());
+    saved.save()?;
+    if let Some(filter) = FILTER.get() {
+        filter
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .channels
+            .push(id.to_owned());
+    }
+    Ok(true)
+}
+
+/// Manage the blocked channels and keywords from the command line
+pub async fn block(command: BlockCli) -> Result<()> {
+    let mut saved = ContentFilter::load()?;
+    match command {
+        BlockCli::Channel { url } => {
+            let rp = crate::auth::rustypipe();
+            let target = rp.query().resolve_string(&url, false).await;
+            let UrlTarget::Channel { id } = target.context("Failed to resolve url")? else {
+                bail!("'{url}' is not a channel");
+            };
+            if block_channel(&id)? {
+                println!("Blocked {id}");
+            } else {
+                println!("{id} was already blocked");
+            }
+            return Ok(());
+        }
+        BlockCli::Keyword { keyword } => {
+            if saved.keywords.contains(&keyword) {
+                println!("'{keyword}' was already blocked");
+                return Ok(());
+            }
+            saved.keywords.push(keyword);
+        }
+        BlockCli::Remove => {
+            let blocked: Vec<String> = saved
+                .channels
+                .iter()
+                .map(|channel| format!("Channel: {channel}"))
+                .chain(
+                    saved
+                        .keywords
+                        .iter()
+                        .map(|keyword| format!("Keyword: {keyword}")),
+                )
+                .collect();
+            if blocked.is_empty() {
+                println!("Nothing blocked");
+                return Ok(());
+            }
+            let index = Select::new("Unblock", blocked).raw_prompt()?.index;
+            if index < saved.channels.len() {
+                saved.channels.remove(index);
+            } else {
+                saved.keywords.remove(index - saved.channels.len());
+            }
+        }
+        BlockCli::List => {
+            for channel in &saved.channels {
+                println!("Channel: {channel}");
+            }
+            for keyword in &saved.keywords {
+                println!("Keyword: {keyword}");
+            }
+            return Ok(());
+        }
+    }
+    saved.save()
+}
+
+impl ContentFilter {
+    pub fn path() -> PathBuf {
+        config_dir().join("filter.json")
+    }
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse '{}'", path.to_string_lossy()))
+    }
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn hides(&self, title: &str, channels: &[(Option<&str>, &str)], duration: Option<u32>) -> bool {
+        let title = title.to_lowercase();
+        self.keywords
+            .iter()
+            .any(|keyword| title.contains(&keyword.to_lowercase()))
+            || channels.iter().any(|(id, name)| {
+                self.channels
+                    .iter()
+                    .any(|hidden| Some(hidden.as_str()) == *id || hidden.eq_ignore_ascii_case(name))
+            })
+            || duration.is_some_and(|duration| {
+                self.min_duration.is_some_and(|min| duration < min)
+                    || self.max_duration.is_some_and(|max| duration > max)
+            })
+    }
+}
+
+pub fn video_allowed(video: &VideoItem) -> bool {
+    let filter = filter();
+    let channel = video
+        .channel
+        .as_ref()
+        .map(|channel| (Some(channel.id.as_str()), channel.name.as_str()));
+    !(filter.hide_live && (video.is_live || video.is_upcoming)
+        || filter.hide_shorts && video.is_short
+        || filter.hides(&video.name, channel.as_slice(), video.duration))
+}
+
+pub fn track_allowed(track: &TrackItem) -> bool {
+    let artists: Vec<(Option<&str>, &str)> = track
+        .artists
+        .iter()
+        .map(|artist| (artist.id.as_deref(), artist.name.as_str()))
+        .collect();
+    !filter().hides(&track.name, &artists, track.duration)
+}
+
+/// The videos the filter lets through
+pub fn videos(videos: Vec<VideoItem>) -> Vec<VideoItem> {
+    videos.into_iter().filter(video_allowed).collect()
+}
+
+/// The tracks the filter lets through
+pub fn tracks(tracks: Vec<TrackItem>) -> Vec<TrackItem> {
+    tracks.into_iter().filter(track_allowed).collect()
+}
+
+pub fn allowed(media: &YoutubeResponse) -> bool {
+    match media {
+        YoutubeResponse::Video(video) => video_allowed(video),
+        YoutubeResponse::Track(track) => track_allowed(track),
+    }
+}
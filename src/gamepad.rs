@@ -0,0 +1,56 @@
+use gilrs::{Button, EventType, Gilrs};
+use ratatui::crossterm::event::KeyCode;
+use tokio::sync::mpsc;
+
+// Volume change of a trigger press
+const VOLUME_STEP: f64 = 5.0;
+
+/// What a gamepad button does in the player
+pub enum PadAction {
+    Key(KeyCode),
+    Volume(f64),
+}
+
+/// Forward the buttons pressed on any connected gamepad
+pub fn spawn() -> mpsc::UnboundedReceiver<Button> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        let Ok(mut gilrs) = Gilrs::new() else {
+            return;
+        };
+        while let Some(event) = gilrs.next_event_blocking(None) {
+            if let EventType::ButtonPressed(button, _) = event.event
+                && tx.send(button).is_err()
+            {
+                // The player closed
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// The d-pad moves in lists, the face buttons control playback outside of them
+/// and `browsing` is whether a list or a popup is open
+pub fn action(button: Button, browsing: bool) -> Option<PadAction> {
+    let key = match button {
+        Button::DPadUp => KeyCode::Up,
+        Button::DPadDown => KeyCode::Down,
+        Button::DPadLeft => KeyCode::Left,
+        Button::DPadRight => KeyCode::Right,
+        Button::South if browsing => KeyCode::Enter,
+        Button::East if browsing => KeyCode::Esc,
+        Button::South => KeyCode::Char(' '),
+        Button::West if !browsing => KeyCode::Left,
+        Button::North if !browsing => KeyCode::Right,
+        Button::Start if !browsing => KeyCode::Char('o'),
+        Button::LeftTrigger | Button::LeftTrigger2 => {
+            return Some(PadAction::Volume(-VOLUME_STEP));
+        }
+        Button::RightTrigger | Button::RightTrigger2 => {
+            return Some(PadAction::Volume(VOLUME_STEP));
+        }
+        _ => return None,
+    };
+    Some(PadAction::Key(key))
+}
@@ -0,0 +1,196 @@
+use crate::app::{YoutubeResponse, YoutubeRs};
+use crate::feed::Feed;
+use crate::remote::{MediaEntry, NowPlaying, PlayerCommand, PlayerRemote};
+use anyhow::Result;
+use axum::extract::{Path, Query, State};
+use axum::http::{StatusCode, header};
+use axum::response::{Html, IntoResponse};
+use axum::routing::{MethodRouter, delete, get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+// Single page remote for phones and browsers on the network
+const WEB_REMOTE: &str = include_str!("web_remote.html");
+
+type ApiResult<T> = Result<T, (StatusCode, String)>;
+
+#[derive(Clone)]
+struct Api {
+    remote: PlayerRemote,
+    // Search results by id, so they can be queued afterwards
+    found: Arc<Mutex<HashMap<String, YoutubeResponse>>>,
+}
+
+impl Api {
+    fn send(&self, command: PlayerCommand) -> ApiResult<StatusCode> {
+        self.remote
+            .commands
+            .send(command)
+            .map(|_| StatusCode::NO_CONTENT)
+            .map_err(|_| (StatusCode::GONE, "The player stopped".to_owned()))
+    }
+}
+
+#[derive(Deserialize)]
+struct SeekParams {
+    position: Option<f64>,
+    offset: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct VolumeParams {
+    value: f64,
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: String,
+    #[serde(default)]
+    music: bool,
+}
+
+#[derive(Deserialize)]
+struct FeedParams {
+    #[serde(default)]
+    downloads: bool,
+}
+
+#[derive(Deserialize)]
+struct EnqueueParams {
+    id: Option<String>,
+    q: Option<String>,
+    #[serde(default)]
+    music: bool,
+}
+
+/// Serve the HTTP remote control api on `addr`
+pub async fn spawn(remote: PlayerRemote, addr: SocketAddr) -> Result<()> {
+    let api = Api {
+        remote,
+        found: Arc::default(),
+    };
+    let router = Router::new()
+        .route("/", get(|| async { Html(WEB_REMOTE) }))
+        .route("/now-playing", get(now_playing))
+        .route("/play", command(PlayerCommand::Play))
+        .route("/pause", command(PlayerCommand::Pause))
+        .route("/toggle", command(PlayerCommand::TogglePause))
+        .route("/next", command(PlayerCommand::Next))
+        .route("/previous", command(PlayerCommand::Previous))
+        .route("/stop", command(PlayerCommand::Stop))
+        .route("/seek", post(seek))
+        .route("/volume", post(volume))
+        .route("/queue", get(queue).post(enqueue).delete(clear_queue))
+        .route("/queue/{index}", delete(dequeue))
+        .route("/search", get(search))
+        .route("/feed.xml", get(feed))
+        .with_state(api);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    Ok(())
+}
+
+fn command(command: PlayerCommand) -> MethodRouter<Api> {
+    post(move |State(api): State<Api>| {
+        let command = command.clone();
+        async move { api.send(command) }
+    })
+}
+
+async fn now_playing(State(api): State<Api>) -> Json<NowPlaying> {
+    Json(api.remote.now_playing.borrow().clone())
+}
+
+async fn seek(State(api): State<Api>, Query(params): Query<SeekParams>) -> ApiResult<StatusCode> {
+    match (params.position, params.offset) {
+        (Some(position), _) => api.send(PlayerCommand::SeekAbsolute(position)),
+        (None, Some(offset)) => api.send(PlayerCommand::SeekRelative(offset)),
+        (None, None) => Err((
+            StatusCode::BAD_REQUEST,
+            "Expected 'position' or 'offset'".to_owned(),
+        )),
+    }
+}
+
+async fn volume(
+    State(api): State<Api>,
+    Query(params): Query<VolumeParams>,
+) -> ApiResult<StatusCode> {
+    api.send(PlayerCommand::SetVolume(params.value))
+}
+
+async fn queue(State(api): State<Api>) -> Json<Vec<MediaEntry>> {
+    Json(api.remote.queue.borrow().clone())
+}
+
+async fn enqueue(
+    State(api): State<Api>,
+    Query(params): Query<EnqueueParams>,
+) -> ApiResult<Json<MediaEntry>> {
+    let media = if let Some(id) = params.id {
+        api.found.lock().unwrap().get(&id).cloned().ok_or((
+            StatusCode::NOT_FOUND,
+            format!("'{id}' is not part of the search results"),
+        ))?
+    } else if let Some(query) = params.q {
+        find(&query, params.music)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or((
+                StatusCode::NOT_FOUND,
+                format!("Nothing found for '{query}'"),
+            ))?
+    } else {
+        return Err((StatusCode::BAD_REQUEST, "Expected 'id' or 'q'".to_owned()));
+    };
+    let entry = MediaEntry::from(&media);
+    api.send(PlayerCommand::Enqueue(Box::new(media)))?;
+    Ok(Json(entry))
+}
+
+async fn dequeue(State(api): State<Api>, Path(index): Path<usize>) -> ApiResult<StatusCode> {
+    if index >= api.remote.queue.borrow().len() {
+        return Err((StatusCode::NOT_FOUND, format!("No media at {index}")));
+    }
+    api.send(PlayerCommand::Dequeue(index))
+}
+
+async fn clear_queue(State(api): State<Api>) -> ApiResult<StatusCode> {
+    api.send(PlayerCommand::ClearQueue)
+}
+
+async fn search(
+    State(api): State<Api>,
+    Query(params): Query<SearchParams>,
+) -> ApiResult<Json<Vec<MediaEntry>>> {
+    let results = find(&params.q, params.music).await?;
+    let mut found = api.found.lock().unwrap();
+    Ok(Json(
+        results
+            .into_iter()
+            .map(|media| {
+                let entry = MediaEntry::from(&media);
+                found.insert(entry.id.clone(), media);
+                entry
+            })
+            .collect(),
+    ))
+}
+
+async fn feed(Query(params): Query<FeedParams>) -> ApiResult<impl IntoResponse> {
+    let feed = Feed::load().map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok((
+        [(header::CONTENT_TYPE, "application/rss+xml")],
+        feed.rss(params.downloads),
+    ))
+}
+
+async fn find(query: &str, music: bool) -> ApiResult<Vec<YoutubeResponse>> {
+    YoutubeRs::search(query, music)
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))
+}
@@ -0,0 +1,81 @@
+//! Images of the player, resized and encoded off its loop
+use anyhow::{Result, anyhow};
+use image::DynamicImage;
+use ratatui_image::picker::Picker;
+use ratatui_image::thread::{ResizeRequest, ResizeResponse, ThreadProtocol};
+use std::sync::{OnceLock, mpsc};
+use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+
+// Asked once, the terminal does not change while ytrs runs
+static PICKER: OnceLock<Result<Picker, String>> = OnceLock::new();
+
+/// The image protocol and the font size of the terminal
+pub fn picker() -> Result<Picker> {
+    PICKER
+        .get_or_init(|| Picker::from_query_stdio().map_err(|e| e.to_string()))
+        .clone()
+        .map_err(|e| anyhow!("the terminal did not answer the image query: {e}"))
+}
+
+/// An image of the player, resized and encoded on its own thread so drawing never waits
+/// for it, the last one is swapped in once ready
+pub struct PlayerImage {
+    // Kept for the whole run, its ids tell the resizes of a replaced image apart
+    protocol: ThreadProtocol,
+    resized: UnboundedReceiver<ResizeResponse>,
+    empty: bool,
+}
+
+impl Default for PlayerImage {
+    fn default() -> Self {
+        let (tx, requests) = mpsc::channel::<ResizeRequest>();
+        let (done, resized) = unbounded_channel();
+        // Ends once the image is dropped
+        std::thread::spawn(move || {
+            for request in requests {
+                match request.resize_encode() {
+                    Ok(response) => {
+                        if done.send(response).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => tracing::warn!("Could not encode the image: {e}"),
+                }
+            }
+        });
+        Self {
+            protocol: ThreadProtocol::new(tx, None),
+            resized,
+            empty: true,
+        }
+    }
+}
+
+impl PlayerImage {
+    /// Show `image` in place of the current one
+    pub fn set(&mut self, picker: &Picker, image: DynamicImage) {
+        self.protocol
+            .replace_protocol(picker.new_resize_protocol(image));
+        self.empty = false;
+    }
+
+    pub fn clear(&mut self) {
+        self.protocol.empty_protocol();
+        self.empty = true;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.empty
+    }
+
+    /// Resolves once a resize is done and swapped in, the image is to be drawn again
+    pub async fn resized(&mut self) {
+        if let Some(response) = self.resized.recv().await {
+            self.protocol.update_resized_protocol(response);
+        }
+    }
+
+    pub fn protocol(&mut self) -> &mut ThreadProtocol {
+        &mut self.protocol
+    }
+}
@@ -0,0 +1,112 @@
+//! Keep the computer from sleeping while the player plays
+use crate::remote::PlayerRemote;
+use anyhow::Result;
+
+/// Hold a sleep inhibitor while a media plays, released on pause and once the player quits
+pub fn spawn(remote: PlayerRemote) {
+    let mut now_playing = remote.now_playing;
+    tokio::spawn(async move {
+        let mut inhibitor = None;
+        while now_playing.changed().await.is_ok() {
+            let playing = {
+                let current = now_playing.borrow_and_update();
+                !current.title.is_empty() && !current.paused
+            };
+            if !playing {
+                inhibitor = None;
+            } else if inhibitor.is_none() {
+                match Inhibitor::take().await {
+                    Ok(taken) => inhibitor = Some(taken),
+                    Err(e) => {
+                        // Not asked again on every change
+                        tracing::warn!("Could not keep the computer awake: {e}");
+                        return;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// A logind inhibitor lock, held as long as its file descriptor is open
+#[cfg(all(unix, not(target_os = "macos")))]
+struct Inhibitor {
+    _lock: zbus::zvariant::OwnedFd,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl Inhibitor {
+    async fn take() -> Result<Self> {
+        let connection = zbus::Connection::system().await?;
+        let reply = connection
+            .call_method(
+                Some("org.freedesktop.login1"),
+                "/org/freedesktop/login1",
+                Some("org.freedesktop.login1.Manager"),
+                "Inhibit",
+                &("sleep:idle", "ytrs", "Playing media", "block"),
+            )
+            .await?;
+        Ok(Self {
+            _lock: reply.body().deserialize()?,
+        })
+    }
+}
+
+/// The execution state belongs to a thread, one is kept waiting while it is held
+#[cfg(windows)]
+struct Inhibitor {
+    // The thread resets the state once it is dropped
+    _awake: std::sync::mpsc::Sender<()>,
+}
+
+#[cfg(windows)]
+impl Inhibitor {
+    async fn take() -> Result<Self> {
+        use windows::Win32::System::Power::{
+            ES_CONTINUOUS, ES_SYSTEM_REQUIRED, SetThreadExecutionState,
+        };
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let (set_tx, set) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let previous = unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) };
+            let _ = set_tx.send(previous.0 != 0);
+            // Until the inhibitor is dropped
+            let _ = rx.recv();
+            unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+        });
+        if !set.await? {
+            anyhow::bail!("Windows refused the execution state");
+        }
+        Ok(Self { _awake: tx })
+    }
+}
+
+/// An IOKit power assertion, released on drop
+#[cfg(target_os = "macos")]
+struct Inhibitor(objc2_io_kit::IOPMAssertionID);
+
+#[cfg(target_os = "macos")]
+impl Inhibitor {
+    async fn take() -> Result<Self> {
+        use objc2_core_foundation::CFString;
+        // kIOPMAssertionTypePreventUserIdleSystemSleep and kIOPMAssertionLevelOn, macros in IOKit
+        let kind = CFString::from_str("PreventUserIdleSystemSleep");
+        let name = CFString::from_str("ytrs playing media");
+        let mut id = 0;
+        let result = unsafe {
+            objc2_io_kit::IOPMAssertionCreateWithName(Some(&*kind), 255, Some(&*name), &mut id)
+        };
+        if result != 0 {
+            anyhow::bail!("IOKit refused the power assertion ({result})");
+        }
+        Ok(Self(id))
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        objc2_io_kit::IOPMAssertionRelease(self.0);
+    }
+}
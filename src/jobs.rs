@@ -0,0 +1,118 @@
+use crate::account::AccountPlaylists;
+use crate::app::YoutubeResponse;
+use crate::audio_tracks::AudioTrack;
+use crate::transcript::Cue;
+use anyhow::Result;
+use image::DynamicImage;
+use std::future::Future;
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+/// What a job of the player hands back once done
+pub enum JobOutput {
+    /// Entries found by a search of the popup, with their label in the list
+    Search {
+        query: String,
+        // What was searched, for the error message
+        source: &'static str,
+        found: Result<Vec<(String, YoutubeResponse)>>,
+    },
+    /// Label and id of the playlists found
+    Playlists {
+        query: String,
+        found: Result<Vec<(String, String)>>,
+    },
+    /// Name and entries of the playlist opened from the popup
+    Playlist {
+        label: String,
+        found: Result<(String, Vec<(String, YoutubeResponse)>)>,
+    },
+    Thumbnail {
+        video_id: String,
+        image: Result<DynamicImage>,
+    },
+    /// Videos related to `video_id`, for the related pane
+    Related {
+        video_id: String,
+        found: Result<Vec<YoutubeResponse>>,
+    },
+    /// Cues of `video_id`, for the transcript pane or the transcript search
+    Cues {
+        video_id: String,
+        search: bool,
+        found: Result<Vec<Cue>>,
+    },
+    AudioTracks {
+        video_id: String,
+        found: Result<Vec<AudioTrack>>,
+    },
+    /// Playlists of the account the media can be saved to
+    AccountPlaylists(Result<AccountPlaylists>),
+    /// Whether a related video could be enqueued once the queue ran out
+    Autoplay(Result<()>),
+}
+
+/// Network calls of the player, run off its loop so it keeps drawing and following mpv
+pub struct Jobs {
+    // Id, label shown while it runs and when it started
+    running: Vec<(u64, String, Instant)>,
+    next_id: u64,
+    tx: mpsc::UnboundedSender<(u64, JobOutput)>,
+    rx: mpsc::UnboundedReceiver<(u64, JobOutput)>,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            running: Vec::new(),
+            next_id: 0,
+            tx,
+            rx,
+        }
+    }
+}
+
+impl Jobs {
+    /// Run `job` in the background, `label` is shown until it is done
+    pub fn spawn(
+        &mut self,
+        label: impl Into<String>,
+        job: impl Future<Output = JobOutput> + Send + 'static,
+    ) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.running.push((id, label.into(), Instant::now()));
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            let _ = tx.send((id, job.await));
+        });
+    }
+
+    /// The outputs of the jobs done since the last call
+    pub fn poll(&mut self) -> Vec<JobOutput> {
+        let mut done = Vec::new();
+        while let Ok((id, output)) = self.rx.try_recv() {
+            self.running.retain(|(running, _, _)| *running != id);
+            done.push(output);
+        }
+        done
+    }
+
+    /// The output of the next job done, waiting for it
+    pub async fn done(&mut self) -> Option<JobOutput> {
+        let (id, output) = self.rx.recv().await?;
+        self.running.retain(|(running, _, _)| *running != id);
+        Some(output)
+    }
+
+    /// The jobs still running, the oldest first, with a spinner
+    pub fn status(&self) -> Option<String> {
+        let (_, label, started) = self.running.first()?;
+        let spinner = ["/", "|", "\\", "-"][(started.elapsed().as_millis() / 150 % 4) as usize];
+        Some(match self.running.len() {
+            1 => format!("{label} {spinner}"),
+            count => format!("{label} (+{} more) {spinner}", count - 1),
+        })
+    }
+}
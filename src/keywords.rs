@@ -0,0 +1,114 @@
+use crate::app::{AudioFormat, YoutubeRs};
+use crate::llm::{Llm, print_progress};
+use crate::transcript;
+use anyhow::{Result, bail};
+use lofty::config::WriteOptions;
+use lofty::file::TaggedFileExt;
+use lofty::probe::Probe;
+use lofty::tag::{Accessor, Tag, TagExt};
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+#[derive(strum::Display, strum::EnumIter, PartialEq)]
+enum KeywordTarget {
+    Comment,
+    Genre,
+    #[strum(to_string = "Comment and Genre")]
+    Both,
+    #[strum(to_string = "Don't write")]
+    Skip,
+}
+
+/// Write `keywords` in the comment and/or genre of the audio file
+fn write_keywords(file: &PathBuf, keywords: &[String], target: &KeywordTarget) -> Result<()> {
+    let mut tagged_file = Probe::open(file)?.guess_file_type()?.read()?;
+    if tagged_file.primary_tag().is_none() {
+        tagged_file.insert_tag(Tag::new(tagged_file.primary_tag_type()));
+    }
+    let tag = tagged_file
+        .primary_tag_mut()
+        .expect("A primary tag was just inserted");
+    if matches!(target, KeywordTarget::Comment | KeywordTarget::Both) {
+        tag.set_comment(keywords.join(", "));
+    }
+    if matches!(target, KeywordTarget::Genre | KeywordTarget::Both) {
+        tag.set_genre(keywords.join("; "));
+    }
+    tag.save_to_path(file, WriteOptions::default())?;
+    Ok(())
+}
+
+impl YoutubeRs {
+    /// Extract keywords from the transcript and offer to tag the downloaded audio file with them
+    pub async fn keywords(&mut self, file: Option<PathBuf>) -> Result<()> {
+        if !self.libraries_exist(&self.args.clone()) {
+            Self::install_lib(&self.args).await?;
+        }
+        let (video, search) = Self::query_ytvideo(self.last_search.clone()).await?;
+        self.last_search = Some(search);
+        let info = Self::get_fetcher(&self.args)
+            .await?
+            .fetch_video_infos(Self::get_video_url(&video.id))
+            .await?;
+        let transcript = transcript::fetch_cues(&info, None)
+            .await?
+            .iter()
+            .map(|cue| cue.text.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n");
+        let llm = Llm::select(&self.args).await?;
+        let transcript = llm.condense(&transcript, print_progress).await?;
+        println!("Extracting keywords ...");
+        let answer = llm
+            .generate(format!(
+                "Give the 5 to 10 keywords that best describe the topic and genre of this content. \
+                Answer only with the keywords separated by commas: \n```{transcript}```"
+            ))
+            .await?;
+        let keywords: Vec<String> = answer
+            .split([',', '\n'])
+            .map(|k| k.trim().trim_start_matches(['-', '*', ' ']).to_string())
+            .filter(|k| !k.is_empty())
+            .collect();
+        if keywords.is_empty() {
+            bail!("The LLM did not answer with any keyword");
+        }
+        println!("Keywords: {}", keywords.join(", "));
+
+        // The file downloaded from this video in the output dir, unless one was given
+        let file = match file {
+            Some(file) => file,
+            None => {
+                let (_, out) = Self::get_libs_path(&self.args);
+                let found = AudioFormat::iter()
+                    .filter_map(|format| {
+                        Self::output_file_name(
+                            &self.args,
+                            &info.title,
+                            None,
+                            &format.to_string().to_lowercase(),
+                        )
+                        .ok()
+                    })
+                    .map(|name| out.join(name))
+                    .find(|path| path.exists());
+                let Some(found) = found else {
+                    println!("No downloaded audio file found for '{}'", info.title);
+                    return Ok(());
+                };
+                found
+            }
+        };
+        let target = inquire::Select::new(
+            &format!("Write the keywords to '{}' as", file.to_string_lossy()),
+            KeywordTarget::iter().collect(),
+        )
+        .prompt()?;
+        if target == KeywordTarget::Skip {
+            return Ok(());
+        }
+        write_keywords(&file, &keywords, &target)?;
+        println!("Tags written to '{}'", file.to_string_lossy());
+        Ok(())
+    }
+}
@@ -0,0 +1,498 @@
+use crate::app::{YoutubeRs, YtrsError};
+use crate::cli::Cli;
+use crate::transcript;
+use crate::utility::cache_dir;
+use anyhow::{Context, Result, anyhow, bail};
+use ollama_rs::Ollama;
+use ollama_rs::generation::completion::request::GenerationRequest;
+use ollama_rs::generation::parameters::{KeepAlive, TimeUnit};
+use ollama_rs::models::ModelOptions;
+use serde::Deserialize;
+use std::io::Write;
+use std::path::PathBuf;
+use tokio_stream::StreamExt;
+
+/// Longest text sent in a single request, about 3k tokens
+const CHUNK_CHARS: usize = 12_000;
+
+/// Environment variables checked in order for the OpenAI compatible api key
+const API_KEY_VARS: [&str; 2] = ["YTRS_LLM_API_KEY", "OPENAI_API_KEY"];
+
+/// Service of the api keys in the system keyring, the user name being the endpoint url
+const KEYRING_SERVICE: &str = "ytrs";
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmBackend {
+    #[default]
+    Ollama,
+    /// Any server implementing the OpenAI chat completions api (llama.cpp, Groq, OpenRouter...)
+    Openai,
+}
+
+enum Client {
+    Ollama(Ollama),
+    OpenAi {
+        http: reqwest::Client,
+        base_url: String,
+        api_key: Option<String>,
+    },
+}
+
+/// A language model served by Ollama or an OpenAI compatible endpoint
+pub struct Llm {
+    client: Client,
+    model: String,
+    params: Params,
+}
+
+/// Generation parameters from the cli, unset ones keep the server defaults
+#[derive(Default)]
+struct Params {
+    temperature: Option<f32>,
+    max_tokens: Option<i32>,
+    system: Option<String>,
+    keep_alive: Option<KeepAlive>,
+}
+
+impl Params {
+    fn from_args(args: &Cli) -> Self {
+        Self {
+            temperature: args.llm_temperature,
+            max_tokens: args.llm_max_tokens,
+            system: args.llm_system.clone(),
+            keep_alive: args.llm_keep_alive.clone(),
+        }
+    }
+}
+
+/// Parse a keep alive duration: '-1' forever, '0' unload right away, or '<n>s|m|h'
+pub fn parse_keep_alive(value: &str) -> Result<KeepAlive, String> {
+    match value {
+        "-1" => return Ok(KeepAlive::Indefinitely),
+        "0" => return Ok(KeepAlive::UnloadOnCompletion),
+        _ => {}
+    }
+    let split = value.len().saturating_sub(1);
+    let unit = match value.get(split..) {
+        Some("s") => TimeUnit::Seconds,
+        Some("m") => TimeUnit::Minutes,
+        Some("h") => TimeUnit::Hours,
+        _ => return Err(format!("'{value}' should be -1, 0 or a duration like 10m")),
+    };
+    let time = value[..split]
+        .parse()
+        .map_err(|_| format!("'{value}' should be -1, 0 or a duration like 10m"))?;
+    Ok(KeepAlive::Until { time, unit })
+}
+
+#[derive(Deserialize)]
+struct ModelList {
+    data: Vec<ModelEntry>,
+}
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+#[derive(Deserialize)]
+struct ChatChunk {
+    choices: Vec<ChatChoice>,
+}
+#[derive(Deserialize)]
+struct ChatChoice {
+    delta: ChatDelta,
+}
+#[derive(Deserialize)]
+struct ChatDelta {
+    content: Option<String>,
+}
+
+impl Llm {
+    async fn client(args: &Cli) -> Result<Client> {
+        Ok(match args.llm {
+            LlmBackend::Ollama => Client::Ollama(match &args.ollama_url {
+                Some(url) => Ollama::try_new(url.as_str())
+                    .with_context(|| format!("Invalid Ollama url '{url}'"))?,
+                None => Ollama::default(),
+            }),
+            LlmBackend::Openai => {
+                let base_url = args
+                    .openai_url
+                    .clone()
+                    .context("--openai-url is required with the openai backend")?
+                    .trim_end_matches('/')
+                    .to_string();
+                Client::OpenAi {
+                    http: reqwest::Client::new(),
+                    api_key: Self::api_key(&base_url).await,
+                    base_url,
+                }
+            }
+        })
+    }
+
+    /// Api key of `base_url` from the environment, or from the system keyring when unset
+    async fn api_key(base_url: &str) -> Option<String> {
+        let from_env = API_KEY_VARS
+            .iter()
+            .find_map(|var| std::env::var(var).ok())
+            .filter(|key| !key.is_empty());
+        if from_env.is_some() {
+            return from_env;
+        }
+        // The keyring clients block, e.g. on D-Bus
+        let user = base_url.to_owned();
+        let found = tokio::task::spawn_blocking(move || {
+            keyring::Entry::new(KEYRING_SERVICE, &user)?.get_password()
+        })
+        .await
+        .ok()?;
+        match found {
+            Ok(key) => Some(key),
+            Err(keyring::Error::NoEntry) => None,
+            Err(e) => {
+                tracing::warn!("Could not read the api key of {base_url} from the keyring: {e}");
+                None
+            }
+        }
+    }
+
+    async fn models(client: &Client) -> Result<Vec<String>> {
+        Ok(match client {
+            Client::Ollama(ollama) => ollama
+                .list_local_models()
+                .await
+                .with_context(|| {
+                    format!("Could not reach the Ollama server at {}", ollama.url_str())
+                })?
+                .into_iter()
+                .map(|llm| llm.name)
+                .collect(),
+            Client::OpenAi {
+                http,
+                base_url,
+                api_key,
+            } => {
+                let mut request = http.get(format!("{base_url}/models"));
+                if let Some(key) = api_key {
+                    request = request.bearer_auth(key);
+                }
+                let body = request
+                    .send()
+                    .await
+                    .with_context(|| format!("Could not reach the LLM server at {base_url}"))?
+                    .error_for_status()?
+                    .text()
+                    .await?;
+                let list: ModelList = serde_json::from_str(&body)?;
+                list.data.into_iter().map(|model| model.id).collect()
+            }
+        })
+    }
+
+    /// Connect to the LLM server from the args, prompting for one of its models
+    /// unless a default model was provided
+    pub async fn select(args: &Cli) -> Result<Self> {
+        let client = Self::client(args).await?;
+        if let Some(model) = &args.llm_model {
+            return Ok(Self {
+                client,
+                model: model.clone(),
+                params: Params::from_args(args),
+            });
+        }
+        let models = Self::models(&client).await?;
+        let model = match inquire::Select::new("Which LLM to use:", models).prompt() {
+            Ok(v) => v,
+            Err(inquire::InquireError::OperationCanceled) => Err(anyhow!(YtrsError::Quit))?,
+            Err(e) => Err(e)?,
+        };
+        Ok(Self {
+            client,
+            model,
+            params: Params::from_args(args),
+        })
+    }
+
+    /// Connect without prompting, using the default model or the first one available
+    pub async fn connect(args: &Cli) -> Result<Self> {
+        let client = Self::client(args).await?;
+        let model = match &args.llm_model {
+            Some(model) => model.clone(),
+            None => Self::models(&client)
+                .await?
+                .into_iter()
+                .next()
+                .context("No model available on the LLM server")?,
+        };
+        Ok(Self {
+            client,
+            model,
+            params: Params::from_args(args),
+        })
+    }
+
+    /// Generate the whole answer to `prompt`
+    pub async fn generate(&self, prompt: impl Into<String>) -> Result<String> {
+        self.generate_stream(prompt, |_| {}).await
+    }
+
+    /// Generate the answer to `prompt`, calling `on_token` with every chunk received
+    pub async fn generate_stream(
+        &self,
+        prompt: impl Into<String>,
+        mut on_token: impl FnMut(&str),
+    ) -> Result<String> {
+        let mut answer = String::new();
+        match &self.client {
+            Client::Ollama(ollama) => {
+                let mut request = GenerationRequest::new(self.model.clone(), prompt.into());
+                let mut options = ModelOptions::default();
+                if let Some(temperature) = self.params.temperature {
+                    options = options.temperature(temperature);
+                }
+                if let Some(max_tokens) = self.params.max_tokens {
+                    options = options.num_predict(max_tokens);
+                }
+                request = request.options(options);
+                if let Some(system) = &self.params.system {
+                    request = request.system(system.as_str());
+                }
+                if let Some(keep_alive) = &self.params.keep_alive {
+                    request = request.keep_alive(keep_alive.clone());
+                }
+                let mut stream = ollama.generate_stream(request).await?;
+                while let Some(res) = stream.next().await {
+                    for resp in res? {
+                        on_token(&resp.response);
+                        answer.push_str(&resp.response);
+                    }
+                }
+            }
+            Client::OpenAi {
+                http,
+                base_url,
+                api_key,
+            } => {
+                let mut messages = Vec::new();
+                if let Some(system) = &self.params.system {
+                    messages.push(serde_json::json!({"role": "system", "content": system}));
+                }
+                messages.push(serde_json::json!({"role": "user", "content": prompt.into()}));
+                let mut body = serde_json::json!({
+                    "model": self.model,
+                    "stream": true,
+                    "messages": messages,
+                });
+                if let Some(temperature) = self.params.temperature {
+                    body["temperature"] = temperature.into();
+                }
+                if let Some(max_tokens) = self.params.max_tokens {
+                    body["max_tokens"] = max_tokens.into();
+                }
+                let mut request = http
+                    .post(format!("{base_url}/chat/completions"))
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body.to_string());
+                if let Some(key) = api_key {
+                    request = request.bearer_auth(key);
+                }
+                let mut response = request.send().await?;
+                if !response.status().is_success() {
+                    bail!(
+                        "LLM server answered {}: {}",
+                        response.status(),
+                        response.text().await.unwrap_or_default()
+                    );
+                }
+                // Server sent events, one `data: {json}` line per token batch. Chunks may
+                // split a character, only whole lines are decoded
+                let mut pending = Vec::new();
+                while let Some(bytes) = response.chunk().await? {
+                    pending.extend_from_slice(&bytes);
+                    while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+                        let line = pending.drain(..=end).collect::<Vec<u8>>();
+                        let line = String::from_utf8_lossy(&line);
+                        let Some(data) = line.trim().strip_prefix("data:") else {
+                            continue;
+                        };
+                        let data = data.trim();
+                        if data == "[DONE]" {
+                            return Ok(answer);
+                        }
+                        let chunk: ChatChunk = serde_json::from_str(data)?;
+                        for choice in chunk.choices {
+                            if let Some(content) = choice.delta.content {
+                                on_token(&content);
+                                answer.push_str(&content);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(answer)
+    }
+
+    /// Shrink `text` until it fits in one request by summarizing its chunks (map-reduce),
+    /// `on_progress` gets the number of chunks done and the total of the current pass
+    pub async fn condense(
+        &self,
+        text: &str,
+        mut on_progress: impl FnMut(usize, usize),
+    ) -> Result<String> {
+        let mut text = text.to_string();
+        while text.len() > CHUNK_CHARS {
+            let chunks = split_chunks(&text, CHUNK_CHARS);
+            let mut summaries = Vec::with_capacity(chunks.len());
+            for (i, chunk) in chunks.iter().enumerate() {
+                on_progress(i, chunks.len());
+                summaries.push(
+                    self.generate(format!(
+                        "Summarize this part of a transcript in detailed bullet points, \
+                        keeping every important fact: \n```{chunk}```"
+                    ))
+                    .await?,
+                );
+            }
+            on_progress(chunks.len(), chunks.len());
+            let condensed = summaries.join("\n");
+            if condensed.len() >= text.len() {
+                // The model does not shrink the text anymore, stop there
+                break;
+            }
+            text = condensed;
+        }
+        Ok(text)
+    }
+
+    /// Generate the answer to `prompt` while printing it to stdout
+    pub async fn print_stream(&self, prompt: impl Into<String>) -> Result<String> {
+        let mut stdout = std::io::stdout();
+        let answer = self
+            .generate_stream(prompt, |token| {
+                let _ = stdout.write_all(token.as_bytes());
+                let _ = stdout.flush();
+            })
+            .await?;
+        println!("\n");
+        Ok(answer)
+    }
+}
+
+/// Progress of the summarization of long transcripts
+pub fn print_progress(done: usize, total: usize) {
+    print!("\rSummarizing long transcript, part {done}/{total} ...");
+    let _ = std::io::stdout().flush();
+    if done == total {
+        println!();
+    }
+}
+
+/// Summaries already generated, stored by video and prompt so asking again is instant and offline
+pub struct SummaryCache {
+    path: PathBuf,
+}
+
+impl SummaryCache {
+    pub fn new(video_id: &str, prompt: &str) -> Self {
+        // A digest that stays the same across Rust releases, unlike the std hashers
+        let prompt = crate::update::sha256(prompt.as_bytes());
+        Self {
+            path: cache_dir()
+                .join("summaries")
+                .join(format!("{video_id}-{prompt}.md")),
+        }
+    }
+
+    pub fn load(&self) -> Option<String> {
+        std::fs::read_to_string(&self.path).ok()
+    }
+
+    pub fn store(&self, summary: &str) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.path, summary)?;
+        Ok(())
+    }
+}
+
+/// Split `text` in chunks of at most `max` bytes, on line boundaries when possible
+fn split_chunks(text: &str, max: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while rest.len() > max {
+        let mut end = max;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let end = rest[..end].rfind('\n').map(|i| i + 1).unwrap_or(end);
+        chunks.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
+
+/// State of the player summary pane
+#[derive(Default)]
+pub struct SummaryPane {
+    pub video_id: Option<String>,
+    pub text: String,
+    pub scroll: u16,
+    pub tokens: Option<tokio::sync::mpsc::UnboundedReceiver<String>>,
+}
+
+impl SummaryPane {
+    /// Start streaming the summary of `video_id` in the background
+    pub fn start(&mut self, video_id: String, args: Cli) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        self.text.clear();
+        self.scroll = 0;
+        self.video_id = Some(video_id.clone());
+        self.tokens = Some(rx);
+        tokio::spawn(async move {
+            let summary = async {
+                let prompt = "Summarize this content in a few bullet points:";
+                let cache = SummaryCache::new(&video_id, prompt);
+                if let Some(summary) = cache.load() {
+                    let _ = tx.send(summary);
+                    return Ok(());
+                }
+                let fetcher = YoutubeRs::get_fetcher(&args).await?;
+                let video = fetcher
+                    .fetch_video_infos(YoutubeRs::get_video_url(&video_id))
+                    .await?;
+                let cues = transcript::fetch_cues(&video, None).await?;
+                let transcript = cues
+                    .iter()
+                    .map(|cue| cue.text.as_str())
+                    .collect::<Vec<&str>>()
+                    .join("\n");
+                let llm = Llm::connect(&args).await?;
+                let transcript = llm.condense(&transcript, |_, _| {}).await?;
+                let summary = llm
+                    .generate_stream(format!("{prompt} \n```{transcript}```"), |token| {
+                        let _ = tx.send(token.to_string());
+                    })
+                    .await?;
+                cache.store(&summary)
+            };
+            if let Err(e) = summary.await {
+                let _ = tx.send(format!("\nError: {e}"));
+            }
+        });
+    }
+
+    /// Append the tokens received since the last frame
+    pub fn poll(&mut self) {
+        if let Some(rx) = &mut self.tokens {
+            while let Ok(token) = rx.try_recv() {
+                self.text.push_str(&token);
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::cli::Cli;
+use crate::utility::config_dir;
+use anyhow::{Context, Result};
+use rustypipe::param::{Country, Language};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Country and language of the YouTube results, kept in `~/.config/ytrs/locale.json`
+#[derive(Serialize, Deserialize, Default)]
+pub struct Locale {
+    // Country code, e.g. 'DE'
+    pub region: Option<String>,
+    // Language code, e.g. 'de' or 'en-GB'
+    pub language: Option<String>,
+    // Language of the subtitles the player shows, e.g. 'en'
+    pub subtitles: Option<String>,
+}
+
+impl Locale {
+    pub fn path() -> PathBuf {
+        config_dir().join("locale.json")
+    }
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse '{}'", path.to_string_lossy()))
+    }
+
+    /// The configured locale, `--region` and `--language` take precedence
+    pub fn resolve(args: &Cli) -> Result<(Option<Country>, Option<Language>)> {
+        let config = Self::load()?;
+        let country = args
+            .region
+            .as_ref()
+            .or(config.region.as_ref())
+            .map(|region| {
+                region
+                    .to_uppercase()
+                    .parse::<Country>()
+                    .with_context(|| format!("Unknown region '{region}'"))
+            })
+            .transpose()?;
+        let lang = args
+            .language
+            .as_ref()
+            .or(config.language.as_ref())
+            .map(|language| {
+                language
+                    .parse::<Language>()
+                    .with_context(|| format!("Unknown language '{language}'"))
+            })
+            .transpose()?;
+        Ok((country, lang))
+    }
+
+    /// The subtitle language the player prefers, `--sub-language` takes precedence
+    pub fn subtitles(args: &Cli) -> Result<Option<String>> {
+        Ok(args.sub_language.clone().or(Self::load()?.subtitles))
+    }
+}
@@ -0,0 +1,66 @@
+use crate::utility::data_dir;
+use anyhow::Result;
+use std::fmt::Display;
+use std::path::PathBuf;
+use tracing::level_filters::LevelFilter;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::prelude::*;
+
+// One file per day, the older ones are deleted
+const KEPT_FILES: usize = 7;
+
+/// Folder of the log files, `~/.local/share/ytrs/logs`
+pub fn dir() -> PathBuf {
+    data_dir().join("logs")
+}
+
+/// Write the warnings and errors to the log file of the day, and more with each `-v`
+pub fn init(verbosity: u8) -> Result<()> {
+    let level = match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    // The libraries are chatty, only their warnings unless '-vvv'
+    let libraries = if verbosity >= 3 {
+        level
+    } else {
+        LevelFilter::WARN
+    };
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("ytrs")
+        .filename_suffix("log")
+        .max_log_files(KEPT_FILES)
+        .build(dir())?;
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                // Written right away, the crash report is not lost when the process exits
+                .with_writer(appender)
+                .with_ansi(false),
+        )
+        .with(
+            Targets::new()
+                .with_default(libraries)
+                .with_target("ytrs", level)
+                .with_target("ytrs_core", level),
+        )
+        .init();
+    Ok(())
+}
+
+/// Log the failure of a result nothing else is done with
+pub trait LogError {
+    fn log_error(self, what: &str);
+}
+
+impl<T, E: Display> LogError for Result<T, E> {
+    fn log_error(self, what: &str) {
+        if let Err(e) = self {
+            tracing::warn!("{what}: {e:#}");
+        }
+    }
+}
@@ -1,22 +1,152 @@
+mod account;
 mod app;
+mod archive;
+mod audio_tracks;
+mod auth;
+mod backend;
+mod banner;
+mod basic_player;
+mod cast;
+mod chapters;
 mod cli;
-mod mpv;
-mod utility;
+mod clipboard;
+mod comments;
+mod config;
+mod control;
+mod crash;
+mod digest;
+mod dlna;
+mod explore;
+mod fallback;
+mod feed;
+mod filter;
+mod gamepad;
+mod http;
+mod images;
+mod inhibit;
+mod jobs;
+mod keywords;
+mod llm;
+mod locale;
+mod log;
+#[cfg(windows)]
+mod media_controls;
+mod midi;
+mod migrate;
+mod mood;
+mod mpd;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod mpris;
+mod notification;
+#[cfg(target_os = "macos")]
+mod now_playing;
+mod osc;
+mod overlay;
+mod podcasts;
+mod premiere;
+mod progress;
+mod ratelimit;
+mod record;
+mod related;
+mod remote;
+mod script;
+mod session;
+mod shorts;
+mod sponsorblock;
+mod stats;
+mod storyboard;
+mod subscriptions;
+mod subtitles;
+mod transcript;
+mod update;
+mod uploads;
+mod watch;
+mod ytdlp;
 
 use anyhow::Result;
 use app::*;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
+use std::path::PathBuf;
+use std::process::ExitCode;
 use strum::IntoEnumIterator;
+use ytrs_core::queue::Queue;
+use ytrs_core::{link, mpv, utility};
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let args = cli::Cli::parse();
+async fn main() -> ExitCode {
+    crash::install();
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        // Leaving a prompt is no failure to report
+        Err(e) if matches!(e.downcast_ref(), Some(YtrsError::Quit)) => script::exit_code(&e),
+        Err(e) => {
+            crash::on_error(&e);
+            eprintln!("Error: {e:?}");
+            script::exit_code(&e)
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    let matches = cli::Cli::command().get_matches();
+    let mut args = cli::Cli::from_arg_matches(&matches)?;
+    // ytrs still runs when the log folder can not be written
+    if let Err(e) = log::init(args.verbose) {
+        eprintln!("Could not open the log file: {e:#}");
+    }
+    tracing::info!("ytrs {} started", env!("CARGO_PKG_VERSION"));
+    migrate::migrate()?;
+    // A broken config can still be fixed with 'config edit'
+    match config::Config::load() {
+        Ok(config) => {
+            config.apply(&mut args, &matches)?;
+            config::configure(config);
+        }
+        Err(_) if matches!(args.command, Some(cli::AppActionCli::Config { .. })) => {}
+        Err(e) => return Err(e),
+    }
+    auth::init(&args).await?;
+    fallback::configure(&args);
+    backend::configure(&args);
+    ratelimit::configure(&args);
+    progress::configure(&args);
+    filter::configure(&args)?;
+    script::check(&args)?;
+    // Scripts read stdout and stderr, and self-update checks anyway
+    if config::config().check_updates != Some(false)
+        && !args.non_interactive
+        && !args.json
+        && !matches!(args.command, Some(cli::AppActionCli::SelfUpdate { .. }))
+    {
+        update::hint();
+    }
     let cloned = args.clone();
     let mut app: Option<YoutubeRs> = None;
     match &args.command {
-        Some(cli::AppActionCli::Download { query, url }) => {
+        Some(cli::AppActionCli::Download {
+            source,
+            query,
+            url,
+            batch,
+            sponsorblock,
+            info,
+        }) => {
             let mut builder = YoutubeRs::builder();
-            if let Some(query) = query {
+            builder.sponsorblock(sponsorblock.clone()).sidecars(*info);
+            let (url, batch) = match source.as_deref() {
+                Some("-") => (None, Some(PathBuf::from("-"))),
+                Some(_) => (source.clone(), batch.clone()),
+                None => (url.clone(), batch.clone()),
+            };
+            if let Some(batch) = batch {
+                app = Some(
+                    builder
+                        .prompt_download()
+                        .prompt_format()
+                        .batch(batch)
+                        .build(cloned),
+                );
+            } else if let Some(query) = query {
                 app = Some(
                     builder
                         .api(None, true)
@@ -30,7 +160,7 @@ async fn main() -> Result<()> {
                     builder
                         .prompt_download()
                         .prompt_format()
-                        .url(url.clone())
+                        .url(url)
                         .build(cloned),
                 );
             } else {
@@ -43,13 +173,33 @@ async fn main() -> Result<()> {
                 )
             }
         }
+        Some(cli::AppActionCli::Search {
+            query,
+            music,
+            limit,
+        }) => {
+            return script::search(&query.join(" "), *music, *limit, args.json).await;
+        }
         Some(cli::AppActionCli::Player {
+            source,
             file,
             url,
             api,
             midi,
+            trending,
         }) => {
             let mut builder = YoutubeRs::builder();
+            // The links after the first one are queued behind it
+            let mut queued = Vec::new();
+            let url = match source.as_deref() {
+                Some("-") => {
+                    let (first, rest) = script::stdin_source()?;
+                    queued = rest;
+                    Some(first)
+                }
+                Some(_) => source.clone(),
+                None => url.clone(),
+            };
             if let Some(file) = file {
                 app = Some(
                     builder
@@ -68,24 +218,52 @@ async fn main() -> Result<()> {
                 } else {
                     None
                 };
+                let mut player = builder
+                    .api(is_music, true)
+                    .midi(*midi)
+                    .url(url)
+                    .build(cloned);
+                let mut entries = Vec::new();
+                for url in queued {
+                    match YoutubeRs::resolve_video(&url).await {
+                        Ok(next) => entries.push(next),
+                        Err(e) => tracing::warn!("Could not queue '{url}': {e:#}"),
+                    }
+                }
+                player.queue = Queue::new(entries);
+                app = Some(player);
+            } else {
                 app = Some(
                     builder
-                        .api(is_music, true)
+                        .audio_player()
                         .midi(*midi)
-                        .url(url.clone())
+                        .trending(*trending)
                         .build(cloned),
                 );
-            } else {
-                app = Some(builder.audio_player().midi(*midi).build(cloned));
             }
         }
         Some(cli::AppActionCli::Transcript {
             query,
             summarize,
             url,
+            find,
+            lang,
+            translate,
+            markdown,
+            chat,
+            refresh,
         }) => {
             let mut builder = YoutubeRs::builder();
-            builder.transcript();
+            builder
+                .transcript()
+                .langs(lang.clone())
+                .translate(translate.clone())
+                .save_summary(*markdown)
+                .chat(*chat)
+                .refresh(*refresh);
+            if let Some(phrase) = find {
+                builder.find(phrase.clone());
+            }
             if let Some(query) = query {
                 builder.query(query);
                 if let Some(b) = summarize {
@@ -102,6 +280,134 @@ async fn main() -> Result<()> {
                 app = Some(builder.build(cloned));
             }
         }
+        Some(cli::AppActionCli::Record {
+            query,
+            url,
+            from_start,
+        }) => {
+            let mut builder = YoutubeRs::builder();
+            builder
+                .api(Some(false), false)
+                .prompt_download()
+                .prompt_format();
+            if let Some(query) = query {
+                builder.query(query);
+            } else if let Some(url) = url {
+                builder.query(url);
+            }
+            let mut app = builder.build(cloned);
+            let AppAction::Download { format } = app.action else {
+                return Ok(());
+            };
+            return app.record(format, *from_start).await;
+        }
+        Some(cli::AppActionCli::Chapters {
+            query,
+            url,
+            save,
+            embed,
+        }) => {
+            let mut builder = YoutubeRs::builder();
+            builder.api(Some(false), false);
+            if let Some(query) = query {
+                builder.query(query);
+            } else if let Some(url) = url {
+                builder.query(url);
+            }
+            return builder.build(cloned).chapters(*save, embed.clone()).await;
+        }
+        Some(cli::AppActionCli::Comments { query, url, limit }) => {
+            let mut builder = YoutubeRs::builder();
+            builder.api(Some(false), false);
+            if let Some(query) = query {
+                builder.query(query);
+            } else if let Some(url) = url {
+                builder.query(url);
+            }
+            return builder.build(cloned).comments(*limit).await;
+        }
+        Some(cli::AppActionCli::Digest {
+            url,
+            limit,
+            refresh,
+        }) => {
+            return YoutubeRs::builder()
+                .refresh(*refresh)
+                .build(cloned)
+                .digest(url, *limit)
+                .await;
+        }
+        Some(cli::AppActionCli::Mood {
+            description,
+            count,
+            midi,
+        }) => {
+            return YoutubeRs::builder()
+                .midi(*midi)
+                .build(cloned)
+                .mood(description, *count)
+                .await;
+        }
+        Some(cli::AppActionCli::Shorts { query, limit, midi }) => {
+            return YoutubeRs::builder()
+                .midi(*midi)
+                .build(cloned)
+                .shorts(query.clone(), *limit)
+                .await;
+        }
+        Some(cli::AppActionCli::Keywords { query, url, file }) => {
+            let mut builder = YoutubeRs::builder();
+            builder.api(Some(false), false);
+            if let Some(query) = query {
+                builder.query(query);
+            } else if let Some(url) = url {
+                builder.query(url);
+            }
+            return builder.build(cloned).keywords(file.clone()).await;
+        }
+        Some(cli::AppActionCli::Watch { command }) => {
+            return YoutubeRs::builder()
+                .build(cloned)
+                .watch(command.clone())
+                .await;
+        }
+        Some(cli::AppActionCli::Subscriptions { command }) => {
+            return YoutubeRs::builder()
+                .build(cloned)
+                .subscriptions(command.clone())
+                .await;
+        }
+        Some(cli::AppActionCli::Block { command }) => {
+            return filter::block(command.clone()).await;
+        }
+        Some(cli::AppActionCli::Config { command }) => {
+            return config::command(command.clone());
+        }
+        Some(cli::AppActionCli::Ctl { command }) => {
+            return control::ctl(command.clone(), args.json).await;
+        }
+        Some(cli::AppActionCli::Midi { command }) => {
+            return midi::run(command.clone());
+        }
+        Some(cli::AppActionCli::Login) => {
+            return auth::login().await;
+        }
+        Some(cli::AppActionCli::Logout) => {
+            return auth::logout().await;
+        }
+        Some(cli::AppActionCli::Libs { command }) => {
+            return ytdlp::run(command.clone(), &args).await;
+        }
+        Some(cli::AppActionCli::Attach { midi }) => {
+            return YoutubeRs::builder()
+                .midi(*midi)
+                .build(cloned)
+                .attach()
+                .await;
+        }
+        Some(cli::AppActionCli::SelfUpdate { check }) => {
+            return update::self_update(*check).await;
+        }
         None => {}
     }
     if let Some(current_app) = &mut app {
@@ -0,0 +1,75 @@
+use crate::log::LogError;
+use crate::remote::{NowPlaying, PlayerCommand, PlayerRemote};
+use anyhow::Result;
+use windows::Foundation::{TypedEventHandler, Uri};
+use windows::Media::Playback::MediaPlayer;
+use windows::Media::{
+    MediaPlaybackStatus, MediaPlaybackType, SystemMediaTransportControls,
+    SystemMediaTransportControlsButton, SystemMediaTransportControlsButtonPressedEventArgs,
+};
+use windows::Storage::Streams::RandomAccessStreamReference;
+use windows::core::HSTRING;
+
+/// Register with the System Media Transport Controls so the Windows media overlay
+/// and the media keys show and drive the player
+pub fn spawn(remote: PlayerRemote) -> Result<()> {
+    // A console app has no window, the controls of an idle MediaPlayer are used instead
+    let player = MediaPlayer::new()?;
+    player.CommandManager()?.SetIsEnabled(false)?;
+    let controls = player.SystemMediaTransportControls()?;
+    controls.SetIsEnabled(true)?;
+    controls.SetIsPlayEnabled(true)?;
+    controls.SetIsPauseEnabled(true)?;
+    controls.SetIsNextEnabled(true)?;
+    controls.SetIsPreviousEnabled(true)?;
+    controls.SetIsStopEnabled(true)?;
+
+    let commands = remote.commands.clone();
+    controls.ButtonPressed(&TypedEventHandler::<
+        SystemMediaTransportControls,
+        SystemMediaTransportControlsButtonPressedEventArgs,
+    >::new(move |_, args| {
+        let command = match args.ok()?.Button()? {
+            SystemMediaTransportControlsButton::Play => PlayerCommand::Play,
+            SystemMediaTransportControlsButton::Pause => PlayerCommand::Pause,
+            SystemMediaTransportControlsButton::Next => PlayerCommand::Next,
+            SystemMediaTransportControlsButton::Previous => PlayerCommand::Previous,
+            SystemMediaTransportControlsButton::Stop => PlayerCommand::Stop,
+            _ => return Ok(()),
+        };
+        let _ = commands.send(command);
+        Ok(())
+    }))?;
+
+    let runtime = tokio::runtime::Handle::current();
+    let mut now_playing = remote.now_playing;
+    std::thread::spawn(move || {
+        // Keeps the controls registered as long as the player runs
+        let _player = player;
+        while runtime.block_on(now_playing.changed()).is_ok() {
+            let current = now_playing.borrow_and_update().clone();
+            update(&controls, &current).log_error("Could not update the media controls");
+        }
+    });
+    Ok(())
+}
+
+fn update(controls: &SystemMediaTransportControls, now_playing: &NowPlaying) -> Result<()> {
+    let updater = controls.DisplayUpdater()?;
+    updater.SetType(MediaPlaybackType::Music)?;
+    let properties = updater.MusicProperties()?;
+    properties.SetTitle(&HSTRING::from(now_playing.title.as_str()))?;
+    properties.SetArtist(&HSTRING::from(now_playing.artist.as_str()))?;
+    if let Some(thumbnail) = &now_playing.thumbnail {
+        updater.SetThumbnail(&RandomAccessStreamReference::CreateFromUri(
+            &Uri::CreateUri(&HSTRING::from(thumbnail.as_str()))?,
+        )?)?;
+    }
+    updater.Update()?;
+    controls.SetPlaybackStatus(if now_playing.paused {
+        MediaPlaybackStatus::Paused
+    } else {
+        MediaPlaybackStatus::Playing
+    })?;
+    Ok(())
+}
@@ -0,0 +1,469 @@
+use crate::cli::MidiCli;
+use crate::log::LogError;
+use crate::remote::{NowPlaying, PlayerCommand, PlayerRemote};
+use crate::utility::config_dir;
+use anyhow::{Context, Result, anyhow, bail};
+use inquire::{Confirm, Select};
+use midir::{MidiIO, MidiInput, MidiInputConnection, MidiOutput, MidiOutputConnection};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
+
+const INPUT_NAME: &str = "midir reading input";
+const OUTPUT_NAME: &str = "midir forwarding output";
+// Mackie Control messages, understood by most DAW controllers
+const PITCH_BEND: u8 = 0xE0;
+const CONTROL_CHANGE: u8 = 0xB0;
+const NOTE_ON: u8 = 0x90;
+const NOTE_OFF: u8 = 0x80;
+const STOP_NOTE: u8 = 93;
+const PLAY_NOTE: u8 = 94;
+// Transport LEDs blink this many times when the track changes
+const FLASHES: usize = 3;
+const FLASH_INTERVAL: Duration = Duration::from_millis(120);
+// How often the plugged devices are checked
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+// Seconds skipped by the seek actions
+const SEEK_STEP: f64 = 5.0;
+// How long the learn mode waits for a control to move
+const LEARN_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// What a control of the device does in the player
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, strum::Display, strum::EnumIter)]
+pub enum MidiAction {
+    Volume,
+    PlayPause,
+    Next,
+    Previous,
+    Stop,
+    SeekForward,
+    SeekBackward,
+}
+
+/// A fader, knob or button of the device, channels go from 0 to 15
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum MidiControl {
+    PitchBend { channel: u8 },
+    ControlChange { channel: u8, controller: u8 },
+    Note { channel: u8, note: u8 },
+}
+
+impl MidiControl {
+    /// The control a message comes from and its value, 0 when a button is released
+    fn parse(message: &[u8]) -> Option<(Self, u8)> {
+        let (status, channel) = (message.first()? & 0xF0, message.first()? & 0x0F);
+        Some(match (status, message.get(1..3)?) {
+            (PITCH_BEND, [_, value]) => (Self::PitchBend { channel }, *value),
+            (CONTROL_CHANGE, [controller, value]) => (
+                Self::ControlChange {
+                    channel,
+                    controller: *controller,
+                },
+                *value,
+            ),
+            (NOTE_ON, [note, velocity]) => (
+                Self::Note {
+                    channel,
+                    note: *note,
+                },
+                *velocity,
+            ),
+            (NOTE_OFF, [note, _]) => (
+                Self::Note {
+                    channel,
+                    note: *note,
+                },
+                0,
+            ),
+            _ => return None,
+        })
+    }
+
+    /// Message moving the control to `value`, motorized faders and LEDs follow it
+    fn message(&self, value: u8) -> [u8; 3] {
+        match *self {
+            Self::PitchBend { channel } => [PITCH_BEND | channel, 0, value],
+            Self::ControlChange {
+                channel,
+                controller,
+            } => [CONTROL_CHANGE | channel, controller, value],
+            Self::Note { channel, note } => [NOTE_ON | channel, note, value],
+        }
+    }
+}
+
+impl std::fmt::Display for MidiControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PitchBend { channel } => write!(f, "pitch bend (channel {})", channel + 1),
+            Self::ControlChange {
+                channel,
+                controller,
+            } => write!(f, "CC {controller} (channel {})", channel + 1),
+            Self::Note { channel, note } => write!(f, "note {note} (channel {})", channel + 1),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MidiBinding {
+    pub action: MidiAction,
+    pub control: MidiControl,
+}
+
+/// Bindings of the device controls, `~/.config/ytrs/midi.json`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MidiMapping {
+    pub bindings: Vec<MidiBinding>,
+}
+
+impl Default for MidiMapping {
+    /// Mackie Control layout, the first fader moves the volume and play or stop toggle pause
+    fn default() -> Self {
+        let binding = |action, control| MidiBinding { action, control };
+        Self {
+            bindings: vec![
+                binding(MidiAction::Volume, MidiControl::PitchBend { channel: 0 }),
+                binding(
+                    MidiAction::PlayPause,
+                    MidiControl::Note {
+                        channel: 0,
+                        note: STOP_NOTE,
+                    },
+                ),
+                binding(
+                    MidiAction::PlayPause,
+                    MidiControl::Note {
+                        channel: 0,
+                        note: PLAY_NOTE,
+                    },
+                ),
+            ],
+        }
+    }
+}
+
+impl MidiMapping {
+    pub fn path() -> PathBuf {
+        config_dir().join("midi.json")
+    }
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse '{}'", path.to_string_lossy()))
+    }
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn command(&self, message: &[u8]) -> Option<PlayerCommand> {
+        let (control, value) = MidiControl::parse(message)?;
+        let binding = self.bindings.iter().find(|b| b.control == control)?;
+        Some(match binding.action {
+            MidiAction::Volume => PlayerCommand::SetVolume(midi_to_volume(value) as f64),
+            // Buttons only act when pressed
+            _ if value == 0 => return None,
+            MidiAction::PlayPause => PlayerCommand::TogglePause,
+            MidiAction::Next => PlayerCommand::Next,
+            MidiAction::Previous => PlayerCommand::Previous,
+            MidiAction::Stop => PlayerCommand::Stop,
+            MidiAction::SeekForward => PlayerCommand::SeekRelative(SEEK_STEP),
+            MidiAction::SeekBackward => PlayerCommand::SeekRelative(-SEEK_STEP),
+        })
+    }
+
+    fn volume_controls(&self) -> impl Iterator<Item = &MidiControl> {
+        self.bindings
+            .iter()
+            .filter(|b| b.action == MidiAction::Volume)
+            .map(|b| &b.control)
+    }
+}
+
+/// Learn, list or reset the bindings of the device controls
+pub fn run(command: MidiCli) -> Result<()> {
+    match command {
+        MidiCli::Learn => learn(),
+        MidiCli::List => {
+            for binding in MidiMapping::load()?.bindings {
+                println!("{}: {}", binding.action, binding.control);
+            }
+            Ok(())
+        }
+        MidiCli::Reset => {
+            let path = MidiMapping::path();
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Bind actions to the next control moved on the device
+fn learn() -> Result<()> {
+    let mut mapping = MidiMapping::load()?;
+    let midi_in = MidiInput::new(INPUT_NAME)?;
+    let mut available = ports(&midi_in);
+    let port = match available.len() {
+        0 => bail!("No midi device found"),
+        1 => available.remove(0).0,
+        _ => {
+            let names = available.iter().map(|(_, name)| name.clone()).collect();
+            let name = Select::new("Select Midi Input Port", names).prompt()?;
+            Port::Named(name)
+                .find(&available)
+                .context("The device was unplugged")?
+        }
+    };
+    let (tx, rx) = std::sync::mpsc::channel();
+    let _connection = midi_in
+        .connect(
+            &port,
+            INPUT_NAME,
+            move |_, message, _| {
+                if let Some((control, _)) = MidiControl::parse(message) {
+                    let _ = tx.send(control);
+                }
+            },
+            (),
+        )
+        .map_err(|e| anyhow!("Could not connect to the device: {e}"))?;
+    loop {
+        let action = Select::new("Action to bind", MidiAction::iter().collect()).prompt()?;
+        // Forget what was moved while picking the action
+        while rx.try_recv().is_ok() {}
+        println!("Move the control for {action} on your device ...");
+        let control = rx
+            .recv_timeout(LEARN_TIMEOUT)
+            .map_err(|_| anyhow!("Nothing was received from the device"))?;
+        mapping.bindings.retain(|b| b.control != control);
+        mapping.bindings.push(MidiBinding { action, control });
+        mapping.save()?;
+        println!("{action} is bound to {control}");
+        if !Confirm::new("Bind another action?")
+            .with_default(true)
+            .prompt()?
+        {
+            return Ok(());
+        }
+    }
+}
+
+/// Port picked at startup, followed by its name so it is found again once replugged
+#[derive(Clone)]
+enum Port {
+    Disabled,
+    // Nothing was plugged at startup, the first device plugged is used
+    Any,
+    Named(String),
+}
+
+impl Port {
+    fn find<P>(&self, ports: &[(P, String)]) -> Option<P>
+    where
+        P: Clone,
+    {
+        match self {
+            Port::Disabled => None,
+            Port::Any => ports.first(),
+            Port::Named(name) => ports.iter().find(|(_, port)| port == name),
+        }
+        .map(|(port, _)| port.clone())
+    }
+}
+
+/// Controller ports of the player
+pub struct MidiPorts {
+    input: Port,
+    output: Port,
+}
+
+/// Pick the input and output ports of the controller
+pub fn select() -> Result<MidiPorts> {
+    Ok(MidiPorts {
+        input: pick("Select Midi Input Port", &MidiInput::new(INPUT_NAME)?)?,
+        output: pick("Select Midi Output Port", &MidiOutput::new(OUTPUT_NAME)?)?,
+    })
+}
+
+fn pick(message: &str, io: &impl MidiIO) -> Result<Port> {
+    let mut names: Vec<String> = ports(io).into_iter().map(|(_, name)| name).collect();
+    Ok(match names.len() {
+        0 => Port::Any,
+        1 => Port::Named(names.remove(0)),
+        _ => {
+            names.insert(0, String::from("None"));
+            match inquire::Select::new(message, names).prompt()?.as_str() {
+                "None" => Port::Disabled,
+                name => Port::Named(name.to_owned()),
+            }
+        }
+    })
+}
+
+fn ports<T: MidiIO>(io: &T) -> Vec<(T::Port, String)> {
+    io.ports()
+        .into_iter()
+        .filter_map(|port| {
+            let name = io.port_name(&port).ok()?;
+            Some((port, name))
+        })
+        .collect()
+}
+
+/// Drive the player from the controller and keep the controller in sync with the player,
+/// the connections are made again when the controller is unplugged and plugged back
+pub fn spawn(remote: PlayerRemote, midi_ports: MidiPorts) {
+    let runtime = tokio::runtime::Handle::current();
+    let mapping = MidiMapping::load().unwrap_or_default();
+    std::thread::spawn(move || {
+        let mut input: Option<(String, MidiInputConnection<()>)> = None;
+        let mut output: Option<(String, MidiOutputConnection)> = None;
+        // What the controller shows, None until it was told the whole state
+        let mut shown: Option<NowPlaying> = None;
+        let mut last_scan: Option<Instant> = None;
+        let mut now_playing = remote.now_playing;
+        loop {
+            if last_scan.is_none_or(|scan| scan.elapsed() >= SCAN_INTERVAL) {
+                last_scan = Some(Instant::now());
+                if let Ok(midi_in) = MidiInput::new(INPUT_NAME) {
+                    let available = ports(&midi_in);
+                    if input
+                        .as_ref()
+                        .is_some_and(|(name, _)| !available.iter().any(|(_, port)| port == name))
+                    {
+                        // Unplugged
+                        input = None;
+                    }
+                    if input.is_none()
+                        && let Some(port) = midi_ports.input.find(&available)
+                        && let Ok(name) = midi_in.port_name(&port)
+                    {
+                        let commands = remote.commands.clone();
+                        let mapping = mapping.clone();
+                        input = midi_in
+                            .connect(
+                                &port,
+                                INPUT_NAME,
+                                move |_, message, _| {
+                                    if let Some(command) = mapping.command(message) {
+                                        let _ = commands.send(command);
+                                    }
+                                },
+                                (),
+                            )
+                            .inspect_err(|e| {
+                                tracing::warn!("Could not connect to the MIDI input '{name}': {e}")
+                            })
+                            .ok()
+                            .map(|connection| (name, connection));
+                    }
+                }
+                if let Ok(midi_out) = MidiOutput::new(OUTPUT_NAME) {
+                    let available = ports(&midi_out);
+                    if output
+                        .as_ref()
+                        .is_some_and(|(name, _)| !available.iter().any(|(_, port)| port == name))
+                    {
+                        output = None;
+                    }
+                    if output.is_none()
+                        && let Some(port) = midi_ports.output.find(&available)
+                        && let Ok(name) = midi_out.port_name(&port)
+                    {
+                        output = midi_out
+                            .connect(&port, OUTPUT_NAME)
+                            .inspect_err(|e| {
+                                tracing::warn!("Could not connect to the MIDI output '{name}': {e}")
+                            })
+                            .ok()
+                            .map(|connection| (name, connection));
+                        shown = None;
+                    }
+                }
+            }
+
+            if let Some((_, connection)) = &mut output {
+                let current = now_playing.borrow_and_update().clone();
+                feedback(connection, &mapping, shown.as_ref(), &current);
+                shown = Some(current);
+            }
+            let changed =
+                runtime.block_on(tokio::time::timeout(SCAN_INTERVAL, now_playing.changed()));
+            if let Ok(Err(_)) = changed {
+                // The player closed
+                break;
+            }
+        }
+        if let Some((_, connection)) = &mut output {
+            send(connection, &[NOTE_ON, PLAY_NOTE, 0]);
+            send(connection, &[NOTE_ON, STOP_NOTE, 0]);
+        }
+    });
+}
+
+/// The fader follows the volume whatever changed it, the transport LEDs show the
+/// playback state and flash when the track changes
+fn feedback(
+    connection: &mut MidiOutputConnection,
+    mapping: &MidiMapping,
+    shown: Option<&NowPlaying>,
+    current: &NowPlaying,
+) {
+    if shown.is_none_or(|shown| current.volume != shown.volume) {
+        for control in mapping.volume_controls() {
+            send(connection, &control.message(volume_to_midi(current.volume)));
+        }
+    }
+    let Some(shown) = shown else {
+        transport_leds(connection, current.paused);
+        return;
+    };
+    if current.url != shown.url || current.title != shown.title {
+        for _ in 0..FLASHES {
+            send(connection, &[NOTE_ON, PLAY_NOTE, 0]);
+            send(connection, &[NOTE_ON, STOP_NOTE, 0]);
+            std::thread::sleep(FLASH_INTERVAL);
+            send(connection, &[NOTE_ON, PLAY_NOTE, 127]);
+            send(connection, &[NOTE_ON, STOP_NOTE, 127]);
+            std::thread::sleep(FLASH_INTERVAL);
+        }
+        transport_leds(connection, current.paused);
+    } else if current.paused != shown.paused {
+        transport_leds(connection, current.paused);
+    }
+}
+
+/// Play lit while playing, stop lit while paused
+fn transport_leds(connection: &mut MidiOutputConnection, paused: bool) {
+    let (play, stop) = if paused { (0, 127) } else { (127, 0) };
+    send(connection, &[NOTE_ON, PLAY_NOTE, play]);
+    send(connection, &[NOTE_ON, STOP_NOTE, stop]);
+}
+
+fn send(connection: &mut MidiOutputConnection, message: &[u8]) {
+    connection
+        .send(message)
+        .log_error("Could not send to the MIDI device");
+}
+
+/// mpv volume, from 0 to 130, to a 7 bit midi value
+fn volume_to_midi(volume: f64) -> u8 {
+    ((volume.clamp(0.0, 130.0) * 127.0) / 130.0) as u8
+}
+
+/// 7 bit midi value to the mpv volume, from 0 to 130
+fn midi_to_volume(value: u8) -> u32 {
+    ((value as u32 * 130) / 127).clamp(0, 130)
+}
@@ -0,0 +1,88 @@
+use crate::utility::{cache_dir, config_dir, data_dir, legacy_dir};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+// What ytrs saved for the user, and the yt-dlp and ffmpeg it installed
+const DATA: [&str; 9] = [
+    "subscriptions.json",
+    "account_playlists.json",
+    "feed.json",
+    "podcasts.json",
+    "watch.json",
+    "rustypipe_cache.json",
+    "auth.json",
+    "libs",
+    "output",
+];
+const CACHE: [&str; 1] = ["summaries"];
+// Only moves on macOS and Windows, where the settings no longer are in `~/.config`
+const CONFIG: [&str; 4] = ["config.toml", "locale.json", "filter.json", "midi.json"];
+
+/// Move what the former `~/.config/ytrs` holds to the config, data and cache directories,
+/// leaving alone what already is there
+pub fn migrate() -> Result<()> {
+    let Some(legacy) = legacy_dir().filter(|dir| dir.is_dir()) else {
+        return Ok(());
+    };
+    let moves = [
+        (data_dir(), &DATA[..]),
+        (cache_dir(), &CACHE[..]),
+        (config_dir(), &CONFIG[..]),
+    ];
+    for (dir, names) in moves {
+        if dir == legacy {
+            continue;
+        }
+        for name in names {
+            let from = legacy.join(name);
+            let to = dir.join(name);
+            if !from.exists() || to.exists() {
+                continue;
+            }
+            move_entry(&from, &to).with_context(|| {
+                format!(
+                    "Could not move '{}' to '{}'",
+                    from.to_string_lossy(),
+                    to.to_string_lossy()
+                )
+            })?;
+            println!(
+                "Moved '{}' to '{}'",
+                from.to_string_lossy(),
+                to.to_string_lossy()
+            );
+            tracing::info!("Moved '{}' to '{}'", from.display(), to.display());
+        }
+    }
+    Ok(())
+}
+
+fn move_entry(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    // Renaming fails across file systems, e.g. when `~/.cache` is a tmpfs
+    copy_entry(from, to)?;
+    if from.is_dir() {
+        std::fs::remove_dir_all(from)?;
+    } else {
+        std::fs::remove_file(from)?;
+    }
+    Ok(())
+}
+
+fn copy_entry(from: &Path, to: &Path) -> Result<()> {
+    if !from.is_dir() {
+        std::fs::copy(from, to)?;
+        return Ok(());
+    }
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        copy_entry(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use crate::app::{TrackInfo, YoutubeResponse, YoutubeRs, YtrsError};
+use crate::llm::Llm;
+use anyhow::{Result, anyhow, bail};
+use inquire::validator::Validation;
+
+impl YoutubeRs {
+    /// Let the LLM pick tracks matching `mood`, then play the edited selection as a queue
+    pub async fn mood(&mut self, mood: &str, count: usize) -> Result<()> {
+        if !self.mpv_installed {
+            self.mpv_installed = Self::check_mpv(&self.args)?;
+        }
+        let llm = Llm::select(&self.args).await?;
+        println!("Looking for tracks ...");
+        let answer = llm
+            .generate(format!(
+                "Propose {count} existing songs matching this description: '{mood}'. \
+                Answer only with one song per line formatted as 'Artist - Title', without numbering."
+            ))
+            .await?;
+        let rp = crate::auth::rustypipe();
+        let mut tracks = Vec::new();
+        for query in answer
+            .lines()
+            .map(|line| line.trim().trim_start_matches(['-', '*', ' ']))
+            .filter(|line| !line.is_empty())
+            .take(count)
+        {
+            let found = rp
+                .query()
+                .unauthenticated()
+                .music_search_tracks(query)
+                .await;
+            match found.map(|res| res.items.items.into_iter().next()) {
+                Ok(Some(track)) => {
+                    println!("Found '{}'", TrackInfo::from(&track));
+                    tracks.push(track);
+                }
+                Ok(None) => println!("Nothing found for '{query}'"),
+                Err(e) => {
+                    println!("Could not search '{query}': {e}");
+                    tracing::warn!("Could not search '{query}': {e}");
+                    tracing::warn!("Could not search '{query}': {e}");
+                }
+            }
+        }
+        if tracks.is_empty() {
+            bail!("No track found for '{mood}'");
+        }
+        let options: Vec<String> = tracks
+            .iter()
+            .map(|track| TrackInfo::from(track).to_string())
+            .collect();
+        let selected = match inquire::MultiSelect::new("Queue", options)
+            .with_all_selected_by_default()
+            .with_help_message("Space to remove/add a track | Enter to start playing")
+            .with_validator(|selection: &[inquire::list_option::ListOption<&String>]| {
+                if selection.is_empty() {
+                    Ok(Validation::Invalid("Keep at least one track".into()))
+                } else {
+                    Ok(Validation::Valid)
+                }
+            })
+            .raw_prompt()
+        {
+            Ok(v) => v,
+            Err(inquire::InquireError::OperationCanceled) => Err(anyhow!(YtrsError::Quit))?,
+            Err(e) => Err(e)?,
+        };
+        let mut queue = selected
+            .into_iter()
+            .map(|option| YoutubeResponse::from(tracks[option.index].clone()));
+        let mut response = queue.next();
+        self.queue = queue.collect();
+        let mut thumbnail = match &response {
+            Some(res) => Self::fetch_yt_thumbnail(&res.get_id(), &self.args)
+                .await
+                .ok(),
+            None => None,
+        };
+        self.player(&mut response, &mut thumbnail, true, self.run_midi)
+            .await;
+        Ok(())
+    }
+}
@@ -0,0 +1,413 @@
+use crate::app::{YoutubeResponse, YoutubeRs};
+use crate::remote::{MediaEntry, NowPlaying, PlayerCommand, PlayerRemote};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+const GREETING: &str = "OK MPD 0.23.5\n";
+const SUPPORTED: &[&str] = &[
+    "add",
+    "addid",
+    "clear",
+    "close",
+    "command_list_begin",
+    "command_list_end",
+    "command_list_ok_begin",
+    "commands",
+    "currentsong",
+    "decoders",
+    "delete",
+    "deleteid",
+    "find",
+    "idle",
+    "listplaylists",
+    "lsinfo",
+    "next",
+    "noidle",
+    "notcommands",
+    "outputs",
+    "pause",
+    "ping",
+    "play",
+    "playid",
+    "playlistid",
+    "playlistinfo",
+    "plchanges",
+    "plchangesposid",
+    "previous",
+    "search",
+    "seek",
+    "seekcur",
+    "seekid",
+    "setvol",
+    "stats",
+    "status",
+    "stop",
+    "tagtypes",
+    "urlhandlers",
+];
+
+// MPD error codes
+const ACK_ARG: u8 = 2;
+const ACK_NO_EXIST: u8 = 50;
+const ACK_UNKNOWN: u8 = 5;
+const ACK_SYSTEM: u8 = 52;
+
+type Ack = (u8, String);
+
+/// Serve a subset of the MPD protocol on `addr`, the playlist is the current media followed by the queue
+pub async fn spawn(remote: PlayerRemote, addr: SocketAddr) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    // Search results by url, so they can be added afterwards
+    let found: Arc<Mutex<HashMap<String, YoutubeResponse>>> = Arc::default();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let client = Client {
+                remote: remote.clone(),
+                found: found.clone(),
+                version: 0,
+                last: None,
+            };
+            tokio::spawn(client.run(stream));
+        }
+    });
+    Ok(())
+}
+
+struct Client {
+    remote: PlayerRemote,
+    found: Arc<Mutex<HashMap<String, YoutubeResponse>>>,
+    // Playlist version, bumped whenever the media or the queue changes
+    version: u32,
+    last: Option<(Option<String>, Vec<MediaEntry>)>,
+}
+
+impl Client {
+    async fn run(mut self, stream: TcpStream) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        writer.write_all(GREETING.as_bytes()).await?;
+        // Commands of the current command list, and whether each one answers 'list_OK'
+        let mut list: Option<(Vec<String>, bool)> = None;
+        while let Some(line) = lines.next_line().await? {
+            let line = line.trim().to_owned();
+            let response = match line.as_str() {
+                "command_list_begin" => {
+                    list = Some((Vec::new(), false));
+                    continue;
+                }
+                "command_list_ok_begin" => {
+                    list = Some((Vec::new(), true));
+                    continue;
+                }
+                "command_list_end" => match list.take() {
+                    Some((commands, list_ok)) => self.run_list(&commands, list_ok).await,
+                    None => ack(0, "command_list_end", &(ACK_ARG, "Not in a list".into())),
+                },
+                _ if list.is_some() => {
+                    if let Some((commands, _)) = &mut list {
+                        commands.push(line);
+                    }
+                    continue;
+                }
+                "close" => break,
+                _ if line == "idle" || line.starts_with("idle ") => {
+                    let changed = tokio::select! {
+                        changed = self.idle() => changed,
+                        // Any line interrupts the idle, 'noidle' is the only one allowed
+                        _ = lines.next_line() => Vec::new(),
+                    };
+                    changed
+                        .iter()
+                        .map(|subsystem| format!("changed: {subsystem}\n"))
+                        .collect::<String>()
+                        + "OK\n"
+                }
+                "noidle" => "OK\n".to_owned(),
+                _ => match self.execute(&line).await {
+                    Ok(body) => body + "OK\n",
+                    Err(e) => ack(0, command_name(&line), &e),
+                },
+            };
+            writer.write_all(response.as_bytes()).await?;
+        }
+        Ok(())
+    }
+
+    async fn run_list(&mut self, commands: &[String], list_ok: bool) -> String {
+        let mut response = String::new();
+        for (index, line) in commands.iter().enumerate() {
+            match self.execute(line).await {
+                Ok(body) => {
+                    response += &body;
+                    if list_ok {
+                        response += "list_OK\n";
+                    }
+                }
+                Err(e) => return response + &ack(index, command_name(line), &e),
+            }
+        }
+        response + "OK\n"
+    }
+
+    /// Wait for the player to change, answers the MPD subsystems that changed
+    async fn idle(&mut self) -> Vec<&'static str> {
+        let before = self.remote.now_playing.borrow_and_update().clone();
+        let queue = self.remote.queue.borrow_and_update().clone();
+        loop {
+            tokio::select! {
+                changed = self.remote.now_playing.changed() => if changed.is_err() {
+                    return Vec::new();
+                },
+                changed = self.remote.queue.changed() => if changed.is_err() {
+                    return Vec::new();
+                },
+            }
+            let now = self.remote.now_playing.borrow_and_update().clone();
+            let mut changed = Vec::new();
+            if now.url != before.url || now.title != before.title {
+                changed.extend(["player", "playlist"]);
+            } else if now.paused != before.paused {
+                changed.push("player");
+            } else if *self.remote.queue.borrow_and_update() != queue {
+                changed.push("playlist");
+            }
+            if now.volume != before.volume {
+                changed.push("mixer");
+            }
+            if !changed.is_empty() {
+                return changed;
+            }
+        }
+    }
+
+    async fn execute(&mut self, line: &str) -> Result<String, Ack> {
+        let args = parse_args(line);
+        let Some((command, args)) = args.split_first() else {
+            return Err((ACK_UNKNOWN, "No command given".into()));
+        };
+        let now_playing = self.remote.now_playing.borrow().clone();
+        let playlist = self.playlist(&now_playing);
+        match command.as_str() {
+            "ping" | "lsinfo" | "listplaylists" | "decoders" => Ok(String::new()),
+            "status" => Ok(self.status(&now_playing, playlist.len())),
+            "stats" => Ok(format!(
+                "artists: 0\nalbums: 0\nsongs: {}\nuptime: 0\nplaytime: {}\n",
+                playlist.len(),
+                now_playing.position as u64
+            )),
+            "currentsong" => Ok(playlist
+                .first()
+                .map(|media| song(media, Some(0)))
+                .unwrap_or_default()),
+            "playlistinfo" | "playlistid" | "plchanges" => Ok(playlist
+                .iter()
+                .enumerate()
+                .map(|(pos, media)| song(media, Some(pos)))
+                .collect()),
+            "plchangesposid" => Ok(playlist
+                .iter()
+                .enumerate()
+                .map(|(pos, _)| format!("cpos: {pos}\nId: {pos}\n"))
+                .collect()),
+            "play" | "playid" => self.send(PlayerCommand::Play),
+            "pause" => match args.first().map(String::as_str) {
+                Some("1") => self.send(PlayerCommand::Pause),
+                Some("0") => self.send(PlayerCommand::Play),
+                _ => self.send(PlayerCommand::TogglePause),
+            },
+            // Stopping would close the player
+            "stop" => self.send(PlayerCommand::Pause),
+            "next" => self.send(PlayerCommand::Next),
+            "previous" => self.send(PlayerCommand::Previous),
+            "setvol" => self.send(PlayerCommand::SetVolume(number(args.first())?)),
+            "seekcur" => {
+                let time = args.first().ok_or((ACK_ARG, "Missing time".into()))?;
+                if time.starts_with(['+', '-']) {
+                    self.send(PlayerCommand::SeekRelative(number(Some(time))?))
+                } else {
+                    self.send(PlayerCommand::SeekAbsolute(number(Some(time))?))
+                }
+            }
+            // Only the current media can be seeked
+            "seek" | "seekid" => {
+                if number(args.first())? != 0.0 {
+                    return Err((ACK_NO_EXIST, "Only the current song can be seeked".into()));
+                }
+                self.send(PlayerCommand::SeekAbsolute(number(args.get(1))?))
+            }
+            "add" | "addid" => {
+                let uri = args.first().ok_or((ACK_ARG, "Missing uri".into()))?;
+                let media = self.resolve(uri).await?;
+                self.send(PlayerCommand::Enqueue(Box::new(media)))?;
+                Ok(if command == "addid" {
+                    format!("Id: {}\n", playlist.len())
+                } else {
+                    String::new()
+                })
+            }
+            "delete" | "deleteid" => {
+                let pos = number(args.first())? as usize;
+                if pos == 0 || pos >= playlist.len() {
+                    return Err((ACK_NO_EXIST, "Bad song index".into()));
+                }
+                self.send(PlayerCommand::Dequeue(pos - 1))
+            }
+            "clear" => self.send(PlayerCommand::ClearQueue),
+            "search" | "find" => {
+                let query = args.last().ok_or((ACK_ARG, "Missing query".into()))?;
+                let results = YoutubeRs::search(query, false)
+                    .await
+                    .map_err(|e| (ACK_SYSTEM, e.to_string()))?;
+                let mut found = self.found.lock().unwrap();
+                Ok(results
+                    .into_iter()
+                    .map(|media| {
+                        let entry = MediaEntry::from(&media);
+                        found.insert(entry.url.clone(), media);
+                        song(&entry, None)
+                    })
+                    .collect())
+            }
+            "outputs" => Ok("outputid: 0\noutputname: mpv\nplugin: mpv\noutputenabled: 1\n".into()),
+            "tagtypes" => Ok("tagtype: Artist\ntagtype: Title\n".into()),
+            "urlhandlers" => Ok("handler: https://\n".into()),
+            "commands" => Ok(SUPPORTED
+                .iter()
+                .map(|command| format!("command: {command}\n"))
+                .collect()),
+            "notcommands" => Ok(String::new()),
+            _ => Err((ACK_UNKNOWN, format!("unknown command \"{command}\""))),
+        }
+    }
+
+    fn send(&self, command: PlayerCommand) -> Result<String, Ack> {
+        self.remote
+            .commands
+            .send(command)
+            .map(|_| String::new())
+            .map_err(|_| (ACK_SYSTEM, "The player stopped".into()))
+    }
+
+    /// Look the uri up in the search results, or search for it
+    async fn resolve(&self, uri: &str) -> Result<YoutubeResponse, Ack> {
+        if let Some(media) = self.found.lock().unwrap().get(uri).cloned() {
+            return Ok(media);
+        }
+        YoutubeRs::search(uri, false)
+            .await
+            .map_err(|e| (ACK_SYSTEM, e.to_string()))?
+            .into_iter()
+            .next()
+            .ok_or((ACK_NO_EXIST, format!("Nothing found for '{uri}'")))
+    }
+
+    /// The current media followed by the queue, bumping the version when it changed
+    fn playlist(&mut self, now_playing: &NowPlaying) -> Vec<MediaEntry> {
+        let queue = self.remote.queue.borrow().clone();
+        let current = (!now_playing.title.is_empty()).then(|| now_playing.url.clone());
+        let state = (current.clone().flatten(), queue.clone());
+        if self.last.as_ref() != Some(&state) {
+            self.version += 1;
+            self.last = Some(state);
+        }
+        let mut playlist = Vec::new();
+        if current.is_some() {
+            playlist.push(MediaEntry {
+                id: String::new(),
+                title: now_playing.title.clone(),
+                artist: now_playing.artist.clone(),
+                url: now_playing.url.clone().unwrap_or_default(),
+                duration: now_playing.duration as u32,
+            });
+        }
+        playlist.extend(queue);
+        playlist
+    }
+
+    fn status(&self, now_playing: &NowPlaying, length: usize) -> String {
+        let mut status = format!(
+            "volume: {}\nrepeat: 0\nrandom: 0\nsingle: 0\nconsume: 0\nplaylist: {}\nplaylistlength: {length}\n",
+            now_playing.volume.min(100.0) as u32,
+            self.version
+        );
+        if length == 0 {
+            status += "state: stop\n";
+            return status;
+        }
+        let state = if now_playing.paused { "pause" } else { "play" };
+        let _ = write!(
+            status,
+            "state: {state}\nsong: 0\nsongid: 0\ntime: {}:{}\nelapsed: {:.3}\nduration: {:.3}\n",
+            now_playing.position as u64,
+            now_playing.duration as u64,
+            now_playing.position,
+            now_playing.duration
+        );
+        if length > 1 {
+            status += "nextsong: 1\nnextsongid: 1\n";
+        }
+        status
+    }
+}
+
+fn song(media: &MediaEntry, pos: Option<usize>) -> String {
+    let mut song = format!(
+        "file: {}\nTitle: {}\nArtist: {}\nTime: {}\nduration: {}\n",
+        media.url, media.title, media.artist, media.duration, media.duration
+    );
+    if let Some(pos) = pos {
+        let _ = write!(song, "Pos: {pos}\nId: {pos}\n");
+    }
+    song
+}
+
+fn ack(index: usize, command: &str, (code, message): &Ack) -> String {
+    format!("ACK [{code}@{index}] {{{command}}} {message}\n")
+}
+
+fn command_name(line: &str) -> &str {
+    line.split_whitespace().next().unwrap_or_default()
+}
+
+fn number(arg: Option<&String>) -> Result<f64, Ack> {
+    arg.and_then(|arg| arg.parse().ok())
+        .ok_or((ACK_ARG, "Expected a number".into()))
+}
+
+/// Split a command line, arguments can be quoted with backslash escapes
+fn parse_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut arg = String::new();
+        if c == '"' {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => arg.extend(chars.next()),
+                    '"' => break,
+                    c => arg.push(c),
+                }
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                arg.push(c);
+                chars.next();
+            }
+        }
+        args.push(arg);
+    }
+    args
+}
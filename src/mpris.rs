@@ -0,0 +1,329 @@
+use crate::app::YoutubeRs;
+use crate::log::LogError;
+use crate::remote::{NowPlaying, PlayerCommand, PlayerRemote};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::{ObjectPath, OwnedValue, Value};
+use zbus::{fdo, interface};
+
+const PATH: &str = "/org/mpris/MediaPlayer2";
+// MPRIS times are in microseconds
+const MICROS: f64 = 1_000_000.0;
+
+/// Register as an MPRIS player on the session bus so the media keys, the desktop
+/// media widgets, `playerctl` and KDE Connect (phone lock screen) drive the player
+pub async fn spawn(remote: PlayerRemote) -> Result<()> {
+    let connection = zbus::connection::Builder::session()?
+        .name(format!(
+            "org.mpris.MediaPlayer2.ytrs.instance{}",
+            std::process::id()
+        ))?
+        .serve_at(PATH, Root)?
+        .serve_at(
+            PATH,
+            Player {
+                remote: remote.clone(),
+                art: Mutex::default(),
+            },
+        )?
+        .build()
+        .await?;
+    let player = connection
+        .object_server()
+        .interface::<_, Player>(PATH)
+        .await?;
+    let mut now_playing = remote.now_playing;
+    tokio::spawn(async move {
+        let mut last = NowPlaying::default();
+        let mut last_update = Instant::now();
+        while now_playing.changed().await.is_ok() {
+            let current = now_playing.borrow_and_update().clone();
+            let elapsed = last_update.elapsed().as_secs_f64();
+            last_update = Instant::now();
+            let emitter = player.signal_emitter();
+            if current.title != last.title || current.url != last.url {
+                player
+                    .get()
+                    .await
+                    .metadata_changed(emitter)
+                    .await
+                    .log_error("Could not signal the MPRIS metadata");
+                // KDE Connect only sends local images to the phone
+                if let Some(thumbnail) = &current.thumbnail {
+                    let file_name = format!("ytrs-mpris-{}.jpg", track_id(&current));
+                    if let Ok(path) =
+                        crate::notification::save_thumbnail(thumbnail, &file_name).await
+                    {
+                        let player = player.get().await;
+                        let previous = player
+                            .art
+                            .lock()
+                            .unwrap()
+                            .replace((thumbnail.clone(), path));
+                        if let Some((_, previous)) = previous {
+                            let _ = std::fs::remove_file(previous);
+                        }
+                        player
+                            .metadata_changed(emitter)
+                            .await
+                            .log_error("Could not signal the MPRIS metadata");
+                    }
+                }
+            }
+            let player = player.get().await;
+            if current.paused != last.paused {
+                player
+                    .playback_status_changed(emitter)
+                    .await
+                    .log_error("Could not signal the MPRIS playback status");
+            }
+            if current.volume != last.volume {
+                player
+                    .volume_changed(emitter)
+                    .await
+                    .log_error("Could not signal the MPRIS volume");
+            }
+            // Only jumps are signaled, the clients follow the position on their own
+            let expected = last.position + if last.paused { 0.0 } else { elapsed };
+            if (current.position - expected).abs() >= 2.0 {
+                Player::seeked(emitter, (current.position * MICROS) as i64)
+                    .await
+                    .log_error("Could not signal the MPRIS seek");
+            }
+            last = current;
+        }
+        if let Some((_, path)) = player.get().await.art.lock().unwrap().take() {
+            let _ = std::fs::remove_file(path);
+        }
+        // Keeps the bus name as long as the player runs
+        drop(connection);
+    });
+    Ok(())
+}
+
+struct Root;
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn raise(&self) {}
+
+    fn quit(&self) {}
+
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> &str {
+        "ytrs"
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        vec!["https".to_owned()]
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+struct Player {
+    remote: PlayerRemote,
+    // Thumbnail url and where it was saved on disk
+    art: Mutex<Option<(String, PathBuf)>>,
+}
+
+impl Player {
+    fn send(&self, command: PlayerCommand) -> fdo::Result<()> {
+        self.remote
+            .commands
+            .send(command)
+            .map_err(|_| fdo::Error::Failed("The player stopped".to_owned()))
+    }
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Player {
+    fn play(&self) -> fdo::Result<()> {
+        self.send(PlayerCommand::Play)
+    }
+
+    fn pause(&self) -> fdo::Result<()> {
+        self.send(PlayerCommand::Pause)
+    }
+
+    fn play_pause(&self) -> fdo::Result<()> {
+        self.send(PlayerCommand::TogglePause)
+    }
+
+    fn next(&self) -> fdo::Result<()> {
+        self.send(PlayerCommand::Next)
+    }
+
+    fn previous(&self) -> fdo::Result<()> {
+        self.send(PlayerCommand::Previous)
+    }
+
+    fn stop(&self) -> fdo::Result<()> {
+        self.send(PlayerCommand::Stop)
+    }
+
+    fn seek(&self, offset: i64) -> fdo::Result<()> {
+        self.send(PlayerCommand::SeekRelative(offset as f64 / MICROS))
+    }
+
+    fn set_position(&self, _track_id: ObjectPath<'_>, position: i64) -> fdo::Result<()> {
+        self.send(PlayerCommand::SeekAbsolute(position as f64 / MICROS))
+    }
+
+    /// Queue a YouTube url
+    async fn open_uri(&self, uri: &str) -> fdo::Result<()> {
+        let media = YoutubeRs::resolve_video(uri)
+            .await
+            .map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        self.send(PlayerCommand::Enqueue(Box::new(media)))
+    }
+
+    #[zbus(signal)]
+    async fn seeked(emitter: &SignalEmitter<'_>, position: i64) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn playback_status(&self) -> &str {
+        let now_playing = self.remote.now_playing.borrow();
+        if now_playing.url.is_none() {
+            "Stopped"
+        } else if now_playing.paused {
+            "Paused"
+        } else {
+            "Playing"
+        }
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        let now_playing = self.remote.now_playing.borrow();
+        let id = track_id(&now_playing);
+        let mut metadata = vec![
+            (
+                "mpris:trackid",
+                Value::from(
+                    ObjectPath::try_from(format!("/org/ytrs/track/{id}"))
+                        .unwrap_or_else(|_| ObjectPath::from_static_str_unchecked(PATH)),
+                ),
+            ),
+            (
+                "mpris:length",
+                Value::from((now_playing.duration * MICROS) as i64),
+            ),
+            ("xesam:title", Value::from(now_playing.title.clone())),
+            (
+                "xesam:artist",
+                Value::from(vec![now_playing.artist.clone()]),
+            ),
+        ];
+        if let Some(url) = &now_playing.url {
+            metadata.push(("xesam:url", Value::from(url.clone())));
+        }
+        if let Some(thumbnail) = &now_playing.thumbnail {
+            let art = match &*self.art.lock().unwrap() {
+                Some((url, path)) if url == thumbnail => {
+                    format!("file://{}", path.to_string_lossy())
+                }
+                _ => thumbnail.clone(),
+            };
+            metadata.push(("mpris:artUrl", Value::from(art)));
+        }
+        metadata
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.to_owned(), value.try_into_owned().ok()?)))
+            .collect()
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        self.remote.now_playing.borrow().volume / 100.0
+    }
+
+    #[zbus(property)]
+    fn set_volume(&self, volume: f64) -> zbus::Result<()> {
+        self.send(PlayerCommand::SetVolume(volume.max(0.0) * 100.0))
+            .map_err(zbus::Error::from)
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> i64 {
+        (self.remote.now_playing.borrow().position * MICROS) as i64
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        true
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
+
+/// Video id usable in an object path, which only allows [A-Za-z0-9_]
+fn track_id(now_playing: &NowPlaying) -> String {
+    now_playing
+        .url
+        .as_deref()
+        .and_then(|url| url.split("v=").nth(1))
+        .unwrap_or_default()
+        .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+}
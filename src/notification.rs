@@ -0,0 +1,62 @@
+use crate::log::LogError;
+use crate::remote::{NowPlaying, PlayerRemote};
+use anyhow::Result;
+use notify_rust::Notification;
+use std::path::PathBuf;
+
+/// Show a desktop notification every time the player moves on to another track
+pub fn spawn(remote: PlayerRemote) {
+    let mut now_playing = remote.now_playing;
+    tokio::spawn(async move {
+        let mut last = None;
+        while now_playing.changed().await.is_ok() {
+            let current = now_playing.borrow_and_update().clone();
+            let track = (current.title.clone(), current.url.clone());
+            if current.title.is_empty() || last.as_ref() == Some(&track) {
+                continue;
+            }
+            last = Some(track);
+            notify(current)
+                .await
+                .log_error("Could not show the notification");
+        }
+    });
+}
+
+async fn notify(now_playing: NowPlaying) -> Result<()> {
+    let thumbnail = match &now_playing.thumbnail {
+        Some(url) => save_thumbnail(url, "ytrs-notification.jpg")
+            .await
+            .ok()
+            .map(|path| path.to_string_lossy().to_string()),
+        None => None,
+    };
+    tokio::task::spawn_blocking(move || {
+        let mut notification = Notification::new();
+        notification
+            .appname("ytrs")
+            .summary(&now_playing.title)
+            .body(&now_playing.artist);
+        if let Some(thumbnail) = thumbnail {
+            notification.image_path(&thumbnail);
+        }
+        notification.show().map(|_| ())
+    })
+    .await??;
+    Ok(())
+}
+
+/// The notification servers and KDE Connect only take images from disk,
+/// `file_name` is created in the temp dir
+pub(crate) async fn save_thumbnail(url: &str, file_name: &str) -> Result<PathBuf> {
+    let bytes = reqwest::Client::new()
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let path = std::env::temp_dir().join(file_name);
+    tokio::fs::write(&path, bytes).await?;
+    Ok(path)
+}
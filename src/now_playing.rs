@@ -0,0 +1,113 @@
+use crate::remote::{NowPlaying, PlayerCommand, PlayerRemote};
+use anyhow::Result;
+use block2::RcBlock;
+use objc2::runtime::AnyObject;
+use objc2_core_foundation::{CFRunLoop, kCFRunLoopDefaultMode};
+use objc2_foundation::{NSDictionary, NSNumber, NSString};
+use objc2_media_player::{
+    MPChangePlaybackPositionCommandEvent, MPMediaItemPropertyArtist,
+    MPMediaItemPropertyPlaybackDuration, MPMediaItemPropertyTitle, MPNowPlayingInfoCenter,
+    MPNowPlayingInfoPropertyElapsedPlaybackTime, MPNowPlayingInfoPropertyPlaybackRate,
+    MPNowPlayingPlaybackState, MPRemoteCommand, MPRemoteCommandCenter, MPRemoteCommandEvent,
+    MPRemoteCommandHandlerStatus,
+};
+use std::ptr::NonNull;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Publish the player to the macOS Now Playing widget and let the media keys
+/// and Control Center drive it
+pub fn spawn(remote: PlayerRemote) -> Result<()> {
+    let center = unsafe { MPRemoteCommandCenter::sharedCommandCenter() };
+    unsafe {
+        on(&center.playCommand(), &remote.commands, |_| {
+            Some(PlayerCommand::Play)
+        });
+        on(&center.pauseCommand(), &remote.commands, |_| {
+            Some(PlayerCommand::Pause)
+        });
+        on(&center.togglePlayPauseCommand(), &remote.commands, |_| {
+            Some(PlayerCommand::TogglePause)
+        });
+        on(&center.nextTrackCommand(), &remote.commands, |_| {
+            Some(PlayerCommand::Next)
+        });
+        on(&center.previousTrackCommand(), &remote.commands, |_| {
+            Some(PlayerCommand::Previous)
+        });
+        on(&center.stopCommand(), &remote.commands, |_| {
+            Some(PlayerCommand::Stop)
+        });
+        on(
+            &center.changePlaybackPositionCommand(),
+            &remote.commands,
+            |event| {
+                let event = event.cast::<MPChangePlaybackPositionCommandEvent>();
+                Some(PlayerCommand::SeekAbsolute(event.as_ref().positionTime()))
+            },
+        );
+    }
+
+    let runtime = tokio::runtime::Handle::current();
+    let mut now_playing = remote.now_playing;
+    std::thread::spawn(move || {
+        while runtime.block_on(now_playing.changed()).is_ok() {
+            let current = now_playing.borrow_and_update().clone();
+            update(&current);
+        }
+    });
+    Ok(())
+}
+
+/// The remote commands are delivered on the main thread, which has no run loop of its own
+/// while the TUI runs, so the player loop hands them over here
+pub fn poll() {
+    unsafe {
+        CFRunLoop::run_in_mode(kCFRunLoopDefaultMode, 0.0, true);
+    }
+}
+
+fn on(
+    command: &MPRemoteCommand,
+    commands: &UnboundedSender<PlayerCommand>,
+    to_command: fn(NonNull<MPRemoteCommandEvent>) -> Option<PlayerCommand>,
+) {
+    let commands = commands.clone();
+    let handler = RcBlock::new(move |event: NonNull<MPRemoteCommandEvent>| {
+        match to_command(event).map(|command| commands.send(command)) {
+            Some(Ok(())) => MPRemoteCommandHandlerStatus::Success,
+            _ => MPRemoteCommandHandlerStatus::CommandFailed,
+        }
+    });
+    unsafe {
+        command.setEnabled(true);
+        command.addTargetWithHandler(&handler);
+    }
+}
+
+fn update(now_playing: &NowPlaying) {
+    let title = NSString::from_str(&now_playing.title);
+    let artist = NSString::from_str(&now_playing.artist);
+    let duration = NSNumber::new_f64(now_playing.duration);
+    let position = NSNumber::new_f64(now_playing.position);
+    let rate = NSNumber::new_f64(if now_playing.paused { 0.0 } else { 1.0 });
+    let objects: [&AnyObject; 5] = [&title, &artist, &duration, &position, &rate];
+    unsafe {
+        let info = NSDictionary::<NSString, AnyObject>::from_slices(
+            &[
+                MPMediaItemPropertyTitle,
+                MPMediaItemPropertyArtist,
+                MPMediaItemPropertyPlaybackDuration,
+                MPNowPlayingInfoPropertyElapsedPlaybackTime,
+                MPNowPlayingInfoPropertyPlaybackRate,
+            ],
+            &objects,
+        );
+        let center = MPNowPlayingInfoCenter::defaultCenter();
+        center.setNowPlayingInfo(Some(&info));
+        center.setPlaybackState(if now_playing.paused {
+            MPNowPlayingPlaybackState::Paused
+        } else {
+            MPNowPlayingPlaybackState::Playing
+        });
+    }
+}
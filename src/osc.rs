@@ -0,0 +1,126 @@
+use crate::log::LogError;
+use crate::remote::{NowPlaying, PlayerCommand, PlayerRemote};
+use anyhow::Result;
+use rosc::{OscBundle, OscMessage, OscPacket, OscTime, OscType};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::UdpSocket;
+
+/// Accept OSC messages on `addr`, every sender gets the player state back when it changes
+pub async fn spawn(remote: PlayerRemote, addr: SocketAddr) -> Result<()> {
+    let socket = Arc::new(UdpSocket::bind(addr).await?);
+    // Control surfaces that talked to the player, they follow its state
+    let clients: Arc<Mutex<Vec<SocketAddr>>> = Arc::default();
+
+    let mut now_playing = remote.now_playing.clone();
+    let feedback_socket = socket.clone();
+    let feedback_clients = clients.clone();
+    tokio::spawn(async move {
+        while now_playing.changed().await.is_ok() {
+            let state = state(&now_playing.borrow_and_update());
+            let clients = feedback_clients.lock().unwrap().clone();
+            for client in clients {
+                feedback_socket
+                    .send_to(&state, client)
+                    .await
+                    .log_error("Could not send the OSC feedback");
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut buf = [0; rosc::decoder::MTU];
+        while let Ok((len, sender)) = socket.recv_from(&mut buf).await {
+            let Ok((_, packet)) = rosc::decoder::decode_udp(&buf[..len]) else {
+                continue;
+            };
+            {
+                let mut clients = clients.lock().unwrap();
+                if !clients.contains(&sender) {
+                    clients.push(sender);
+                }
+            }
+            for message in messages(packet) {
+                if message.addr == "/ytrs/now-playing" {
+                    let state = state(&remote.now_playing.borrow());
+                    socket
+                        .send_to(&state, sender)
+                        .await
+                        .log_error("Could not answer the OSC client");
+                } else if let Some(command) = command(message)
+                    && remote.commands.send(command).is_err()
+                {
+                    return;
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+fn messages(packet: OscPacket) -> Vec<OscMessage> {
+    match packet {
+        OscPacket::Message(message) => vec![message],
+        OscPacket::Bundle(bundle) => bundle.content.into_iter().flat_map(messages).collect(),
+    }
+}
+
+fn command(message: OscMessage) -> Option<PlayerCommand> {
+    let value = message.args.into_iter().next().and_then(number);
+    // Buttons send 1 when pressed and 0 when released
+    let pressed = value.is_none_or(|value| value != 0.0);
+    Some(match message.addr.as_str() {
+        "/ytrs/play" if pressed => PlayerCommand::Play,
+        "/ytrs/pause" if pressed => PlayerCommand::Pause,
+        "/ytrs/toggle" if pressed => PlayerCommand::TogglePause,
+        "/ytrs/next" if pressed => PlayerCommand::Next,
+        "/ytrs/previous" if pressed => PlayerCommand::Previous,
+        "/ytrs/stop" if pressed => PlayerCommand::Stop,
+        "/ytrs/volume" => PlayerCommand::SetVolume(value?),
+        "/ytrs/seek" => PlayerCommand::SeekAbsolute(value?),
+        "/ytrs/seek/relative" => PlayerCommand::SeekRelative(value?),
+        _ => return None,
+    })
+}
+
+fn number(arg: OscType) -> Option<f64> {
+    match arg {
+        OscType::Float(value) => Some(value as f64),
+        OscType::Double(value) => Some(value),
+        OscType::Int(value) => Some(value as f64),
+        OscType::Long(value) => Some(value as f64),
+        OscType::Bool(value) => Some(if value { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+/// Bundle of the player state, one message per value so faders and labels can bind to them
+fn state(now_playing: &NowPlaying) -> Vec<u8> {
+    let message = |addr: &str, arg: OscType| {
+        OscPacket::Message(OscMessage {
+            addr: addr.to_owned(),
+            args: vec![arg],
+        })
+    };
+    let bundle = OscPacket::Bundle(OscBundle {
+        timetag: OscTime::from((0, 1)),
+        content: vec![
+            message("/ytrs/title", OscType::String(now_playing.title.clone())),
+            message("/ytrs/artist", OscType::String(now_playing.artist.clone())),
+            message(
+                "/ytrs/position",
+                OscType::Float(now_playing.position as f32),
+            ),
+            message(
+                "/ytrs/duration",
+                OscType::Float(now_playing.duration as f32),
+            ),
+            message("/ytrs/volume", OscType::Float(now_playing.volume as f32)),
+            message(
+                "/ytrs/paused",
+                OscType::Int(if now_playing.paused { 1 } else { 0 }),
+            ),
+        ],
+    });
+    rosc::encoder::encode(&bundle).unwrap_or_default()
+}
@@ -0,0 +1,57 @@
+use crate::log::LogError;
+use crate::remote::{NowPlaying, PlayerRemote};
+use crate::utility::format_time;
+use anyhow::Result;
+use std::path::PathBuf;
+
+pub const DEFAULT_TEMPLATE: &str = "{artist} - {title}";
+
+/// Keep `path` up to date with what the player is playing, as json when the file
+/// ends with `.json` and through `template` otherwise
+pub fn spawn(remote: PlayerRemote, path: PathBuf, template: String) {
+    let mut now_playing = remote.now_playing;
+    tokio::spawn(async move {
+        while now_playing.changed().await.is_ok() {
+            let current = now_playing.borrow_and_update().clone();
+            write(&path, &template, &current)
+                .await
+                .log_error("Could not write the now playing file");
+        }
+    });
+}
+
+async fn write(path: &PathBuf, template: &str, now_playing: &NowPlaying) -> Result<()> {
+    let content = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::to_string_pretty(now_playing)?
+    } else {
+        render(template, now_playing)
+    };
+    // Overlays read the file at any time, never let them see it half written
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, content).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
+
+fn render(template: &str, now_playing: &NowPlaying) -> String {
+    template
+        .replace("{title}", &now_playing.title)
+        .replace("{artist}", &now_playing.artist)
+        .replace("{url}", now_playing.url.as_deref().unwrap_or_default())
+        .replace(
+            "{position}",
+            &format_time(now_playing.position as u32).to_string(),
+        )
+        .replace(
+            "{duration}",
+            &format_time(now_playing.duration as u32).to_string(),
+        )
+        .replace(
+            "{status}",
+            if now_playing.paused {
+                "Paused"
+            } else {
+                "Playing"
+            },
+        )
+}
@@ -0,0 +1,215 @@
+use crate::app::YoutubeResponse;
+use crate::mpv::MpvIpc;
+use crate::utility::{data_dir, format_time};
+use anyhow::{Context, Result};
+use rustypipe::model::{MusicItem, TrackType};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+// Episodes are played once the outro starts
+const PLAYED_MARGIN: f64 = 30.0;
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A podcast opened from the search, listed again under the podcasts of the explore tab
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Show {
+    pub id: String,
+    pub name: String,
+}
+
+/// Where the listening of an episode stopped
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Episode {
+    pub position: f64,
+    pub played: bool,
+}
+
+/// Podcasts and episode progress, kept in `~/.local/share/ytrs/podcasts.json`
+#[derive(Serialize, Deserialize, Default)]
+pub struct Podcasts {
+    pub shows: Vec<Show>,
+    pub episodes: HashMap<String, Episode>,
+}
+
+impl Podcasts {
+    pub fn path() -> PathBuf {
+        data_dir().join("podcasts.json")
+    }
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse '{}'", path.to_string_lossy()))
+    }
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Keep the show listed, the last opened first
+    pub fn follow(&mut self, show: Show) {
+        self.shows.retain(|s| s.id != show.id);
+        self.shows.insert(0, show);
+    }
+
+    /// Where to resume `id` from, unless it was played
+    pub fn position(&self, id: &str) -> Option<f64> {
+        self.episodes
+            .get(id)
+            .filter(|episode| !episode.played && episode.position > 0.0)
+            .map(|episode| episode.position)
+    }
+
+    /// Mark `id` played or not, returns the new state
+    pub fn toggle_played(&mut self, id: &str) -> bool {
+        let episode = self.episodes.entry(id.to_owned()).or_default();
+        episode.played = !episode.played;
+        episode.position = 0.0;
+        episode.played
+    }
+
+    /// Label of an episode in the lists, e.g. '✓ ' once played or '[12:34] ' when started
+    pub fn marker(&self, id: &str) -> String {
+        match self.episodes.get(id) {
+            Some(episode) if episode.played => "✓ ".to_owned(),
+            Some(episode) if episode.position > 0.0 => {
+                format!("[{}] ", format_time(episode.position as u32))
+            }
+            _ => String::new(),
+        }
+    }
+}
+
+pub fn is_episode(media: &YoutubeResponse) -> bool {
+    matches!(media, YoutubeResponse::Track(track) if track.track_type == TrackType::Episode)
+}
+
+/// Resumes the episodes the player loads and records how far they were listened to
+pub struct EpisodeProgress {
+    podcasts: Podcasts,
+    loaded_rx: mpsc::Receiver<serde_json::Value>,
+    // Episode loaded, and where it is being seeked to
+    loaded: Option<String>,
+    resuming: Option<f64>,
+    changed: bool,
+    saved: Instant,
+}
+
+impl EpisodeProgress {
+    pub async fn new(mpv: &mut MpvIpc) -> Self {
+        Self {
+            podcasts: Podcasts::load().unwrap_or_default(),
+            loaded_rx: mpv.subscribe("file-loaded").await,
+            loaded: None,
+            resuming: None,
+            changed: false,
+            saved: Instant::now(),
+        }
+    }
+
+    /// Seek the episode just loaded to where it stopped, then follow its position
+    pub async fn update(
+        &mut self,
+        mpv: &mut MpvIpc,
+        media: Option<&YoutubeResponse>,
+        playback_time: f64,
+        duration: f64,
+    ) {
+        let episode = media
+            .filter(|media| is_episode(media))
+            .map(YoutubeResponse::get_id);
+        while self.loaded_rx.try_recv().is_ok() {
+            self.loaded = episode.clone();
+            self.resuming = None;
+            if let Some(id) = &episode
+                && let Some(position) = self.podcasts.position(id)
+            {
+                let _ = mpv
+                    .send_command(json!(["seek", position, "absolute"]))
+                    .await;
+                self.resuming = Some(position);
+            }
+        }
+        // The time of the previous media or the start of this one until the seek is done
+        if let Some(position) = self.resuming {
+            if (playback_time - position).abs() > 5.0 {
+                return;
+            }
+            self.resuming = None;
+        }
+        let Some(id) = episode.filter(|id| self.loaded.as_ref() == Some(id)) else {
+            return;
+        };
+        if playback_time <= 0.0 {
+            return;
+        }
+        let entry = self.podcasts.episodes.entry(id).or_default();
+        if duration > 0.0 && playback_time >= duration - PLAYED_MARGIN {
+            if !entry.played {
+                entry.played = true;
+                entry.position = 0.0;
+                self.changed = true;
+            }
+        } else if !entry.played && entry.position != playback_time {
+            entry.position = playback_time;
+            self.changed = true;
+        }
+        if self.changed && self.saved.elapsed() >= SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+    /// Mark the episode `media` played or not, returns the new state
+    pub fn toggle_played(&mut self, media: &YoutubeResponse) -> Option<bool> {
+        if !is_episode(media) {
+            return None;
+        }
+        let played = self.podcasts.toggle_played(&media.get_id());
+        self.save();
+        Some(played)
+    }
+
+    /// Write the progress, keeping the shows the explore tab followed meanwhile
+    pub fn save(&mut self) {
+        let mut podcasts = Podcasts::load().unwrap_or_default();
+        podcasts.episodes = self.podcasts.episodes.clone();
+        if podcasts.save().is_ok() {
+            self.changed = false;
+        }
+        self.saved = Instant::now();
+    }
+}
+
+/// Podcasts of YouTube Music matching `query`
+pub async fn search(query: &str) -> Result<Vec<Show>> {
+    // No search filter keeps only the podcasts, they are among the playlists of the main results
+    let found = crate::auth::rustypipe()
+        .query()
+        .unauthenticated()
+        .music_search_main(format!("{query} podcast"))
+        .await;
+    Ok(found
+        .context("Failed to search YouTube Music")?
+        .items
+        .items
+        .into_iter()
+        .filter_map(|item| match item {
+            MusicItem::Playlist(playlist) if playlist.is_podcast => Some(Show {
+                id: playlist.id,
+                name: playlist.name,
+            }),
+            _ => None,
+        })
+        .collect())
+}
@@ -0,0 +1,103 @@
+use crate::app::{YoutubeRs, YtrsError};
+use crate::utility::format_time;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use inquire::Confirm;
+use rustypipe::model::VideoItem;
+use std::io::Write;
+use std::time::Duration;
+use tokio::process::Command;
+
+// Premieres often go live a bit after their announced time
+const LIVE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+impl YoutubeRs {
+    /// When the scheduled premiere or stream `video` starts, from the search result or else yt-dlp
+    pub(crate) async fn premiere_start(&self, video: &VideoItem) -> Result<Option<DateTime<Utc>>> {
+        if let Some(date) = video.publish_date {
+            return Ok(DateTime::from_timestamp(date.unix_timestamp(), 0));
+        }
+        let output = self
+            .ytdlp_print(&Self::get_video_url(&video.id), "release_timestamp")
+            .await?;
+        Ok(output
+            .parse::<i64>()
+            .ok()
+            .and_then(|ts| DateTime::from_timestamp(ts, 0)))
+    }
+
+    /// Show a countdown until the premiere starts, returns false if the user does not want to `verb` it
+    pub(crate) async fn wait_premiere(
+        &self,
+        name: &str,
+        start: Option<DateTime<Utc>>,
+        verb: &str,
+    ) -> Result<bool> {
+        let when = match start {
+            Some(start) => start
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            None => "an unknown time".to_string(),
+        };
+        let wait = Confirm::new(&format!(
+            "'{name}' is scheduled for {when}, wait and {verb} it automatically?"
+        ))
+        .with_default(true)
+        .prompt()?;
+        if !wait {
+            return Ok(false);
+        }
+        let mut stdout = std::io::stdout();
+        while let Some(start) = start {
+            let remaining = (start - Utc::now()).num_seconds();
+            if remaining <= 0 {
+                break;
+            }
+            print!("\rStarting in {} ", format_time(remaining as u32));
+            stdout.flush()?;
+            Self::sleep_or_quit(Duration::from_secs(1)).await?;
+        }
+        println!();
+        Ok(true)
+    }
+
+    /// Wait until yt-dlp sees the premiere `url` live, as mpv can not load it before
+    pub(crate) async fn wait_live(&self, url: &str) -> Result<()> {
+        println!("Waiting for the premiere to go live, press Ctrl+C to stop");
+        while self.ytdlp_print(url, "live_status").await? == "is_upcoming" {
+            Self::sleep_or_quit(LIVE_CHECK_INTERVAL).await?;
+        }
+        Ok(())
+    }
+
+    async fn ytdlp_print(&self, url: &str, field: &str) -> Result<String> {
+        let output = Command::new(Self::ytdlp_bin(&self.args))
+            .args(crate::auth::ytdlp_args(&self.args))
+            .args(["--ignore-no-formats-error", "--print", field, url])
+            .output()
+            .await
+            .context("Could not start yt-dlp")?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    }
+
+    async fn sleep_or_quit(duration: Duration) -> Result<()> {
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => Ok(()),
+            _ = tokio::signal::ctrl_c() => {
+                println!();
+                Err(YtrsError::Quit.into())
+            }
+        }
+    }
+}
+
+/// Countdown shown by the player until the premiere `name` starts
+pub fn countdown(name: &str, start: DateTime<Utc>) -> String {
+    let remaining = (start - Utc::now()).num_seconds().max(0);
+    format!(
+        "Premiere of '{name}' at {}, starts in {}",
+        start.with_timezone(&chrono::Local).format("%H:%M"),
+        format_time(remaining as u32)
+    )
+}
@@ -0,0 +1,76 @@
+//! Progress of the downloads and transcripts, as text or as json lines with `--json`
+use crate::cli::Cli;
+use serde::Serialize;
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::OnceLock;
+
+static JSON: OnceLock<bool> = OnceLock::new();
+
+/// Remember whether `--json` is given
+pub fn configure(args: &Cli) {
+    let _ = JSON.set(args.json);
+}
+
+fn json() -> bool {
+    JSON.get().copied().unwrap_or_default()
+}
+
+/// One json line, told apart by its `event` field
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    /// An entry of a batch is started, `index` counts from 1
+    Start {
+        index: usize,
+        total: usize,
+        url: &'a str,
+    },
+    /// What is being done for the current entry
+    Step {
+        step: &'a str,
+    },
+    Downloaded {
+        id: &'a str,
+        title: &'a str,
+        path: &'a Path,
+    },
+    /// Already in the download archive
+    Skipped {
+        id: &'a str,
+        title: &'a str,
+    },
+    Failed {
+        entry: &'a str,
+        error: String,
+    },
+    /// The end of a batch
+    Done {
+        downloaded: usize,
+        skipped: usize,
+        failed: usize,
+    },
+    Transcript {
+        lang: &'a str,
+        path: &'a Path,
+    },
+    Summary {
+        path: &'a Path,
+    },
+}
+
+/// Print `event` with `--json`
+pub fn emit(event: Event<'_>) {
+    if json()
+        && let Ok(line) = serde_json::to_string(&event)
+    {
+        println!("{line}");
+    }
+}
+
+/// Print `message`, unless `--json` keeps stdout for the events
+pub fn say(message: impl Display) {
+    if !json() {
+        println!("{message}");
+    }
+}
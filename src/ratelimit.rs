@@ -0,0 +1,81 @@
+use crate::cli::Cli;
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// Messages of RustyPipe and yt-dlp when YouTube refuses too many requests
+const RATE_LIMITED: [&str; 3] = ["status code: 429", "HTTP Error 429", "Too Many Requests"];
+// Tries of a request YouTube keeps refusing
+const MAX_ATTEMPTS: u32 = 4;
+// Wait before the first retry, doubled on each one after
+const BASE_DELAY: Duration = Duration::from_secs(2);
+const MINUTE: Duration = Duration::from_secs(60);
+
+// Requests allowed per minute, 0 for no limit
+static BUDGET: OnceLock<usize> = OnceLock::new();
+// When the requests of the last minute were sent, oldest first
+static RECENT: Mutex<VecDeque<Instant>> = Mutex::new(VecDeque::new());
+
+/// Remember the budget given with `--requests-per-minute`
+pub fn configure(args: &Cli) {
+    let _ = BUDGET.set(args.requests_per_minute);
+}
+
+/// Wait until another request fits in the budget of the last minute
+async fn acquire() {
+    let budget = BUDGET.get().copied().unwrap_or_default();
+    if budget == 0 {
+        return;
+    }
+    loop {
+        let wait = {
+            let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            while recent
+                .front()
+                .is_some_and(|sent| now.duration_since(*sent) >= MINUTE)
+            {
+                recent.pop_front();
+            }
+            if recent.len() < budget {
+                recent.push_back(now);
+                return;
+            }
+            MINUTE.saturating_sub(now.duration_since(recent[0]))
+        };
+        tokio::time::sleep(wait).await;
+    }
+}
+
+fn is_rate_limited(error: &str) -> bool {
+    RATE_LIMITED.iter().any(|message| error.contains(message))
+}
+
+// Exponential delay with up to a second of jitter, so retries of parallel requests do not line up
+fn backoff(attempt: u32) -> Duration {
+    let jitter = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.subsec_millis())
+        .unwrap_or_default();
+    BASE_DELAY * 2u32.pow(attempt) + Duration::from_millis(jitter as u64)
+}
+
+/// Send `request` within the budget, waiting longer each time YouTube refuses it for too many requests
+pub async fn throttled<T, F, Fut>(mut request: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        acquire().await;
+        match request().await {
+            Err(e) if attempt + 1 < MAX_ATTEMPTS && is_rate_limited(&format!("{e:#}")) => {
+                tokio::time::sleep(backoff(attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::app::{Format, YoutubeRs};
+use crate::cli::Cli;
+use anyhow::{Context, Result, bail};
+use std::path::PathBuf;
+use tokio::process::Command;
+
+impl YoutubeRs {
+    /// Record the selected livestream with yt-dlp until the stream ends or Ctrl+C is pressed
+    pub async fn record(&mut self, format: Format, from_start: bool) -> Result<()> {
+        if !self.libraries_exist(&self.args.clone()) {
+            Self::install_lib(&self.args).await?;
+        }
+        let (video, search) = Self::query_ytvideo(self.last_search.clone()).await?;
+        self.last_search = Some(search);
+        let url = Self::get_video_url(&video.id);
+        if video.is_upcoming {
+            let start = self.premiere_start(&video).await?;
+            if !self.wait_premiere(&video.name, start, "record").await? {
+                return Ok(());
+            }
+        } else {
+            let info = Self::get_fetcher(&self.args)
+                .await?
+                .fetch_video_infos(url.clone())
+                .await?;
+            if info.live_status != "is_live" {
+                bail!("'{}' is not live ({})", info.title, info.live_status);
+            }
+        }
+        let (_, out) = Self::get_libs_path(&self.args);
+        let libs = Self::get_libs(&self.args);
+        let mut cmd = Command::new(Self::ytdlp_bin(&self.args));
+        cmd.arg(&url)
+            .args(crate::auth::ytdlp_args(&self.args))
+            .arg("--ffmpeg-location")
+            .arg(&libs.ffmpeg)
+            .arg("-P")
+            .arg(&out)
+            .args(["-o", "%(title)s %(epoch>%Y-%m-%d %H-%M)s.%(ext)s"])
+            // Keep the partial file playable if the recording is interrupted
+            .arg("--hls-use-mpegts");
+        if from_start {
+            cmd.arg("--live-from-start");
+        }
+        if video.is_upcoming {
+            // Premieres often go live a bit after their announced time
+            cmd.args(["--wait-for-video", "10-60"]);
+        }
+        match format {
+            Format::Audio { format } => {
+                cmd.args(["-f", "bestaudio", "-x", "--audio-format"])
+                    .arg(format.to_string().to_lowercase());
+            }
+            Format::Video { format } => {
+                cmd.arg("--remux-video")
+                    .arg(format.to_string().to_lowercase());
+            }
+        }
+        println!(
+            "Recording '{}' {}, press Ctrl+C to stop",
+            video.name,
+            if from_start {
+                "from the start"
+            } else {
+                "from now"
+            }
+        );
+        let mut child = cmd.spawn().context("Could not start yt-dlp")?;
+        let (status, interrupted) = tokio::select! {
+            status = child.wait() => (status?, false),
+            _ = tokio::signal::ctrl_c() => {
+                // yt-dlp receives the interrupt too and finalizes the file before exiting
+                println!("Stopping recording ...");
+                (child.wait().await?, true)
+            }
+        };
+        if !status.success() && !interrupted {
+            bail!("yt-dlp exited with {status}");
+        }
+        println!("Recording saved in '{}'", out.to_string_lossy());
+        Ok(())
+    }
+
+    pub(crate) fn ytdlp_bin(args: &Cli) -> PathBuf {
+        let youtube = Self::get_libs(args).youtube;
+        if cfg!(target_os = "windows") {
+            PathBuf::from(format!("{}.exe", youtube.to_string_lossy()))
+        } else {
+            youtube
+        }
+    }
+}
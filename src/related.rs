@@ -0,0 +1,54 @@
+use crate::app::YoutubeResponse;
+use crate::backend::{YoutubeBackend, backend};
+use anyhow::Result;
+use ratatui::widgets::ListState;
+
+/// State of the player pane listing the videos related to the current one
+#[derive(Default)]
+pub struct RelatedPane {
+    // Video the related items belong to
+    pub video_id: Option<String>,
+    pub items: Vec<YoutubeResponse>,
+    pub selected: ListState,
+    pub error: Option<String>,
+}
+
+impl RelatedPane {
+    /// Whether the items related to `video_id` are to be fetched, they are unless already listed
+    pub fn request(&mut self, video_id: &str) -> bool {
+        if self.video_id.as_deref() == Some(video_id) {
+            return false;
+        }
+        self.items.clear();
+        self.error = None;
+        self.video_id = Some(video_id.to_owned());
+        true
+    }
+
+    /// List the items fetched for `video_id`, unless another video was requested meanwhile
+    pub fn loaded(&mut self, video_id: &str, found: Result<Vec<YoutubeResponse>>) {
+        if self.video_id.as_deref() != Some(video_id) {
+            return;
+        }
+        match found {
+            Ok(items) => self.items = items,
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        self.selected.select_first();
+    }
+
+    pub fn selected_item(&self) -> Option<&YoutubeResponse> {
+        self.selected.selected().and_then(|i| self.items.get(i))
+    }
+}
+
+/// Videos YouTube recommends after `video_id`, most relevant first
+pub async fn related(video_id: &str) -> Result<Vec<YoutubeResponse>> {
+    Ok(backend()
+        .related(video_id)
+        .await?
+        .iter()
+        .filter(|v| !v.is_live && !v.is_upcoming && crate::filter::video_allowed(v))
+        .map(YoutubeResponse::from)
+        .collect())
+}
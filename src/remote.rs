@@ -0,0 +1,141 @@
+use crate::app::{YoutubeResponse, YoutubeRs};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, watch};
+
+/// Commands sent to the player from outside the TUI (media keys, remote controls...)
+#[derive(Debug, Clone)]
+pub enum PlayerCommand {
+    Play,
+    Pause,
+    TogglePause,
+    Next,
+    Previous,
+    Stop,
+    // Seconds from the current position
+    SeekRelative(f64),
+    SeekAbsolute(f64),
+    // From 0 to 130 like mpv
+    SetVolume(f64),
+    Enqueue(Box<YoutubeResponse>),
+    // Index in the upcoming part of the queue
+    Dequeue(usize),
+    ClearQueue,
+}
+
+/// What the player is currently playing
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: String,
+    pub url: Option<String>,
+    pub thumbnail: Option<String>,
+    pub duration: f64,
+    pub position: f64,
+    pub paused: bool,
+    pub volume: f64,
+}
+
+/// A video or track as shown to the remotes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaEntry {
+    pub id: String,
+    pub title: String,
+    pub artist: String,
+    pub url: String,
+    pub duration: u32,
+}
+
+impl From<&YoutubeResponse> for MediaEntry {
+    fn from(value: &YoutubeResponse) -> Self {
+        let id = value.get_id();
+        Self {
+            url: YoutubeRs::get_video_url(&id),
+            id,
+            title: value.get_name(),
+            artist: value.get_author(),
+            duration: value.get_duration(),
+        }
+    }
+}
+
+impl NowPlaying {
+    /// Whether the integrations need to be told about `other`,
+    /// the position only counts when it jumps
+    fn differs(&self, other: &Self) -> bool {
+        self.title != other.title
+            || self.artist != other.artist
+            || self.url != other.url
+            || self.paused != other.paused
+            || self.volume != other.volume
+            || self.duration != other.duration
+            || (self.position - other.position).abs() >= 1.0
+    }
+}
+
+/// Handle given to an integration to follow and drive the player
+#[derive(Clone)]
+pub struct PlayerRemote {
+    pub commands: mpsc::UnboundedSender<PlayerCommand>,
+    pub now_playing: watch::Receiver<NowPlaying>,
+    // What plays after the current media
+    pub queue: watch::Receiver<Vec<MediaEntry>>,
+}
+
+/// Player side of the remotes
+pub struct RemoteHub {
+    commands_tx: mpsc::UnboundedSender<PlayerCommand>,
+    commands_rx: mpsc::UnboundedReceiver<PlayerCommand>,
+    now_playing: watch::Sender<NowPlaying>,
+    queue: watch::Sender<Vec<MediaEntry>>,
+}
+
+impl Default for RemoteHub {
+    fn default() -> Self {
+        let (commands_tx, commands_rx) = mpsc::unbounded_channel();
+        Self {
+            commands_tx,
+            commands_rx,
+            now_playing: watch::Sender::new(NowPlaying::default()),
+            queue: watch::Sender::new(Vec::new()),
+        }
+    }
+}
+
+impl RemoteHub {
+    pub fn remote(&self) -> PlayerRemote {
+        PlayerRemote {
+            commands: self.commands_tx.clone(),
+            now_playing: self.now_playing.subscribe(),
+            queue: self.queue.subscribe(),
+        }
+    }
+
+    /// Share the player state, the remotes are only woken up by meaningful changes
+    pub fn publish(&self, now_playing: NowPlaying) {
+        self.now_playing.send_if_modified(|current| {
+            if current.differs(&now_playing) {
+                *current = now_playing;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    pub fn publish_queue(&self, queue: &[YoutubeResponse]) {
+        let queue: Vec<MediaEntry> = queue.iter().map(MediaEntry::from).collect();
+        self.queue.send_if_modified(|current| {
+            if *current != queue {
+                *current = queue;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// The next command of a remote, waiting for one
+    pub async fn recv(&mut self) -> Option<PlayerCommand> {
+        self.commands_rx.recv().await
+    }
+}
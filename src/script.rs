@@ -0,0 +1,153 @@
+use crate::app::{YoutubeResponse, YoutubeRs, YtrsError};
+use crate::backend::{YoutubeBackend, backend};
+use crate::cli::{AppActionCli, BlockCli, Cli, ConfigCli, MidiCli, SubscriptionsCli, WatchCli};
+use crate::config::config;
+use crate::link::YoutubeLink;
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::io::Read;
+use std::process::ExitCode;
+use thiserror::Error;
+
+/// Failures a script tells apart by the exit code
+#[derive(Error, Debug)]
+pub enum ScriptError {
+    #[error("No results for '{0}'")]
+    NoResults(String),
+    #[error("{0} asks in the terminal, it can not run with --non-interactive")]
+    NeedsTerminal(String),
+    #[error("{failed} of the {total} downloads failed")]
+    PartialFailure { failed: usize, total: usize },
+}
+
+/// 0 when it went well, 1 when nothing was found, 2 for the failures, 3 when only some
+/// downloads of a batch failed and 4 when a prompt was left
+pub fn exit_code(error: &anyhow::Error) -> ExitCode {
+    if matches!(error.downcast_ref(), Some(YtrsError::Quit)) {
+        return ExitCode::from(4);
+    }
+    match error.downcast_ref::<ScriptError>() {
+        Some(ScriptError::NoResults(_)) => ExitCode::from(1),
+        Some(ScriptError::PartialFailure { .. }) => ExitCode::from(3),
+        _ => ExitCode::from(2),
+    }
+}
+
+/// With `--non-interactive`, fail right away instead of opening a prompt or the player
+pub fn check(args: &Cli) -> Result<()> {
+    if !args.non_interactive {
+        return Ok(());
+    }
+    let download_configured = config().download.is_some()
+        && (config().audio_format.is_some() || config().video_format.is_some());
+    let command = match &args.command {
+        Some(AppActionCli::Search { .. }) | Some(AppActionCli::Ctl { .. }) => return Ok(()),
+        Some(AppActionCli::Download {
+            source, url, batch, ..
+        }) if download_configured && (source.is_some() || url.is_some() || batch.is_some()) => {
+            return Ok(());
+        }
+        Some(AppActionCli::Watch { command }) if !matches!(command, WatchCli::Remove) => {
+            return Ok(());
+        }
+        Some(AppActionCli::Subscriptions {
+            command:
+                SubscriptionsCli::Add { .. } | SubscriptionsCli::Import { .. } | SubscriptionsCli::List,
+        })
+        | Some(AppActionCli::Block {
+            command: BlockCli::Channel { .. } | BlockCli::Keyword { .. } | BlockCli::List,
+        })
+        | Some(AppActionCli::Config {
+            command: ConfigCli::Path,
+        })
+        | Some(AppActionCli::Midi {
+            command: MidiCli::List | MidiCli::Reset,
+        })
+        | Some(AppActionCli::Login)
+        | Some(AppActionCli::Logout)
+        | Some(AppActionCli::Libs { .. })
+        | Some(AppActionCli::SelfUpdate { .. }) => return Ok(()),
+        Some(AppActionCli::Download { .. }) => {
+            "Download without a url, --url or --batch, or without 'download' and its format in the config,"
+        }
+        Some(_) => "This command",
+        None => "The menu",
+    };
+    Err(ScriptError::NeedsTerminal(command.to_owned()).into())
+}
+
+/// One result of `search`
+#[derive(Serialize)]
+struct Found {
+    id: String,
+    url: String,
+    title: String,
+    channel: String,
+    duration: u32,
+    live: bool,
+}
+
+/// Print the results of `query`, one per line: tab separated for fzf and cut, or json
+pub async fn search(query: &str, music: bool, limit: usize, json: bool) -> Result<()> {
+    let found: Vec<YoutubeResponse> = if music {
+        crate::filter::tracks(backend().search_tracks(query).await?)
+            .into_iter()
+            .map(YoutubeResponse::Track)
+            .collect()
+    } else {
+        crate::filter::videos(backend().search_videos(query).await?)
+            .into_iter()
+            .map(YoutubeResponse::Video)
+            .collect()
+    };
+    if found.is_empty() {
+        return Err(ScriptError::NoResults(query.to_owned()).into());
+    }
+    for media in found.iter().take(limit) {
+        let found = Found {
+            id: media.get_id(),
+            url: YoutubeRs::get_video_url(&media.get_id()),
+            title: media.get_name(),
+            channel: media.get_author(),
+            duration: media.get_duration(),
+            live: media.is_live(),
+        };
+        if json {
+            println!("{}", serde_json::to_string(&found)?);
+        } else {
+            println!(
+                "{}\t{}\t{}\t{}",
+                found.url,
+                found.title,
+                found.channel,
+                crate::utility::format_time(found.duration)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// What `ytrs player -` plays: the first link read from stdin and the ones to queue after it,
+/// or the whole text as a query when it is not only links
+pub fn stdin_source() -> Result<(String, Vec<String>)> {
+    let mut content = String::new();
+    std::io::stdin()
+        .read_to_string(&mut content)
+        .context("Could not read stdin")?;
+    let lines: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    let Some((first, rest)) = lines.split_first() else {
+        bail!("Nothing was read from stdin");
+    };
+    if lines.iter().all(|line| YoutubeLink::parse(line).is_some()) {
+        Ok((
+            first.to_string(),
+            rest.iter().map(|line| line.to_string()).collect(),
+        ))
+    } else {
+        Ok((lines.join(" "), Vec::new()))
+    }
+}
@@ -0,0 +1,119 @@
+//! Detaching the player: mpv keeps playing the queue once ytrs exits, `ytrs attach` comes back to it
+use crate::app::{YoutubeResponse, YoutubeRs};
+use crate::utility::runtime_dir;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use ytrs_core::mpv::MpvIpc;
+
+/// The player left playing, in the runtime dir until attached again
+#[derive(Serialize, Deserialize)]
+struct Session {
+    ipc_path: PathBuf,
+    // Another mpv could listen on the same socket later
+    pid: u32,
+    audio_only: bool,
+    // At the first position of the mpv playlist, the queue comes after it
+    first: Option<YoutubeResponse>,
+    response: Option<YoutubeResponse>,
+    queue: Vec<YoutubeResponse>,
+    // Local file played instead of a media
+    file: Option<String>,
+}
+
+impl Session {
+    fn path() -> PathBuf {
+        runtime_dir().join("session.json")
+    }
+
+    fn store(&self) -> Result<()> {
+        std::fs::create_dir_all(runtime_dir())?;
+        std::fs::write(Self::path(), serde_json::to_string(self)?)
+            .context("Could not save the detached player")
+    }
+
+    /// The detached session, forgotten once read
+    fn take() -> Result<Self> {
+        let Ok(content) = std::fs::read_to_string(Self::path()) else {
+            bail!("No detached player, 'D' in the player detaches it");
+        };
+        let _ = std::fs::remove_file(Self::path());
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    async fn connect(&self) -> Result<MpvIpc> {
+        let mut mpv = MpvIpc::connect(&self.ipc_path)
+            .await
+            .context("The detached player is not running anymore")?;
+        if mpv.get_prop::<u32>("pid").await.ok() != Some(self.pid) {
+            mpv.disconnect().await;
+            bail!("The detached player is not running anymore");
+        }
+        Ok(mpv)
+    }
+}
+
+impl YoutubeRs {
+    /// Leave `mpv` playing on its own and remember what it plays for `ytrs attach`
+    pub(crate) async fn detach(
+        &mut self,
+        mpv: &mut MpvIpc,
+        response: &Option<YoutubeResponse>,
+        first: Option<YoutubeResponse>,
+        file: Option<String>,
+        audio_only: bool,
+    ) -> Result<()> {
+        let session = Session {
+            ipc_path: mpv
+                .ipc_path()
+                .cloned()
+                .context("mpv listens on no socket to come back to")?,
+            pid: mpv.get_prop("pid").await?,
+            audio_only,
+            first,
+            response: response.clone(),
+            queue: self.queue.entries().to_vec(),
+            file,
+        };
+        mpv.detach().await?;
+        session.store()
+    }
+
+    /// `ytrs attach`, the player of the detached mpv
+    pub async fn attach(&mut self) -> Result<()> {
+        let session = Session::take()?;
+        let mpv = session.connect().await?;
+        self.queue = session.queue.into_iter().collect();
+        self.last_search = session.file;
+        self.attached = Some((mpv, session.first));
+        let mut response = session.response;
+        self.player(&mut response, &mut None, session.audio_only, self.run_midi)
+            .await;
+        Ok(())
+    }
+}
+
+/// The terminal closing, the player detaches instead of stopping
+pub struct Hangup {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangup {
+    pub fn listen() -> Self {
+        Self {
+            #[cfg(unix)]
+            signal: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok(),
+        }
+    }
+
+    /// Resolves once the terminal is gone, never where there is no SIGHUP
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal {
+            signal.recv().await;
+            return;
+        }
+        std::future::pending::<()>().await
+    }
+}
@@ -0,0 +1,78 @@
+use crate::app::{YoutubeResponse, YoutubeRs};
+use crate::backend::{YoutubeBackend, backend};
+use crate::subscriptions::Subscriptions;
+use anyhow::{Result, bail};
+use rustypipe::model::VideoItem;
+use rustypipe::param::ChannelVideoTab;
+
+impl YoutubeRs {
+    /// Play shorts back to back, from a search or else the subscribed channels
+    pub async fn shorts(&mut self, query: Option<String>, limit: usize) -> Result<()> {
+        if !self.mpv_installed {
+            self.mpv_installed = Self::check_mpv(&self.args)?;
+        }
+        let subscriptions = Subscriptions::load()?;
+        let mut shorts = match query {
+            Some(query) => search_shorts(&query).await?,
+            None if subscriptions.channels.is_empty() => search_shorts("").await?,
+            None => subscribed_shorts(&subscriptions).await,
+        };
+        shorts.truncate(limit);
+        if shorts.is_empty() {
+            bail!("No short found");
+        }
+        let mut queue = shorts.into_iter().map(YoutubeResponse::Video);
+        let mut response = queue.next();
+        self.queue = queue.collect();
+        let mut thumbnail = match &response {
+            Some(res) => Self::fetch_yt_thumbnail(&res.get_id(), &self.args)
+                .await
+                .ok(),
+            None => None,
+        };
+        self.player(&mut response, &mut thumbnail, false, self.run_midi)
+            .await;
+        Ok(())
+    }
+}
+
+// YouTube puts the shorts matching the hashtag in the results
+async fn search_shorts(query: &str) -> Result<Vec<VideoItem>> {
+    let found = backend()
+        .search_videos(format!("{query} #shorts").trim())
+        .await?;
+    Ok(crate::filter::videos(found)
+        .into_iter()
+        .filter(|video| video.is_short)
+        .collect())
+}
+
+/// The shorts of every subscribed channel, taking turns so one channel does not fill the feed
+async fn subscribed_shorts(subscriptions: &Subscriptions) -> Vec<VideoItem> {
+    let rp = crate::auth::rustypipe();
+    let mut per_channel = Vec::new();
+    for channel in &subscriptions.channels {
+        println!("Checking {channel} ...");
+        let shorts = rp
+            .query()
+            .unauthenticated()
+            .channel_videos_tab(&channel.id, ChannelVideoTab::Shorts)
+            .await;
+        match shorts {
+            Ok(shorts) => per_channel.push(crate::filter::videos(shorts.content.items).into_iter()),
+            Err(e) => {
+                println!("Could not check {channel}: {e}");
+                tracing::warn!("Could not check {channel}: {e}");
+                tracing::warn!("Could not check {channel}: {e}");
+            }
+        }
+    }
+    let mut shorts = Vec::new();
+    loop {
+        let before = shorts.len();
+        shorts.extend(per_channel.iter_mut().filter_map(Iterator::next));
+        if shorts.len() == before {
+            return shorts;
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::process::Command;
+use yt_dlp::model::chapter::Chapter;
+
+const SPONSORBLOCK_API: &str = "https://sponsor.ajay.app/api/skipSegments";
+
+/// SponsorBlock segment categories that can be cut out of downloads
+#[derive(
+    clap::ValueEnum, Clone, Copy, Debug, PartialEq, strum::Display, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SponsorCategory {
+    Sponsor,
+    Selfpromo,
+    Interaction,
+    Intro,
+    Outro,
+    Preview,
+    MusicOfftopic,
+    Filler,
+}
+
+#[derive(Deserialize)]
+struct SponsorSegment {
+    segment: [f64; 2],
+}
+
+/// Fetch the segments of `video_id` matching `categories`, sorted and merged when overlapping
+pub async fn fetch_segments(
+    video_id: &str,
+    categories: &[SponsorCategory],
+) -> Result<Vec<(f64, f64)>> {
+    let categories = serde_json::to_string(categories)?;
+    let url = reqwest::Url::parse_with_params(
+        SPONSORBLOCK_API,
+        [("videoID", video_id), ("categories", categories.as_str())],
+    )?;
+    let response = reqwest::Client::new()
+        .get(url)
+        .send()
+        .await
+        .context("Could not reach SponsorBlock")?;
+    // SponsorBlock answers 404 when the video has no segments
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(Vec::new());
+    }
+    let body = response.error_for_status()?.text().await?;
+    let mut segments: Vec<(f64, f64)> = serde_json::from_str::<Vec<SponsorSegment>>(&body)?
+        .into_iter()
+        .map(|s| (s.segment[0], s.segment[1]))
+        .collect();
+    segments.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut merged: Vec<(f64, f64)> = Vec::new();
+    for (start, end) in segments {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    Ok(merged)
+}
+
+/// Position of `t` once the `segments` before it are removed
+fn shift(t: f64, segments: &[(f64, f64)]) -> f64 {
+    let removed: f64 = segments
+        .iter()
+        .filter(|(start, _)| *start < t)
+        .map(|(start, end)| end.min(t) - start)
+        .sum();
+    t - removed
+}
+
+pub(crate) fn chapters_metadata(chapters: &[Chapter], segments: &[(f64, f64)]) -> String {
+    let mut meta = String::from(";FFMETADATA1\n");
+    for chapter in chapters {
+        let start = shift(chapter.start_time, segments);
+        let end = shift(chapter.end_time, segments);
+        if end - start < 1.0 {
+            continue;
+        }
+        meta.push_str(&format!(
+            "[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            (start * 1000.0) as u64,
+            (end * 1000.0) as u64,
+            chapter
+                .title_or("")
+                .replace(['=', ';', '#', '\\', '\n'], " ")
+        ));
+    }
+    meta
+}
+
+/// Cut `segments` out of `file` with ffmpeg, keeping the chapters in sync
+pub async fn remove_segments(
+    ffmpeg: &Path,
+    file: &Path,
+    segments: &[(f64, f64)],
+    chapters: &[Chapter],
+    audio_only: bool,
+) -> Result<()> {
+    if segments.is_empty() {
+        return Ok(());
+    }
+    let keep = format!(
+        "not({})",
+        segments
+            .iter()
+            .map(|(start, end)| format!("between(t,{start},{end})"))
+            .collect::<Vec<_>>()
+            .join("+")
+    );
+    let extension = file
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp = file.with_extension(format!("sponsorblock.{extension}"));
+    let meta = file.with_extension("chapters.txt");
+    std::fs::write(&meta, chapters_metadata(chapters, segments))?;
+    let mut cmd = Command::new(ffmpeg);
+    cmd.args(["-y", "-loglevel", "error", "-i"])
+        .arg(file)
+        .arg("-i")
+        .arg(&meta)
+        .args(["-map_metadata", "0", "-map_chapters", "1"])
+        .arg("-af")
+        .arg(format!("aselect='{keep}',asetpts=N/SR/TB"));
+    if audio_only {
+        // Keep the cover art untouched
+        cmd.args(["-map", "0:a", "-map", "0:v?", "-c:v", "copy"]);
+    } else {
+        cmd.args(["-map", "0:v:0", "-map", "0:a?"])
+            .arg("-vf")
+            .arg(format!("select='{keep}',setpts=N/FRAME_RATE/TB"));
+    }
+    let status = cmd.arg(&tmp).status().await;
+    let _ = std::fs::remove_file(&meta);
+    let status = status.context("Could not start ffmpeg")?;
+    if !status.success() {
+        let _ = std::fs::remove_file(&tmp);
+        bail!("ffmpeg could not remove the SponsorBlock segments ({status})");
+    }
+    std::fs::rename(&tmp, file)?;
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use crate::app::YoutubeResponse;
+use crate::ratelimit::throttled;
+use anyhow::Result;
+use chrono::DateTime;
+use rustypipe::model::{TrackItem, VideoDetails, VideoItem};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::mpsc;
+
+/// Counters and date of a video, as much of them as YouTube gave
+#[derive(Clone, Default)]
+pub struct VideoStats {
+    // Hidden by some creators
+    pub likes: Option<u32>,
+    // Current viewers of a livestream
+    pub views: Option<u64>,
+    pub published: Option<String>,
+    pub subscribers: Option<u64>,
+    pub live: bool,
+}
+
+impl From<&VideoItem> for VideoStats {
+    fn from(video: &VideoItem) -> Self {
+        Self {
+            likes: None,
+            views: video.view_count,
+            published: published(
+                video.publish_date_txt.clone(),
+                video.publish_date.map(|date| date.unix_timestamp()),
+            ),
+            subscribers: video
+                .channel
+                .as_ref()
+                .and_then(|channel| channel.subscriber_count),
+            live: video.is_live,
+        }
+    }
+}
+
+impl From<&TrackItem> for VideoStats {
+    fn from(track: &TrackItem) -> Self {
+        Self {
+            views: track.view_count,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<&VideoDetails> for VideoStats {
+    fn from(video: &VideoDetails) -> Self {
+        Self {
+            likes: video.like_count,
+            views: Some(video.view_count),
+            published: published(
+                video.publish_date_txt.clone(),
+                video.publish_date.map(|date| date.unix_timestamp()),
+            ),
+            subscribers: video.channel.subscriber_count,
+            live: video.is_live,
+        }
+    }
+}
+
+impl VideoStats {
+    /// The fetched details completed with what the search result has
+    fn or(self, other: Self) -> Self {
+        Self {
+            likes: self.likes.or(other.likes),
+            views: self.views.or(other.views),
+            published: self.published.or(other.published),
+            subscribers: self.subscribers.or(other.subscribers),
+            live: self.live || other.live,
+        }
+    }
+}
+
+impl std::fmt::Display for VideoStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(views) = self.views {
+            parts.push(if self.live {
+                format!("{} watching", compact(views))
+            } else {
+                format!("{} views", compact(views))
+            });
+        }
+        if let Some(likes) = self.likes {
+            parts.push(format!("{} likes", compact(likes as u64)));
+        }
+        if let Some(published) = &self.published {
+            parts.push(published.clone());
+        }
+        if let Some(subscribers) = self.subscribers {
+            parts.push(format!("{} subscribers", compact(subscribers)));
+        }
+        write!(f, "{}", parts.join(" · "))
+    }
+}
+
+fn published(text: Option<String>, timestamp: Option<i64>) -> Option<String> {
+    text.or_else(|| {
+        timestamp
+            .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+            .map(|date| date.format("%Y-%m-%d").to_string())
+    })
+}
+
+// e.g. 1234567 as '1.2M'
+fn compact(count: u64) -> String {
+    match count {
+        0..1_000 => count.to_string(),
+        1_000..1_000_000 => format!("{:.1}K", count as f64 / 1e3),
+        1_000_000..1_000_000_000 => format!("{:.1}M", count as f64 / 1e6),
+        _ => format!("{:.1}B", count as f64 / 1e9),
+    }
+}
+
+/// Details of the videos looked at in the player, fetched in the background
+pub struct StatsCache {
+    fetched: HashMap<String, VideoStats>,
+    // Requested once, even when the request failed
+    requested: HashSet<String>,
+    tx: mpsc::UnboundedSender<(String, VideoStats)>,
+    rx: mpsc::UnboundedReceiver<(String, VideoStats)>,
+}
+
+impl Default for StatsCache {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            fetched: HashMap::new(),
+            requested: HashSet::new(),
+            tx,
+            rx,
+        }
+    }
+}
+
+impl StatsCache {
+    /// Fetch the details of `video_id` unless they already were
+    pub fn request(&mut self, video_id: &str) {
+        if !self.requested.insert(video_id.to_owned()) {
+            return;
+        }
+        let tx = self.tx.clone();
+        let video_id = video_id.to_owned();
+        tokio::spawn(async move {
+            if let Ok(details) = fetch(&video_id).await {
+                let _ = tx.send((video_id, VideoStats::from(&details)));
+            }
+        });
+    }
+
+    /// Keep the details fetched since the last call
+    pub fn poll(&mut self) {
+        while let Ok((video_id, stats)) = self.rx.try_recv() {
+            self.fetched.insert(video_id, stats);
+        }
+    }
+
+    /// Resolves once the stats of a media are fetched, kept like by `poll`
+    pub async fn fetched(&mut self) {
+        if let Some((video_id, stats)) = self.rx.recv().await {
+            self.fetched.insert(video_id, stats);
+        }
+    }
+
+    /// The stats of `media`, from its details once they are fetched
+    pub fn get(&self, media: &YoutubeResponse) -> VideoStats {
+        let listed = match media {
+            YoutubeResponse::Video(video) => VideoStats::from(video),
+            YoutubeResponse::Track(track) => VideoStats::from(track),
+        };
+        match self.fetched.get(&media.get_id()) {
+            Some(fetched) => fetched.clone().or(listed),
+            None => listed,
+        }
+    }
+}
+
+async fn fetch(video_id: &str) -> Result<VideoDetails> {
+    throttled(|| async move {
+        Ok(crate::auth::rustypipe()
+            .query()
+            .unauthenticated()
+            .video_details(video_id)
+            .await?)
+    })
+    .await
+}
@@ -0,0 +1,107 @@
+use crate::images::{self, PlayerImage};
+use anyhow::{Context, Result};
+use image::DynamicImage;
+use std::collections::HashMap;
+use yt_dlp::model::Video;
+
+/// Sprite sheets of small frames YouTube shows when hovering the progress bar
+pub struct Storyboard {
+    // Seconds between two frames
+    interval: f64,
+    rows: u32,
+    columns: u32,
+    width: u32,
+    height: u32,
+    sheets: Vec<String>,
+    // Sheets already downloaded, by index
+    loaded: HashMap<usize, DynamicImage>,
+}
+
+impl Storyboard {
+    /// The storyboard of `video` with the largest frames, if it has one
+    pub fn from_video(video: &Video) -> Option<Self> {
+        let format = video
+            .formats
+            .iter()
+            .filter(|format| {
+                format
+                    .storyboard_info
+                    .fragments
+                    .as_ref()
+                    .is_some_and(|fragments| !fragments.is_empty())
+            })
+            .max_by_key(|format| format.video_resolution.width.unwrap_or_default())?;
+        let rows = format.storyboard_info.rows? as u32;
+        let columns = format.storyboard_info.columns? as u32;
+        let fragments = format.storyboard_info.fragments.as_ref()?;
+        // Every sheet but the last one is full
+        let interval = *fragments[0].duration / (rows * columns) as f64;
+        Some(Self {
+            interval,
+            rows,
+            columns,
+            width: format.video_resolution.width?,
+            height: format.video_resolution.height?,
+            sheets: fragments
+                .iter()
+                .map(|fragment| fragment.url.clone())
+                .collect(),
+            loaded: HashMap::new(),
+        })
+    }
+
+    /// The frame closest to `time`, downloading its sheet if needed
+    pub async fn frame(&mut self, time: f64) -> Result<DynamicImage> {
+        let per_sheet = (self.rows * self.columns) as usize;
+        let index = (time.max(0.0) / self.interval) as usize;
+        let sheet = (index / per_sheet).min(self.sheets.len() - 1);
+        let cell = (index - sheet * per_sheet).min(per_sheet - 1) as u32;
+        if !self.loaded.contains_key(&sheet) {
+            let bytes = reqwest::Client::new()
+                .get(&self.sheets[sheet])
+                .send()
+                .await?
+                .bytes()
+                .await?;
+            let image = image::load_from_memory(&bytes).context("Could not read the storyboard")?;
+            self.loaded.insert(sheet, image);
+        }
+        let image = &self.loaded[&sheet];
+        Ok(image.crop_imm(
+            (cell % self.columns) * self.width,
+            (cell / self.columns) * self.height,
+            self.width,
+            self.height,
+        ))
+    }
+}
+
+/// State of the seek preview, showing the storyboard frame of where a seek would go
+#[derive(Default)]
+pub struct SeekPreview {
+    // Media the storyboard belongs to
+    pub video_id: Option<String>,
+    pub storyboard: Option<Storyboard>,
+    // Position previewed, seeked to on Enter
+    pub target: Option<f64>,
+    // Frame shown instead of the thumbnail while previewing
+    pub frame: PlayerImage,
+}
+
+impl SeekPreview {
+    /// Preview the frame at `time`
+    pub async fn show(&mut self, time: f64) -> Result<()> {
+        self.target = Some(time);
+        let Some(storyboard) = &mut self.storyboard else {
+            return Ok(());
+        };
+        let frame = storyboard.frame(time).await?;
+        self.frame.set(&images::picker()?, frame);
+        Ok(())
+    }
+
+    pub fn close(&mut self) {
+        self.target = None;
+        self.frame.clear();
+    }
+}
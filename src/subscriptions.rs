@@ -0,0 +1,331 @@
+use crate::app::{Format, VideoInfo, YoutubeResponse, YoutubeRs, YtrsError};
+use crate::cli::SubscriptionsCli;
+use crate::utility::data_dir;
+use anyhow::{Context, Result, anyhow, bail};
+use inquire::Select;
+use inquire::validator::Validation;
+use inquire_derive::Selectable;
+use rustypipe::model::{UrlTarget, VideoItem};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// What to do with the uploads picked in the feed
+#[derive(Copy, Clone, Debug, Selectable, strum::Display)]
+enum FeedAction {
+    Play,
+    Download,
+}
+
+/// A channel followed locally, without a YouTube account
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Subscription {
+    pub id: String,
+    pub name: String,
+}
+
+impl std::fmt::Display for Subscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Subscribed channels, kept in `~/.local/share/ytrs/subscriptions.json`
+#[derive(Serialize, Deserialize, Default)]
+pub struct Subscriptions {
+    pub channels: Vec<Subscription>,
+}
+
+impl Subscriptions {
+    pub fn path() -> PathBuf {
+        data_dir().join("subscriptions.json")
+    }
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse '{}'", path.to_string_lossy()))
+    }
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Subscribe to the channel, returns false when it already was
+    pub fn subscribe(id: &str, name: &str) -> Result<bool> {
+        let mut subscriptions = Self::load()?;
+        if subscriptions.channels.iter().any(|c| c.id == id) {
+            return Ok(false);
+        }
+        subscriptions.channels.push(Subscription {
+            id: id.to_owned(),
+            name: name.to_owned(),
+        });
+        subscriptions.save()?;
+        Ok(true)
+    }
+
+    /// Read the channels of a subscription export, whichever of the common formats it is
+    fn parse_export(content: &str) -> Result<Vec<Subscription>> {
+        let content = content.trim_start_matches('\u{feff}').trim_start();
+        let channels: Vec<Subscription> = if content.starts_with('{') {
+            // NewPipe: {"subscriptions": [{"url": ..., "name": ...}]}
+            let export: serde_json::Value =
+                serde_json::from_str(content).context("Not a NewPipe export")?;
+            export["subscriptions"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|channel| {
+                    Some(Subscription {
+                        id: channel_id(channel["url"].as_str()?)?,
+                        name: channel["name"].as_str().unwrap_or_default().to_owned(),
+                    })
+                })
+                .collect()
+        } else if content.contains("<opml") {
+            // <outline text="Name" xmlUrl="https://www.youtube.com/feeds/videos.xml?channel_id=UC..."/>
+            content
+                .split("<outline")
+                .skip(1)
+                .filter_map(|outline| {
+                    // The attributes can be on several lines
+                    let outline = outline.split('>').next()?.replace(['\n', '\r', '\t'], " ");
+                    let name = xml_attribute(&outline, "title")
+                        .or_else(|| xml_attribute(&outline, "text"))
+                        .unwrap_or_default();
+                    Some(Subscription {
+                        id: channel_id(&xml_attribute(&outline, "xmlUrl")?)?,
+                        name,
+                    })
+                })
+                .collect()
+        } else {
+            // Google Takeout: Channel Id,Channel Url,Channel Title
+            content
+                .lines()
+                .skip(1)
+                .filter_map(|line| {
+                    let mut fields = line.splitn(3, ',');
+                    let id = fields.next()?.trim().trim_matches('"');
+                    let _url = fields.next()?;
+                    let name = fields.next().unwrap_or_default().trim().trim_matches('"');
+                    id.starts_with("UC").then(|| Subscription {
+                        id: id.to_owned(),
+                        name: name.replace("\"\"", "\""),
+                    })
+                })
+                .collect()
+        };
+        if channels.is_empty() {
+            bail!(
+                "No YouTube channel found, expected a Google Takeout csv, an OPML file or a NewPipe export"
+            );
+        }
+        Ok(channels)
+    }
+}
+
+/// Id of the channel of a channel or feed url
+fn channel_id(url: &str) -> Option<String> {
+    let id = url
+        .split("/channel/")
+        .nth(1)
+        .or_else(|| url.split("channel_id=").nth(1))?;
+    let id: String = id
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    id.starts_with("UC").then_some(id)
+}
+
+fn xml_attribute(tag: &str, name: &str) -> Option<String> {
+    let value = tag
+        .split(&format!(" {name}=\""))
+        .nth(1)?
+        .split('"')
+        .next()?;
+    Some(
+        value
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
+
+impl YoutubeRs {
+    pub async fn subscriptions(&mut self, command: SubscriptionsCli) -> Result<()> {
+        match command {
+            SubscriptionsCli::Add { url } => {
+                let rp = crate::auth::rustypipe();
+                let target = rp.query().resolve_string(&url, false).await;
+                let UrlTarget::Channel { id } = target.context("Failed to resolve url")? else {
+                    bail!("'{url}' is not a channel");
+                };
+                let channel = rp.query().channel_videos(&id).await;
+                let name = channel?.name;
+                if Subscriptions::subscribe(&id, &name)? {
+                    println!("Subscribed to {name}");
+                } else {
+                    println!("Already subscribed to {name}");
+                }
+            }
+            SubscriptionsCli::Import { file } => {
+                let content = std::fs::read_to_string(&file)
+                    .with_context(|| format!("Could not read '{}'", file.to_string_lossy()))?;
+                let mut subscriptions = Subscriptions::load()?;
+                let mut imported = 0;
+                let mut known = 0;
+                for channel in Subscriptions::parse_export(&content)? {
+                    if subscriptions.channels.iter().any(|c| c.id == channel.id) {
+                        known += 1;
+                    } else {
+                        subscriptions.channels.push(channel);
+                        imported += 1;
+                    }
+                }
+                subscriptions.save()?;
+                println!("Subscribed to {imported} channel(s), {known} already were");
+            }
+            SubscriptionsCli::Remove => {
+                let mut subscriptions = Subscriptions::load()?;
+                if subscriptions.channels.is_empty() {
+                    println!("No subscription");
+                    return Ok(());
+                }
+                let channel =
+                    Select::new("Unsubscribe from", subscriptions.channels.clone()).prompt()?;
+                subscriptions.channels.retain(|c| c.id != channel.id);
+                subscriptions.save()?;
+            }
+            SubscriptionsCli::List => {
+                for channel in Subscriptions::load()?.channels {
+                    println!("{channel}");
+                }
+            }
+            SubscriptionsCli::Feed { limit } => self.subscriptions_feed(limit).await?,
+        }
+        Ok(())
+    }
+
+    /// Gather the latest uploads of every subscribed channel, then play or download a selection
+    async fn subscriptions_feed(&mut self, limit: usize) -> Result<()> {
+        let subscriptions = Subscriptions::load()?;
+        if subscriptions.channels.is_empty() {
+            bail!("No subscription, add one with 'ytrs subscriptions add <url>'");
+        }
+        let rp = crate::auth::rustypipe();
+        let mut uploads: Vec<VideoItem> = Vec::new();
+        for channel in &subscriptions.channels {
+            println!("Checking {channel} ...");
+            let videos = rp
+                .query()
+                .unauthenticated()
+                .channel_videos(&channel.id)
+                .await;
+            match videos {
+                Ok(videos) => {
+                    uploads.extend(videos.content.items.into_iter().filter(|v| {
+                        !v.is_upcoming && !v.is_live && crate::filter::video_allowed(v)
+                    }))
+                }
+                Err(e) => {
+                    println!("Could not check {channel}: {e}");
+                    tracing::warn!("Could not check {channel}: {e}");
+                    tracing::warn!("Could not check {channel}: {e}");
+                }
+            }
+        }
+        // Newest first, the dates are estimated from texts like '2 days ago'
+        uploads.sort_by_key(|v| std::cmp::Reverse(v.publish_date));
+        uploads.truncate(limit);
+        if uploads.is_empty() {
+            bail!("No upload found");
+        }
+
+        let options: Vec<String> = uploads
+            .iter()
+            .map(|v| {
+                format!(
+                    "{}{}",
+                    v.publish_date_txt
+                        .as_ref()
+                        .map(|date| format!("[{date}] "))
+                        .unwrap_or_default(),
+                    VideoInfo::from(v)
+                )
+            })
+            .collect();
+        let selected = match inquire::MultiSelect::new("Feed", options)
+            .with_help_message("Space to select uploads | Enter to confirm")
+            .with_validator(|selection: &[inquire::list_option::ListOption<&String>]| {
+                if selection.is_empty() {
+                    Ok(Validation::Invalid("Select at least one upload".into()))
+                } else {
+                    Ok(Validation::Valid)
+                }
+            })
+            .raw_prompt()
+        {
+            Ok(v) => v,
+            Err(inquire::InquireError::OperationCanceled) => Err(anyhow!(YtrsError::Quit))?,
+            Err(e) => Err(e)?,
+        };
+        let selected: Vec<&VideoItem> = selected
+            .into_iter()
+            .map(|option| &uploads[option.index])
+            .collect();
+
+        match FeedAction::select("Selected uploads").prompt()? {
+            FeedAction::Play => {
+                if !self.mpv_installed {
+                    self.mpv_installed = Self::check_mpv(&self.args)?;
+                }
+                let mut queue = selected.into_iter().map(YoutubeResponse::from);
+                let mut response = queue.next();
+                self.queue = queue.collect();
+                let mut thumbnail = match &response {
+                    Some(res) => Self::fetch_yt_thumbnail(&res.get_id(), &self.args)
+                        .await
+                        .ok(),
+                    None => None,
+                };
+                self.player(&mut response, &mut thumbnail, false, self.run_midi)
+                    .await;
+            }
+            FeedAction::Download => {
+                let format = Format::prompt("Download as")?;
+                if !self.libraries_exist(&self.args.clone()) {
+                    Self::install_lib(&self.args).await?;
+                }
+                // One folder per channel, like the watched channels
+                let mut failed = None;
+                for channel in &subscriptions.channels {
+                    let urls: Vec<String> = selected
+                        .iter()
+                        .filter(|v| v.channel.as_ref().is_some_and(|c| c.id == channel.id))
+                        .map(|v| Self::get_video_url(&v.id))
+                        .collect();
+                    // The other channels are still downloaded
+                    if !urls.is_empty()
+                        && let Err(e) = self.download_urls(&urls, format, Some(&channel.name)).await
+                    {
+                        failed = Some(e);
+                    }
+                }
+                if let Some(e) = failed {
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
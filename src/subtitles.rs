@@ -0,0 +1,55 @@
+use crate::mpv::MpvIpc;
+use ratatui::widgets::ListState;
+
+/// A subtitle track mpv loaded for the current media, `None` turns them off
+#[derive(Clone)]
+pub struct SubtitleTrack {
+    pub id: Option<i64>,
+    pub label: String,
+}
+
+/// State of the player pane listing the subtitle tracks of the current media
+#[derive(Default)]
+pub struct SubtitlesPane {
+    pub tracks: Vec<SubtitleTrack>,
+    pub selected: ListState,
+}
+
+impl SubtitlesPane {
+    /// List the subtitle tracks of the current media, the shown one selected
+    pub async fn load(&mut self, mpv: &mut MpvIpc) {
+        let list = mpv
+            .get_prop::<Vec<serde_json::Value>>("track-list")
+            .await
+            .unwrap_or_default();
+        self.tracks = vec![SubtitleTrack {
+            id: None,
+            label: "Off".to_owned(),
+        }];
+        let mut shown = 0;
+        for track in list.iter().filter(|track| track["type"] == "sub") {
+            let Some(id) = track["id"].as_i64() else {
+                continue;
+            };
+            if track["selected"].as_bool().unwrap_or_default() {
+                shown = self.tracks.len();
+            }
+            let language = track["lang"].as_str().unwrap_or_default();
+            let label = match (track["title"].as_str(), language) {
+                (Some(title), "") => title.to_owned(),
+                (Some(title), language) => format!("{title} [{language}]"),
+                (None, "") => format!("Track {id}"),
+                (None, language) => language.to_owned(),
+            };
+            self.tracks.push(SubtitleTrack {
+                id: Some(id),
+                label,
+            });
+        }
+        self.selected.select(Some(shown));
+    }
+
+    pub fn selected_track(&self) -> Option<&SubtitleTrack> {
+        self.selected.selected().and_then(|i| self.tracks.get(i))
+    }
+}
@@ -0,0 +1,136 @@
+use crate::llm::Llm;
+use anyhow::Result;
+
+pub use ytrs_core::transcript::*;
+
+/// Translate the text of every cue to `target` with the LLM, timestamps are kept as is
+pub async fn translate(llm: &Llm, cues: &[Cue], target: &str) -> Result<Vec<Cue>> {
+    const LINES_PER_REQUEST: usize = 40;
+    let mut translated = cues.to_vec();
+    for (n, chunk) in translated.chunks_mut(LINES_PER_REQUEST).enumerate() {
+        println!(
+            "Translating lines {}-{} of {}",
+            n * LINES_PER_REQUEST + 1,
+            n * LINES_PER_REQUEST + chunk.len(),
+            cues.len()
+        );
+        let lines: String = chunk
+            .iter()
+            .enumerate()
+            .map(|(i, cue)| format!("{i}|{}\n", cue.text))
+            .collect();
+        let answer = llm
+            .generate(format!(
+                "Translate every line below to '{target}'. Each line starts with a number and a '|', \
+                keep that prefix and output exactly one translated line per input line, without any comment.\n\n{lines}"
+            ))
+            .await?;
+        // Lines the model skipped or mangled keep their original text
+        for line in answer.lines() {
+            if let Some((i, text)) = line.split_once('|')
+                && let Ok(i) = i.trim().parse::<usize>()
+                && let Some(cue) = chunk.get_mut(i)
+                && !text.trim().is_empty()
+            {
+                cue.text = text.trim().to_string();
+            }
+        }
+    }
+    Ok(translated)
+}
+
+/// State of the player popup searching the transcript of the current video
+#[derive(Default)]
+pub struct TranscriptSearch {
+    pub open: bool,
+    pub query: String,
+    // Video the cues belong to
+    pub video_id: Option<String>,
+    pub cues: Vec<Cue>,
+    pub matches: Vec<Cue>,
+    pub selected: ratatui::widgets::ListState,
+    pub error: Option<String>,
+}
+
+/// State of the player pane following the transcript of the current video
+#[derive(Default)]
+pub struct TranscriptView {
+    // Video the cues belong to
+    pub video_id: Option<String>,
+    pub cues: Vec<Cue>,
+    pub selected: ratatui::widgets::ListState,
+    // The selection was moved by hand and no longer follows the playback
+    pub browsing: bool,
+    pub error: Option<String>,
+}
+
+impl TranscriptSearch {
+    /// Whether the cues of `video_id` are to be fetched before searching them
+    pub fn request(&mut self, video_id: &str) -> bool {
+        if self.video_id.as_deref() == Some(video_id) {
+            return false;
+        }
+        self.cues.clear();
+        self.error = None;
+        self.video_id = Some(video_id.to_owned());
+        true
+    }
+
+    /// Search the cues fetched for `video_id`, a failure is fetched again on the next search
+    pub fn loaded(&mut self, video_id: &str, found: Result<Vec<Cue>>) {
+        if self.video_id.as_deref() != Some(video_id) {
+            return;
+        }
+        match found {
+            Ok(cues) => {
+                self.cues = cues;
+                self.search();
+            }
+            Err(e) => {
+                self.error = Some(e.to_string());
+                self.video_id = None;
+            }
+        }
+    }
+
+    /// List the cues matching the query, which is cleared for the next one
+    pub fn search(&mut self) {
+        self.matches = search(&self.cues, &self.query)
+            .into_iter()
+            .cloned()
+            .collect();
+        self.selected.select_first();
+        self.query.clear();
+    }
+}
+
+impl TranscriptView {
+    /// Whether the cues of `video_id` are to be fetched, they are unless already shown
+    pub fn request(&mut self, video_id: &str) -> bool {
+        if self.video_id.as_deref() == Some(video_id) {
+            return false;
+        }
+        self.browsing = false;
+        self.selected = ratatui::widgets::ListState::default();
+        self.cues.clear();
+        self.error = None;
+        self.video_id = Some(video_id.to_owned());
+        true
+    }
+
+    /// Show the cues fetched for `video_id`, unless another video was requested meanwhile
+    pub fn loaded(&mut self, video_id: &str, found: Result<Vec<Cue>>) {
+        if self.video_id.as_deref() != Some(video_id) {
+            return;
+        }
+        match found {
+            Ok(cues) => self.cues = cues,
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    /// Index of the cue said at `time`
+    pub fn current(&self, time: f64) -> Option<usize> {
+        self.cues.iter().rposition(|cue| cue.start <= time)
+    }
+}
@@ -0,0 +1,187 @@
+//! `ytrs self-update` and the hint shown at startup when a newer release is out
+use crate::utility::cache_dir;
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+const RELEASES: &str = "https://api.github.com/repos/Owlyat/ytrs/releases/latest";
+// Seconds the last check is trusted before asking GitHub again
+const CHECK_EVERY: i64 = 24 * 60 * 60;
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// The last check of the releases, in the cache dir
+#[derive(Serialize, Deserialize)]
+struct LastCheck {
+    // Unix timestamp
+    checked: i64,
+    latest: String,
+}
+
+impl Release {
+    fn version(&self) -> Result<Version> {
+        Version::parse(self.tag_name.trim_start_matches('v'))
+            .with_context(|| format!("Unknown version '{}'", self.tag_name))
+    }
+
+    fn asset(&self, name: &str) -> Result<&Asset> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .with_context(|| format!("The release {} has no '{name}'", self.tag_name))
+    }
+}
+
+fn current() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).expect("the crate version is semver")
+}
+
+/// Name of the binary of this platform in the releases, like 'ytrs-linux-x86_64'
+fn asset_name() -> String {
+    format!(
+        "ytrs-{}-{}{}",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+fn client() -> Result<reqwest::Client> {
+    // GitHub refuses the requests without one
+    Ok(reqwest::Client::builder()
+        .user_agent(concat!("ytrs/", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
+
+async fn latest() -> Result<Release> {
+    let body = client()?
+        .get(RELEASES)
+        .send()
+        .await
+        .context("Could not reach GitHub")?
+        .error_for_status()?
+        .text()
+        .await?;
+    let release: Release = serde_json::from_str(&body)?;
+    LastCheck::store(&release.tag_name);
+    Ok(release)
+}
+
+async fn fetch(url: &str) -> Result<Vec<u8>> {
+    Ok(client()?
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?
+        .to_vec())
+}
+
+/// Hex digest of `data`, as sha256sum prints it
+pub(crate) fn sha256(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Replace the running executable by the binary of the latest release, once its checksum matches
+pub async fn self_update(check: bool) -> Result<()> {
+    let release = latest().await?;
+    let latest = release.version()?;
+    if latest <= current() {
+        println!("ytrs {} is the latest version", current());
+        return Ok(());
+    }
+    if check {
+        println!(
+            "ytrs {latest} is available, this is {}, run 'ytrs self-update'",
+            current()
+        );
+        return Ok(());
+    }
+    let name = asset_name();
+    let binary = release.asset(&name)?;
+    let checksum = release.asset(&format!("{name}.sha256"))?;
+    println!("Downloading ytrs {latest} ...");
+    let data = fetch(&binary.browser_download_url).await?;
+    // The format of sha256sum: the digest, then the file name
+    let expected = String::from_utf8(fetch(&checksum.browser_download_url).await?)?
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if sha256(&data) != expected {
+        bail!("The checksum of '{name}' does not match, nothing was replaced");
+    }
+    let exe = std::env::current_exe().context("Could not find the ytrs executable")?;
+    let download = exe.with_file_name(format!(".{name}.new"));
+    std::fs::write(&download, &data)
+        .with_context(|| format!("Could not write '{}'", download.to_string_lossy()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&download, std::fs::Permissions::from_mode(0o755))?;
+    }
+    let replaced = self_replace::self_replace(&download)
+        .with_context(|| format!("Could not replace '{}'", exe.to_string_lossy()));
+    let _ = std::fs::remove_file(&download);
+    replaced?;
+    println!("ytrs updated from {} to {latest}", current());
+    Ok(())
+}
+
+impl LastCheck {
+    fn path() -> PathBuf {
+        cache_dir().join("update_check.json")
+    }
+
+    fn load() -> Option<Self> {
+        serde_json::from_str(&std::fs::read_to_string(Self::path()).ok()?).ok()
+    }
+
+    fn store(latest: &str) {
+        let check = Self {
+            checked: Utc::now().timestamp(),
+            latest: latest.to_owned(),
+        };
+        if let Ok(content) = serde_json::to_string(&check) {
+            let _ = std::fs::create_dir_all(cache_dir());
+            let _ = std::fs::write(Self::path(), content);
+        }
+    }
+}
+
+/// Tell on stderr when the last check found a newer release, and check again in the
+/// background once a day, its answer is shown the next time
+pub fn hint() {
+    let last = LastCheck::load();
+    if let Some(latest) = last
+        .as_ref()
+        .and_then(|last| Version::parse(last.latest.trim_start_matches('v')).ok())
+        .filter(|latest| *latest > current())
+    {
+        eprintln!("ytrs {latest} is available, run 'ytrs self-update'");
+    }
+    if last.is_none_or(|last| Utc::now().timestamp() - last.checked >= CHECK_EVERY) {
+        tokio::spawn(async {
+            if let Err(e) = latest().await {
+                tracing::debug!("Could not check for updates: {e:#}");
+            }
+        });
+    }
+}
@@ -0,0 +1,89 @@
+use crate::app::YoutubeResponse;
+use crate::log::LogError;
+use crate::subscriptions::Subscriptions;
+use anyhow::Result;
+use notify_rust::Notification;
+use ratatui::widgets::ListState;
+use rustypipe::model::VideoItem;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// State of the player pane listing the uploads of the subscriptions found while it runs
+#[derive(Default)]
+pub struct UploadsPane {
+    // Newest first
+    pub items: Vec<YoutubeResponse>,
+    pub selected: ListState,
+    // Upload to download once the player hands the terminal over
+    pub download: Option<YoutubeResponse>,
+}
+
+impl UploadsPane {
+    pub fn push(&mut self, video: VideoItem) {
+        self.items.insert(0, YoutubeResponse::Video(video));
+        self.selected.select_first();
+    }
+
+    pub fn selected_item(&self) -> Option<&YoutubeResponse> {
+        self.selected.selected().and_then(|i| self.items.get(i))
+    }
+}
+
+/// Check the subscribed channels every `interval` and send their new uploads,
+/// with a desktop notification for each
+pub fn spawn(interval: Duration) -> mpsc::UnboundedReceiver<VideoItem> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut known: HashSet<String> = HashSet::new();
+        // The uploads already there on the first check of a channel are not new
+        let mut checked: HashSet<String> = HashSet::new();
+        loop {
+            let subscriptions = Subscriptions::load().unwrap_or_default();
+            for channel in &subscriptions.channels {
+                let videos = crate::auth::rustypipe()
+                    .query()
+                    .unauthenticated()
+                    .channel_videos(&channel.id)
+                    .await;
+                let Ok(videos) = videos else {
+                    continue;
+                };
+                let first_check = checked.insert(channel.id.clone());
+                for video in videos.content.items.into_iter().rev() {
+                    if video.is_upcoming
+                        || video.is_live
+                        || !crate::filter::video_allowed(&video)
+                        || !known.insert(video.id.clone())
+                        || first_check
+                    {
+                        continue;
+                    }
+                    notify(&channel.name, &video.name)
+                        .await
+                        .log_error("Could not show the notification");
+                    if tx.send(video).is_err() {
+                        return;
+                    }
+                }
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+    rx
+}
+
+async fn notify(channel: &str, title: &str) -> Result<()> {
+    let summary = format!("New upload from {channel}");
+    let body = title.to_owned();
+    tokio::task::spawn_blocking(move || {
+        Notification::new()
+            .appname("ytrs")
+            .summary(&summary)
+            .body(&body)
+            .show()
+            .map(|_| ())
+    })
+    .await??;
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use crate::app::Format;
+use crate::utility::data_dir;
+use anyhow::{Context, Result, bail};
+use rustypipe::model::{UrlTarget, VideoItem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, strum::Display)]
+pub enum WatchKind {
+    Channel,
+    Playlist,
+}
+
+/// A channel or playlist whose new uploads are downloaded automatically
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WatchEntry {
+    pub kind: WatchKind,
+    pub id: String,
+    pub name: String,
+    pub format: Format,
+    // Uploads already handled, either downloaded or present when the entry was added
+    #[serde(default)]
+    pub seen: HashSet<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct WatchList {
+    pub entries: Vec<WatchEntry>,
+}
+
+impl std::fmt::Display for WatchEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = match self.format {
+            Format::Audio { format } => format.to_string(),
+            Format::Video { format } => format.to_string(),
+        };
+        write!(f, "{} '{}' [{format}]", self.kind, self.name)
+    }
+}
+
+impl WatchList {
+    pub fn path() -> PathBuf {
+        data_dir().join("watch.json")
+    }
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Could not parse '{}'", path.to_string_lossy()))
+    }
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+impl WatchEntry {
+    /// Resolve a channel or playlist url into a new entry
+    pub async fn resolve(url: &str, format: Format) -> Result<Self> {
+        let rp = crate::auth::rustypipe();
+        let target = rp
+            .query()
+            .resolve_string(url, false)
+            .await
+            .context("Failed to resolve url")?;
+        let entry = match target {
+            UrlTarget::Channel { id } => {
+                let channel = rp.query().channel_videos(&id).await?;
+                Self {
+                    kind: WatchKind::Channel,
+                    name: channel.name,
+                    seen: channel.content.items.into_iter().map(|v| v.id).collect(),
+                    id,
+                    format,
+                }
+            }
+            UrlTarget::Playlist { id } => {
+                let playlist = rp.query().playlist(&id).await?;
+                Self {
+                    kind: WatchKind::Playlist,
+                    name: playlist.name,
+                    seen: playlist.videos.items.into_iter().map(|v| v.id).collect(),
+                    id,
+                    format,
+                }
+            }
+            _ => {
+                bail!("'{url}' is not a channel or a playlist")
+            }
+        };
+        Ok(entry)
+    }
+
+    /// Uploads not handled yet, oldest first
+    pub async fn new_uploads(&self) -> Result<Vec<VideoItem>> {
+        let rp = crate::auth::rustypipe();
+        let videos = match self.kind {
+            WatchKind::Channel => rp.query().channel_videos(&self.id).await?.content.items,
+            WatchKind::Playlist => rp.query().playlist(&self.id).await?.videos.items,
+        };
+        Ok(videos
+            .into_iter()
+            .filter(|v| {
+                !self.seen.contains(&v.id)
+                    && !v.is_upcoming
+                    && !v.is_live
+                    && crate::filter::video_allowed(v)
+            })
+            .rev()
+            .collect())
+    }
+}
@@ -0,0 +1,193 @@
+//! yt-dlp and ffmpeg found in the PATH or installed by ytrs, yt-dlp from the release channel
+//! picked and checked against the published checksums
+use crate::app::YoutubeRs;
+use crate::cli::{Cli, LibsCli};
+use crate::update::sha256;
+use anyhow::{Context, Result, bail};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Where yt-dlp is installed from
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum YtdlpChannel {
+    #[default]
+    Stable,
+    /// Built every day, with the latest fixes of the extractors
+    Nightly,
+}
+
+impl YtdlpChannel {
+    fn repo(self) -> &'static str {
+        match self {
+            Self::Stable => "yt-dlp/yt-dlp",
+            Self::Nightly => "yt-dlp/yt-dlp-nightly-builds",
+        }
+    }
+
+    fn download_url(self, asset: &str) -> String {
+        format!(
+            "https://github.com/{}/releases/latest/download/{asset}",
+            self.repo()
+        )
+    }
+}
+
+/// Where yt-dlp and ffmpeg are looked for
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LibsSource {
+    /// The copies ytrs installed in its libs folder
+    Private,
+    /// The ones installed on the system, found in the PATH
+    System,
+}
+
+/// `name` in the PATH, or `private` when the sources of `order` have none.
+/// The private copies come first unless told otherwise, the PATH saves installing them
+pub fn locate(order: &[LibsSource], private: PathBuf, name: &str) -> PathBuf {
+    let order = match order {
+        [] => &[LibsSource::Private, LibsSource::System][..],
+        order => order,
+    };
+    for source in order {
+        match source {
+            LibsSource::Private if installed(&private) => return private,
+            LibsSource::System => {
+                if let Some(path) = in_path(name) {
+                    return path;
+                }
+            }
+            LibsSource::Private => {}
+        }
+    }
+    private
+}
+
+/// Whether `path` exists, with the '.exe' yt-dlp and ffmpeg have on Windows
+pub fn installed(path: &Path) -> bool {
+    path.exists() || with_exe(path).exists()
+}
+
+fn with_exe(path: &Path) -> PathBuf {
+    PathBuf::from(format!(
+        "{}{}",
+        path.to_string_lossy(),
+        std::env::consts::EXE_SUFFIX
+    ))
+}
+
+fn in_path(name: &str) -> Option<PathBuf> {
+    let name = format!("{name}{}", std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(&name))
+        .find(|path| path.is_file())
+}
+
+/// The release asset of this platform, the zipapp needing python where there is no build
+fn asset_name() -> &'static str {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => "yt-dlp_linux",
+        ("linux", "aarch64") => "yt-dlp_linux_aarch64",
+        ("linux", "arm") => "yt-dlp_linux_armv7l",
+        ("macos", _) => "yt-dlp_macos",
+        ("windows", "x86") => "yt-dlp_x86.exe",
+        ("windows", "aarch64") => "yt-dlp_arm64.exe",
+        ("windows", _) => "yt-dlp.exe",
+        _ => "yt-dlp",
+    }
+}
+
+async fn fetch(url: &str) -> Result<Vec<u8>> {
+    Ok(reqwest::get(url)
+        .await
+        .with_context(|| format!("Could not reach '{url}'"))?
+        .error_for_status()?
+        .bytes()
+        .await?
+        .to_vec())
+}
+
+/// Download yt-dlp of `channel` to `path`, once it matches the checksum of the release
+pub async fn install(path: &Path, channel: YtdlpChannel) -> Result<()> {
+    let asset = asset_name();
+    println!("Downloading yt-dlp ({channel:?}) ...");
+    let data = fetch(&channel.download_url(asset)).await?;
+    let sums = String::from_utf8(fetch(&channel.download_url("SHA2-256SUMS")).await?)?;
+    // One '<digest>  <asset>' per line
+    let Some(expected) = sums.lines().find_map(|line| {
+        let (digest, name) = line.split_once(char::is_whitespace)?;
+        (name.trim().trim_start_matches('*') == asset).then(|| digest.to_lowercase())
+    }) else {
+        bail!("No checksum published for '{asset}'");
+    };
+    if sha256(&data) != expected {
+        bail!("The checksum of '{asset}' does not match, yt-dlp was not installed");
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // Written aside first, a failed write leaves the installed one working
+    let new = path.with_extension("new");
+    std::fs::write(&new, &data)
+        .with_context(|| format!("Could not write '{}'", new.to_string_lossy()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&new, std::fs::Permissions::from_mode(0o755))?;
+    }
+    std::fs::rename(&new, path)?;
+    println!("yt-dlp {} installed", version(path, "--version").await?);
+    Ok(())
+}
+
+/// First line printed by `binary` for `arg`
+async fn version(binary: &Path, arg: &str) -> Result<String> {
+    let output = Command::new(binary)
+        .arg(arg)
+        .output()
+        .await
+        .with_context(|| format!("Could not run '{}'", binary.to_string_lossy()))?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_owned())
+}
+
+/// Path of yt-dlp with the extension of the platform, as the yt-dlp crate looks for it
+pub fn executable(args: &Cli) -> PathBuf {
+    let (libs, _) = YoutubeRs::get_libs_path(args);
+    libs.join(format!("yt-dlp{}", std::env::consts::EXE_SUFFIX))
+}
+
+/// `ytrs libs`
+pub async fn run(command: LibsCli, args: &Cli) -> Result<()> {
+    let libs = YoutubeRs::get_libs(args);
+    match command {
+        LibsCli::Install => YoutubeRs::install_lib(args).await,
+        LibsCli::Update => {
+            // The private copy would not be the one used
+            let (dir, _) = YoutubeRs::get_libs_path(args);
+            if libs.youtube != dir.join("yt-dlp") {
+                bail!(
+                    "The yt-dlp used is '{}', update it with its package manager or put 'private' first in --libs-order",
+                    libs.youtube.to_string_lossy()
+                );
+            }
+            install(&executable(args), args.ytdlp_channel).await
+        }
+        LibsCli::Versions => {
+            for (name, binary, arg) in [
+                ("yt-dlp", &libs.youtube, "--version"),
+                ("ffmpeg", &libs.ffmpeg, "-version"),
+            ] {
+                match version(binary, arg).await {
+                    Ok(version) => println!("{name}: {version}"),
+                    Err(_) => println!("{name}: not installed at '{}'", binary.to_string_lossy()),
+                }
+            }
+            Ok(())
+        }
+    }
+}
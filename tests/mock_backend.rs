@@ -0,0 +1,153 @@
+//! Searches and downloads through `--mock-backend`, without any network access nor yt-dlp
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A home of its own for each test, so they neither share nor touch the user's settings
+struct Home(PathBuf);
+
+impl Home {
+    fn new(test: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("ytrs-test-{}-{test}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    /// Download the configured format without asking, as `--non-interactive` requires
+    fn configure_download(&self) {
+        let config = self.0.join("config").join("ytrs");
+        std::fs::create_dir_all(&config).unwrap();
+        std::fs::write(
+            config.join("config.toml"),
+            "download = \"Audio\"\naudio_format = \"MP3\"\n",
+        )
+        .unwrap();
+    }
+
+    fn output(&self) -> PathBuf {
+        self.0.join("output")
+    }
+
+    fn ytrs(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_ytrs"))
+            .args(["--mock-backend", "--non-interactive", "--output-path"])
+            .arg(&self.0)
+            .args(args)
+            .env("HOME", &self.0)
+            .env("XDG_CONFIG_HOME", self.0.join("config"))
+            .env("XDG_DATA_HOME", self.0.join("data"))
+            .env("XDG_CACHE_HOME", self.0.join("cache"))
+            .env("XDG_RUNTIME_DIR", self.0.join("runtime"))
+            .output()
+            .unwrap()
+    }
+}
+
+impl Drop for Home {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Url of the first mock result of `query`
+fn first_url(home: &Home, query: &str) -> String {
+    let output = home.ytrs(&["search", query, "--limit", "1"]);
+    stdout(&output).split('\t').next().unwrap().to_owned()
+}
+
+/// The media downloaded to `dir`, yt-dlp keeps its cache next to them
+fn downloaded(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .filter(|name| name.ends_with(".mp3"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[test]
+fn search_prints_the_mock_results() {
+    let home = Home::new("search");
+    let output = home.ytrs(&["search", "lofi"]);
+    assert!(output.status.success(), "{output:?}");
+    let lines: Vec<String> = stdout(&output).lines().map(str::to_owned).collect();
+    assert_eq!(lines.len(), 5);
+    for (n, line) in lines.iter().enumerate() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        assert_eq!(fields.len(), 4, "{line}");
+        assert!(fields[0].contains("watch?v=mock"), "{line}");
+        assert_eq!(fields[1], format!("lofi video {}", n + 1));
+        assert_eq!(fields[2], "Mock channel");
+    }
+    // The same query finds the same results
+    assert_eq!(
+        stdout(&home.ytrs(&["search", "lofi"])),
+        lines.join("\n") + "\n"
+    );
+}
+
+#[test]
+fn search_limits_the_json_results() {
+    let home = Home::new("search-json");
+    let output = home.ytrs(&["--json", "search", "--music", "--limit", "2", "lofi"]);
+    assert!(output.status.success(), "{output:?}");
+    let found: Vec<serde_json::Value> = stdout(&output)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0]["title"], "lofi track 1");
+    assert_eq!(found[0]["channel"], "Mock artist");
+    assert!(found[0]["id"].as_str().unwrap().starts_with("mock"));
+    assert_eq!(found[0]["live"], false);
+}
+
+#[test]
+fn download_writes_the_mock_file() {
+    let home = Home::new("download");
+    home.configure_download();
+    let url = first_url(&home, "lofi");
+    let id = url.rsplit('=').next().unwrap();
+    let output = home.ytrs(&["download", &url]);
+    assert!(output.status.success(), "{output:?}");
+    let file = home.output().join(format!("Mock video {id}.mp3"));
+    assert_eq!(
+        std::fs::read_to_string(file).unwrap(),
+        format!("Mock media {id}\n")
+    );
+}
+
+#[test]
+fn download_batch_reports_the_unavailable_videos() {
+    let home = Home::new("download-batch");
+    home.configure_download();
+    let batch = home.0.join("batch.txt");
+    std::fs::write(
+        &batch,
+        format!(
+            "{}\n# Not a mock result\nhttps://www.youtube.com/watch?v=dQw4w9WgXcQ\n",
+            first_url(&home, "lofi")
+        ),
+    )
+    .unwrap();
+    let output = home.ytrs(&["download", "--batch", batch.to_str().unwrap()]);
+    // Only some of the downloads failed
+    assert_eq!(output.status.code(), Some(3), "{output:?}");
+    assert_eq!(downloaded(&home.output()).len(), 1);
+}
+
+#[test]
+fn download_fails_when_it_would_ask() {
+    let home = Home::new("download-query");
+    let output = home.ytrs(&["download", "--query", "lofi"]);
+    assert_eq!(output.status.code(), Some(2), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--non-interactive"));
+    assert!(downloaded(&home.output()).is_empty());
+}
@@ -0,0 +1,189 @@
+//! Audio played by ytrs itself through rodio, for machines with no player installed
+use crate::player::PlayerBackend;
+use anyhow::{Context, Result, anyhow, bail};
+use rodio::{Decoder, OutputStreamBuilder, Sink, Source};
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// An audio player decoding local files and direct audio streams with symphonia.
+/// Streams are downloaded whole before they play, and the formats are the ones of
+/// symphonia: MP3, M4A/AAC, FLAC, Vorbis and WAV, not Opus.
+pub struct AudioPlayer {
+    player: Sink,
+    playlist: Vec<String>,
+    current: Option<usize>,
+    duration: Option<Duration>,
+    closed: bool,
+    // The output device lives in its own thread, closed once this is dropped
+    _output: mpsc::Sender<()>,
+}
+
+impl AudioPlayer {
+    /// Open the default output device
+    pub async fn open() -> Result<Self> {
+        let (player_tx, player_rx) = oneshot::channel();
+        let (output, closed) = mpsc::channel::<()>();
+        std::thread::spawn(move || match OutputStreamBuilder::open_default_stream() {
+            Ok(mut stream) => {
+                // It would print over the terminal interface
+                stream.log_on_drop(false);
+                _ = player_tx.send(Ok(Sink::connect_new(stream.mixer())));
+                // Nothing is ever sent, it returns once the player is dropped
+                _ = closed.recv();
+            }
+            Err(e) => _ = player_tx.send(Err(e)),
+        });
+        let player = player_rx
+            .await?
+            .context("Failed to open the audio output")?;
+        Ok(Self {
+            player,
+            playlist: Vec::new(),
+            current: None,
+            duration: None,
+            closed: false,
+            _output: output,
+        })
+    }
+
+    // Play the entry `index` of the playlist in place of the current one
+    async fn open_entry(&mut self, index: usize) -> Result<()> {
+        let location = self.playlist[index].clone();
+        self.current = Some(index);
+        if location.starts_with("http://") || location.starts_with("https://") {
+            let data = reqwest::get(&location)
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await
+                .context("Failed to download the stream")?;
+            let len = data.len() as u64;
+            let decoder = Decoder::builder()
+                .with_data(Cursor::new(data))
+                .with_byte_len(len)
+                .with_seekable(true)
+                .build()
+                .context("Failed to decode the stream")?;
+            self.play(decoder);
+        } else {
+            let file = std::fs::File::open(&location)
+                .with_context(|| format!("Failed to open '{location}'"))?;
+            self.play(Decoder::try_from(file).context("Failed to decode the file")?);
+        }
+        Ok(())
+    }
+
+    fn play<R: Read + Seek + Send + Sync + 'static>(&mut self, decoder: Decoder<R>) {
+        self.duration = decoder.total_duration();
+        self.player.clear();
+        self.player.append(decoder);
+        // Clearing paused it
+        self.player.play();
+    }
+
+    // Go on with the next entry once the current one ended
+    async fn follow(&mut self) {
+        if let Some(index) = self.current
+            && self.player.empty()
+            && index + 1 < self.playlist.len()
+            && let Err(e) = self.open_entry(index + 1).await
+        {
+            tracing::warn!("Could not play '{}': {e:#}", self.playlist[index + 1]);
+        }
+    }
+}
+
+impl PlayerBackend for AudioPlayer {
+    async fn load(&mut self, url: &str) -> Result<()> {
+        self.playlist = vec![url.to_owned()];
+        self.open_entry(0).await
+    }
+    async fn append(&mut self, url: &str) -> Result<()> {
+        self.playlist.push(url.to_owned());
+        // Nothing was playing yet
+        if self.current.is_none() {
+            return self.open_entry(self.playlist.len() - 1).await;
+        }
+        Ok(())
+    }
+    async fn next(&mut self) -> Result<()> {
+        match self.current {
+            Some(index) if index + 1 < self.playlist.len() => self.open_entry(index + 1).await,
+            _ => bail!("End of the playlist"),
+        }
+    }
+    async fn previous(&mut self) -> Result<()> {
+        match self.current {
+            Some(index) if index > 0 => self.open_entry(index - 1).await,
+            _ => self.seek(0.0, false).await,
+        }
+    }
+    async fn set_paused(&mut self, paused: bool) -> Result<()> {
+        if paused {
+            self.player.pause();
+        } else {
+            self.player.play();
+        }
+        Ok(())
+    }
+    async fn paused(&mut self) -> Result<bool> {
+        Ok(self.player.is_paused())
+    }
+    async fn seek(&mut self, seconds: f64, relative: bool) -> Result<()> {
+        let target = if relative {
+            self.player.get_pos().as_secs_f64() + seconds
+        } else {
+            seconds
+        };
+        let end = self
+            .duration
+            .map_or(f64::MAX, |duration| duration.as_secs_f64());
+        self.player
+            .try_seek(Duration::from_secs_f64(target.clamp(0.0, end)))
+            // Its error can not be sent between threads
+            .map_err(|e| anyhow!("Failed to seek: {e}"))
+    }
+    async fn volume(&mut self) -> Result<f64> {
+        Ok(f64::from(self.player.volume()) * 100.0)
+    }
+    async fn set_volume(&mut self, volume: f64) -> Result<()> {
+        self.player
+            .set_volume((volume.clamp(0.0, 130.0) / 100.0) as f32);
+        Ok(())
+    }
+    async fn position(&mut self) -> Result<f64> {
+        self.follow().await;
+        Ok(self.player.get_pos().as_secs_f64())
+    }
+    async fn duration(&mut self) -> Result<f64> {
+        self.duration
+            .map(|duration| duration.as_secs_f64())
+            .context("Unknown duration")
+    }
+    async fn title(&mut self) -> Result<String> {
+        let location = self
+            .current
+            .map(|index| self.playlist[index].as_str())
+            .filter(|location| !location.starts_with("http"))
+            .context("No title")?;
+        Ok(Path::new(location)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string())
+    }
+    async fn playlist_pos(&mut self) -> Option<usize> {
+        self.follow().await;
+        self.current
+    }
+    async fn running(&mut self) -> bool {
+        !self.closed
+    }
+    async fn quit(&mut self) {
+        self.player.stop();
+        self.closed = true;
+    }
+}
@@ -0,0 +1,55 @@
+//! Downloads through yt-dlp and ffmpeg
+use crate::link::watch_url;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use yt_dlp::Youtube;
+use yt_dlp::model::{AudioCodecPreference, AudioQuality, VideoCodecPreference, VideoQuality};
+
+/// Fetcher downloading into `output_dir`, with the yt-dlp and ffmpeg binaries of `libs_dir`
+///
+/// The binaries are installed there when missing.
+pub async fn fetcher(libs_dir: &Path, output_dir: &Path) -> Result<Youtube> {
+    Youtube::with_new_binaries(libs_dir, output_dir)
+        .await
+        .context("Failed to retrieve Youtube Fetcher")
+}
+
+/// Download the best audio of `video_id` in `codec` (e.g. 'mp3') as `file_name`, in the output
+/// directory of `fetcher`
+pub async fn audio(
+    fetcher: &Youtube,
+    video_id: &str,
+    file_name: &str,
+    codec: &str,
+) -> Result<PathBuf> {
+    fetcher
+        .download_audio_stream_with_quality(
+            watch_url(video_id),
+            file_name,
+            AudioQuality::Best,
+            AudioCodecPreference::Custom(codec.to_owned()),
+        )
+        .await
+        .with_context(|| format!("Failed to download the audio of '{video_id}'"))
+}
+
+/// Download the best video of `video_id` in `codec` (e.g. 'mp4') with its audio as `file_name`,
+/// in the output directory of `fetcher`
+pub async fn video(
+    fetcher: &Youtube,
+    video_id: &str,
+    file_name: &str,
+    codec: &str,
+) -> Result<PathBuf> {
+    fetcher
+        .download_video_with_quality(
+            watch_url(video_id),
+            file_name,
+            VideoQuality::Best,
+            VideoCodecPreference::Custom(codec.to_owned()),
+            AudioQuality::Best,
+            AudioCodecPreference::MP3,
+        )
+        .await
+        .with_context(|| format!("Failed to download '{video_id}'"))
+}
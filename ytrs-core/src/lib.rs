@@ -0,0 +1,40 @@
+//! Search, queue, download and play YouTube videos, the part of [ytrs](https://github.com/Owlyat/ytrs)
+//! other Rust tools can embed without its terminal interface.
+//!
+//! Playing requires mpv in the `PATH`, or VLC, or only plays audio with [`audio::AudioPlayer`].
+//! Downloading installs yt-dlp and ffmpeg on first use.
+//!
+//! ```no_run
+//! use ytrs_core::link::watch_url;
+//! use ytrs_core::mpv::{MpvIpc, MpvSpawnOptions};
+//! use ytrs_core::queue::Queue;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let rp = rustypipe::client::RustyPipe::new();
+//! let videos = ytrs_core::search::videos(&rp.query(), "lofi hip hop").await?;
+//! let Some((first, rest)) = videos.split_first() else {
+//!     return Ok(());
+//! };
+//! let mut mpv = MpvIpc::spawn(&MpvSpawnOptions::default(), true).await?;
+//! mpv.send_command(serde_json::json!(["loadfile", watch_url(&first.id)]))
+//!     .await?;
+//! // Played after the first video
+//! let mut queue = Queue::default();
+//! for video in rest {
+//!     queue.push(&mut mpv, video.id.clone()).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+#![warn(missing_docs)]
+
+pub mod audio;
+pub mod download;
+pub mod link;
+pub mod mpv;
+pub mod player;
+pub mod queue;
+pub mod search;
+pub mod transcript;
+pub mod utility;
+pub mod vlc;
@@ -0,0 +1,102 @@
+//! Links of YouTube and YouTube Music
+use reqwest::Url;
+
+/// What a YouTube or YouTube Music link points to
+#[derive(Clone, Default)]
+pub struct YoutubeLink {
+    /// Video of a watch, youtu.be, shorts, embed or live link
+    pub video_id: Option<String>,
+    /// Playlist from `list=`
+    pub playlist_id: Option<String>,
+    /// Seconds to start playing at, from `t=` or `start=`
+    pub start: Option<u64>,
+    /// Links of music.youtube.com
+    pub music: bool,
+}
+
+impl YoutubeLink {
+    /// Parse watch, youtu.be, shorts, embed, live and playlist links, `None` for anything else
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let url = Url::parse(text)
+            .or_else(|_| Url::parse(&format!("https://{text}")))
+            .ok()?;
+        let host = url.host_str()?.to_lowercase();
+        let host = host
+            .strip_prefix("www.")
+            .or(host.strip_prefix("m."))
+            .unwrap_or(&host);
+        let mut segments = url.path_segments()?.filter(|s| !s.is_empty());
+        let query = |key: &str| {
+            url.query_pairs()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.into_owned())
+        };
+        let mut link = Self {
+            music: host == "music.youtube.com",
+            ..Self::default()
+        };
+        match host {
+            "youtu.be" => link.video_id = segments.next().map(str::to_owned),
+            "youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => {
+                link.video_id = match segments.next() {
+                    Some("watch") => query("v"),
+                    Some("shorts" | "embed" | "live" | "v") => segments.next().map(str::to_owned),
+                    Some("playlist") => None,
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        }
+        link.video_id = link.video_id.filter(|id| is_video_id(id));
+        link.playlist_id = query("list").filter(|id| !id.is_empty());
+        // Shared links put the time in the fragment too, e.g. `#t=1m30s`
+        let fragment_time = url
+            .fragment()
+            .and_then(|fragment| fragment.strip_prefix("t="))
+            .map(str::to_owned);
+        link.start = query("t")
+            .or(query("start"))
+            .or(fragment_time)
+            .and_then(|time| parse_time(&time));
+        (link.video_id.is_some() || link.playlist_id.is_some()).then_some(link)
+    }
+}
+
+fn is_video_id(id: &str) -> bool {
+    id.len() == 11
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// `90`, `90s`, `1m30s` or `1h2m3s`
+fn parse_time(time: &str) -> Option<u64> {
+    if let Ok(seconds) = time.parse() {
+        return Some(seconds);
+    }
+    let mut total = 0;
+    let mut number = String::new();
+    for c in time.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' => {
+                let value: u64 = number.parse().ok()?;
+                number.clear();
+                total += value
+                    * match c {
+                        'h' => 3600,
+                        'm' => 60,
+                        _ => 1,
+                    };
+            }
+            _ => return None,
+        }
+    }
+    number.is_empty().then_some(total)
+}
+
+/// Watch link of `video_id`, which mpv and yt-dlp play
+pub fn watch_url(video_id: &str) -> String {
+    format!("https://www.youtube.com/watch?v={video_id}")
+}
@@ -0,0 +1,630 @@
+//! mpv driven through its JSON IPC
+use crate::player::{SharedChild, release, track, wait_or_kill};
+use anyhow::{Context, anyhow, bail};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader, WriteHalf};
+use tokio::sync::{Mutex, mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+use tokio::{process, time};
+use tokio_util::sync::CancellationToken;
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(target_os = "windows")]
+mod mpv_platform {
+    use super::unix_timestamp;
+    use std::path::PathBuf;
+    use tokio::net::windows::named_pipe::{ClientOptions, NamedPipeClient};
+    pub type Stream = NamedPipeClient;
+    pub async fn connect(path: &PathBuf) -> Result<Stream, ()> {
+        let opts = ClientOptions::new();
+        opts.open(path).or(Err(()))
+    }
+    pub fn generate_ipc_path() -> PathBuf {
+        format!(
+            "\\\\.\\pipe\\mpv_ipc_{}_{}",
+            std::process::id(),
+            unix_timestamp()
+        )
+        .into()
+    }
+    pub fn default_mpv_bin() -> PathBuf {
+        "mpv.exe".into()
+    }
+}
+#[cfg(not(target_os = "windows"))]
+mod mpv_platform {
+    use super::unix_timestamp;
+    use std::path::PathBuf;
+    use tokio::net::UnixStream;
+    pub type Stream = UnixStream;
+    pub async fn connect(path: &PathBuf) -> Result<Stream, ()> {
+        UnixStream::connect(&path).await.or(Err(()))
+    }
+    pub fn generate_ipc_path() -> PathBuf {
+        let dir = std::env::temp_dir();
+        dir.join(format!(
+            "mpv_ipc_{}_{}.sock",
+            std::process::id(),
+            unix_timestamp()
+        ))
+    }
+    pub fn default_mpv_bin() -> PathBuf {
+        "mpv".into()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct MpvCommand {
+    request_id: usize,
+    command: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct MpvResponse {
+    request_id: usize,
+    data: MpvDataOption,
+    error: String,
+}
+
+type LockedMpvIdMap<T> = Arc<Mutex<HashMap<usize, T>>>;
+type MpvDataOption = Option<serde_json::Value>;
+
+/// How [`MpvIpc::spawn`] starts mpv
+#[derive(Clone, Default)]
+pub struct MpvSpawnOptions {
+    /// mpv binary, `mpv` from the `PATH` by default
+    pub mpv_path: Option<PathBuf>,
+    /// IPC socket (or named pipe on Windows), a new one in the temporary directory by default
+    pub ipc_path: Option<PathBuf>,
+    /// Config directory of mpv instead of the user one
+    pub config_dir: Option<PathBuf>,
+    /// Let mpv print to the terminal
+    pub inherit_stdout: bool,
+    /// Given as is to mpv, after the other arguments
+    pub extra_args: Vec<String>,
+    /// Shell command reading the raw audio on its stdin instead of playing it
+    pub audio_command: Option<String>,
+}
+
+/// mpv arguments writing the audio to `file` as raw PCM, 48000 Hz 16 bit stereo
+pub fn pcm_output_args(file: &str) -> Vec<String> {
+    vec![
+        "--ao=pcm".to_owned(),
+        format!("--ao-pcm-file={file}"),
+        "--ao-pcm-waveheader=no".to_owned(),
+        "--audio-format=s16".to_owned(),
+        "--audio-samplerate=48000".to_owned(),
+        "--audio-channels=stereo".to_owned(),
+    ]
+}
+
+/// An mpv player driven through its JSON IPC
+pub struct MpvIpc {
+    shutdown: CancellationToken,
+    // Set when the connection ended without mpv shutting down
+    lost: Arc<AtomicBool>,
+    writer: WriteHalf<mpv_platform::Stream>,
+    request_id: usize,
+    requests: LockedMpvIdMap<oneshot::Sender<anyhow::Result<serde_json::Value>>>,
+    event_handlers: Arc<Mutex<HashMap<String, Vec<mpsc::Sender<serde_json::Value>>>>>,
+    observers: LockedMpvIdMap<mpsc::Sender<MpvDataOption>>,
+    tasks: Vec<JoinHandle<()>>,
+    // Only set when spawned, the players merely connected to are left running
+    child: Option<SharedChild>,
+    // The socket connected to, none for the player embedded on unix
+    ipc_path: Option<PathBuf>,
+    // The player core when it runs in this process, see `MpvIpc::embed`
+    #[cfg(feature = "libmpv")]
+    embedded: Option<libmpv2::Mpv>,
+}
+
+impl MpvIpc {
+    /// Attach to an existing mpv IPC socket.
+    pub async fn connect(ipc_path: &PathBuf) -> anyhow::Result<Self> {
+        // Retry before giving up
+        for n in 0..10 {
+            if n > 0 {
+                time::sleep(Duration::from_millis(100) * n).await;
+            }
+            if let Ok(stream) = mpv_platform::connect(ipc_path).await {
+                let mut sself = Self::attach(stream);
+                sself.ipc_path = Some(ipc_path.clone());
+                return Ok(sself);
+            }
+        }
+        bail!("failed to connect to mpv socket");
+    }
+
+    // Drive the mpv at the other end of `stream`
+    fn attach(stream: mpv_platform::Stream) -> Self {
+        let (reader, writer) = io::split(stream);
+        let mut line_reader = BufReader::new(reader).lines();
+        let requests = Arc::new(Mutex::new(HashMap::<
+            usize,
+            oneshot::Sender<anyhow::Result<serde_json::Value>>,
+        >::new()));
+        let observers = Arc::new(Mutex::new(
+            HashMap::<usize, mpsc::Sender<MpvDataOption>>::new(),
+        ));
+        let event_handlers = Arc::new(Mutex::new(HashMap::<
+            String,
+            Vec<mpsc::Sender<serde_json::Value>>,
+        >::new()));
+        let shutdown = CancellationToken::new();
+
+        let lost = Arc::new(AtomicBool::new(false));
+
+        let shutdown_ref = shutdown.clone();
+        let lost_ref = lost.clone();
+        let requests_ref = requests.clone();
+        let observers_ref = observers.clone();
+        let event_handlers_ref = event_handlers.clone();
+        let mpv_ipc_task = tokio::spawn(async move {
+            loop {
+                let res = tokio::select! {
+                    line = line_reader.next_line() => { line },
+                    _ = shutdown_ref.cancelled() => {
+                        break;
+                    }
+                };
+                let Ok(Some(str)) = res else {
+                    tracing::info!("Lost the connection to mpv");
+                    // mpv did not say it was shutting down
+                    lost_ref.store(true, Ordering::Relaxed);
+                    shutdown_ref.cancel();
+                    // TODO: this should also abort tasks etc
+
+                    // Send faked shutdown event to any listeners
+                    if let Some(list) = event_handlers_ref.lock().await.get("shutdown") {
+                        for handler in list {
+                            // Like the other events, a subscriber may have stopped listening
+                            let _ = handler.send(json!({"event": "shutdown"})).await;
+                        }
+                    }
+                    break; // stop main loop
+                };
+
+                let Ok(json) = serde_json::from_str::<serde_json::Value>(str.as_str()) else {
+                    tracing::warn!("Ignored a line mpv sent that is not json: {str}");
+                    continue;
+                };
+                if let Ok(mpv_resp) = MpvResponse::deserialize(&json) {
+                    // The request may have been given up on meanwhile
+                    if let Some(tx) = requests_ref.lock().await.remove(&mpv_resp.request_id) {
+                        let _ = if mpv_resp.error == "success" {
+                            tx.send(Ok(mpv_resp.data.unwrap_or(serde_json::Value::Null)))
+                        } else {
+                            tx.send(Err(anyhow!(mpv_resp.error)))
+                        };
+                    }
+                } else if let Some(event) = json
+                    .as_object()
+                    .and_then(|j| j.get("event"))
+                    .and_then(|j| j.as_str())
+                {
+                    if let Some(list) = event_handlers_ref.lock().await.get_mut(event) {
+                        // Forget the subscribers that dropped their receiver
+                        list.retain(|handler| !handler.is_closed());
+                        for handler in list.iter() {
+                            let _ = handler.send(json.clone()).await;
+                        }
+                    }
+                    if event == "property-change"
+                        && let Some(id) = json["id"].as_u64()
+                        && let Some(tx) = observers_ref.lock().await.get(&(id as usize))
+                    {
+                        // Nobody follows the property anymore once its receiver is dropped
+                        let _ = tx.send(json.get("data").cloned()).await;
+                    }
+                    if event == "shutdown" {
+                        shutdown_ref.cancel();
+                        // TODO: this should also abort tasks etc
+                        break; // stop main loop
+                    }
+                }
+            }
+        });
+
+        Self {
+            shutdown,
+            lost,
+            writer,
+            request_id: 0,
+            requests,
+            observers,
+            event_handlers,
+            tasks: vec![mpv_ipc_task],
+            child: None,
+            ipc_path: None,
+            #[cfg(feature = "libmpv")]
+            embedded: None,
+        }
+    }
+
+    /// Spawn a new mpv process and attach to it.
+    pub async fn spawn(opt: &MpvSpawnOptions, audio_only: bool) -> anyhow::Result<Self> {
+        let mpv_path = opt
+            .mpv_path
+            .as_ref()
+            .map(std::borrow::Cow::Borrowed)
+            .unwrap_or_else(|| std::borrow::Cow::Owned(mpv_platform::default_mpv_bin()));
+        let ipc_path = opt
+            .ipc_path
+            .as_ref()
+            .map(std::borrow::Cow::Borrowed)
+            .unwrap_or_else(|| std::borrow::Cow::Owned(mpv_platform::generate_ipc_path()));
+        let mut args = vec![
+            "--idle".to_owned(),
+            "--input-ipc-server=".to_owned() + &ipc_path.to_string_lossy(),
+        ];
+        if audio_only {
+            args.push("--no-video".to_string());
+        }
+        if let Some(config_dir) = &opt.config_dir {
+            args.push("--config-dir=".to_owned() + &config_dir.to_string_lossy());
+        }
+        if opt.audio_command.is_some() {
+            if !cfg!(unix) {
+                bail!("Piping the audio to a command is only supported on unix");
+            }
+            // The audio goes through stdout, the messages of mpv would corrupt it
+            args.push("--no-terminal".to_owned());
+            args.extend(pcm_output_args("/dev/stdout"));
+        }
+        args.extend(opt.extra_args.iter().cloned());
+        let stdout_mode = || {
+            if opt.inherit_stdout {
+                Stdio::inherit()
+            } else {
+                Stdio::null()
+            }
+        };
+        let mut command = process::Command::new(mpv_path.as_ref());
+        // Out of the process group of the terminal, closing it does not stop a detached player
+        #[cfg(unix)]
+        command.process_group(0);
+        let mut child = command
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(if opt.audio_command.is_some() {
+                Stdio::piped()
+            } else {
+                stdout_mode()
+            })
+            .stderr(stdout_mode())
+            // Killed when the spawning fails below
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to spawn mpv process")?;
+        if let Some(command) = &opt.audio_command
+            && let Some(audio) = child.stdout.take()
+        {
+            // Ends on its own once mpv quits and closes the pipe
+            let mut audio_command = process::Command::new("sh");
+            // Left running with mpv when it is detached
+            #[cfg(unix)]
+            audio_command.process_group(0);
+            audio_command
+                .arg("-c")
+                .arg(command)
+                .stdin(TryInto::<Stdio>::try_into(audio)?)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .with_context(|| format!("Failed to run '{command}'"))?;
+        }
+        let child_pid = child.id();
+
+        // Connect
+        let mut sself = Self::connect(&ipc_path).await?;
+
+        // Another mpv listening on the socket would be driven, and quit, instead of this one
+        let ipc_pid = sself.get_prop::<u32>("pid").await?;
+        if Some(ipc_pid) != child_pid {
+            sself.disconnect().await;
+            bail!("Connected to another mpv than the one spawned");
+        }
+        sself.child = Some(track(child));
+
+        Ok(sself)
+    }
+
+    /// Run mpv inside this process through libmpv and attach to it, no mpv binary is needed.
+    /// The arguments of `opt` are set as options of libmpv, its binary and audio command are
+    /// not supported.
+    #[cfg(feature = "libmpv")]
+    pub async fn embed(opt: &MpvSpawnOptions, audio_only: bool) -> anyhow::Result<Self> {
+        if opt.audio_command.is_some() {
+            bail!("Piping the audio to a command needs an mpv process");
+        }
+        // Unlike the library defaults, behave like the command line player
+        let mut options = vec![
+            ("config", "yes".to_owned()),
+            (
+                "terminal",
+                if opt.inherit_stdout { "yes" } else { "no" }.to_owned(),
+            ),
+            ("input-default-bindings", "yes".to_owned()),
+            ("input-vo-keyboard", "yes".to_owned()),
+            ("osc", "yes".to_owned()),
+            ("ytdl", "yes".to_owned()),
+        ];
+        if audio_only {
+            options.push(("video", "no".to_owned()));
+        }
+        if let Some(config_dir) = &opt.config_dir {
+            options.push(("config-dir", config_dir.to_string_lossy().to_string()));
+        }
+        // mpv reads its end of the socket pair, there is no socket file
+        #[cfg(unix)]
+        let (stream, ipc_client) = {
+            use std::os::fd::IntoRawFd;
+            let (ours, theirs) = std::os::unix::net::UnixStream::pair()?;
+            ours.set_nonblocking(true)?;
+            let stream = tokio::net::UnixStream::from_std(ours)?;
+            (stream, format!("fd://{}", theirs.into_raw_fd()))
+        };
+        #[cfg(unix)]
+        options.push(("input-ipc-client", ipc_client));
+        // Windows has no socket pairs, the named pipe stays within the process
+        #[cfg(not(unix))]
+        let ipc_path = opt
+            .ipc_path
+            .clone()
+            .unwrap_or_else(mpv_platform::generate_ipc_path);
+        #[cfg(not(unix))]
+        options.push(("input-ipc-server", ipc_path.to_string_lossy().to_string()));
+        let extra_args: Vec<_> = opt
+            .extra_args
+            .iter()
+            .filter_map(|arg| option(arg))
+            .collect();
+        let embedded = libmpv2::Mpv::with_initializer(|init| {
+            for (name, value) in &options {
+                init.set_option(name, value.as_str())?;
+            }
+            for (name, value) in &extra_args {
+                init.set_option(name, *value)?;
+            }
+            Ok(())
+        })
+        .context("Failed to start libmpv")?;
+        #[cfg(unix)]
+        let mut sself = Self::attach(stream);
+        #[cfg(not(unix))]
+        let mut sself = Self::connect(&ipc_path).await?;
+        sself.embedded = Some(embedded);
+        Ok(sself)
+    }
+
+    /// False once mpv quit or the connection was lost
+    pub async fn running(&self) -> bool {
+        !self.shutdown.is_cancelled()
+    }
+
+    /// Resolves once mpv quit or the connection was lost
+    pub async fn stopped(&self) {
+        self.shutdown.cancelled().await
+    }
+
+    /// True when the connection ended without mpv shutting down, it crashed or was killed.
+    /// Never set by [`MpvIpc::quit`] or [`MpvIpc::disconnect`].
+    pub fn crashed(&self) -> bool {
+        self.lost.load(Ordering::Relaxed)
+    }
+
+    /// Send a command to mpv and wait for a reply.
+    /// This should not be used to `quit` because it will never receive a reply. Use the `quit` function instead.
+    pub async fn send_command(
+        &mut self,
+        cmd: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        tracing::trace!("mpv command {cmd}");
+        let result = self.request(cmd.clone()).await;
+        if let Err(e) = &result {
+            // The properties polled while nothing is loaded are unavailable, that is expected
+            if cmd.get(0).and_then(|name| name.as_str()) == Some("get_property") {
+                tracing::debug!("mpv command {cmd} failed: {e:#}");
+            } else {
+                tracing::warn!("mpv command {cmd} failed: {e:#}");
+            }
+        }
+        result
+    }
+
+    async fn request(&mut self, cmd: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        if self.shutdown.is_cancelled() {
+            bail!("mpv instance has shut down");
+        }
+        let (tx, rx) = oneshot::channel::<anyhow::Result<serde_json::Value>>();
+        self.request_id += 1;
+        self.requests.lock().await.insert(self.request_id, tx);
+        let str = serde_json::to_string(&MpvCommand {
+            request_id: self.request_id,
+            command: cmd,
+        })
+        .unwrap();
+        self.writer.write_all((str + "\n").as_bytes()).await?;
+        tokio::select! {
+            result = rx => result?,
+            _ = self.shutdown.cancelled() => bail!("mpv shutdown"),
+        }
+    }
+
+    fn abort_tasks(&mut self) {
+        for handle in &self.tasks {
+            handle.abort();
+        }
+        self.tasks.clear();
+    }
+
+    /// Shuts down the mpv player and disconnects, only the player spawned by [`MpvIpc::spawn`]
+    /// is waited for and killed when it does not exit, never another mpv.
+    pub async fn quit(&mut self) {
+        self.abort_tasks();
+        let quit_fut = self
+            .writer
+            .write_all(("{\"command\":[\"quit\"]}\n").as_bytes());
+        _ = tokio::time::timeout(Duration::from_secs(2), quit_fut).await;
+        _ = self.writer.shutdown().await;
+        if let Some(child) = self.child.take() {
+            // mpv exits on its own once it read `quit`
+            wait_or_kill(child).await;
+        }
+        #[cfg(feature = "libmpv")]
+        if let Some(embedded) = self.embedded.take() {
+            // Destroying the core waits for it to stop playing
+            _ = tokio::task::spawn_blocking(move || drop(embedded)).await;
+        }
+        self.shutdown.cancel();
+    }
+
+    // Whether the player is ours to quit, not one merely connected to
+    fn owned(&self) -> bool {
+        #[cfg(feature = "libmpv")]
+        if self.embedded.is_some() {
+            return true;
+        }
+        self.child.is_some()
+    }
+
+    /// The IPC socket mpv listens on, to connect to it again with [`MpvIpc::connect`]
+    pub fn ipc_path(&self) -> Option<&PathBuf> {
+        self.ipc_path.as_ref()
+    }
+
+    /// Disconnect and leave the player running on its own, even once this process exits.
+    /// Only a player listening on a socket can be detached, not the one of [`MpvIpc::embed`]
+    pub async fn detach(&mut self) -> anyhow::Result<()> {
+        #[cfg(feature = "libmpv")]
+        if self.embedded.is_some() {
+            bail!("The embedded player stops with ytrs");
+        }
+        if self.ipc_path.is_none() {
+            bail!("mpv listens on no socket to come back to");
+        }
+        if let Some(child) = self.child.take() {
+            release(child);
+        }
+        self.disconnect().await;
+        Ok(())
+    }
+
+    /// Disconnect from the IPC socket.
+    pub async fn disconnect(&mut self) {
+        self.abort_tasks();
+        _ = self.writer.shutdown().await;
+        self.shutdown.cancel();
+    }
+
+    /// Read the property `name`
+    pub async fn get_prop<T: DeserializeOwned>(&mut self, name: &str) -> anyhow::Result<T> {
+        self.send_command(json!(["get_property", name]))
+            .await
+            .and_then(|json| {
+                T::deserialize(json).map_err(|_| anyhow!("failed to deserialize prop"))
+            })
+    }
+
+    /// Write the property `name`
+    pub async fn set_prop(&mut self, name: &str, value: impl Serialize) -> anyhow::Result<()> {
+        self.send_command(json!(["set_property", name, value]))
+            .await
+            .map(|_| ())
+    }
+
+    /// Receive the `event` events of mpv, they must be read for mpv to go on
+    pub async fn subscribe(&mut self, event: &str) -> mpsc::Receiver<serde_json::Value> {
+        let (tx, rx) = mpsc::channel(10);
+        self.event_handlers
+            .lock()
+            .await
+            .entry(event.to_owned())
+            .or_default()
+            .push(tx);
+        rx
+    }
+
+    /// Follow the property `name`, `default` stands for it while it is unavailable
+    pub async fn observe_prop<T: 'static + Send + Sync + Clone + DeserializeOwned>(
+        &mut self,
+        name: impl AsRef<str> + 'static + Send + Sync + Serialize + Display,
+        default: T,
+    ) -> watch::Receiver<T> {
+        // Create observer
+        self.request_id += 1;
+        let id = self.request_id;
+        let (json_tx, mut json_rx) = mpsc::channel::<MpvDataOption>(10);
+        self.observers.lock().await.insert(id, json_tx);
+        if let Err(e) = self
+            .send_command(json!(["observe_property", id, name.as_ref()]))
+            .await
+        {
+            tracing::warn!("Could not observe '{name}': {e}");
+        }
+
+        // Create converter
+        let init_val = self
+            .get_prop(name.as_ref())
+            .await
+            .unwrap_or_else(|_| default.clone());
+        let (t_tx, t_rx) = watch::channel::<T>(init_val);
+        self.tasks.push(tokio::spawn(async move {
+            // Ends with the connection
+            while let Some(data) = json_rx.recv().await {
+                if let Some(json) = data {
+                    if let Ok(val) = T::deserialize(&json) {
+                        _ = t_tx.send(val);
+                    } else {
+                        _ = t_tx.send(default.clone());
+                    }
+                } else {
+                    _ = t_tx.send(default.clone());
+                }
+            }
+        }));
+        t_rx
+    }
+}
+
+/// The option of libmpv standing for the argument `arg` of the command line player
+#[cfg(feature = "libmpv")]
+fn option(arg: &str) -> Option<(&str, &str)> {
+    let arg = arg.strip_prefix("--")?;
+    Some(match arg.split_once('=') {
+        Some((name, value)) => (name, value),
+        None => match arg.strip_prefix("no-") {
+            Some(name) => (name, "no"),
+            None => (arg, "yes"),
+        },
+    })
+}
+
+impl Drop for MpvIpc {
+    fn drop(&mut self) {
+        tokio::task::block_in_place(move || {
+            tokio::runtime::Handle::current().block_on(async {
+                if self.owned() {
+                    self.quit().await;
+                } else {
+                    self.disconnect().await;
+                }
+            });
+        });
+    }
+}
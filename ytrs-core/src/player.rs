@@ -0,0 +1,156 @@
+//! What plays the media: mpv, or VLC or rodio where mpv is not available
+use crate::mpv::MpvIpc;
+use anyhow::Result;
+use serde_json::json;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::process::Child;
+use tokio::time;
+
+// How long a player gets to exit once told to quit, before it is killed
+const QUIT_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub(crate) type SharedChild = Arc<Mutex<Child>>;
+
+// Players spawned and not quit yet
+static SPAWNED: Mutex<Vec<SharedChild>> = Mutex::new(Vec::new());
+
+// Still usable after a panic, which is when `kill_spawned` is needed
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Keep the handle of a spawned player for [`kill_spawned`]
+pub(crate) fn track(child: Child) -> SharedChild {
+    let child = Arc::new(Mutex::new(child));
+    lock(&SPAWNED).push(child.clone());
+    child
+}
+
+/// Wait for a player told to quit to exit, and kill it when it hangs
+pub(crate) async fn wait_or_kill(child: SharedChild) {
+    lock(&SPAWNED).retain(|spawned| !Arc::ptr_eq(spawned, &child));
+    let exited = time::timeout(QUIT_TIMEOUT, async {
+        while matches!(lock(&child).try_wait(), Ok(None)) {
+            time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .is_ok();
+    if !exited {
+        _ = lock(&child).start_kill();
+    }
+}
+
+/// Kill a player right away
+pub(crate) fn kill(child: SharedChild) {
+    lock(&SPAWNED).retain(|spawned| !Arc::ptr_eq(spawned, &child));
+    _ = lock(&child).start_kill();
+}
+
+/// Forget a player left running on purpose, it outlives this process
+pub(crate) fn release(child: SharedChild) {
+    lock(&SPAWNED).retain(|spawned| !Arc::ptr_eq(spawned, &child));
+    // Dropping the last handle kills it
+    std::mem::forget(child);
+}
+
+/// Kill the players spawned and not quit yet, for when the program can not quit them
+/// itself, e.g. from a panic hook
+pub fn kill_spawned() {
+    for child in lock(&SPAWNED).drain(..) {
+        _ = lock(&child).start_kill();
+    }
+}
+
+/// Playback controls every player offers, mpv exposes much more through [`MpvIpc`]
+pub trait PlayerBackend {
+    /// Play `url` right away, in place of the playlist
+    fn load(&mut self, url: &str) -> impl Future<Output = Result<()>> + Send;
+    /// Play `url` after the playlist
+    fn append(&mut self, url: &str) -> impl Future<Output = Result<()>> + Send;
+    /// Skip to the next entry of the playlist
+    fn next(&mut self) -> impl Future<Output = Result<()>> + Send;
+    /// Go back to the previous entry of the playlist
+    fn previous(&mut self) -> impl Future<Output = Result<()>> + Send;
+    /// Pause or resume
+    fn set_paused(&mut self, paused: bool) -> impl Future<Output = Result<()>> + Send;
+    /// Whether the playback is paused
+    fn paused(&mut self) -> impl Future<Output = Result<bool>> + Send;
+    /// Seek to `seconds` from the start, or by `seconds` when `relative`
+    fn seek(&mut self, seconds: f64, relative: bool) -> impl Future<Output = Result<()>> + Send;
+    /// Volume in percent
+    fn volume(&mut self) -> impl Future<Output = Result<f64>> + Send;
+    /// Set the volume in percent, up to 130
+    fn set_volume(&mut self, volume: f64) -> impl Future<Output = Result<()>> + Send;
+    /// Seconds played of the media
+    fn position(&mut self) -> impl Future<Output = Result<f64>> + Send;
+    /// Length of the media in seconds
+    fn duration(&mut self) -> impl Future<Output = Result<f64>> + Send;
+    /// Title of the media, as the player knows it
+    fn title(&mut self) -> impl Future<Output = Result<String>> + Send;
+    /// Index of the media playing in the playlist, when the player tells it
+    fn playlist_pos(&mut self) -> impl Future<Output = Option<usize>> + Send {
+        async { None }
+    }
+    /// False once the player quit or the connection was lost
+    fn running(&mut self) -> impl Future<Output = bool> + Send;
+    /// Close the player
+    fn quit(&mut self) -> impl Future<Output = ()> + Send;
+}
+
+impl PlayerBackend for MpvIpc {
+    async fn load(&mut self, url: &str) -> Result<()> {
+        self.send_command(json!(["loadfile", url])).await?;
+        Ok(())
+    }
+    async fn append(&mut self, url: &str) -> Result<()> {
+        self.send_command(json!(["loadfile", url, "append-play"]))
+            .await?;
+        Ok(())
+    }
+    async fn next(&mut self) -> Result<()> {
+        self.send_command(json!(["playlist-next"])).await?;
+        Ok(())
+    }
+    async fn previous(&mut self) -> Result<()> {
+        self.send_command(json!(["playlist-prev"])).await?;
+        Ok(())
+    }
+    async fn set_paused(&mut self, paused: bool) -> Result<()> {
+        self.set_prop("pause", paused).await
+    }
+    async fn paused(&mut self) -> Result<bool> {
+        self.get_prop("pause").await
+    }
+    async fn seek(&mut self, seconds: f64, relative: bool) -> Result<()> {
+        let mode = if relative { "relative" } else { "absolute" };
+        self.send_command(json!(["seek", seconds, mode])).await?;
+        Ok(())
+    }
+    async fn volume(&mut self) -> Result<f64> {
+        self.get_prop("volume").await
+    }
+    async fn set_volume(&mut self, volume: f64) -> Result<()> {
+        self.set_prop("volume", volume.clamp(0.0, 130.0)).await
+    }
+    async fn position(&mut self) -> Result<f64> {
+        self.get_prop("playback-time").await
+    }
+    async fn duration(&mut self) -> Result<f64> {
+        self.get_prop("duration").await
+    }
+    async fn title(&mut self) -> Result<String> {
+        self.get_prop("media-title").await
+    }
+    async fn playlist_pos(&mut self) -> Option<usize> {
+        self.get_prop("playlist-pos").await.ok()
+    }
+    async fn running(&mut self) -> bool {
+        MpvIpc::running(self).await
+    }
+    async fn quit(&mut self) {
+        MpvIpc::quit(self).await
+    }
+}
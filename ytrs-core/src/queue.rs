@@ -0,0 +1,117 @@
+//! Media played one after the other by mpv
+//!
+//! The first entry of the mpv playlist is what the player was opened with, the queue holds the
+//! ones after it and mirrors them in mpv as they are queued or removed.
+use crate::link::watch_url;
+use crate::mpv::MpvIpc;
+use anyhow::Result;
+use serde_json::json;
+
+/// A media a [`Queue`] can hold
+pub trait Queued {
+    /// What mpv loads to play it, a link or a path
+    fn url(&self) -> String;
+}
+
+/// A YouTube video or YouTube Music track id
+impl Queued for String {
+    fn url(&self) -> String {
+        watch_url(self)
+    }
+}
+
+/// The media queued after the first entry of an mpv playlist
+pub struct Queue<T> {
+    entries: Vec<T>,
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// Queued before mpv plays anything, like [`Queue::new`]
+impl<T> FromIterator<T> for Queue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(entries: I) -> Self {
+        Self {
+            entries: entries.into_iter().collect(),
+        }
+    }
+}
+
+impl<T: Queued> Queue<T> {
+    /// Queue `entries` before mpv plays anything, [`Queue::load`] then appends them
+    pub fn new(entries: Vec<T>) -> Self {
+        Self { entries }
+    }
+
+    /// Append the whole queue to `mpv`, e.g. once it started or restarted with the first entry
+    pub async fn load(&self, mpv: &mut MpvIpc) {
+        for entry in &self.entries {
+            let _ = mpv
+                .send_command(json!(["loadfile", entry.url(), "append"]))
+                .await;
+        }
+    }
+
+    /// Play `entry` after the queued ones
+    pub async fn push(&mut self, mpv: &mut MpvIpc, entry: T) -> Result<()> {
+        mpv.send_command(json!(["loadfile", entry.url(), "append"]))
+            .await?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Remove the queued entry at `index`, `None` when there is none
+    pub async fn remove(&mut self, mpv: &mut MpvIpc, index: usize) -> Option<T> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        // The first entry of the mpv playlist is not part of the queue
+        let _ = mpv
+            .send_command(json!(["playlist-remove", index + 1]))
+            .await;
+        Some(self.entries.remove(index))
+    }
+
+    /// Empty the queue, mpv only keeps the entry playing which becomes its first one
+    pub async fn clear(&mut self, mpv: &mut MpvIpc) {
+        let _ = mpv.send_command(json!(["playlist-clear"])).await;
+        self.entries.clear();
+    }
+
+    /// Forget the queued entries once mpv replaced its whole playlist
+    pub fn forget(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl<T> Queue<T> {
+    /// The queued entry at `position` in the mpv playlist, `None` for the first entry
+    pub fn at(&self, position: usize) -> Option<&T> {
+        self.entries.get(position.checked_sub(1)?)
+    }
+
+    /// The entries after the one at `position` in the mpv playlist
+    pub fn after(&self, position: usize) -> &[T] {
+        self.entries.get(position..).unwrap_or_default()
+    }
+
+    /// The queued entries, in order
+    pub fn entries(&self) -> &[T] {
+        &self.entries
+    }
+
+    /// Number of queued entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing is queued
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,16 @@
+//! Searches of YouTube and YouTube Music
+use anyhow::{Context, Result};
+use rustypipe::client::RustyPipeQuery;
+use rustypipe::model::{TrackItem, VideoItem};
+
+/// Videos of YouTube matching `terms`, first page of the results
+pub async fn videos(query: &RustyPipeQuery, terms: &str) -> Result<Vec<VideoItem>> {
+    let found = query.search::<VideoItem, _>(terms).await;
+    Ok(found.context("Failed to search YouTube")?.items.items)
+}
+
+/// Tracks of YouTube Music matching `terms`, first page of the results
+pub async fn tracks(query: &RustyPipeQuery, terms: &str) -> Result<Vec<TrackItem>> {
+    let found = query.music_search_tracks(terms).await;
+    Ok(found.context("Failed to search YouTube Music")?.items.items)
+}
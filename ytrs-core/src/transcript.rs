@@ -0,0 +1,197 @@
+//! Transcripts of the videos, from their captions
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use yt_dlp::model::Video;
+use yt_dlp::model::caption::{Extension, Subtitle};
+
+/// A timed line of a transcript
+#[derive(Clone, Debug)]
+pub struct Cue {
+    /// Seconds from the start of the video
+    pub start: f64,
+    /// Seconds from the start of the video
+    pub end: f64,
+    /// Said during the cue, without the markup
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+struct Json3 {
+    events: Vec<Json3Event>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Json3Event {
+    t_start_ms: u64,
+    #[serde(default)]
+    d_duration_ms: u64,
+    #[serde(default)]
+    segs: Vec<Json3Seg>,
+}
+
+#[derive(Deserialize)]
+struct Json3Seg {
+    utf8: String,
+}
+
+/// Parse `00:01:02.345`, `01:02.345` or the srt flavored `00:01:02,345`
+fn parse_timestamp(s: &str) -> Option<f64> {
+    let s = s.trim().replace(',', ".");
+    let mut secs = 0.0;
+    for part in s.split(':') {
+        secs = secs * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(secs)
+}
+
+fn strip_tags(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut in_tag = false;
+    for c in line.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.replace("&amp;", "&")
+        .replace("&gt;", ">")
+        .replace("&lt;", "<")
+        .replace("&nbsp;", " ")
+        .trim()
+        .to_string()
+}
+
+/// Parse srt and vtt captions, consecutive duplicated lines (rolling auto captions) are merged
+fn parse_timed_text(content: &str) -> Vec<Cue> {
+    let mut cues: Vec<Cue> = Vec::new();
+    let mut timing: Option<(f64, f64)> = None;
+    for line in content.lines() {
+        if let Some((start, end)) = line.split_once("-->") {
+            // vtt settings may follow the end timestamp
+            let end = end.split_whitespace().next().unwrap_or_default();
+            timing = parse_timestamp(start).zip(parse_timestamp(end));
+            continue;
+        }
+        let Some((start, end)) = timing else {
+            continue;
+        };
+        if line.trim().is_empty() {
+            timing = None;
+            continue;
+        }
+        let text = strip_tags(line);
+        if text.is_empty() {
+            continue;
+        }
+        match cues.last_mut() {
+            Some(last) if last.text == text => last.end = last.end.max(end),
+            _ => cues.push(Cue { start, end, text }),
+        }
+    }
+    cues
+}
+
+fn parse_json3(content: &str) -> Result<Vec<Cue>> {
+    let json: Json3 = serde_json::from_str(content).context("Invalid json3 captions")?;
+    Ok(json
+        .events
+        .into_iter()
+        .filter_map(|e| {
+            let text: String = e.segs.iter().map(|s| s.utf8.as_str()).collect();
+            let text = text.replace('\n', " ").trim().to_string();
+            (!text.is_empty()).then(|| Cue {
+                start: e.t_start_ms as f64 / 1000.0,
+                end: (e.t_start_ms + e.d_duration_ms) as f64 / 1000.0,
+                text,
+            })
+        })
+        .collect())
+}
+
+/// Parse caption content downloaded with the `extension` format
+pub fn parse(content: &str, extension: &str) -> Result<Vec<Cue>> {
+    match extension {
+        "vtt" | "srt" => Ok(parse_timed_text(content)),
+        "json3" => parse_json3(content),
+        _ => bail!("Unsupported caption format '{extension}'"),
+    }
+}
+
+/// Cues containing `phrase`, case insensitive
+pub fn search<'a>(cues: &'a [Cue], phrase: &str) -> Vec<&'a Cue> {
+    let phrase = phrase.to_lowercase();
+    cues.iter()
+        .filter(|c| c.text.to_lowercase().contains(&phrase))
+        .collect()
+}
+
+/// Pick the best vtt track for `lang` (or english, or anything), uploaded subtitles first
+pub fn pick_subtitle(video: &Video, lang: Option<&str>) -> Option<Subtitle> {
+    let preferred = [lang.unwrap_or("en"), "en", "en-orig"];
+    let manual = preferred
+        .iter()
+        .find_map(|l| video.subtitles.get(*l))
+        .or_else(|| video.subtitles.values().next())
+        .and_then(|subs| subs.iter().find(|s| s.is_format(&Extension::Vtt)).cloned());
+    manual.or_else(|| {
+        preferred
+            .iter()
+            .find_map(|l| video.automatic_captions.get(*l).map(|c| (*l, c)))
+            .or_else(|| {
+                video
+                    .automatic_captions
+                    .iter()
+                    .next()
+                    .map(|(l, c)| (l.as_str(), c))
+            })
+            .and_then(|(l, caps)| {
+                caps.iter()
+                    .find(|c| c.extension == Extension::Vtt)
+                    .map(|c| Subtitle::from_automatic_caption(c, l.to_string()))
+            })
+    })
+}
+
+/// Download and parse the transcript of `video`
+pub async fn fetch_cues(video: &Video, lang: Option<&str>) -> Result<Vec<Cue>> {
+    let Some(subtitle) = pick_subtitle(video, lang) else {
+        bail!("No transcript available for '{}'", video.title);
+    };
+    let content = reqwest::Client::new()
+        .get(&subtitle.url)
+        .send()
+        .await?
+        .text()
+        .await?;
+    parse(&content, subtitle.file_extension())
+}
+
+fn srt_timestamp(t: f64) -> String {
+    let ms = (t * 1000.0) as u64;
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+/// Render cues as a srt file
+pub fn to_srt(cues: &[Cue]) -> String {
+    cues.iter()
+        .enumerate()
+        .map(|(i, cue)| {
+            format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                srt_timestamp(cue.start),
+                srt_timestamp(cue.end),
+                cue.text
+            )
+        })
+        .collect()
+}
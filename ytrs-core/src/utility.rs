@@ -0,0 +1,69 @@
+//! Formatting and paths shared by ytrs
+use std::path::PathBuf;
+
+/// `3725` seconds as `[01:02:05]`
+pub fn format_time(d: u32) -> impl std::fmt::Display {
+    let hours = d / 3600;
+    let minutes = (d % 3600) / 60;
+    let secs = d % 60;
+    let hours = if hours > 0 {
+        format!("{hours:02}:")
+    } else {
+        "".to_owned()
+    };
+    let minutes = if minutes > 0 {
+        format!("{minutes:02}:")
+    } else {
+        "".to_owned()
+    };
+    format!("[{}{}{secs:02}]", hours, minutes)
+}
+
+/// `name` with only letters, digits, spaces and dashes, to name downloaded files
+pub fn sanitize_file_name(name: &str) -> String {
+    name.replace(|c: char| !c.is_alphanumeric() && c != ' ' && c != '-', "_")
+}
+
+/// `text` to put in XML, e.g. the RSS feed
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Where ytrs keeps its settings: `$XDG_CONFIG_HOME/ytrs` (`~/.config/ytrs`),
+/// `~/Library/Application Support/ytrs` on macOS or `%APPDATA%\ytrs` on Windows
+pub fn config_dir() -> PathBuf {
+    app_dir(dirs::config_dir())
+}
+
+/// Where ytrs keeps what it saved for the user and the tools it installed: `$XDG_DATA_HOME/ytrs`
+/// (`~/.local/share/ytrs`), `~/Library/Application Support/ytrs` on macOS or `%APPDATA%\ytrs` on Windows
+pub fn data_dir() -> PathBuf {
+    app_dir(dirs::data_dir())
+}
+
+/// Where ytrs keeps what it can fetch or generate again: `$XDG_CACHE_HOME/ytrs` (`~/.cache/ytrs`),
+/// `~/Library/Caches/ytrs` on macOS or `%LOCALAPPDATA%\ytrs` on Windows
+pub fn cache_dir() -> PathBuf {
+    app_dir(dirs::cache_dir())
+}
+
+/// Where ytrs puts the files only living while it runs, `$XDG_RUNTIME_DIR/ytrs` or else the cache directory
+pub fn runtime_dir() -> PathBuf {
+    dirs::runtime_dir()
+        .map(|dir| dir.join("ytrs"))
+        .unwrap_or_else(cache_dir)
+}
+
+/// `~/.config/ytrs`, where ytrs kept everything before following the conventions of each platform
+pub fn legacy_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config").join("ytrs"))
+}
+
+// The working directory when the home directory is unknown
+fn app_dir(base: Option<PathBuf>) -> PathBuf {
+    base.map(|dir| dir.join("ytrs"))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
@@ -0,0 +1,215 @@
+//! VLC driven through its remote control interface, for systems without mpv
+use crate::player::{PlayerBackend, SharedChild, kill, track, wait_or_kill};
+use anyhow::{Context, Result, bail};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::TcpStream;
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::{process, time};
+
+// How long VLC takes to answer a query at most
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(2);
+// VLC volume of 100%
+const FULL_VOLUME: f64 = 256.0;
+
+/// How [`Vlc::spawn`] starts VLC
+#[derive(Clone, Default)]
+pub struct VlcSpawnOptions {
+    /// VLC binary, `vlc` from the `PATH` by default
+    pub vlc_path: Option<PathBuf>,
+    /// Arguments appended to the ones of ytrs
+    pub extra_args: Vec<String>,
+}
+
+/// A VLC player driven through its `rc` interface over TCP
+pub struct Vlc {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+    child: Option<SharedChild>,
+    closed: bool,
+}
+
+impl Vlc {
+    /// Spawn VLC with its remote control interface on a free local port, and attach to it
+    pub async fn spawn(opt: &VlcSpawnOptions, audio_only: bool) -> Result<Self> {
+        let vlc_path = opt.vlc_path.clone().unwrap_or_else(default_vlc_bin);
+        let port = std::net::TcpListener::bind("127.0.0.1:0")?
+            .local_addr()?
+            .port();
+        let address = format!("127.0.0.1:{port}");
+        let mut args = vec![
+            "--intf".to_owned(),
+            "rc".to_owned(),
+            "--rc-host".to_owned(),
+            address.clone(),
+        ];
+        if cfg!(windows) {
+            // Without it VLC opens a console window for the interface
+            args.push("--rc-quiet".to_owned());
+        }
+        if audio_only {
+            args.push("--no-video".to_owned());
+        }
+        args.extend(opt.extra_args.iter().cloned());
+        let child = process::Command::new(&vlc_path)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            // Killed when the connection fails below
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to spawn '{}'", vlc_path.to_string_lossy()))?;
+        let mut stream = None;
+        for n in 0..10 {
+            time::sleep(Duration::from_millis(200) * (n + 1)).await;
+            if let Ok(connected) = TcpStream::connect(&address).await {
+                stream = Some(connected);
+                break;
+            }
+        }
+        let Some(stream) = stream else {
+            bail!("failed to connect to the remote control interface of VLC");
+        };
+        let (reader, writer) = stream.into_split();
+        Ok(Self {
+            lines: BufReader::new(reader).lines(),
+            writer,
+            child: Some(track(child)),
+            closed: false,
+        })
+    }
+
+    async fn command(&mut self, command: &str) -> Result<()> {
+        tracing::trace!("vlc command {command}");
+        let sent = self
+            .writer
+            .write_all(format!("{command}\n").as_bytes())
+            .await;
+        if let Err(e) = sent {
+            self.closed = true;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// The first line VLC answers to `command` that `parse` accepts, skipping the
+    /// prompts and the status changes it prints meanwhile
+    async fn query<T>(&mut self, command: &str, parse: impl Fn(&str) -> Option<T>) -> Result<T> {
+        self.command(command).await?;
+        let answer = time::timeout(ANSWER_TIMEOUT, async {
+            while let Ok(Some(line)) = self.lines.next_line().await {
+                let line = line.trim_start_matches("> ").trim();
+                if line.is_empty() || line.starts_with("status change:") {
+                    continue;
+                }
+                if let Some(answer) = parse(line) {
+                    return Some(answer);
+                }
+            }
+            self.closed = true;
+            None
+        })
+        .await;
+        match answer {
+            Ok(Some(answer)) => Ok(answer),
+            Ok(None) => bail!("VLC closed the connection"),
+            Err(_) => bail!("VLC did not answer '{command}'"),
+        }
+    }
+
+    async fn number(&mut self, command: &str) -> Result<f64> {
+        self.query(command, |line| line.parse().ok()).await
+    }
+}
+
+impl PlayerBackend for Vlc {
+    async fn load(&mut self, url: &str) -> Result<()> {
+        self.command("clear").await?;
+        self.command(&format!("add {url}")).await
+    }
+    async fn append(&mut self, url: &str) -> Result<()> {
+        self.command(&format!("enqueue {url}")).await
+    }
+    async fn next(&mut self) -> Result<()> {
+        self.command("next").await
+    }
+    async fn previous(&mut self) -> Result<()> {
+        self.command("prev").await
+    }
+    async fn set_paused(&mut self, paused: bool) -> Result<()> {
+        if paused {
+            // 'pause' toggles
+            if !self.paused().await? {
+                self.command("pause").await?;
+            }
+            Ok(())
+        } else {
+            self.command("play").await
+        }
+    }
+    async fn paused(&mut self) -> Result<bool> {
+        let playing = self.number("is_playing").await?;
+        Ok(playing == 0.0)
+    }
+    async fn seek(&mut self, seconds: f64, relative: bool) -> Result<()> {
+        let target = if relative {
+            self.position().await? + seconds
+        } else {
+            seconds
+        };
+        self.command(&format!("seek {}", target.max(0.0) as u64))
+            .await
+    }
+    async fn volume(&mut self) -> Result<f64> {
+        Ok(self.number("volume").await? * 100.0 / FULL_VOLUME)
+    }
+    async fn set_volume(&mut self, volume: f64) -> Result<()> {
+        let volume = volume.clamp(0.0, 130.0) * FULL_VOLUME / 100.0;
+        self.command(&format!("volume {}", volume as u32)).await
+    }
+    async fn position(&mut self) -> Result<f64> {
+        self.number("get_time").await
+    }
+    async fn duration(&mut self) -> Result<f64> {
+        self.number("get_length").await
+    }
+    async fn title(&mut self) -> Result<String> {
+        self.query("get_title", |line| Some(line.to_owned())).await
+    }
+    async fn running(&mut self) -> bool {
+        let exited = self.child.as_ref().is_some_and(|child| {
+            !matches!(child.lock().map(|mut child| child.try_wait()), Ok(Ok(None)))
+        });
+        !self.closed && !exited
+    }
+    async fn quit(&mut self) {
+        _ = self.command("shutdown").await;
+        _ = self.writer.shutdown().await;
+        self.closed = true;
+        if let Some(child) = self.child.take() {
+            wait_or_kill(child).await;
+        }
+    }
+}
+
+impl Drop for Vlc {
+    fn drop(&mut self) {
+        // Not quit, e.g. on an error
+        if let Some(child) = self.child.take() {
+            kill(child);
+        }
+    }
+}
+
+fn default_vlc_bin() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        PathBuf::from("/Applications/VLC.app/Contents/MacOS/VLC")
+    } else if cfg!(windows) {
+        PathBuf::from("vlc.exe")
+    } else {
+        PathBuf::from("vlc")
+    }
+}